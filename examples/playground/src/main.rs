@@ -101,6 +101,10 @@ impl serde::Serialize for ScriptParameter {
                 ParameterType::Float => "float",
                 ParameterType::Integer => "integer",
                 ParameterType::Enum => "enum",
+                ParameterType::Note => "note",
+                ParameterType::String => "string",
+                ParameterType::FloatArray => "float_array",
+                ParameterType::IntegerArray => "integer_array",
             }
         };
         s.serialize_field("type", &parameter_type)?;
@@ -112,6 +116,17 @@ impl serde::Serialize for ScriptParameter {
     }
 }
 
+impl ScriptParameter {
+    /// Only parameters with raw values can be edited in the frontend, via `set_parameter_value`.
+    /// String and array parameters are not exposed and keep their default values.
+    fn is_editable(parameter: &Parameter) -> bool {
+        !matches!(
+            parameter.parameter_type(),
+            ParameterType::String | ParameterType::FloatArray | ParameterType::IntegerArray
+        )
+    }
+}
+
 impl From<&Rc<RefCell<pattrns::Parameter>>> for ScriptParameter {
     fn from(value: &Rc<RefCell<pattrns::Parameter>>) -> Self {
         Self(Rc::clone(value))
@@ -142,6 +157,7 @@ struct Playground {
     script_parameters: Vec<ScriptParameter>,
     script_parameter_values: HashMap<String, f64>,
     script_error: String,
+    script_callback_errors: LuaCallbackErrorChannel,
     playing_notes: Vec<PlayingNote>,
     output_start_sample_time: u64,
     emitted_sample_time: u64,
@@ -200,6 +216,7 @@ impl Playground {
         let script_parameters = Vec::new();
         let script_parameter_values = HashMap::new();
         let script_error = String::new();
+        let script_callback_errors = LuaCallbackErrorChannel::new();
 
         // MIDI note playback
         let playing_notes = Vec::new();
@@ -231,6 +248,7 @@ impl Playground {
             script_parameters,
             script_parameter_values,
             script_error,
+            script_callback_errors,
             playing_notes,
             instrument_id,
             output_start_sample_time,
@@ -419,7 +437,7 @@ impl Playground {
                 .borrow()
                 .parameters()
                 .iter()
-                .find(|p| p.borrow().id() == id)
+                .find(|p| p.borrow().id() == id && ScriptParameter::is_editable(&p.borrow()))
             {
                 parameter.borrow_mut().set_value(value);
             }
@@ -523,9 +541,9 @@ impl Playground {
                     self.emitted_sample_time + samples_to_emit,
                 );
                 // handle runtime errors
-                if let Some(err) = pattrns::bindings::has_lua_callback_errors() {
+                if let Some(err) = self.script_callback_errors.first_error() {
                     self.update_script_error(&err.to_string());
-                    pattrns::bindings::clear_lua_callback_errors();
+                    self.script_callback_errors.clear();
                 }
             }
             self.emitted_sample_time += samples_to_emit;
@@ -535,7 +553,7 @@ impl Playground {
     // Rebuild sequence and pattern from actual script content
     fn rebuild_sequence(&mut self) {
         // clear runtime errors
-        pattrns::bindings::clear_lua_callback_errors();
+        self.script_callback_errors.clear();
         // build pattern and set compile errors and parameters
         let (pattern, error) = self.new_pattern();
         self.update_script_error(&error);
@@ -544,6 +562,7 @@ impl Playground {
                 .borrow()
                 .parameters()
                 .iter()
+                .filter(|p| ScriptParameter::is_editable(&p.borrow()))
                 .map(ScriptParameter::from)
                 .collect::<Vec<_>>(),
         );
@@ -553,7 +572,7 @@ impl Playground {
                 .borrow()
                 .parameters()
                 .iter()
-                .find(|p| p.borrow().id() == id)
+                .find(|p| p.borrow().id() == id && ScriptParameter::is_editable(&p.borrow()))
             {
                 let clamped_value = value.clamp(
                     *parameter.borrow().range().start(),
@@ -647,11 +666,12 @@ impl Playground {
     /// Create a new pattern from the currently set script content.
     fn new_pattern(&self) -> (Rc<RefCell<dyn Pattern>>, String) {
         // create a new pattern from our script
-        match new_pattern_from_string(
+        match new_pattern_from_string_with_options(
             self.time_base,
             self.instrument_id.map(InstrumentId::from),
             &self.script_content,
             "[script]",
+            &ScriptOptions::default().with_callback_errors(self.script_callback_errors.clone()),
         ) {
             Ok(pattern) => {
                 // return pattern as it is
@@ -860,6 +860,7 @@ const app = {
                     break;
 
                 case 'integer':
+                case 'note':
                 case 'float':
                     control = document.createElement('input');
                     control.type = 'number';
@@ -909,6 +910,11 @@ const app = {
                         backend.updateParameterValue(param.id, parseInt(e.target.value, 10));
                     });
                     break;
+
+                default:
+                    // string and array parameters are not exposed by the backend
+                    console.warn(`Unsupported parameter type: '${param.type}'`);
+                    break;
             }
 
             if (control) {
@@ -10,15 +10,33 @@ use lazy_static::lazy_static;
 use mlua::prelude::*;
 
 use self::{
+    arpeggio::{arpeggio_from_value, ArpeggioUserData},
+    arrangement::{sequence_from_userdata, ArrangementUserData},
     cycle::CycleUserData,
+    definitions::RHYTHM_PROPERTIES,
+    gate::{
+        bar_gate, cycle_gate_from_values, density_gate_from_value, invert_gate_from_value,
+        logic_gate_from_values, mute_gate_from_values, probability_gate_from_value,
+        threshold_gate_from_value, GateUserData,
+    },
+    generative::{
+        automaton_rhythm_from_value, lsystem_rhythm_from_value, markov_rhythm_from_value,
+        random_walk_rhythm_from_value, RhythmUserData,
+    },
+    melody::{melody_from_value, MelodyUserData},
     note::NoteUserData,
-    parameter::ParameterUserData,
+    parameter::{
+        array_parameter_values, automate_parameter_from_value, note_parameter_value,
+        ParameterUserData,
+    },
+    phrase::PhraseUserData,
     rhythm::pattern_from_userdata,
     sequence::SequenceUserData,
     unwrap::{
-        bad_argument_error, note_event_from_value, optional_string_from_value, string_from_value,
-        validate_table_properties,
+        bad_argument_error, note_event_from_value, optional_string_from_value,
+        script_limits_from_value, string_from_value, validate_table_properties,
     },
+    voicing::voice_leading_from_value,
 };
 
 use crate::{
@@ -26,26 +44,36 @@ use crate::{
     note::chord::Chord,
     pattern::{beat_time::BeatTimePattern, second_time::SecondTimePattern, Pattern},
     time::BeatTimeBase,
-    Note, Parameter, Scale,
+    Note, Parameter, Scale, Sequence, VoiceLeading,
 };
 
 // ---------------------------------------------------------------------------------------------
 
 // private binding impls
+mod arpeggio;
+mod arrangement;
 mod callback;
 mod cycle;
+mod definitions;
+mod gate;
+mod generative;
+mod limits;
+mod melody;
 mod note;
+mod options;
 mod parameter;
+mod phrase;
 mod rhythm;
 mod scale;
 mod sequence;
 mod timeout;
 mod unwrap;
+mod voicing;
 
 // public re-exports
-pub use callback::{
-    add_lua_callback_error, clear_lua_callback_errors, has_lua_callback_errors, lua_callback_errors,
-};
+pub use callback::{LuaCallbackError, LuaCallbackErrorChannel, LuaCallbackKind};
+pub use limits::{ScriptLimitError, ScriptLimits};
+pub use options::ScriptOptions;
 
 // internal re-exports
 pub(crate) use callback::{ContextPlaybackState, LuaCallback};
@@ -64,10 +92,12 @@ pub(crate) struct LuaAppData {
     pub(crate) rand_rgn: Xoshiro256PlusPlus,
     /// Declared global variables for the strict checks.
     pub(crate) declared_globals: HashSet<Vec<u8>>,
+    /// Channel which receives errors from Lua callbacks of this Lua instance.
+    pub(crate) callback_errors: LuaCallbackErrorChannel,
 }
 
 impl LuaAppData {
-    fn new() -> Self {
+    fn new(callback_errors: LuaCallbackErrorChannel) -> Self {
         let rand_seed = None;
         let rand_rgn = Xoshiro256PlusPlus::from_seed(rand::rng().random());
         let declared_globals = HashSet::new();
@@ -75,6 +105,7 @@ impl LuaAppData {
             rand_seed,
             rand_rgn,
             declared_globals,
+            callback_errors,
         }
     }
 }
@@ -85,6 +116,12 @@ impl LuaAppData {
 /// hook instance to limit duration of script calls.
 /// Use [`register_bindings`] to register the bindings for the newly created engine.
 pub(crate) fn new_engine() -> LuaResult<(Lua, LuaTimeoutHook)> {
+    new_engine_with_options(&ScriptOptions::default())
+}
+
+/// Create a new raw lua engine with preloaded packages, but no bindings, which applies the given
+/// script execution limits and reports callback errors to the given error channel.
+pub(crate) fn new_engine_with_options(options: &ScriptOptions) -> LuaResult<(Lua, LuaTimeoutHook)> {
     // create a new lua instance with the allowed std libraries
     let lua = Lua::new_with(
         // Only basics: no OS, IO, PACKAGE, DEBUG, FFI!
@@ -93,13 +130,32 @@ pub(crate) fn new_engine() -> LuaResult<(Lua, LuaTimeoutHook)> {
     )
     .expect("Failed to create a new lua engine");
     // install a timeout hook
-    let timeout_hook = LuaTimeoutHook::new(&lua);
+    let timeout_hook = LuaTimeoutHook::new_with_limits(&lua, &options.limits);
     // create new app data
-    lua.set_app_data(LuaAppData::new());
+    let callback_errors = options.callback_errors.clone().unwrap_or_default();
+    lua.set_app_data(LuaAppData::new(callback_errors));
     // return the lua instance and timeout manager
     Ok((lua, timeout_hook))
 }
 
+/// Create a new lua engine with registered bindings for the given script. Callback errors are
+/// tagged with the script name, unless the options' error channel has a pattern id set.
+fn new_script_engine(
+    time_base: &BeatTimeBase,
+    options: &ScriptOptions,
+    script_name: &str,
+) -> LuaResult<(Lua, LuaTimeoutHook)> {
+    let callback_errors = match &options.callback_errors {
+        Some(channel) if channel.pattern_id().is_some() => channel.clone(),
+        Some(channel) => channel.with_pattern_id(script_name),
+        None => LuaCallbackErrorChannel::new().with_pattern_id(script_name),
+    };
+    let options = options.clone().with_callback_errors(callback_errors);
+    let (mut lua, timeout_hook) = new_engine_with_options(&options)?;
+    register_bindings(&mut lua, &timeout_hook, time_base)?;
+    Ok((lua, timeout_hook))
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Evaluate a lua script file which creates and returns a pattern.
@@ -111,11 +167,25 @@ pub fn new_pattern_from_file<P: AsRef<Path>>(
     time_base: BeatTimeBase,
     instrument: Option<InstrumentId>,
     file_path: P,
+) -> Result<Rc<RefCell<dyn Pattern>>, Box<dyn std::error::Error>> {
+    new_pattern_from_file_with_options(time_base, instrument, file_path, &ScriptOptions::default())
+}
+
+/// Evaluate a lua script file which creates and returns a pattern.
+/// Uses the given script options instead of the default [`ScriptOptions`].
+///
+/// ### Errors
+/// Will return `Err` if `file_name` does not exist, failed to load or the lua file at the given
+/// path fails to evaulate to a valid pattern.
+pub fn new_pattern_from_file_with_options<P: AsRef<Path>>(
+    time_base: BeatTimeBase,
+    instrument: Option<InstrumentId>,
+    file_path: P,
+    options: &ScriptOptions,
 ) -> Result<Rc<RefCell<dyn Pattern>>, Box<dyn std::error::Error>> {
     // create a new engine and register bindings
-    let (mut lua, mut timeout_hook) =
-        new_engine().map_err(Into::<Box<dyn std::error::Error>>::into)?;
-    register_bindings(&mut lua, &timeout_hook, &time_base)?;
+    let script_name = file_path.as_ref().display().to_string();
+    let (lua, mut timeout_hook) = new_script_engine(&time_base, options, &script_name)?;
     // restart the timeout hook
     timeout_hook.reset();
     // compile and evaluate script
@@ -134,11 +204,30 @@ pub fn new_pattern_from_string(
     instrument: Option<InstrumentId>,
     script: &str,
     script_name: &str,
+) -> Result<Rc<RefCell<dyn Pattern>>, Box<dyn std::error::Error>> {
+    new_pattern_from_string_with_options(
+        time_base,
+        instrument,
+        script,
+        script_name,
+        &ScriptOptions::default(),
+    )
+}
+
+/// Evaluate a Lua string expression which creates and returns a pattern.
+/// Uses the given script options instead of the default [`ScriptOptions`].
+///
+/// ### Errors
+/// Will return `Err` if the lua string contents fail to evaluate to a valid pattern.
+pub fn new_pattern_from_string_with_options(
+    time_base: BeatTimeBase,
+    instrument: Option<InstrumentId>,
+    script: &str,
+    script_name: &str,
+    options: &ScriptOptions,
 ) -> Result<Rc<RefCell<dyn Pattern>>, Box<dyn std::error::Error>> {
     // create a new engine and register bindings
-    let (mut lua, mut timeout_hook) =
-        new_engine().map_err(Into::<Box<dyn std::error::Error>>::into)?;
-    register_bindings(&mut lua, &timeout_hook, &time_base)?;
+    let (lua, mut timeout_hook) = new_script_engine(&time_base, options, script_name)?;
     // restart the timeout hook
     timeout_hook.reset();
     // compile and evaluate script
@@ -148,6 +237,81 @@ pub fn new_pattern_from_string(
     pattern_from_userdata(&lua, &timeout_hook, &result, &time_base, instrument).map_err(Into::into)
 }
 
+/// Evaluate a lua script file which creates and returns an arrangement, phrase or pattern.
+/// Phrases and patterns get wrapped into a sequence with a single phrase.
+///
+/// ### Errors
+/// Will return `Err` if `file_name` does not exist, failed to load or the lua file at the given
+/// path fails to evaulate to a valid arrangement, phrase or pattern.
+pub fn new_sequence_from_file<P: AsRef<Path>>(
+    time_base: BeatTimeBase,
+    file_path: P,
+) -> Result<Sequence, Box<dyn std::error::Error>> {
+    new_sequence_from_file_with_options(time_base, file_path, &ScriptOptions::default())
+}
+
+/// Evaluate a lua script file which creates and returns an arrangement, phrase or pattern.
+/// Uses the given script options instead of the default [`ScriptOptions`].
+/// Phrases and patterns get wrapped into a sequence with a single phrase.
+///
+/// ### Errors
+/// Will return `Err` if `file_name` does not exist, failed to load or the lua file at the given
+/// path fails to evaulate to a valid arrangement, phrase or pattern.
+pub fn new_sequence_from_file_with_options<P: AsRef<Path>>(
+    time_base: BeatTimeBase,
+    file_path: P,
+    options: &ScriptOptions,
+) -> Result<Sequence, Box<dyn std::error::Error>> {
+    // create a new engine and register bindings
+    let script_name = file_path.as_ref().display().to_string();
+    let (lua, mut timeout_hook) = new_script_engine(&time_base, options, &script_name)?;
+    // restart the timeout hook
+    timeout_hook.reset();
+    // compile and evaluate script
+    let chunk = lua.load(file_path.as_ref());
+    let result = chunk.eval::<LuaValue>()?;
+    // convert result
+    sequence_from_userdata(&lua, &timeout_hook, &result, &time_base).map_err(Into::into)
+}
+
+/// Evaluate a Lua string expression which creates and returns an arrangement, phrase or pattern.
+/// Phrases and patterns get wrapped into a sequence with a single phrase.
+///
+/// ### Errors
+/// Will return `Err` if the lua string contents fail to evaluate to a valid arrangement,
+/// phrase or pattern.
+pub fn new_sequence_from_string(
+    time_base: BeatTimeBase,
+    script: &str,
+    script_name: &str,
+) -> Result<Sequence, Box<dyn std::error::Error>> {
+    new_sequence_from_string_with_options(time_base, script, script_name, &ScriptOptions::default())
+}
+
+/// Evaluate a Lua string expression which creates and returns an arrangement, phrase or pattern.
+/// Uses the given script options instead of the default [`ScriptOptions`].
+/// Phrases and patterns get wrapped into a sequence with a single phrase.
+///
+/// ### Errors
+/// Will return `Err` if the lua string contents fail to evaluate to a valid arrangement,
+/// phrase or pattern.
+pub fn new_sequence_from_string_with_options(
+    time_base: BeatTimeBase,
+    script: &str,
+    script_name: &str,
+    options: &ScriptOptions,
+) -> Result<Sequence, Box<dyn std::error::Error>> {
+    // create a new engine and register bindings
+    let (lua, mut timeout_hook) = new_script_engine(&time_base, options, script_name)?;
+    // restart the timeout hook
+    timeout_hook.reset();
+    // compile and evaluate script
+    let chunk = lua.load(script).set_name(script_name);
+    let result = chunk.eval::<LuaValue>()?;
+    // convert result
+    sequence_from_userdata(&lua, &timeout_hook, &result, &time_base).map_err(Into::into)
+}
+
 // -------------------------------------------------------------------------------------------------
 
 /// Register pattrns bindings to the given Lua engine.
@@ -159,6 +323,8 @@ pub(crate) fn register_bindings(
 ) -> LuaResult<()> {
     register_global_bindings(lua, timeout_hook, time_base)?;
     register_parameter_bindings(lua)?;
+    register_rhythm_bindings(lua)?;
+    register_gate_bindings(lua, timeout_hook, time_base)?;
     register_math_bindings(lua)?;
     register_table_bindings(lua)?;
     register_pulse_bindings(lua)?;
@@ -228,6 +394,19 @@ fn register_global_bindings(
         })?,
     )?;
 
+    // function detect_scale(notes...)
+    globals.raw_set(
+        "detect_scale",
+        lua.create_function(|_lua, args: LuaMultiValue| -> LuaResult<Option<Scale>> {
+            let notes = notes_from_args(args)?;
+            Ok(Scale::detect(&notes)
+                .into_iter()
+                .next()
+                .filter(|(_, score)| *score > 0.0)
+                .map(|(scale, _)| scale))
+        })?,
+    )?;
+
     // function note(args...)
     globals.raw_set(
         "note",
@@ -266,6 +445,23 @@ fn register_global_bindings(
         })?,
     )?;
 
+    // function detect_chord(notes...)
+    globals.raw_set(
+        "detect_chord",
+        lua.create_function(|_lua, args: LuaMultiValue| -> LuaResult<Option<String>> {
+            let notes = notes_from_args(args)?;
+            Ok(Chord::detect(&notes).map(|chord| chord.to_string()))
+        })?,
+    )?;
+
+    // function voice_leading(options?)
+    globals.raw_set(
+        "voice_leading",
+        lua.create_function(|_lua, options: LuaValue| -> LuaResult<VoiceLeading> {
+            voice_leading_from_value(&options)
+        })?,
+    )?;
+
     // function sequence(args...)
     globals.raw_set(
         "sequence",
@@ -296,17 +492,15 @@ fn register_global_bindings(
             let time_base = *time_base;
             move |lua, table: LuaTable| -> LuaResult<LuaValue> {
                 // error on unknown option keys
-                const RHYTHM_PROPERTIES: [&str; 8] = [
-                    "unit",
-                    "resolution",
-                    "offset",
-                    "repeats",
-                    "parameter",
-                    "pulse",
-                    "gate",
-                    "event",
-                ];
                 validate_table_properties(&table, &RHYTHM_PROPERTIES)?;
+                // apply custom script limits for the pattern's callbacks
+                let timeout_hook = if table.contains_key("limits")? {
+                    let value = table.get::<LuaValue>("limits")?;
+                    let limits = script_limits_from_value(&value, timeout_hook.limits())?;
+                    timeout_hook.with_limits(lua, &limits)
+                } else {
+                    timeout_hook.clone()
+                };
                 // check which time unit is specified
                 let second_time_unit = match table.get::<String>("unit") {
                     Ok(unit) => matches!(unit.as_str(), "seconds" | "ms"),
@@ -323,6 +517,45 @@ fn register_global_bindings(
         })?,
     )?;
 
+    // function arpeggio { args... }
+    globals.raw_set(
+        "arpeggio",
+        lua.create_function(|_lua, options: LuaValue| -> LuaResult<ArpeggioUserData> {
+            arpeggio_from_value(&options)
+        })?,
+    )?;
+
+    // function melody { args... }
+    globals.raw_set(
+        "melody",
+        lua.create_function(|_lua, options: LuaValue| -> LuaResult<MelodyUserData> {
+            melody_from_value(&options)
+        })?,
+    )?;
+
+    // function phrase { args... }
+    globals.raw_set(
+        "phrase",
+        lua.create_function({
+            let timeout_hook = timeout_hook.clone();
+            let time_base = *time_base;
+            move |lua, table: LuaTable| -> LuaResult<PhraseUserData> {
+                PhraseUserData::from_table(lua, &timeout_hook, &time_base, &table)
+            }
+        })?,
+    )?;
+
+    // function arrangement { phrases... }
+    globals.raw_set(
+        "arrangement",
+        lua.create_function({
+            let time_base = *time_base;
+            move |_lua, table: LuaTable| -> LuaResult<ArrangementUserData> {
+                ArrangementUserData::from_table(&time_base, &table)
+            }
+        })?,
+    )?;
+
     // set a globals metatable to catch access to undeclared variables
     let globals_mt = lua.create_table()?;
     globals_mt.set(
@@ -374,12 +607,18 @@ fn register_global_bindings(
 fn register_parameter_bindings(lua: &mut Lua) -> LuaResult<()> {
     let parameter = lua.create_table()?;
 
-    // function boolean(id, default, name?, description?)
+    // function boolean(id, default, name?, description?, automation?)
     parameter.raw_set(
         "boolean",
         lua.create_function(
             |_lua,
-             (id, default, name, description): (LuaValue, LuaValue, LuaValue, LuaValue)|
+             (id, default, name, description, automation): (
+                LuaValue,
+                LuaValue,
+                LuaValue,
+                LuaValue,
+                LuaValue,
+            )|
              -> LuaResult<ParameterUserData> {
                 let id = string_from_value(&id, "boolean", "id", 1)?;
                 if id.is_empty() {
@@ -396,25 +635,27 @@ fn register_parameter_bindings(lua: &mut Lua) -> LuaResult<()> {
                 let name = optional_string_from_value(&name, "boolean", "name", 3)?;
                 let description =
                     optional_string_from_value(&description, "boolean", "description", 4)?;
+                let parameter = Parameter::with_boolean(&id, &name, &description, default);
                 Ok(ParameterUserData {
-                    parameter: Parameter::with_boolean(&id, &name, &description, default),
+                    parameter: automate_parameter_from_value(parameter, &automation, "boolean", 5)?,
                 })
             },
         )?,
     )?;
 
-    // function integer(id, default, range, name?, description?)
+    // function integer(id, default, range, name?, description?, automation?)
     parameter.raw_set(
         "integer",
         #[allow(clippy::unnecessary_cast)]
         lua.create_function(
             |_lua,
-             (id, default, range, name, description): (
+             (id, default, range, name, description, automation): (
                 LuaValue,
                 LuaValue,
                 Option<LuaTable>,
                 LuaValue,
                 LuaValue,
+                LuaValue,
             )|
              -> LuaResult<ParameterUserData> {
                 let id = string_from_value(&id, "integer", "id", 1)?;
@@ -453,25 +694,27 @@ fn register_parameter_bindings(lua: &mut Lua) -> LuaResult<()> {
                 let name = optional_string_from_value(&name, "integer", "name", 3)?;
                 let description =
                     optional_string_from_value(&description, "integer", "description", 4)?;
+                let parameter = Parameter::with_integer(&id, &name, &description, range, default);
                 Ok(ParameterUserData {
-                    parameter: Parameter::with_integer(&id, &name, &description, range, default),
+                    parameter: automate_parameter_from_value(parameter, &automation, "integer", 6)?,
                 })
             },
         )?,
     )?;
 
-    // function number_input(id, default, range, name?, description?)
+    // function number_input(id, default, range, name?, description?, automation?)
     parameter.raw_set(
         "number",
         #[allow(clippy::unnecessary_cast)]
         lua.create_function(
             |_lua,
-             (id, default, range, name, description): (
+             (id, default, range, name, description, automation): (
                 LuaValue,
                 LuaValue,
                 Option<LuaTable>,
                 LuaValue,
                 LuaValue,
+                LuaValue,
             )|
              -> LuaResult<ParameterUserData> {
                 let id = string_from_value(&id, "number", "id", 1)?;
@@ -513,24 +756,26 @@ fn register_parameter_bindings(lua: &mut Lua) -> LuaResult<()> {
                 let name = optional_string_from_value(&name, "number", "name", 3)?;
                 let description =
                     optional_string_from_value(&description, "number", "description", 4)?;
+                let parameter = Parameter::with_float(&id, &name, &description, range, default);
                 Ok(ParameterUserData {
-                    parameter: Parameter::with_float(&id, &name, &description, range, default),
+                    parameter: automate_parameter_from_value(parameter, &automation, "number", 6)?,
                 })
             },
         )?,
     )?;
 
-    // function enum(id, default, values, name?, description?)
+    // function enum(id, default, values, name?, description?, automation?)
     parameter.raw_set(
         "enum",
         lua.create_function(
             |_lua,
-             (id, default, value_table, name, description): (
+             (id, default, value_table, name, description, automation): (
                 LuaValue,
                 LuaValue,
                 LuaTable,
                 LuaValue,
                 LuaValue,
+                LuaValue,
             )|
              -> LuaResult<ParameterUserData> {
                 let id = string_from_value(&id, "enum", "id", 1)?;
@@ -565,8 +810,215 @@ fn register_parameter_bindings(lua: &mut Lua) -> LuaResult<()> {
                 let name = optional_string_from_value(&name, "enum", "name", 3)?;
                 let description =
                     optional_string_from_value(&description, "enum", "description", 4)?;
+                let parameter = Parameter::with_enum(&id, &name, &description, values, default);
+                Ok(ParameterUserData {
+                    parameter: automate_parameter_from_value(parameter, &automation, "enum", 6)?,
+                })
+            },
+        )?,
+    )?;
+
+    // function note(id, default, range?, name?, description?, automation?)
+    parameter.raw_set(
+        "note",
+        lua.create_function(
+            |_lua,
+             (id, default, range, name, description, automation): (
+                LuaValue,
+                LuaValue,
+                Option<LuaTable>,
+                LuaValue,
+                LuaValue,
+                LuaValue,
+            )|
+             -> LuaResult<ParameterUserData> {
+                let id = string_from_value(&id, "note", "id", 1)?;
+                if id.is_empty() {
+                    return Err(bad_argument_error("note", "id", 1, "ids can not be empty"));
+                }
+                let default = note_parameter_value(&default, "note", "default", 2)?;
+                let range = {
+                    if let Some(range) = range {
+                        let start =
+                            note_parameter_value(&range.get::<LuaValue>(1)?, "note", "range", 3)?;
+                        let end =
+                            note_parameter_value(&range.get::<LuaValue>(2)?, "note", "range", 3)?;
+                        start..=end
+                    } else {
+                        Note::C0..=Note::G10
+                    }
+                };
+                if !range.contains(&default) {
+                    return Err(bad_argument_error(
+                        "note",
+                        "range",
+                        3,
+                        &format!(
+                            "default value must be within range {}..={}",
+                            range.start(),
+                            range.end()
+                        ),
+                    ));
+                }
+                let name = optional_string_from_value(&name, "note", "name", 4)?;
+                let description =
+                    optional_string_from_value(&description, "note", "description", 5)?;
+                let parameter = Parameter::with_note(&id, &name, &description, range, default);
                 Ok(ParameterUserData {
-                    parameter: Parameter::with_enum(&id, &name, &description, values, default),
+                    parameter: automate_parameter_from_value(parameter, &automation, "note", 6)?,
+                })
+            },
+        )?,
+    )?;
+
+    // function string(id, default, name?, description?)
+    parameter.raw_set(
+        "string",
+        lua.create_function(
+            |_lua,
+             (id, default, name, description): (LuaValue, LuaValue, LuaValue, LuaValue)|
+             -> LuaResult<ParameterUserData> {
+                let id = string_from_value(&id, "string", "id", 1)?;
+                if id.is_empty() {
+                    return Err(bad_argument_error(
+                        "string",
+                        "id",
+                        1,
+                        "ids can not be empty",
+                    ));
+                }
+                let default = string_from_value(&default, "string", "default", 2)?;
+                let name = optional_string_from_value(&name, "string", "name", 3)?;
+                let description =
+                    optional_string_from_value(&description, "string", "description", 4)?;
+                Ok(ParameterUserData {
+                    parameter: Parameter::with_string(&id, &name, &description, &default),
+                })
+            },
+        )?,
+    )?;
+
+    // function integer_array(id, default, range?, name?, description?)
+    parameter.raw_set(
+        "integer_array",
+        #[allow(clippy::unnecessary_cast)]
+        lua.create_function(
+            |_lua,
+             (id, default, range, name, description): (
+                LuaValue,
+                LuaValue,
+                Option<LuaTable>,
+                LuaValue,
+                LuaValue,
+            )|
+             -> LuaResult<ParameterUserData> {
+                let id = string_from_value(&id, "integer_array", "id", 1)?;
+                if id.is_empty() {
+                    return Err(bad_argument_error(
+                        "integer_array",
+                        "id",
+                        1,
+                        "ids can not be empty",
+                    ));
+                }
+                let default =
+                    array_parameter_values(&default, true, "integer_array", "default", 2)?
+                        .into_iter()
+                        .map(|v| v as i32)
+                        .collect::<Vec<_>>();
+                let range = {
+                    if let Some(range) = range {
+                        let start = range.get::<LuaInteger>(1)? as i32;
+                        let end = range.get::<LuaInteger>(2)? as i32;
+                        start..=end
+                    } else {
+                        0..=100
+                    }
+                };
+                if !default.iter().all(|v| range.contains(v)) {
+                    return Err(bad_argument_error(
+                        "integer_array",
+                        "range",
+                        3,
+                        &format!(
+                            "default values must be within range {}..={}",
+                            range.start(),
+                            range.end()
+                        ),
+                    ));
+                }
+                let name = optional_string_from_value(&name, "integer_array", "name", 4)?;
+                let description =
+                    optional_string_from_value(&description, "integer_array", "description", 5)?;
+                Ok(ParameterUserData {
+                    parameter: Parameter::with_integer_array(
+                        &id,
+                        &name,
+                        &description,
+                        range,
+                        default,
+                    ),
+                })
+            },
+        )?,
+    )?;
+
+    // function number_array(id, default, range?, name?, description?)
+    parameter.raw_set(
+        "number_array",
+        lua.create_function(
+            |_lua,
+             (id, default, range, name, description): (
+                LuaValue,
+                LuaValue,
+                Option<LuaTable>,
+                LuaValue,
+                LuaValue,
+            )|
+             -> LuaResult<ParameterUserData> {
+                let id = string_from_value(&id, "number_array", "id", 1)?;
+                if id.is_empty() {
+                    return Err(bad_argument_error(
+                        "number_array",
+                        "id",
+                        1,
+                        "ids can not be empty",
+                    ));
+                }
+                let default =
+                    array_parameter_values(&default, false, "number_array", "default", 2)?;
+                let range = {
+                    if let Some(range) = range {
+                        let start = range.get::<f64>(1)?;
+                        let end = range.get::<f64>(2)?;
+                        start..=end
+                    } else {
+                        0.0..=1.0
+                    }
+                };
+                if !default.iter().all(|v| range.contains(v)) {
+                    return Err(bad_argument_error(
+                        "number_array",
+                        "range",
+                        3,
+                        &format!(
+                            "default values must be within range {}..={}",
+                            range.start(),
+                            range.end()
+                        ),
+                    ));
+                }
+                let name = optional_string_from_value(&name, "number_array", "name", 4)?;
+                let description =
+                    optional_string_from_value(&description, "number_array", "description", 5)?;
+                Ok(ParameterUserData {
+                    parameter: Parameter::with_float_array(
+                        &id,
+                        &name,
+                        &description,
+                        range,
+                        default,
+                    ),
                 })
             },
         )?,
@@ -577,6 +1029,150 @@ fn register_parameter_bindings(lua: &mut Lua) -> LuaResult<()> {
     Ok(())
 }
 
+fn register_rhythm_bindings(lua: &mut Lua) -> LuaResult<()> {
+    let rhythm = lua.create_table()?;
+
+    // function markov(options)
+    rhythm.raw_set(
+        "markov",
+        lua.create_function(|_lua, options: LuaValue| -> LuaResult<RhythmUserData> {
+            markov_rhythm_from_value(&options)
+        })?,
+    )?;
+
+    // function lsystem(options)
+    rhythm.raw_set(
+        "lsystem",
+        lua.create_function(|_lua, options: LuaValue| -> LuaResult<RhythmUserData> {
+            lsystem_rhythm_from_value(&options)
+        })?,
+    )?;
+
+    // function automaton(options)
+    rhythm.raw_set(
+        "automaton",
+        lua.create_function(|_lua, options: LuaValue| -> LuaResult<RhythmUserData> {
+            automaton_rhythm_from_value(&options)
+        })?,
+    )?;
+
+    // function random_walk(options?)
+    rhythm.raw_set(
+        "random_walk",
+        lua.create_function(|lua, options: LuaValue| -> LuaResult<RhythmUserData> {
+            random_walk_rhythm_from_value(lua, &options)
+        })?,
+    )?;
+
+    lua.globals().raw_set("rhythm", rhythm)?;
+
+    Ok(())
+}
+
+fn register_gate_bindings(
+    lua: &mut Lua,
+    timeout_hook: &LuaTimeoutHook,
+    time_base: &BeatTimeBase,
+) -> LuaResult<()> {
+    let gate = lua.create_table()?;
+
+    // function threshold(value?)
+    gate.raw_set(
+        "threshold",
+        lua.create_function(|_lua, value: LuaValue| -> LuaResult<GateUserData> {
+            threshold_gate_from_value(&value)
+        })?,
+    )?;
+
+    // function probability(seed?)
+    gate.raw_set(
+        "probability",
+        lua.create_function(|_lua, seed: LuaValue| -> LuaResult<GateUserData> {
+            probability_gate_from_value(&seed)
+        })?,
+    )?;
+
+    // function every_nth_cycle(n, offset?)
+    gate.raw_set(
+        "every_nth_cycle",
+        lua.create_function(
+            |_lua, (n, offset): (LuaValue, LuaValue)| -> LuaResult<GateUserData> {
+                cycle_gate_from_values(&n, &offset)
+            },
+        )?,
+    )?;
+
+    // function first_in_bar()
+    gate.raw_set(
+        "first_in_bar",
+        lua.create_function(|_lua, ()| -> LuaResult<GateUserData> { Ok(bar_gate(true)) })?,
+    )?;
+
+    // function last_in_bar()
+    gate.raw_set(
+        "last_in_bar",
+        lua.create_function(|_lua, ()| -> LuaResult<GateUserData> { Ok(bar_gate(false)) })?,
+    )?;
+
+    // function mute(ranges, length?)
+    gate.raw_set(
+        "mute",
+        lua.create_function(
+            |_lua, (ranges, length): (LuaValue, LuaValue)| -> LuaResult<GateUserData> {
+                mute_gate_from_values(&ranges, &length)
+            },
+        )?,
+    )?;
+
+    // function density(density)
+    gate.raw_set(
+        "density",
+        lua.create_function(|_lua, density: LuaValue| -> LuaResult<GateUserData> {
+            density_gate_from_value(&density)
+        })?,
+    )?;
+
+    // function all(...)
+    gate.raw_set(
+        "all",
+        lua.create_function({
+            let timeout_hook = timeout_hook.clone();
+            let time_base = *time_base;
+            move |lua, gates: LuaMultiValue| -> LuaResult<GateUserData> {
+                logic_gate_from_values(lua, &timeout_hook, &time_base, gates, true)
+            }
+        })?,
+    )?;
+
+    // function any(...)
+    gate.raw_set(
+        "any",
+        lua.create_function({
+            let timeout_hook = timeout_hook.clone();
+            let time_base = *time_base;
+            move |lua, gates: LuaMultiValue| -> LuaResult<GateUserData> {
+                logic_gate_from_values(lua, &timeout_hook, &time_base, gates, false)
+            }
+        })?,
+    )?;
+
+    // function invert(gate)
+    gate.raw_set(
+        "invert",
+        lua.create_function({
+            let timeout_hook = timeout_hook.clone();
+            let time_base = *time_base;
+            move |lua, gate: LuaValue| -> LuaResult<GateUserData> {
+                invert_gate_from_value(lua, &timeout_hook, &time_base, &gate)
+            }
+        })?,
+    )?;
+
+    lua.globals().raw_set("gate", gate)?;
+
+    Ok(())
+}
+
 fn register_math_bindings(lua: &mut Lua) -> LuaResult<()> {
     let math = lua.globals().get::<LuaTable>("math")?;
 
@@ -698,6 +1294,16 @@ fn register_pulse_bindings(lua: &mut Lua) -> LuaResult<()> {
 
 // --------------------------------------------------------------------------------------------------
 
+// Collect notes from the given note, chord or sequence arguments, as used in function `note`.
+fn notes_from_args(args: LuaMultiValue) -> LuaResult<Vec<Note>> {
+    Ok(NoteUserData::from(args)?
+        .notes
+        .into_iter()
+        .flatten()
+        .map(|note_event| note_event.note)
+        .collect())
+}
+
 // Generate a new random number in Lua math.random style.
 fn generate_random_number<R: rand::Rng>(
     func_name: &'static str,
@@ -837,6 +1443,14 @@ mod test {
 
         // pulse.lua is present
         assert!(lua.load(r#"return pulse.new()"#).eval::<LuaTable>().is_ok());
+        assert!(lua
+            .load(r#"return pulse.timeline("tresillo"):complement()[2] == 1"#)
+            .eval::<bool>()
+            .is_ok_and(|v| v));
+        assert!(lua
+            .load(r#"return #pulse.maximally_even(3, 8, 1) == 8"#)
+            .eval::<bool>()
+            .is_ok_and(|v| v));
 
         // math.randomstate is present
         assert!(lua
@@ -872,6 +1486,255 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn script_limits() -> Result<(), Box<dyn std::error::Error>> {
+        let never_ending_loop = r#"
+            local i = 0
+            while true do
+                i = i + 1
+            end
+            "#;
+
+        // timeout
+        let (lua, mut timeout_hook) =
+            new_engine_with_options(&ScriptOptions::default().with_limits(
+                ScriptLimits::unlimited().with_timeout(std::time::Duration::from_millis(10)),
+            ))?;
+        timeout_hook.reset();
+        let err = lua.load(never_ending_loop).exec().unwrap_err();
+        assert!(matches!(
+            ScriptLimitError::from_error(&err),
+            Some(ScriptLimitError::Timeout(_))
+        ));
+
+        // instruction limit
+        let (lua, mut timeout_hook) = new_engine_with_options(
+            &ScriptOptions::default()
+                .with_limits(ScriptLimits::unlimited().with_instruction_limit(10000)),
+        )?;
+        timeout_hook.reset();
+        let err = lua.load(never_ending_loop).exec().unwrap_err();
+        assert_eq!(
+            ScriptLimitError::from_error(&err),
+            Some(ScriptLimitError::InstructionLimit(10000))
+        );
+        // instruction budget is reset with the hook
+        timeout_hook.reset();
+        assert!(lua
+            .load(r#"local i = 0; while i < 100 do i = i + 1 end"#)
+            .exec()
+            .is_ok());
+
+        // memory limit
+        let (lua, mut timeout_hook) = new_engine_with_options(
+            &ScriptOptions::default()
+                .with_limits(ScriptLimits::default().with_memory_limit(1024 * 1024)),
+        )?;
+        timeout_hook.reset();
+        let err = lua
+            .load(r#"return string.rep("x", 4 * 1024 * 1024)"#)
+            .exec()
+            .unwrap_err();
+        assert_eq!(
+            ScriptLimitError::from_error(&err),
+            Some(ScriptLimitError::MemoryLimit)
+        );
+
+        // errors from public pattern constructors
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let err = new_pattern_from_string_with_options(
+            time_base,
+            None,
+            never_ending_loop,
+            "[test never ending loop]",
+            &ScriptOptions::default()
+                .with_limits(ScriptLimits::unlimited().with_instruction_limit(10000)),
+        )
+        .unwrap_err();
+        assert_eq!(
+            ScriptLimitError::from_error(err.as_ref()),
+            Some(ScriptLimitError::InstructionLimit(10000))
+        );
+        assert!(new_pattern_from_string_with_options(
+            time_base,
+            None,
+            r#"return pattern { unit = "1/4", event = "c4" }"#,
+            "[test pattern]",
+            &ScriptOptions::default().with_limits(ScriptLimits::unlimited()),
+        )
+        .is_ok());
+
+        // overridden limits share the engine's hook
+        let (lua, mut timeout_hook) = new_engine_with_options(
+            &ScriptOptions::default().with_limits(ScriptLimits::unlimited()),
+        )?;
+        let mut strict_hook = timeout_hook.with_limits(
+            &lua,
+            &ScriptLimits::unlimited().with_instruction_limit(10000),
+        );
+        strict_hook.reset();
+        let err = lua.load(never_ending_loop).exec().unwrap_err();
+        assert_eq!(
+            ScriptLimitError::from_error(&err),
+            Some(ScriptLimitError::InstructionLimit(10000))
+        );
+        timeout_hook.reset();
+        assert!(lua
+            .load(r#"local i = 0; while i < 100000 do i = i + 1 end"#)
+            .exec()
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn pattern_script_limits() -> Result<(), Box<dyn std::error::Error>> {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+
+        // strict limits for a single pattern in an unlimited engine
+        let channel = LuaCallbackErrorChannel::new();
+        let pattern = new_pattern_from_string_with_options(
+            time_base,
+            None,
+            r#"return pattern {
+                limits = { instructions = 10000 },
+                event = function(context)
+                    while true do end
+                end
+            }"#,
+            "[strict pattern]",
+            &ScriptOptions::default()
+                .with_limits(ScriptLimits::unlimited())
+                .with_callback_errors(channel.clone()),
+        )?;
+        while pattern.borrow_mut().run_until_time(44100).is_some() {}
+        let error = channel.first_error().expect("expected a callback error");
+        assert_eq!(
+            ScriptLimitError::from_error(&error),
+            Some(ScriptLimitError::InstructionLimit(10000))
+        );
+
+        // loose limits for a single pattern in a strict engine
+        let channel = LuaCallbackErrorChannel::new();
+        let pattern = new_pattern_from_string_with_options(
+            time_base,
+            None,
+            r#"return pattern {
+                limits = { instructions = false },
+                event = function(context)
+                    local i = 0
+                    while i < 100000 do i = i + 1 end
+                    return "c4"
+                end
+            }"#,
+            "[loose pattern]",
+            &ScriptOptions::default()
+                .with_limits(ScriptLimits::unlimited().with_instruction_limit(10000))
+                .with_callback_errors(channel.clone()),
+        )?;
+        while pattern.borrow_mut().run_until_time(44100).is_some() {}
+        assert!(!channel.has_errors());
+
+        // invalid limits
+        for limits in [
+            "{ timeout = -1 }",
+            "{ timeout = true }",
+            "{ instructions = 0 }",
+            "{ instructions = 1.5 }",
+            "{ memory = 1024 }",
+            "100",
+        ] {
+            assert!(
+                new_pattern_from_string(
+                    time_base,
+                    None,
+                    &format!(r#"return pattern {{ limits = {limits}, event = "c4" }}"#),
+                    "[invalid limits]",
+                )
+                .is_err(),
+                "expected limits '{limits}' to be rejected"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn callback_errors() -> Result<(), Box<dyn std::error::Error>> {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+
+        // shared channel with a subscriber
+        let channel = LuaCallbackErrorChannel::new();
+        let reported = Rc::new(RefCell::new(Vec::new()));
+        channel.subscribe({
+            let reported = Rc::clone(&reported);
+            move |err| reported.borrow_mut().push(err.clone())
+        });
+        let options = ScriptOptions::default().with_callback_errors(channel.clone());
+
+        let pulse_pattern = new_pattern_from_string_with_options(
+            time_base,
+            None,
+            r#"return pattern {
+                pulse = function(context)
+                    assert(context.pulse_step < 3, "pulse failed")
+                    return 1
+                end,
+                event = "c4"
+            }"#,
+            "[pulse pattern]",
+            &options,
+        )?;
+        let event_pattern = new_pattern_from_string_with_options(
+            time_base,
+            None,
+            r#"return pattern { event = function(context) error("event failed") end }"#,
+            "[event pattern]",
+            &options
+                .clone()
+                .with_callback_errors(channel.with_pattern_id("events")),
+        )?;
+        assert!(!channel.has_errors());
+
+        while pulse_pattern
+            .borrow_mut()
+            .run_until_time(44100 * 4)
+            .is_some()
+        {}
+        while event_pattern.borrow_mut().run_until_time(44100).is_some() {}
+
+        let errors = channel.errors();
+        assert!(errors.len() >= 2);
+        assert_eq!(errors[0].pattern_id.as_deref(), Some("[pulse pattern]"));
+        assert_eq!(errors[0].kind, LuaCallbackKind::Pulse);
+        assert_eq!(errors[0].pulse_step, Some(2));
+        assert!(errors[0]
+            .location
+            .as_ref()
+            .is_some_and(|location| location.contains("[pulse pattern]")));
+        assert!(errors[0].error.to_string().contains("pulse failed"));
+        assert!(errors[1..]
+            .iter()
+            .all(|err| err.pattern_id.as_deref() == Some("events")
+                && err.kind == LuaCallbackKind::Event));
+        assert_eq!(reported.borrow().len(), errors.len());
+
+        channel.clear();
+        assert!(!channel.has_errors());
+        assert!(channel.first_error().is_none());
+        Ok(())
+    }
+
     #[test]
     fn create_pattern() -> Result<(), Box<dyn std::error::Error>> {
         // create a new engine and register bindings
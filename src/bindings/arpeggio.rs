@@ -0,0 +1,174 @@
+use mlua::prelude::*;
+
+use super::{
+    definitions::ARPEGGIO_PROPERTIES,
+    unwrap::{bad_argument_error, note_events_from_value, validate_table_properties},
+};
+
+use crate::emitters::{ArpeggioEmitter, ArpeggioMode};
+
+// ---------------------------------------------------------------------------------------------
+
+/// Opaque Lua Userdata impl for a native arpeggio emitter.
+pub(crate) struct ArpeggioUserData {
+    pub(crate) emitter: ArpeggioEmitter,
+}
+
+// Use default IntoLua impl for LuaUserData
+impl LuaUserData for ArpeggioUserData {}
+
+// ---------------------------------------------------------------------------------------------
+
+// create an arpeggio emitter from the given optional Lua options table
+pub(crate) fn arpeggio_from_value(value: &LuaValue) -> LuaResult<ArpeggioUserData> {
+    const FUNCTION: &str = "arpeggio";
+    let table = match value {
+        LuaValue::Nil => None,
+        LuaValue::Table(table) => {
+            validate_table_properties(table, &ARPEGGIO_PROPERTIES)?;
+            Some(table.clone())
+        }
+        _ => {
+            return Err(bad_argument_error(
+                FUNCTION,
+                "options",
+                1,
+                "expecting an options table or nil",
+            ))
+        }
+    };
+    let get = |key: &str| -> LuaResult<LuaValue> {
+        match &table {
+            Some(table) => table.get::<LuaValue>(key),
+            None => Ok(LuaValue::Nil),
+        }
+    };
+    let error = |message: &str| bad_argument_error(FUNCTION, "options", 1, message);
+    // notes
+    let notes = match get("notes")? {
+        LuaValue::Nil => Vec::new(),
+        value => note_events_from_value(&value, None)?
+            .into_iter()
+            .flatten()
+            .collect(),
+    };
+    let mut emitter = ArpeggioEmitter::new(notes);
+    // mode
+    match get("mode")? {
+        LuaValue::Nil => (),
+        LuaValue::String(mode) => {
+            let mode = ArpeggioMode::try_from(&*mode.to_string_lossy())
+                .map_err(|err| error(&format!("'mode' is invalid: {}", err)))?;
+            emitter = emitter.with_mode(mode);
+        }
+        _ => return Err(error("'mode' must be a mode name string")),
+    }
+    // octaves
+    const MAX_OCTAVES: i64 = ArpeggioEmitter::MAX_OCTAVES as i64;
+    match get("octaves")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(count) if (1..=MAX_OCTAVES).contains(&count) => {
+            emitter = emitter.with_octaves(count as usize);
+        }
+        _ => {
+            return Err(error(&format!(
+                "'octaves' must be an integer in range [1..={}]",
+                MAX_OCTAVES
+            )))
+        }
+    }
+    // repeats
+    match get("repeats")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(count) if count > 0 => emitter = emitter.with_repeats(count as usize),
+        _ => return Err(error("'repeats' must be an integer > 0")),
+    }
+    // gate_length
+    match get("gate_length")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(length) if length == 1 => emitter = emitter.with_gate_length(1.0),
+        LuaValue::Number(length) if length > 0.0 && length <= 1.0 => {
+            emitter = emitter.with_gate_length(length)
+        }
+        _ => return Err(error("'gate_length' must be a number in range (0 - 1]")),
+    }
+    // latch
+    match get("latch")? {
+        LuaValue::Nil => (),
+        LuaValue::Boolean(latch) => emitter = emitter.with_latch(latch),
+        _ => return Err(error("'latch' must be a boolean")),
+    }
+    // seed
+    match get("seed")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(seed) => emitter = emitter.with_seed(Some(seed as u64)),
+        _ => return Err(error("'seed' must be an integer value")),
+    }
+    Ok(ArpeggioUserData { emitter })
+}
+
+// ---------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{bindings::*, Emitter, Event, Note, RhythmEvent};
+
+    fn new_test_engine() -> LuaResult<Lua> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok(lua)
+    }
+
+    #[test]
+    fn arpeggio() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        let user_data = lua
+            .load(r#"return arpeggio { notes = chord("c4", "major"), mode = "down", octaves = 2 }"#)
+            .eval::<LuaAnyUserData>()?;
+        let mut emitter = user_data.borrow::<ArpeggioUserData>()?.emitter.clone();
+        let notes = (0..3)
+            .map(|_| match emitter.run(RhythmEvent::default(), true) {
+                Some(events) => match &events[0].event {
+                    Event::NoteEvents(notes) => notes[0].as_ref().map(|note| note.note),
+                    _ => None,
+                },
+                None => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(notes, vec![Some(Note::G5), Some(Note::E5), Some(Note::C5)]);
+
+        assert!(lua.load("return arpeggio()").exec().is_ok());
+        assert!(lua
+            .load(r#"return arpeggio { mode = "sideways" }"#)
+            .exec()
+            .is_err());
+        assert!(lua.load("return arpeggio { octaves = 0 }").exec().is_err());
+        assert!(lua
+            .load("return arpeggio { octaves = 1000000000 }")
+            .exec()
+            .is_err());
+        assert!(lua.load("return arpeggio { octaves = 11 }").exec().is_err());
+        assert!(lua
+            .load("return arpeggio { gate_length = 2 }")
+            .exec()
+            .is_err());
+        assert!(lua.load("return arpeggio { speed = 2 }").exec().is_err());
+
+        // arpeggios can be used in patterns
+        assert!(lua
+            .load(r#"return pattern { pulse = { 1, 1 }, event = arpeggio { notes = "c4'maj" } }"#)
+            .exec()
+            .is_ok());
+        Ok(())
+    }
+}
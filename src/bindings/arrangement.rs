@@ -0,0 +1,218 @@
+use mlua::prelude::*;
+
+use super::{phrase::PhraseUserData, rhythm::pattern_from_userdata, LuaTimeoutHook};
+
+use crate::prelude::*;
+
+// ---------------------------------------------------------------------------------------------
+
+/// Arrangement Userdata in bindings: a sequence of phrases.
+#[derive(Clone, Debug)]
+pub struct ArrangementUserData {
+    pub sequence: Sequence,
+}
+
+impl ArrangementUserData {
+    // create an arrangement from the given Lua phrase array
+    pub(crate) fn from_table(time_base: &BeatTimeBase, table: &LuaTable) -> LuaResult<Self> {
+        let mut phrases = Vec::new();
+        for (index, value) in table.clone().sequence_values::<LuaValue>().enumerate() {
+            let value = value?;
+            if let Some(phrase) = value
+                .as_userdata()
+                .filter(|user_data| user_data.is::<PhraseUserData>())
+            {
+                phrases.push(phrase.borrow::<PhraseUserData>()?.phrase.clone());
+            } else {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "phrase".to_string(),
+                    message: Some(format!("arrangement item #{} is not a phrase", index + 1)),
+                });
+            }
+        }
+        if phrases.is_empty() {
+            return Err(LuaError::RuntimeError(
+                "an arrangement needs at least one phrase".to_string(),
+            ));
+        }
+        // continue slots take over patterns from the previous phrase, which wraps around
+        for (index, phrase) in phrases.iter().enumerate() {
+            let previous_index = (index + phrases.len() - 1) % phrases.len();
+            let previous_slot_count = phrases[previous_index].pattern_slots().len();
+            for (slot_index, slot) in phrase.pattern_slots().iter().enumerate() {
+                if matches!(slot, PatternSlot::Continue) && slot_index >= previous_slot_count {
+                    return Err(LuaError::RuntimeError(format!(
+                        "phrase #{} continues pattern slot #{}, but the previous phrase #{} \
+                         only has {} pattern slots",
+                        index + 1,
+                        slot_index + 1,
+                        previous_index + 1,
+                        previous_slot_count
+                    )));
+                }
+            }
+        }
+        let sequence = Sequence::new(*time_base, phrases);
+        Ok(Self { sequence })
+    }
+}
+
+impl LuaUserData for ArrangementUserData {
+    // ArrangementUserData is only passed through ATM
+}
+
+// ---------------------------------------------------------------------------------------------
+
+// unwrap a Sequence from the given LuaValue, which is expected to be an arrangement, phrase,
+// pattern or cycle user data. Phrases and patterns are wrapped into a single phrase sequence.
+pub(crate) fn sequence_from_userdata(
+    lua: &Lua,
+    timeout_hook: &LuaTimeoutHook,
+    value: &LuaValue,
+    time_base: &BeatTimeBase,
+) -> LuaResult<Sequence> {
+    if let Some(user_data) = value.as_userdata() {
+        if user_data.is::<ArrangementUserData>() {
+            // NB: take instead of cloning: arrangement userdata has no other usage than being defined
+            Ok(user_data.take::<ArrangementUserData>()?.sequence)
+        } else if user_data.is::<PhraseUserData>() {
+            let phrase = user_data.take::<PhraseUserData>()?.phrase;
+            Ok(Sequence::new(*time_base, vec![phrase]))
+        } else {
+            let pattern = pattern_from_userdata(lua, timeout_hook, value, time_base, None)?;
+            let phrase = Phrase::new(*time_base, vec![pattern], BeatTimeStep::Bar(1.0));
+            Ok(Sequence::new(*time_base, vec![phrase]))
+        }
+    } else {
+        Err(LuaError::FromLuaConversionError {
+            from: value.type_name(),
+            to: "sequence".to_string(),
+            message: Some(
+                "script must return an arrangement, phrase, pattern or cycle".to_string(),
+            ),
+        })
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{bindings::*, BeatTimeStep, Event, Note, PatternSlot};
+
+    use super::ArrangementUserData;
+
+    fn new_test_engine() -> Result<(Lua, LuaTimeoutHook), LuaError> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok((lua, timeout_hook))
+    }
+
+    fn evaluate_arrangement_userdata(
+        lua: &Lua,
+        expression: &str,
+    ) -> LuaResult<ArrangementUserData> {
+        Ok(lua
+            .load(expression)
+            .eval::<LuaValue>()?
+            .as_userdata()
+            .ok_or(LuaError::RuntimeError("No user data".to_string()))?
+            .borrow::<ArrangementUserData>()?
+            .clone())
+    }
+
+    #[test]
+    fn arrangement() -> LuaResult<()> {
+        let (lua, _) = new_test_engine()?;
+
+        // invalid content
+        assert!(evaluate_arrangement_userdata(&lua, r#"arrangement { }"#).is_err());
+        assert!(evaluate_arrangement_userdata(&lua, r#"arrangement { "c4" }"#).is_err());
+        assert!(
+            evaluate_arrangement_userdata(&lua, r#"arrangement { pattern { event = "c4" } }"#)
+                .is_err()
+        );
+
+        // continue slots must exist in the previous phrase
+        assert!(evaluate_arrangement_userdata(
+            &lua,
+            r#"
+            local p = pattern { event = "c4" }
+            return arrangement {
+                phrase { patterns = { p } },
+                phrase { patterns = { "continue", "continue" } }
+            }
+            "#
+        )
+        .is_err());
+
+        let sequence = evaluate_arrangement_userdata(
+            &lua,
+            r#"
+            local p = pattern { event = "c4" }
+            local intro = phrase { length = 2, patterns = { p, "stop" } }
+            local main = phrase { length = 4, patterns = { "continue", cycle("e4 g4") } }
+            return arrangement { intro, main, intro }
+            "#,
+        )?
+        .sequence;
+        let phrases = sequence.phrases();
+        assert_eq!(phrases.len(), 3);
+        assert_eq!(phrases[0].length(), BeatTimeStep::Bar(2.0));
+        assert_eq!(phrases[1].length(), BeatTimeStep::Bar(4.0));
+        assert!(matches!(
+            phrases[1].pattern_slots()[0],
+            PatternSlot::Continue
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn create_sequence() -> Result<(), Box<dyn std::error::Error>> {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+
+        // patterns, cycles and phrases are wrapped into a single phrase sequence
+        for script in [
+            r#"return pattern { unit = "1/4", event = "c4" }"#,
+            r#"return cycle("c4")"#,
+            r#"return phrase { patterns = { cycle("c4") } }"#,
+        ] {
+            let sequence = new_sequence_from_string(time_base, script, "[test sequence]")?;
+            assert_eq!(sequence.phrases().len(), 1);
+        }
+
+        // arrangements
+        let mut sequence = new_sequence_from_string(
+            time_base,
+            r#"
+            return arrangement {
+                phrase { patterns = { pattern { unit = "bars", event = "c4" } } },
+                phrase { patterns = { pattern { unit = "bars", event = "d4" } } }
+            }
+            "#,
+            "[test arrangement]",
+        )?;
+        let mut notes = Vec::new();
+        sequence.consume_events_until_time(4 * 88200, &mut |_, event| {
+            if let Some(Event::NoteEvents(note_events)) = event.event {
+                notes.extend(note_events.into_iter().flatten().map(|n| n.note));
+            }
+        });
+        assert_eq!(notes, vec![Note::C4, Note::D4, Note::C4, Note::D4]);
+        Ok(())
+    }
+}
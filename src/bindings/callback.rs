@@ -1,64 +1,204 @@
-use std::{cell::RefCell, collections::HashMap, fmt::Debug, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
+    rc::Rc,
+    time::SystemTime,
+};
 
 use mlua::prelude::*;
 
-use lazy_static::lazy_static;
-use std::sync::RwLock;
+use super::LuaAppData;
 
 use crate::{BeatTimeBase, Event, Parameter, ParameterSet, RhythmEvent};
 
 // -------------------------------------------------------------------------------------------------
 
-lazy_static! {
-    static ref LUA_CALLBACK_ERRORS: RwLock<Vec<LuaError>> = Vec::new().into();
+/// Kind of a Lua callback, as reported in [`LuaCallbackError`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LuaCallbackKind {
+    /// A pattern's `pulse` function.
+    Pulse,
+    /// A pattern's `gate` function.
+    Gate,
+    /// A pattern's `event` function.
+    Event,
+    /// A cycle's `map` function.
+    CycleMap,
+    /// A cycle without a callback, e.g. when the cycle failed to generate events.
+    Cycle,
 }
 
-/// Returns some error if there are any Lua callback errors, with the !first! error that happened.
-/// Use `lua_callback_errors` to get fetch all errors since the errors got cleared.
-///
-/// ### Panics
-/// Panics if accessing the global lua callback error vector fails.
-pub fn has_lua_callback_errors() -> Option<LuaError> {
-    LUA_CALLBACK_ERRORS
-        .read()
-        .expect("Failed to lock Lua callback error vector")
-        .first()
-        .cloned()
+impl Display for LuaCallbackKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Pulse => "pulse",
+            Self::Gate => "gate",
+            Self::Event => "event",
+            Self::CycleMap => "cycle map",
+            Self::Cycle => "cycle",
+        };
+        write!(f, "{}", name)
+    }
 }
 
-/// Returns all Lua callback errors, if any. Check with `has_lua_callback_errors()` to avoid
-/// possible vec clone overhead, if that's relevant.
-///
-/// ### Panics
-/// Panics if accessing the global lua callback error vector failed.
-pub fn lua_callback_errors() -> Vec<LuaError> {
-    LUA_CALLBACK_ERRORS
-        .read()
-        .expect("Failed to lock Lua callback error vector")
-        .clone()
+// -------------------------------------------------------------------------------------------------
+
+/// A runtime error from a Lua callback, as reported to a [`LuaCallbackErrorChannel`].
+#[derive(Debug, Clone)]
+pub struct LuaCallbackError {
+    /// Id of the pattern the callback belongs to: the channel's pattern id. When unset, this is
+    /// the name of the script the pattern got created from.
+    pub pattern_id: Option<String>,
+    /// Kind of the callback which failed.
+    pub kind: LuaCallbackKind,
+    /// Source location of the callback function in form `source:line`, if known.
+    pub location: Option<String>,
+    /// Zero-based pulse step (or cycle step for cycle callbacks) at which the callback failed,
+    /// if known.
+    pub pulse_step: Option<usize>,
+    /// System time at which the error got reported.
+    pub timestamp: SystemTime,
+    /// The actual Lua error.
+    pub error: LuaError,
 }
 
-/// Clears all Lua callback errors.
-///
-/// ### Panics
-/// Panics if accessing the global lua callback error vector failed.
-pub fn clear_lua_callback_errors() {
-    LUA_CALLBACK_ERRORS
-        .write()
-        .expect("Failed to lock Lua callback error vector")
-        .clear();
+impl Display for LuaCallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Lua {} callback in pattern '{}'",
+            self.kind,
+            self.pattern_id.as_deref().unwrap_or("unknown")
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, " at '{}'", location)?;
+        }
+        if let Some(pulse_step) = self.pulse_step {
+            write!(f, " (step {})", pulse_step + 1)?;
+        }
+        write!(f, " failed to evaluate:\n{}", self.error)
+    }
 }
 
-/// Add/signal a new Lua callback errors.
+impl Error for LuaCallbackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+type LuaCallbackErrorHandler = Rc<dyn Fn(&LuaCallbackError)>;
+
+/// Collects runtime errors of Lua callbacks from one or more Lua engines and forwards them to
+/// subscribed handlers.
+///
+/// Each Lua engine reports errors to its own channel by default. To collect errors from multiple
+/// patterns in one place, pass a shared channel in the [`ScriptOptions`](super::ScriptOptions)
+/// when creating the patterns, and use `with_pattern_id` to tag errors of each pattern.
 ///
-/// ### Panics
-/// Panics if accessing the global lua callback error vector failed.
-pub fn add_lua_callback_error(name: &str, err: &LuaError) {
-    log::warn!("Lua callback '{}' failed to evaluate:\n{}", name, err);
-    LUA_CALLBACK_ERRORS
-        .write()
-        .expect("Failed to lock Lua callback error vector")
-        .push(err.clone());
+/// Clones share the same error list and handlers.
+#[derive(Clone, Default)]
+pub struct LuaCallbackErrorChannel {
+    pattern_id: Option<String>,
+    errors: Rc<RefCell<Vec<LuaCallbackError>>>,
+    handlers: Rc<RefCell<Vec<LuaCallbackErrorHandler>>>,
+}
+
+impl LuaCallbackErrorChannel {
+    /// Create a new, empty error channel without any handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a new channel which shares errors and handlers with this channel, but tags
+    /// reported errors with the given pattern id.
+    #[must_use]
+    pub fn with_pattern_id<S: Into<String>>(&self, pattern_id: S) -> Self {
+        Self {
+            pattern_id: Some(pattern_id.into()),
+            errors: Rc::clone(&self.errors),
+            handlers: Rc::clone(&self.handlers),
+        }
+    }
+
+    /// The pattern id which is used to tag errors, if any.
+    pub fn pattern_id(&self) -> Option<&str> {
+        self.pattern_id.as_deref()
+    }
+
+    /// Subscribe to errors: the given handler gets called with each new error.
+    ///
+    /// NB: Handlers are called in the thread that runs the patterns, which may be a real-time
+    /// audio thread, so avoid blocking in handlers.
+    pub fn subscribe<F: Fn(&LuaCallbackError) + 'static>(&self, handler: F) {
+        self.handlers.borrow_mut().push(Rc::new(handler));
+    }
+
+    /// Returns true if any errors got reported since the last `clear` call.
+    pub fn has_errors(&self) -> bool {
+        !self.errors.borrow().is_empty()
+    }
+
+    /// Returns the first error that got reported since the last `clear` call, if any.
+    pub fn first_error(&self) -> Option<LuaCallbackError> {
+        self.errors.borrow().first().cloned()
+    }
+
+    /// Returns all errors that got reported since the last `clear` call.
+    pub fn errors(&self) -> Vec<LuaCallbackError> {
+        self.errors.borrow().clone()
+    }
+
+    /// Clears all reported errors.
+    pub fn clear(&self) {
+        self.errors.borrow_mut().clear();
+    }
+
+    /// Report a new error: logs, collects and forwards the error to all handlers.
+    pub(crate) fn report(
+        &self,
+        kind: LuaCallbackKind,
+        location: Option<String>,
+        pulse_step: Option<usize>,
+        error: &LuaError,
+    ) {
+        let error = LuaCallbackError {
+            pattern_id: self.pattern_id.clone(),
+            kind,
+            location,
+            pulse_step,
+            timestamp: SystemTime::now(),
+            error: error.clone(),
+        };
+        log::warn!("{}", error);
+        self.errors.borrow_mut().push(error.clone());
+        // NB: clone handlers, so handlers can subscribe new handlers
+        let handlers = self.handlers.borrow().clone();
+        for handler in handlers {
+            handler(&error);
+        }
+    }
+}
+
+impl Debug for LuaCallbackErrorChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LuaCallbackErrorChannel")
+            .field("pattern_id", &self.pattern_id)
+            .field("errors", &self.errors)
+            .field("handlers", &self.handlers.borrow().len())
+            .finish()
+    }
+}
+
+/// Access the error channel of the given Lua engine.
+pub(crate) fn lua_callback_error_channel(lua: &Lua) -> LuaCallbackErrorChannel {
+    lua.app_data_ref::<LuaAppData>()
+        .expect("Failed to access Lua app data")
+        .callback_errors
+        .clone()
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -87,8 +227,9 @@ impl ContextPlaybackState {
 /// The passed context is created as an empty table with the callback, and should be filled up
 /// with values before it's called.
 ///
-/// Errors from callbacks should be handled by calling `self.handle_error` so external clients
-/// can deal with them later, as appropriate.
+/// Errors from callbacks should be handled by calling `self.handle_error`, which reports them to
+/// the Lua engine's [`LuaCallbackErrorChannel`], so external clients can deal with them later,
+/// as appropriate.
 ///
 /// By memorizing the original generator function and environment, it also can be reset to its
 /// initial state by calling the original generator function again to fetch a new freshly
@@ -103,13 +244,14 @@ pub(crate) struct LuaCallback {
     generator: Option<LuaFunction>,
     function: LuaFunction,
     initialized: bool,
-    #[allow(unused)]
+    kind: LuaCallbackKind,
+    location: Option<String>,
     lua: Lua,
 }
 
 impl LuaCallback {
-    /// Create a new Callback from a lua function.
-    pub fn new(lua: &Lua, function: LuaFunction) -> LuaResult<Self> {
+    /// Create a new Callback of the given kind from a lua function.
+    pub fn new(lua: &Lua, function: LuaFunction, kind: LuaCallbackKind) -> LuaResult<Self> {
         // create a strong lua ref, to ensure the function stays valid
         let lua = lua.clone();
         // create a new callback context
@@ -118,12 +260,20 @@ impl LuaCallback {
         let environment = function.environment();
         let generator = None;
         let initialized = false;
+        // memorize the function's source location for error reports
+        let info = function.info();
+        let location = info.short_src.map(|source| match info.line_defined {
+            Some(line) => format!("{}:{}", source, line),
+            None => source,
+        });
         Ok(Self {
             environment,
             context,
             generator,
             function,
             initialized,
+            kind,
+            location,
             lua,
         })
     }
@@ -316,10 +466,28 @@ impl LuaCallback {
         }
     }
 
-    /// Report a Lua callback errors. The error will be logged and usually cleared after
-    /// the next callback call.
+    /// Report a Lua callback error to the Lua engine's error channel. The error will be
+    /// logged and forwarded to the channel's subscribers.
     pub fn handle_error(&self, err: &LuaError) {
-        add_lua_callback_error(&self.name(), err)
+        // fetch the current step from the context
+        let step_key: &[u8] = match self.kind {
+            LuaCallbackKind::CycleMap | LuaCallbackKind::Cycle => b"step",
+            _ => b"pulse_step",
+        };
+        let pulse_step = self
+            .context
+            .borrow::<CallbackContext>()
+            .ok()
+            .and_then(|context| match context.values.get(step_key) {
+                Some(ContextValue::Number(step)) => Some((*step as usize).saturating_sub(1)),
+                _ => None,
+            });
+        lua_callback_error_channel(&self.lua).report(
+            self.kind,
+            self.location.clone(),
+            pulse_step,
+            err,
+        );
     }
 
     /// Reset the callback function or iterator to its initial state.
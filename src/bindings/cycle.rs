@@ -233,8 +233,11 @@ mod test {
                     end
                 end)"#,
         )?;
-        let mapping_callback =
-            LuaCallback::new(&lua, mapped_cycle.mapping_function.unwrap().clone())?;
+        let mapping_callback = LuaCallback::new(
+            &lua,
+            mapped_cycle.mapping_function.unwrap().clone(),
+            LuaCallbackKind::CycleMap,
+        )?;
         let mut event_iter = ScriptedCycleEmitter::with_mapping_callback(
             mapped_cycle.cycle,
             &timeout_hook,
@@ -0,0 +1,815 @@
+//! Declarative description of the Lua API which is exposed by the bindings.
+//!
+//! The descriptions are used to validate option tables in the bindings, and in tests to verify
+//! that the LuaLS type definitions in `types/pattrns/library` match what the bindings register.
+
+// -------------------------------------------------------------------------------------------------
+
+/// Description of a global function or table function (`table.name`), or of a class method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) struct LuaFunctionDefinition {
+    /// Function name, including the table name for table functions (e.g. `parameter.boolean`).
+    pub name: &'static str,
+    /// Parameter names and LuaLS types, as declared in the type definitions. Variadic parameters
+    /// are named `...`. Optional parameters have a `?` type suffix.
+    pub params: &'static [(&'static str, &'static str)],
+}
+
+impl LuaFunctionDefinition {
+    const fn new(name: &'static str, params: &'static [(&'static str, &'static str)]) -> Self {
+        Self { name, params }
+    }
+}
+
+/// Description of a userdata class with its fields and methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) struct LuaClassDefinition {
+    /// Class name, as declared in the type definitions.
+    pub name: &'static str,
+    /// Lua expression which creates an instance of the class.
+    pub constructor: &'static str,
+    /// Readable fields.
+    pub fields: &'static [&'static str],
+    /// Methods, called with the `:` syntax.
+    pub methods: &'static [LuaFunctionDefinition],
+}
+
+/// Description of a table which is passed as options to a global function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) struct LuaOptionsDefinition {
+    /// Class name of the options table, as declared in the type definitions.
+    pub name: &'static str,
+    /// Valid keys of the options table.
+    pub properties: &'static [&'static str],
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Valid option keys of the global `pattern` function.
+pub(crate) const RHYTHM_PROPERTIES: [&str; 11] = [
+    "unit",
+    "resolution",
+    "offset",
+    "repeats",
+    "parameter",
+    "pulse",
+    "dynamics",
+    "probability",
+    "gate",
+    "event",
+    "limits",
+];
+
+/// Valid keys of the `limits` table in the global `pattern` function.
+pub(crate) const SCRIPT_LIMITS_PROPERTIES: [&str; 2] = ["timeout", "instructions"];
+
+/// Valid option keys of the global `phrase` function.
+pub(crate) const PHRASE_PROPERTIES: [&str; 3] = ["unit", "length", "patterns"];
+
+/// Valid keys of parameter automation tables in the `parameter.*` functions.
+pub(crate) const PARAMETER_AUTOMATION_PROPERTIES: [&str; 8] = [
+    "curve", "length", "lfo", "period", "phase", "range", "steps", "step",
+];
+
+/// Valid option keys of the global `voice_leading` function.
+pub(crate) const VOICE_LEADING_PROPERTIES: [&str; 3] = ["range", "voices", "fixed_bass"];
+
+/// Valid option keys of the global `arpeggio` function.
+pub(crate) const ARPEGGIO_PROPERTIES: [&str; 7] = [
+    "notes",
+    "mode",
+    "octaves",
+    "repeats",
+    "gate_length",
+    "latch",
+    "seed",
+];
+
+/// Valid option keys of the global `melody` function.
+pub(crate) const MELODY_PROPERTIES: [&str; 8] = [
+    "scale",
+    "range",
+    "max_leap",
+    "step_probability",
+    "contour",
+    "avoid_repeats",
+    "phrase_length",
+    "seed",
+];
+
+/// Valid option keys of the `rhythm.markov` function.
+pub(crate) const MARKOV_RHYTHM_PROPERTIES: [&str; 5] =
+    ["states", "transitions", "start", "seed", "length"];
+
+/// Valid option keys of the `rhythm.lsystem` function.
+pub(crate) const LSYSTEM_RHYTHM_PROPERTIES: [&str; 5] =
+    ["axiom", "rules", "values", "iterations", "length"];
+
+/// Valid option keys of the `rhythm.automaton` function.
+pub(crate) const AUTOMATON_RHYTHM_PROPERTIES: [&str; 3] = ["rule", "cells", "length"];
+
+/// Valid option keys of the `rhythm.random_walk` function.
+pub(crate) const RANDOM_WALK_RHYTHM_PROPERTIES: [&str; 6] =
+    ["start", "min", "max", "step", "seed", "length"];
+
+// -------------------------------------------------------------------------------------------------
+
+/// Global functions and functions in global tables. Overloads are listed as separate entries.
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) const GLOBAL_FUNCTIONS: &[LuaFunctionDefinition] = &[
+    LuaFunctionDefinition::new("scale", &[("key", "string|number"), ("mode", "ScaleMode")]),
+    LuaFunctionDefinition::new(
+        "scale",
+        &[("key", "string|number"), ("intervals", "integer[]")],
+    ),
+    LuaFunctionDefinition::new("scale_names", &[]),
+    LuaFunctionDefinition::new("detect_scale", &[("...", "NoteValue")]),
+    LuaFunctionDefinition::new("note", &[("...", "NoteValue")]),
+    LuaFunctionDefinition::new("note_number", &[("note", "NoteValue")]),
+    LuaFunctionDefinition::new("chord", &[("key", "NoteValue"), ("mode", "ChordName")]),
+    LuaFunctionDefinition::new("chord_names", &[]),
+    LuaFunctionDefinition::new("detect_chord", &[("...", "NoteValue")]),
+    LuaFunctionDefinition::new("voice_leading", &[("options", "VoiceLeadingOptions?")]),
+    LuaFunctionDefinition::new("sequence", &[("...", "NoteValue|Note")]),
+    LuaFunctionDefinition::new("cycle", &[("input", "string")]),
+    LuaFunctionDefinition::new("pattern", &[("options", "PatternOptions")]),
+    LuaFunctionDefinition::new("arpeggio", &[("options", "ArpeggioOptions?")]),
+    LuaFunctionDefinition::new("melody", &[("options", "MelodyOptions")]),
+    LuaFunctionDefinition::new("phrase", &[("options", "PhraseOptions")]),
+    LuaFunctionDefinition::new("arrangement", &[("phrases", "Phrase[]")]),
+    LuaFunctionDefinition::new(
+        "parameter.boolean",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterBooleanDefault"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+            ("automation", "ParameterAutomation?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.integer",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterIntegerDefault"),
+            ("range", "ParameterIntegerRange?"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+            ("automation", "ParameterAutomation?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.number",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterNumberDefault"),
+            ("range", "ParameterNumberRange?"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+            ("automation", "ParameterAutomation?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.enum",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterEnumDefault"),
+            ("values", "string[]"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+            ("automation", "ParameterAutomation?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.note",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterNoteDefault"),
+            ("range", "ParameterNoteRange?"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+            ("automation", "ParameterAutomation?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.string",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterStringDefault"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.integer_array",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterIntegerArrayDefault"),
+            ("range", "ParameterIntegerRange?"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+        ],
+    ),
+    LuaFunctionDefinition::new(
+        "parameter.number_array",
+        &[
+            ("id", "ParameterId"),
+            ("default", "ParameterNumberArrayDefault"),
+            ("range", "ParameterNumberRange?"),
+            ("name", "ParameterName?"),
+            ("description", "ParameterDescription?"),
+        ],
+    ),
+    LuaFunctionDefinition::new("rhythm.markov", &[("options", "MarkovRhythmOptions")]),
+    LuaFunctionDefinition::new("rhythm.lsystem", &[("options", "LSystemRhythmOptions")]),
+    LuaFunctionDefinition::new("rhythm.automaton", &[("options", "AutomatonRhythmOptions")]),
+    LuaFunctionDefinition::new(
+        "rhythm.random_walk",
+        &[("options", "RandomWalkRhythmOptions?")],
+    ),
+    LuaFunctionDefinition::new("gate.threshold", &[("value", "number?")]),
+    LuaFunctionDefinition::new("gate.probability", &[("seed", "integer?")]),
+    LuaFunctionDefinition::new(
+        "gate.every_nth_cycle",
+        &[("n", "integer"), ("offset", "integer?")],
+    ),
+    LuaFunctionDefinition::new("gate.first_in_bar", &[]),
+    LuaFunctionDefinition::new("gate.last_in_bar", &[]),
+    LuaFunctionDefinition::new(
+        "gate.mute",
+        &[("ranges", "[number, number][]"), ("length", "number?")],
+    ),
+    LuaFunctionDefinition::new("gate.density", &[("density", "number|ParameterId")]),
+    LuaFunctionDefinition::new("gate.all", &[("...", "GateValue")]),
+    LuaFunctionDefinition::new("gate.any", &[("...", "GateValue")]),
+    LuaFunctionDefinition::new("gate.invert", &[("gate", "GateValue")]),
+];
+
+/// Global tables which are implemented in Lua and thus have no definitions here.
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) const LUA_MODULES: &[&str] = &["pulse"];
+
+/// Methods of note and sequence user data.
+const NOTE_METHODS: &[LuaFunctionDefinition] = &[
+    LuaFunctionDefinition::new("transpose", &[("step", "integer|integer[]")]),
+    LuaFunctionDefinition::new("amplify", &[("factor", "number|number[]")]),
+    LuaFunctionDefinition::new("instrument", &[("instrument", "number|number[]")]),
+    LuaFunctionDefinition::new("volume", &[("volume", "number|number[]")]),
+    LuaFunctionDefinition::new("panning", &[("panning", "number|number[]")]),
+    LuaFunctionDefinition::new("delay", &[("delay", "number|number[]")]),
+    LuaFunctionDefinition::new("length", &[("length", "number|number[]")]),
+    LuaFunctionDefinition::new(
+        "attribute",
+        &[("name", "string"), ("value", "number|number[]")],
+    ),
+];
+
+/// User data classes which are returned by the global functions. Opaque classes, which are only
+/// passed through, are listed with no fields and methods.
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) const CLASSES: &[LuaClassDefinition] = &[
+    LuaClassDefinition {
+        name: "Note",
+        constructor: r#"note("c4")"#,
+        fields: &["notes"],
+        methods: NOTE_METHODS,
+    },
+    LuaClassDefinition {
+        name: "Sequence",
+        constructor: r#"sequence("c4", "e4")"#,
+        fields: &["notes"],
+        methods: NOTE_METHODS,
+    },
+    LuaClassDefinition {
+        name: "Scale",
+        constructor: r#"scale("c4", "major")"#,
+        fields: &["key", "mode", "notes"],
+        methods: &[
+            LuaFunctionDefinition::new(
+                "chord",
+                &[("degree", "DegreeValue"), ("note_count", "integer?")],
+            ),
+            LuaFunctionDefinition::new("degree", &[("...", "DegreeValue")]),
+            LuaFunctionDefinition::new("notes_iter", &[("count", "integer?")]),
+            LuaFunctionDefinition::new("fit", &[("...", "NoteValue")]),
+            LuaFunctionDefinition::new("progression", &[("progression", "string")]),
+        ],
+    },
+    LuaClassDefinition {
+        name: "VoiceLeading",
+        constructor: r#"voice_leading()"#,
+        fields: &["voicing"],
+        methods: &[
+            LuaFunctionDefinition::new("voice", &[("...", "NoteValue")]),
+            LuaFunctionDefinition::new("voice_sequence", &[("...", "Sequence|NoteValue")]),
+            LuaFunctionDefinition::new("reset", &[]),
+        ],
+    },
+    LuaClassDefinition {
+        name: "Cycle",
+        constructor: r#"cycle("c4 e4")"#,
+        fields: &[],
+        methods: &[LuaFunctionDefinition::new(
+            "map",
+            &[(
+                "map",
+                "{ [string]: CycleMapNoteValue }|CycleMapFunction|CycleMapGenerator",
+            )],
+        )],
+    },
+    LuaClassDefinition {
+        name: "Pattern",
+        constructor: r#"pattern { pulse = { 1 } }"#,
+        fields: &[],
+        methods: &[
+            LuaFunctionDefinition::new("fast", &[("factor", "number")]),
+            LuaFunctionDefinition::new("slow", &[("factor", "number")]),
+            LuaFunctionDefinition::new("rev", &[]),
+            LuaFunctionDefinition::new("ply", &[("count", "integer")]),
+            LuaFunctionDefinition::new("degrade", &[("amount", "number?")]),
+            LuaFunctionDefinition::new(
+                "every",
+                &[
+                    ("count", "integer"),
+                    ("fn", "fun(pattern: Pattern): Pattern"),
+                ],
+            ),
+            LuaFunctionDefinition::new("sometimes", &[("fn", "fun(pattern: Pattern): Pattern")]),
+            LuaFunctionDefinition::new(
+                "off",
+                &[
+                    ("amount", "number"),
+                    ("fn", "(fun(pattern: Pattern): Pattern)?"),
+                ],
+            ),
+        ],
+    },
+    LuaClassDefinition {
+        name: "Phrase",
+        constructor: r#"phrase { patterns = { pattern { pulse = { 1 } } } }"#,
+        fields: &[],
+        methods: &[],
+    },
+    LuaClassDefinition {
+        name: "Arrangement",
+        constructor: r#"arrangement { phrase { patterns = { pattern { pulse = { 1 } } } } }"#,
+        fields: &[],
+        methods: &[],
+    },
+    LuaClassDefinition {
+        name: "Arpeggio",
+        constructor: r#"arpeggio()"#,
+        fields: &[],
+        methods: &[],
+    },
+    LuaClassDefinition {
+        name: "Melody",
+        constructor: r#"melody { scale = scale("c4", "major") }"#,
+        fields: &[],
+        methods: &[],
+    },
+    LuaClassDefinition {
+        name: "Rhythm",
+        constructor: r#"rhythm.random_walk()"#,
+        fields: &[],
+        methods: &[],
+    },
+    LuaClassDefinition {
+        name: "Gate",
+        constructor: r#"gate.threshold()"#,
+        fields: &[],
+        methods: &[],
+    },
+    LuaClassDefinition {
+        name: "Parameter",
+        constructor: r#"parameter.boolean("id", true)"#,
+        fields: &[],
+        methods: &[],
+    },
+];
+
+/// Option tables of global functions.
+#[cfg_attr(not(test), allow(dead_code))]
+pub(crate) const OPTIONS: &[LuaOptionsDefinition] = &[
+    LuaOptionsDefinition {
+        name: "PatternOptions",
+        properties: &RHYTHM_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "ScriptLimitsOptions",
+        properties: &SCRIPT_LIMITS_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "PhraseOptions",
+        properties: &PHRASE_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "ParameterAutomation",
+        properties: &PARAMETER_AUTOMATION_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "VoiceLeadingOptions",
+        properties: &VOICE_LEADING_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "ArpeggioOptions",
+        properties: &ARPEGGIO_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "MelodyOptions",
+        properties: &MELODY_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "MarkovRhythmOptions",
+        properties: &MARKOV_RHYTHM_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "LSystemRhythmOptions",
+        properties: &LSYSTEM_RHYTHM_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "AutomatonRhythmOptions",
+        properties: &AUTOMATON_RHYTHM_PROPERTIES,
+    },
+    LuaOptionsDefinition {
+        name: "RandomWalkRhythmOptions",
+        properties: &RANDOM_WALK_RHYTHM_PROPERTIES,
+    },
+];
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::bindings::*;
+
+    // LuaLS type definitions of the bindings. Lua implemented modules and extensions are skipped.
+    const TYPE_DEFINITIONS: &[&str] = &[
+        include_str!("../../types/pattrns/library/arpeggio.lua"),
+        include_str!("../../types/pattrns/library/arrangement.lua"),
+        include_str!("../../types/pattrns/library/chord.lua"),
+        include_str!("../../types/pattrns/library/cycle.lua"),
+        include_str!("../../types/pattrns/library/gate.lua"),
+        include_str!("../../types/pattrns/library/melody.lua"),
+        include_str!("../../types/pattrns/library/note.lua"),
+        include_str!("../../types/pattrns/library/parameter.lua"),
+        include_str!("../../types/pattrns/library/pattern.lua"),
+        include_str!("../../types/pattrns/library/phrase.lua"),
+        include_str!("../../types/pattrns/library/rhythm.lua"),
+        include_str!("../../types/pattrns/library/scale.lua"),
+        include_str!("../../types/pattrns/library/sequence.lua"),
+        include_str!("../../types/pattrns/library/voicing.lua"),
+    ];
+
+    // Function name with parameter names and types.
+    type Signature = (String, Vec<(String, String)>);
+
+    // Declarations parsed from the type definition files.
+    #[derive(Default)]
+    struct Declarations {
+        functions: BTreeSet<Signature>,
+        methods: BTreeMap<String, BTreeSet<Signature>>,
+        fields: BTreeMap<String, BTreeSet<String>>,
+    }
+
+    fn parse_signature(declaration: &str, param_types: &[(String, String)]) -> Signature {
+        let (name, rest) = declaration
+            .split_once('(')
+            .unwrap_or_else(|| panic!("Invalid function declaration: '{declaration}'"));
+        let (params, _) = rest
+            .split_once(')')
+            .unwrap_or_else(|| panic!("Invalid function declaration: '{declaration}'"));
+        let params = params
+            .split(',')
+            .map(str::trim)
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let param_type = param_types
+                    .iter()
+                    .find(|(name, _)| name == param)
+                    .map(|(_, param_type)| param_type.clone())
+                    .unwrap_or_else(|| {
+                        panic!("Missing @param type of '{param}' in '{declaration}'")
+                    });
+                (param.to_string(), param_type)
+            })
+            .collect();
+        (name.trim().to_string(), params)
+    }
+
+    // Parse name and type of a `---@param name type description` annotation. Types end at the
+    // first whitespace outside of brackets, which does not follow a function's return type colon.
+    fn parse_param(annotation: &str) -> (String, String) {
+        let (name, rest) = annotation.split_once(' ').unwrap_or((annotation, ""));
+        let mut depth = 0;
+        let mut param_type = String::new();
+        for char in rest.trim().chars() {
+            match char {
+                '(' | '[' | '{' | '<' => depth += 1,
+                ')' | ']' | '}' | '>' => depth -= 1,
+                _ => (),
+            }
+            if char.is_whitespace() && depth == 0 && !param_type.ends_with(':') {
+                break;
+            }
+            param_type.push(char);
+        }
+        (name.to_string(), param_type)
+    }
+
+    fn parse_declarations() -> Declarations {
+        let mut declarations = Declarations::default();
+        for content in TYPE_DEFINITIONS {
+            let mut current_class = None;
+            let mut param_types = Vec::new();
+            for line in content.lines() {
+                if let Some(param) = line.strip_prefix("---@param ") {
+                    param_types.push(parse_param(param));
+                }
+                if let Some(class) = line.strip_prefix("---@class ") {
+                    let name = class.split([' ', ':']).next().unwrap_or_default();
+                    declarations.fields.entry(name.to_string()).or_default();
+                    current_class = Some(name.to_string());
+                } else if let Some(field) = line.strip_prefix("---@field ") {
+                    if let Some(class) = &current_class {
+                        let name = field.split_whitespace().next().unwrap_or_default();
+                        let name = name.trim_end_matches('?').to_string();
+                        declarations
+                            .fields
+                            .entry(class.clone())
+                            .or_default()
+                            .insert(name);
+                    }
+                } else if !line.starts_with("---") {
+                    current_class = None;
+                }
+                if let Some(declaration) = line.strip_prefix("function ") {
+                    let (name, params) = parse_signature(declaration, &param_types);
+                    param_types.clear();
+                    if let Some((class, method)) = name.split_once(':') {
+                        declarations
+                            .methods
+                            .entry(class.to_string())
+                            .or_default()
+                            .insert((method.to_string(), params));
+                    } else {
+                        declarations.functions.insert((name, params));
+                    }
+                }
+            }
+        }
+        declarations
+    }
+
+    fn signatures(functions: &[LuaFunctionDefinition]) -> BTreeSet<Signature> {
+        functions
+            .iter()
+            .map(|function| {
+                (
+                    function.name.to_string(),
+                    function
+                        .params
+                        .iter()
+                        .map(|(name, param_type)| (name.to_string(), param_type.to_string()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn new_test_engine() -> Result<(Lua, LuaTimeoutHook), LuaError> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok((lua, timeout_hook))
+    }
+
+    // Test engine with registered bindings and the debug library, which is used to inspect the
+    // fields and methods of user data metatables.
+    #[cfg(not(any(feature = "luau", feature = "luau-jit")))]
+    fn new_debug_engine() -> Result<Lua, LuaError> {
+        // SAFETY: the debug library is only used to inspect metatables and upvalues in tests.
+        let mut lua = unsafe {
+            Lua::unsafe_new_with(
+                LuaStdLib::STRING | LuaStdLib::TABLE | LuaStdLib::MATH | LuaStdLib::DEBUG,
+                LuaOptions::default(),
+            )
+        };
+        lua.set_app_data(LuaAppData::new(LuaCallbackErrorChannel::new()));
+        let mut timeout_hook = LuaTimeoutHook::new(&lua);
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok(lua)
+    }
+
+    #[test]
+    fn type_definitions() {
+        let declarations = parse_declarations();
+
+        // global functions
+        assert_eq!(
+            declarations.functions,
+            signatures(GLOBAL_FUNCTIONS),
+            "global functions in the type definitions do not match the bindings"
+        );
+
+        // classes
+        for class in CLASSES {
+            assert_eq!(
+                declarations
+                    .methods
+                    .get(class.name)
+                    .cloned()
+                    .unwrap_or_default(),
+                signatures(class.methods),
+                "methods of class '{}' in the type definitions do not match the bindings",
+                class.name
+            );
+            assert_eq!(
+                declarations.fields.get(class.name).cloned(),
+                Some(class.fields.iter().map(|f| f.to_string()).collect()),
+                "fields of class '{}' in the type definitions do not match the bindings",
+                class.name
+            );
+        }
+        for class in declarations.methods.keys() {
+            assert!(
+                CLASSES.iter().any(|c| c.name == class),
+                "class '{class}' in the type definitions has no binding definition"
+            );
+        }
+
+        // options
+        for options in OPTIONS {
+            assert_eq!(
+                declarations.fields.get(options.name).cloned(),
+                Some(options.properties.iter().map(|p| p.to_string()).collect()),
+                "fields of '{}' in the type definitions do not match the bindings",
+                options.name
+            );
+        }
+    }
+
+    #[test]
+    fn registered_bindings() -> LuaResult<()> {
+        let (plain_lua, _) = new_engine()?;
+        let (lua, _) = new_test_engine()?;
+
+        // global functions and tables
+        let plain_globals = plain_lua
+            .globals()
+            .pairs::<String, LuaValue>()
+            .map(|pair| pair.map(|(key, _)| key))
+            .collect::<LuaResult<BTreeSet<_>>>()?;
+        let mut registered = BTreeSet::new();
+        for pair in lua.globals().pairs::<String, LuaValue>() {
+            let (key, value) = pair?;
+            if plain_globals.contains(&key) || LUA_MODULES.contains(&key.as_str()) {
+                continue;
+            }
+            match value {
+                LuaValue::Function(_) => {
+                    registered.insert(key);
+                }
+                LuaValue::Table(table) => {
+                    for pair in table.pairs::<String, LuaValue>() {
+                        let (name, value) = pair?;
+                        assert!(value.is_function(), "'{key}.{name}' is not a function");
+                        registered.insert(format!("{key}.{name}"));
+                    }
+                }
+                _ => panic!("unexpected global '{key}'"),
+            }
+        }
+        let defined = GLOBAL_FUNCTIONS
+            .iter()
+            .map(|function| function.name.to_string())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(registered, defined);
+
+        // user data fields and methods which are defined
+        for class in CLASSES {
+            for field in class.fields {
+                assert!(
+                    lua.load(format!("return ({}).{}", class.constructor, field))
+                        .eval::<LuaValue>()
+                        .is_ok_and(|value| !value.is_nil()),
+                    "missing field '{}' in class '{}'",
+                    field,
+                    class.name
+                );
+            }
+            for method in class.methods {
+                assert_eq!(
+                    lua.load(format!(
+                        "return type(({}).{})",
+                        class.constructor, method.name
+                    ))
+                    .eval::<String>()?,
+                    "function",
+                    "missing method '{}' in class '{}'",
+                    method.name,
+                    class.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "luau", feature = "luau-jit")))]
+    fn registered_user_data() -> LuaResult<()> {
+        let lua = new_debug_engine()?;
+
+        // Collect names of methods and field getters from a user data's `__index` metamethod,
+        // which either is the method table, or a function with method and getter table upvalues.
+        let inspect = lua
+            .load(
+                r#"
+                local function collect(table, names)
+                    for name, value in pairs(table) do
+                        if type(value) == "function" then
+                            names[#names + 1] = name
+                        end
+                    end
+                end
+                return function(value)
+                    local methods, fields = {}, {}
+                    local index = debug.getmetatable(value).__index
+                    if type(index) == "table" then
+                        collect(index, methods)
+                    elseif type(index) == "function" then
+                        local i = 1
+                        while true do
+                            local name, upvalue = debug.getupvalue(index, i)
+                            if name == nil then
+                                break
+                            elseif type(upvalue) == "table" then
+                                if name == "field_getters" then
+                                    collect(upvalue, fields)
+                                elseif name == "__index" or name == "methods" then
+                                    collect(upvalue, methods)
+                                end
+                            end
+                            i = i + 1
+                        end
+                    end
+                    return methods, fields
+                end
+                "#,
+            )
+            .eval::<LuaFunction>()?;
+
+        for class in CLASSES {
+            let value = lua
+                .load(format!("return {}", class.constructor))
+                .eval::<LuaAnyUserData>()?;
+            let (methods, fields) = inspect.call::<(Vec<String>, Vec<String>)>(value)?;
+            assert_eq!(
+                methods.into_iter().collect::<BTreeSet<_>>(),
+                class
+                    .methods
+                    .iter()
+                    .map(|method| method.name.to_string())
+                    .collect::<BTreeSet<_>>(),
+                "registered methods of class '{}' do not match the definitions",
+                class.name
+            );
+            assert_eq!(
+                fields.into_iter().collect::<BTreeSet<_>>(),
+                class
+                    .fields
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect::<BTreeSet<_>>(),
+                "registered fields of class '{}' do not match the definitions",
+                class.name
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use mlua::prelude::*;
+
+use super::{
+    generative::property_from_value,
+    unwrap::{bad_argument_error, gate_from_value},
+    LuaTimeoutHook,
+};
+
+use crate::{
+    gates::{
+        AndGate, BarGate, CycleGate, DensityGate, MuteGate, NotGate, OrGate, ProbabilityGate,
+        ThresholdGate,
+    },
+    BeatTimeBase, Gate,
+};
+
+// ---------------------------------------------------------------------------------------------
+
+/// Opaque Lua Userdata impl for a native gate.
+pub(crate) struct GateUserData {
+    pub(crate) gate: Box<dyn Gate>,
+}
+
+impl GateUserData {
+    fn new<G: Gate + 'static>(gate: G) -> Self {
+        Self {
+            gate: Box::new(gate),
+        }
+    }
+}
+
+// Use default IntoLua impl for LuaUserData
+impl LuaUserData for GateUserData {}
+
+// ---------------------------------------------------------------------------------------------
+
+// create a threshold gate from the given optional threshold value
+pub(crate) fn threshold_gate_from_value(value: &LuaValue) -> LuaResult<GateUserData> {
+    let threshold = match value {
+        LuaValue::Nil => 0.0,
+        LuaValue::Integer(value) => *value as f32,
+        LuaValue::Number(value) => *value as f32,
+        _ => {
+            return Err(bad_argument_error(
+                "gate.threshold",
+                "value",
+                1,
+                "expecting a number or nil",
+            ))
+        }
+    };
+    Ok(GateUserData::new(ThresholdGate::with_threshold(threshold)))
+}
+
+// create a probability gate from the given optional seed value
+pub(crate) fn probability_gate_from_value(value: &LuaValue) -> LuaResult<GateUserData> {
+    let seed = match value {
+        LuaValue::Nil => None,
+        LuaValue::Integer(seed) => Some(*seed as u64),
+        _ => {
+            return Err(bad_argument_error(
+                "gate.probability",
+                "seed",
+                1,
+                "expecting an integer or nil",
+            ))
+        }
+    };
+    Ok(GateUserData::new(ProbabilityGate::new(seed)))
+}
+
+// create a cycle gate from the given every and optional offset values
+pub(crate) fn cycle_gate_from_values(
+    every: &LuaValue,
+    offset: &LuaValue,
+) -> LuaResult<GateUserData> {
+    const FUNCTION: &str = "gate.every_nth_cycle";
+    let every = match every {
+        LuaValue::Integer(every) if *every > 0 => *every as usize,
+        _ => {
+            return Err(bad_argument_error(
+                FUNCTION,
+                "n",
+                1,
+                "expecting an integer > 0",
+            ))
+        }
+    };
+    let offset = match offset {
+        LuaValue::Nil => 0,
+        LuaValue::Integer(offset) if *offset >= 0 => *offset as usize,
+        _ => {
+            return Err(bad_argument_error(
+                FUNCTION,
+                "offset",
+                2,
+                "expecting an integer >= 0 or nil",
+            ))
+        }
+    };
+    Ok(GateUserData::new(CycleGate::new(every, offset)))
+}
+
+// create a first or last in bar gate
+pub(crate) fn bar_gate(first: bool) -> GateUserData {
+    if first {
+        GateUserData::new(BarGate::first())
+    } else {
+        GateUserData::new(BarGate::last())
+    }
+}
+
+// create a mute gate from the given ranges table and optional length value
+pub(crate) fn mute_gate_from_values(
+    ranges: &LuaValue,
+    length: &LuaValue,
+) -> LuaResult<GateUserData> {
+    const FUNCTION: &str = "gate.mute";
+    let ranges_error = || {
+        bad_argument_error(
+            FUNCTION,
+            "ranges",
+            1,
+            "expecting a table of { start, end } beat time ranges",
+        )
+    };
+    let table = ranges.as_table().ok_or_else(ranges_error)?;
+    let mut mute_ranges = Vec::new();
+    for range in table.clone().sequence_values::<LuaTable>() {
+        let range = range.map_err(|_| ranges_error())?;
+        let start = range.get::<f64>(1).map_err(|_| ranges_error())?;
+        let end = range.get::<f64>(2).map_err(|_| ranges_error())?;
+        if end < start {
+            return Err(bad_argument_error(
+                FUNCTION,
+                "ranges",
+                1,
+                "range ends must be >= range starts",
+            ));
+        }
+        mute_ranges.push(start..end);
+    }
+    let length = match length {
+        LuaValue::Nil => None,
+        LuaValue::Integer(length) if *length > 0 => Some(*length as f64),
+        LuaValue::Number(length) if *length > 0.0 => Some(*length),
+        _ => {
+            return Err(bad_argument_error(
+                FUNCTION,
+                "length",
+                2,
+                "expecting a number > 0 or nil",
+            ))
+        }
+    };
+    Ok(GateUserData::new(MuteGate::new(mute_ranges, length)))
+}
+
+// create a density gate from the given number or parameter id
+pub(crate) fn density_gate_from_value(value: &LuaValue) -> LuaResult<GateUserData> {
+    let density = property_from_value(value, "density", 1.0, "gate.density")?;
+    Ok(GateUserData::new(DensityGate::new(density)))
+}
+
+// create an and or or gate from the given gate or gate function values
+pub(crate) fn logic_gate_from_values(
+    lua: &Lua,
+    timeout_hook: &LuaTimeoutHook,
+    time_base: &BeatTimeBase,
+    values: LuaMultiValue,
+    all: bool,
+) -> LuaResult<GateUserData> {
+    let gates = values
+        .iter()
+        .map(|value| gate_from_value(lua, timeout_hook, value, time_base))
+        .collect::<LuaResult<Vec<_>>>()?;
+    if all {
+        Ok(GateUserData::new(AndGate::new(gates)))
+    } else {
+        Ok(GateUserData::new(OrGate::new(gates)))
+    }
+}
+
+// create a not gate from the given gate or gate function value
+pub(crate) fn invert_gate_from_value(
+    lua: &Lua,
+    timeout_hook: &LuaTimeoutHook,
+    time_base: &BeatTimeBase,
+    value: &LuaValue,
+) -> LuaResult<GateUserData> {
+    let gate = gate_from_value(lua, timeout_hook, value, time_base)?;
+    Ok(GateUserData::new(NotGate::new(gate)))
+}
+
+// ---------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{bindings::*, BeatTimePattern, RhythmEvent};
+
+    fn new_test_engine() -> LuaResult<Lua> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok(lua)
+    }
+
+    fn run_gate(lua: &Lua, code: &str, pulses: &[f32]) -> LuaResult<Vec<bool>> {
+        let user_data = lua.load(code).eval::<LuaAnyUserData>()?;
+        let mut gate = user_data.borrow::<GateUserData>()?.gate.duplicate();
+        Ok(pulses
+            .iter()
+            .map(|value| {
+                gate.run(&RhythmEvent {
+                    value: *value,
+                    step_time: 1.0,
+                })
+            })
+            .collect())
+    }
+
+    #[test]
+    fn gates() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        assert_eq!(
+            run_gate(&lua, "return gate.threshold(0.5)", &[1.0, 0.5, 0.0])?,
+            vec![true, false, false]
+        );
+        assert_eq!(
+            run_gate(&lua, "return gate.first_in_bar()", &[1.0; 5])?,
+            vec![true, false, false, false, true]
+        );
+        assert_eq!(
+            run_gate(&lua, "return gate.mute({ { 1, 3 } }, 4)", &[1.0; 5])?,
+            vec![true, false, false, true, true]
+        );
+        assert_eq!(
+            run_gate(
+                &lua,
+                "return gate.all(gate.density(0.5), function(context) return true end)",
+                &[1.0; 4]
+            )?,
+            vec![true, false, true, false]
+        );
+        assert_eq!(
+            run_gate(&lua, "return gate.invert(gate.any())", &[0.0, 1.0])?,
+            vec![true, true]
+        );
+
+        assert!(lua.load("return gate.every_nth_cycle(0)").exec().is_err());
+        assert!(lua.load("return gate.mute({ 1, 2 })").exec().is_err());
+        assert!(lua.load("return gate.invert(1)").exec().is_err());
+
+        // gates can be used in patterns
+        assert!(lua
+            .load("return pattern { pulse = { 1, 1 }, gate = gate.every_nth_cycle(2) }")
+            .exec()
+            .is_ok());
+
+        // endless rhythms count bars as cycles
+        let pattern = lua
+            .load(
+                r#"return pattern {
+                    unit = "1/4",
+                    pulse = function(context) return 1 end,
+                    gate = gate.every_nth_cycle(2),
+                    event = "c4"
+                }"#,
+            )
+            .eval::<LuaAnyUserData>()?;
+        let mut pattern = pattern.borrow_mut::<BeatTimePattern>()?;
+        let emitted = (0..12)
+            .map(|_| pattern.next().is_some_and(|event| event.event.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, [[true; 4], [false; 4], [true; 4]].concat());
+        Ok(())
+    }
+}
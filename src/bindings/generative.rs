@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+
+use mlua::prelude::*;
+
+use super::{
+    definitions::{
+        AUTOMATON_RHYTHM_PROPERTIES, LSYSTEM_RHYTHM_PROPERTIES, MARKOV_RHYTHM_PROPERTIES,
+        RANDOM_WALK_RHYTHM_PROPERTIES,
+    },
+    unwrap::{bad_argument_error, validate_table_properties},
+};
+
+use crate::{
+    rhythms::{
+        CellularAutomatonRhythm, LSystemGenerator, LSystemRhythm, MarkovRhythm, RandomWalkRhythm,
+    },
+    Rhythm, RhythmProperty,
+};
+
+// ---------------------------------------------------------------------------------------------
+
+/// Opaque Lua Userdata impl for a native rhythm.
+pub(crate) struct RhythmUserData {
+    pub(crate) rhythm: Box<dyn Rhythm>,
+}
+
+// Use default IntoLua impl for LuaUserData
+impl LuaUserData for RhythmUserData {}
+
+// ---------------------------------------------------------------------------------------------
+
+// unwrap the options table of a rhythm function
+fn options_table(value: &LuaValue, function: &str, properties: &[&str]) -> LuaResult<LuaTable> {
+    match value {
+        LuaValue::Table(table) => {
+            validate_table_properties(table, properties)?;
+            Ok(table.clone())
+        }
+        _ => Err(bad_argument_error(
+            function,
+            "options",
+            1,
+            "expecting an options table",
+        )),
+    }
+}
+
+// read an optional number or parameter id property
+fn property_from_table(
+    table: &LuaTable,
+    key: &str,
+    default: f64,
+    function: &str,
+) -> LuaResult<RhythmProperty> {
+    property_from_value(&table.get::<LuaValue>(key)?, key, default, function)
+}
+
+// convert a number or parameter id to a rhythm property
+pub(crate) fn property_from_value(
+    value: &LuaValue,
+    key: &str,
+    default: f64,
+    function: &str,
+) -> LuaResult<RhythmProperty> {
+    match value {
+        LuaValue::Nil => Ok(RhythmProperty::new(default)),
+        LuaValue::Integer(value) => Ok(RhythmProperty::new(*value as f64)),
+        LuaValue::Number(value) => Ok(RhythmProperty::new(*value)),
+        LuaValue::String(id) => Ok(RhythmProperty::with_parameter(
+            &id.to_string_lossy(),
+            default,
+        )),
+        _ => Err(bad_argument_error(
+            function,
+            "options",
+            1,
+            &format!("'{}' must be a number or a parameter id string", key),
+        )),
+    }
+}
+
+// read optional seed and length properties
+fn seed_and_length_from_table(
+    table: &LuaTable,
+    function: &str,
+) -> LuaResult<(Option<u64>, Option<usize>)> {
+    let seed = match table.get::<LuaValue>("seed")? {
+        LuaValue::Nil => None,
+        LuaValue::Integer(seed) => Some(seed as u64),
+        _ => {
+            return Err(bad_argument_error(
+                function,
+                "options",
+                1,
+                "'seed' must be an integer value",
+            ))
+        }
+    };
+    let length = match table.get::<LuaValue>("length")? {
+        LuaValue::Nil => None,
+        LuaValue::Integer(length) if length > 0 => Some(length as usize),
+        _ => {
+            return Err(bad_argument_error(
+                function,
+                "options",
+                1,
+                "'length' must be an integer value > 0",
+            ))
+        }
+    };
+    Ok((seed, length))
+}
+
+// read a table of pulse values
+fn pulse_values_from_value(value: &LuaValue, key: &str, function: &str) -> LuaResult<Vec<f32>> {
+    let error = || {
+        bad_argument_error(
+            function,
+            "options",
+            1,
+            &format!("'{}' must be a non empty table of numbers or booleans", key),
+        )
+    };
+    let table = value.as_table().ok_or_else(error)?;
+    let mut values = Vec::new();
+    for value in table.clone().sequence_values::<LuaValue>() {
+        let value = match value? {
+            LuaValue::Boolean(value) => {
+                if value {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            LuaValue::Integer(value) => value as f32,
+            LuaValue::Number(value) => value as f32,
+            _ => return Err(error()),
+        };
+        values.push(value);
+    }
+    if values.is_empty() {
+        return Err(error());
+    }
+    Ok(values)
+}
+
+// read a single character string
+fn char_from_str(str: &str, function: &str, key: &str) -> LuaResult<char> {
+    let mut chars = str.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Ok(char),
+        _ => Err(bad_argument_error(
+            function,
+            "options",
+            1,
+            &format!(
+                "'{}' keys must be single characters, got '{}' instead",
+                key, str
+            ),
+        )),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+
+// create a markov rhythm from the given Lua options table
+pub(crate) fn markov_rhythm_from_value(value: &LuaValue) -> LuaResult<RhythmUserData> {
+    const FUNCTION: &str = "markov";
+    let table = options_table(value, FUNCTION, &MARKOV_RHYTHM_PROPERTIES)?;
+    let error = |message: &str| bad_argument_error(FUNCTION, "options", 1, message);
+    let states = pulse_values_from_value(&table.get::<LuaValue>("states")?, "states", FUNCTION)?;
+    // transitions
+    let transitions_error = || {
+        error(&format!(
+            "'transitions' must be a {0}x{0} table of weights, with a row for each state",
+            states.len()
+        ))
+    };
+    let transitions_table = table
+        .get::<Option<LuaTable>>("transitions")?
+        .ok_or_else(transitions_error)?;
+    let mut transitions = Vec::with_capacity(states.len());
+    for row in transitions_table.sequence_values::<LuaValue>() {
+        let row = row?;
+        let row = row.as_table().ok_or_else(transitions_error)?;
+        let weights = row
+            .clone()
+            .sequence_values::<LuaValue>()
+            .map(|weight| {
+                let weight = weight?;
+                let is_valid = match weight {
+                    LuaValue::Integer(weight) => weight >= 0,
+                    LuaValue::Number(weight) => weight.is_finite() && weight >= 0.0,
+                    _ => true,
+                };
+                if !is_valid {
+                    return Err(error("'transitions' weights must be finite numbers >= 0"));
+                }
+                property_from_value(&weight, "transitions", 0.0, FUNCTION)
+            })
+            .collect::<LuaResult<Vec<_>>>()?;
+        if weights.len() != states.len() {
+            return Err(transitions_error());
+        }
+        transitions.push(weights);
+    }
+    if transitions.len() != states.len() {
+        return Err(transitions_error());
+    }
+    // start
+    let start = match table.get::<LuaValue>("start")? {
+        LuaValue::Nil => 0,
+        LuaValue::Integer(start) if start >= 1 && start as usize <= states.len() => {
+            start as usize - 1
+        }
+        _ => {
+            return Err(error(&format!(
+                "'start' must be a state index in range [1..={}]",
+                states.len()
+            )))
+        }
+    };
+    let (seed, length) = seed_and_length_from_table(&table, FUNCTION)?;
+    let rhythm = MarkovRhythm::new(states, transitions, start, seed).with_length(length);
+    Ok(RhythmUserData {
+        rhythm: Box::new(rhythm),
+    })
+}
+
+// create a lsystem rhythm from the given Lua options table
+pub(crate) fn lsystem_rhythm_from_value(value: &LuaValue) -> LuaResult<RhythmUserData> {
+    const FUNCTION: &str = "lsystem";
+    let table = options_table(value, FUNCTION, &LSYSTEM_RHYTHM_PROPERTIES)?;
+    let error = |message: &str| bad_argument_error(FUNCTION, "options", 1, message);
+    let axiom = table
+        .get::<Option<String>>("axiom")?
+        .filter(|axiom| !axiom.is_empty())
+        .ok_or_else(|| error("'axiom' must be a non empty string"))?;
+    // rules
+    let mut rules = HashMap::new();
+    if let Some(rules_table) = table.get::<Option<LuaTable>>("rules")? {
+        for pair in rules_table.pairs::<String, String>() {
+            let (key, replacement) =
+                pair.map_err(|_| error("'rules' must be a table of string replacements"))?;
+            rules.insert(char_from_str(&key, FUNCTION, "rules")?, replacement);
+        }
+    }
+    // values
+    let mut values = HashMap::new();
+    if let Some(values_table) = table.get::<Option<LuaTable>>("values")? {
+        for pair in values_table.pairs::<String, LuaValue>() {
+            let (key, value) =
+                pair.map_err(|_| error("'values' must be a table of character pulse values"))?;
+            let value = match value {
+                LuaValue::Boolean(value) => {
+                    if value {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                LuaValue::Integer(value) => value as f32,
+                LuaValue::Number(value) => value as f32,
+                _ => return Err(error("'values' must be a table of character pulse values")),
+            };
+            values.insert(char_from_str(&key, FUNCTION, "values")?, value);
+        }
+    }
+    let iterations_value = table.get::<LuaValue>("iterations")?;
+    let max_iterations = LSystemGenerator::MAX_ITERATIONS as f64;
+    if let Some(iterations) = match &iterations_value {
+        LuaValue::Integer(value) => Some(*value as f64),
+        LuaValue::Number(value) => Some(*value),
+        _ => None,
+    } {
+        if !(0.0..=max_iterations).contains(&iterations) {
+            return Err(error(&format!(
+                "'iterations' must be in range [0..={}]",
+                LSystemGenerator::MAX_ITERATIONS
+            )));
+        }
+    }
+    let iterations = property_from_value(&iterations_value, "iterations", 1.0, FUNCTION)?;
+    let (_, length) = seed_and_length_from_table(&table, FUNCTION)?;
+    let rhythm = LSystemRhythm::new(&axiom, rules, values, iterations).with_length(length);
+    Ok(RhythmUserData {
+        rhythm: Box::new(rhythm),
+    })
+}
+
+// create a cellular automaton rhythm from the given Lua options table
+pub(crate) fn automaton_rhythm_from_value(value: &LuaValue) -> LuaResult<RhythmUserData> {
+    const FUNCTION: &str = "automaton";
+    let table = options_table(value, FUNCTION, &AUTOMATON_RHYTHM_PROPERTIES)?;
+    let rule = property_from_table(&table, "rule", 30.0, FUNCTION)?;
+    let cells = pulse_values_from_value(&table.get::<LuaValue>("cells")?, "cells", FUNCTION)?
+        .into_iter()
+        .map(|value| value > 0.0)
+        .collect();
+    let (_, length) = seed_and_length_from_table(&table, FUNCTION)?;
+    let rhythm = CellularAutomatonRhythm::new(rule, cells).with_length(length);
+    Ok(RhythmUserData {
+        rhythm: Box::new(rhythm),
+    })
+}
+
+// create a random walk rhythm from the given Lua options table
+pub(crate) fn random_walk_rhythm_from_value(
+    lua: &Lua,
+    value: &LuaValue,
+) -> LuaResult<RhythmUserData> {
+    const FUNCTION: &str = "random_walk";
+    let table = match value {
+        LuaValue::Nil => lua.create_table()?,
+        _ => options_table(value, FUNCTION, &RANDOM_WALK_RHYTHM_PROPERTIES)?,
+    };
+    let start = property_from_table(&table, "start", 0.5, FUNCTION)?;
+    let min = property_from_table(&table, "min", 0.0, FUNCTION)?;
+    let max = property_from_table(&table, "max", 1.0, FUNCTION)?;
+    let step = property_from_table(&table, "step", 0.1, FUNCTION)?;
+    let (seed, length) = seed_and_length_from_table(&table, FUNCTION)?;
+    let rhythm = RandomWalkRhythm::new(start, min, max, step, seed).with_length(length);
+    Ok(RhythmUserData {
+        rhythm: Box::new(rhythm),
+    })
+}
+
+// ---------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::bindings::*;
+
+    fn new_test_engine() -> LuaResult<Lua> {
+        // create a new engine and register bindings
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok(lua)
+    }
+
+    fn run_pulses(lua: &Lua, code: &str, count: usize) -> LuaResult<Vec<f32>> {
+        let user_data = lua.load(code).eval::<LuaAnyUserData>()?;
+        let mut rhythm = user_data.borrow::<RhythmUserData>()?.rhythm.duplicate();
+        Ok((0..count)
+            .map(|_| rhythm.run().map(|event| event.value).unwrap_or(-1.0))
+            .collect())
+    }
+
+    #[test]
+    fn generative_rhythms() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        // invalid options
+        for code in [
+            r#"rhythm.markov()"#,
+            r#"rhythm.markov{ states = {1, 0} }"#,
+            r#"rhythm.markov{ states = {1, 0}, transitions = {{1, 0}} }"#,
+            r#"rhythm.markov{ states = {1, 0}, transitions = {{1, 0}, {0, 1}}, start = 3 }"#,
+            r#"rhythm.markov{ states = {1}, transitions = {{1}}, wurst = 1 }"#,
+            r#"rhythm.markov{ states = {1, 0}, transitions = {{1, math.huge}, {1, 0}} }"#,
+            r#"rhythm.markov{ states = {1, 0}, transitions = {{1, -1}, {1, 0}} }"#,
+            r#"rhythm.lsystem{ axiom = "" }"#,
+            r#"rhythm.lsystem{ axiom = "a", rules = { ab = "a" } }"#,
+            r#"rhythm.lsystem{ axiom = "a", rules = { a = "ab" }, iterations = 65 }"#,
+            r#"rhythm.lsystem{ axiom = "a", rules = { a = "ab" }, iterations = -1 }"#,
+            r#"rhythm.automaton{ rule = 30 }"#,
+            r#"rhythm.automaton{ rule = {}, cells = {1} }"#,
+            r#"rhythm.random_walk{ length = 0 }"#,
+        ] {
+            assert!(
+                lua.load(code).eval::<LuaAnyUserData>().is_err(),
+                "expected an error for '{}'",
+                code
+            );
+        }
+
+        // valid rhythms
+        assert_eq!(
+            run_pulses(
+                &lua,
+                r#"rhythm.markov{ states = {1, 0}, transitions = {{0, 1}, {1, 0}} }"#,
+                4
+            )?,
+            vec![1.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(
+            run_pulses(
+                &lua,
+                r#"rhythm.lsystem{ axiom = "a", rules = { a = "ab", b = "a" },
+                    values = { a = true }, iterations = 3 }"#,
+                6
+            )?,
+            vec![1.0, 0.0, 1.0, 1.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            run_pulses(
+                &lua,
+                r#"rhythm.automaton{ rule = 90, cells = {0, 0, 1, 0, 0}, length = 7 }"#,
+                8
+            )?,
+            vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+        );
+        let walk = run_pulses(
+            &lua,
+            r#"rhythm.random_walk{ start = 0.5, min = 0.25, max = 0.75, seed = 1 }"#,
+            16,
+        )?;
+        assert!(walk.iter().all(|v| (0.25..=0.75).contains(v)));
+
+        // rhythms in patterns, with parameter bound properties
+        assert!(lua
+            .load(
+                r#"pattern {
+                    parameter = { parameter.integer("rule", 30, {0, 255}) },
+                    pulse = rhythm.automaton{ rule = "rule", cells = {0, 0, 0, 1, 0, 0, 0, 0} },
+                    event = "c4"
+                }"#
+            )
+            .eval::<LuaValue>()
+            .is_ok());
+        Ok(())
+    }
+}
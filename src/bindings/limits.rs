@@ -0,0 +1,148 @@
+use std::{error::Error, fmt::Display, time::Duration};
+
+use mlua::prelude::*;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Execution limits for Lua scripts and Lua callbacks of a single Lua engine.
+///
+/// The `timeout` and `instruction_limit` budgets apply to every single script evaluation or
+/// callback invocation: each new callback call starts with a fresh budget. The `memory_limit`
+/// applies to the engine as a whole.
+///
+/// Use strict instruction limits in real-time contexts, where wall-clock time is not a reliable
+/// measure, and generous or no limits when rendering offline.
+///
+/// Patterns can override the timeout and instruction limits for their own callbacks with the
+/// `limits` option in Lua, e.g. `pattern { limits = { timeout = 500 }, ... }`, so a single heavy
+/// generator does not force loose limits on all other callbacks of the engine.
+///
+/// Hitting a limit fails the script or callback with a [`ScriptLimitError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// Max wall-clock time a script or callback may run. `None` disables the timeout.
+    pub timeout: Option<Duration>,
+    /// Max number of Lua VM instructions a script or callback may run. `None` disables the limit.
+    ///
+    /// NB: With Luau, instructions are not counted individually, but the number of VM interrupts,
+    /// which happen on function calls and loop iterations.
+    pub instruction_limit: Option<u64>,
+    /// Max memory in bytes the Lua engine may allocate. `None` disables the limit.
+    pub memory_limit: Option<usize>,
+}
+
+impl Default for ScriptLimits {
+    /// Limits for scripts which are running in a real-time alike context: a 200 ms timeout,
+    /// but no instruction or memory limits.
+    fn default() -> Self {
+        Self {
+            timeout: Some(Self::DEFAULT_TIMEOUT),
+            instruction_limit: None,
+            memory_limit: None,
+        }
+    }
+}
+
+impl ScriptLimits {
+    /// Default number of ms a script may run before a timeout error is fired.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(200);
+
+    /// No execution limits at all. Use with care: never ending loops in scripts will then block
+    /// the calling thread forever.
+    pub fn unlimited() -> Self {
+        Self {
+            timeout: None,
+            instruction_limit: None,
+            memory_limit: None,
+        }
+    }
+
+    /// Return new limits with the given wall-clock timeout.
+    #[must_use]
+    pub fn with_timeout<T: Into<Option<Duration>>>(self, timeout: T) -> Self {
+        let timeout = timeout.into();
+        Self { timeout, ..self }
+    }
+
+    /// Return new limits with the given instruction count budget.
+    #[must_use]
+    pub fn with_instruction_limit<T: Into<Option<u64>>>(self, instruction_limit: T) -> Self {
+        let instruction_limit = instruction_limit.into();
+        Self {
+            instruction_limit,
+            ..self
+        }
+    }
+
+    /// Return new limits with the given memory limit in bytes.
+    #[must_use]
+    pub fn with_memory_limit<T: Into<Option<usize>>>(self, memory_limit: T) -> Self {
+        let memory_limit = memory_limit.into();
+        Self {
+            memory_limit,
+            ..self
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Error kinds for scripts or callbacks which exceeded one of their [`ScriptLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLimitError {
+    /// Script took longer than the given timeout.
+    Timeout(Duration),
+    /// Script ran more than the given number of instructions.
+    InstructionLimit(u64),
+    /// Script failed to allocate memory within the engine's memory limit.
+    MemoryLimit,
+}
+
+impl ScriptLimitError {
+    /// Try to find a script limit error in the given error or its sources, e.g. in errors
+    /// returned by [`new_pattern_from_string`](super::new_pattern_from_string) or in Lua
+    /// callback errors.
+    pub fn from_error(err: &(dyn Error + 'static)) -> Option<Self> {
+        if let Some(err) = err.downcast_ref::<Self>() {
+            Some(*err)
+        } else if let Some(err) = err.downcast_ref::<LuaError>() {
+            match err {
+                LuaError::MemoryError(_) => Some(Self::MemoryLimit),
+                LuaError::ExternalError(err) => Self::from_error(err.as_ref()),
+                LuaError::CallbackError { cause, .. } | LuaError::WithContext { cause, .. } => {
+                    Self::from_error(cause.as_ref())
+                }
+                _ => None,
+            }
+        } else {
+            err.source().and_then(Self::from_error)
+        }
+    }
+}
+
+impl Display for ScriptLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout(timeout) => write!(
+                f,
+                "Script timeout. Execution took longer than {} ms to complete.\n\
+                 Please avoid overhead and check for never ending loops in your script. \
+                 Also note that the script is running in real-time thread!",
+                timeout.as_millis()
+            ),
+            Self::InstructionLimit(limit) => write!(
+                f,
+                "Script instruction limit reached. Execution took more than {} instructions \
+                 to complete.\n\
+                 Please avoid overhead and check for never ending loops in your script.",
+                limit
+            ),
+            Self::MemoryLimit => write!(
+                f,
+                "Script memory limit reached. Please avoid creating large tables or strings."
+            ),
+        }
+    }
+}
+
+impl Error for ScriptLimitError {}
@@ -0,0 +1,204 @@
+use mlua::prelude::*;
+
+use super::{
+    definitions::MELODY_PROPERTIES,
+    generative::property_from_value,
+    unwrap::{bad_argument_error, note_event_from_value, validate_table_properties},
+};
+
+use crate::{emitters::MelodyEmitter, Scale};
+
+// ---------------------------------------------------------------------------------------------
+
+/// Opaque Lua Userdata impl for a native melody emitter.
+pub(crate) struct MelodyUserData {
+    pub(crate) emitter: MelodyEmitter,
+}
+
+// Use default IntoLua impl for LuaUserData
+impl LuaUserData for MelodyUserData {}
+
+// ---------------------------------------------------------------------------------------------
+
+// create a melody emitter from the given Lua options table
+pub(crate) fn melody_from_value(value: &LuaValue) -> LuaResult<MelodyUserData> {
+    const FUNCTION: &str = "melody";
+    let table = match value {
+        LuaValue::Table(table) => {
+            validate_table_properties(table, &MELODY_PROPERTIES)?;
+            table.clone()
+        }
+        _ => {
+            return Err(bad_argument_error(
+                FUNCTION,
+                "options",
+                1,
+                "expecting an options table",
+            ))
+        }
+    };
+    let error = |message: &str| bad_argument_error(FUNCTION, "options", 1, message);
+    // scale
+    let scale = match table.get::<LuaValue>("scale")? {
+        LuaValue::UserData(userdata) if userdata.is::<Scale>() => {
+            userdata.borrow::<Scale>()?.clone()
+        }
+        _ => {
+            return Err(error(
+                "'scale' must be a scale, e.g. scale(\"c4\", \"minor\")",
+            ))
+        }
+    };
+    let mut emitter = MelodyEmitter::new(scale.clone());
+    // range
+    match table.get::<LuaValue>("range")? {
+        LuaValue::Nil => (),
+        LuaValue::Table(range) => {
+            let range_error =
+                || error("'range' must be a { low, high } note table, containing scale notes");
+            let range = range
+                .sequence_values::<LuaValue>()
+                .collect::<LuaResult<Vec<_>>>()?;
+            if range.len() != 2 {
+                return Err(range_error());
+            }
+            let low = note_event_from_value(&range[0], Some(0))?.map(|event| event.note);
+            let high = note_event_from_value(&range[1], Some(1))?.map(|event| event.note);
+            match (low, high) {
+                (Some(low), Some(high)) if low.is_note_on() && high.is_note_on() => {
+                    let scale_keys = scale.notes().iter().map(|n| n.key()).collect::<Vec<_>>();
+                    if !(low as u8..=high as u8).any(|n| scale_keys.contains(&(n % 12))) {
+                        return Err(range_error());
+                    }
+                    emitter = emitter.with_range(low, high);
+                }
+                _ => return Err(range_error()),
+            }
+        }
+        _ => return Err(error("'range' must be a { low, high } note table")),
+    }
+    // max_leap, step_probability and contour
+    emitter = emitter
+        .with_max_leap(property_from_value(
+            &table.get::<LuaValue>("max_leap")?,
+            "max_leap",
+            4.0,
+            FUNCTION,
+        )?)
+        .with_step_probability(property_from_value(
+            &table.get::<LuaValue>("step_probability")?,
+            "step_probability",
+            0.75,
+            FUNCTION,
+        )?)
+        .with_contour(property_from_value(
+            &table.get::<LuaValue>("contour")?,
+            "contour",
+            0.0,
+            FUNCTION,
+        )?);
+    // avoid_repeats
+    match table.get::<LuaValue>("avoid_repeats")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(count) if count >= 0 => {
+            emitter = emitter.with_avoid_repeats(count as usize);
+        }
+        _ => return Err(error("'avoid_repeats' must be an integer >= 0")),
+    }
+    // phrase_length
+    match table.get::<LuaValue>("phrase_length")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(length) if length >= 0 => {
+            emitter = emitter.with_phrase_length(length as usize);
+        }
+        _ => return Err(error("'phrase_length' must be an integer >= 0")),
+    }
+    // seed
+    match table.get::<LuaValue>("seed")? {
+        LuaValue::Nil => (),
+        LuaValue::Integer(seed) => emitter = emitter.with_seed(Some(seed as u64)),
+        _ => return Err(error("'seed' must be an integer value")),
+    }
+    Ok(MelodyUserData { emitter })
+}
+
+// ---------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{bindings::*, Emitter, Event, Note, RhythmEvent};
+
+    fn new_test_engine() -> LuaResult<Lua> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok(lua)
+    }
+
+    #[test]
+    fn melody() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        let user_data = lua
+            .load(
+                r#"return melody {
+                    scale = scale("c4", "major"),
+                    range = { "c4", "c5" },
+                    max_leap = 2,
+                    contour = "contour",
+                    phrase_length = 4,
+                    seed = 1234
+                }"#,
+            )
+            .eval::<LuaAnyUserData>()?;
+        let mut emitter = user_data.borrow::<MelodyUserData>()?.emitter.clone();
+        let notes = (0..8)
+            .map(|_| match emitter.run(RhythmEvent::default(), true) {
+                Some(events) => match &events[0].event {
+                    Event::NoteEvents(notes) => notes[0].as_ref().map(|note| note.note),
+                    _ => None,
+                },
+                None => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(notes
+            .iter()
+            .all(|note| note.is_some_and(|note| (Note::C4..=Note::C5).contains(&note))));
+        assert_eq!(notes[3].map(|note| note.key()), Some(Note::C4.key()));
+
+        assert!(lua.load("return melody()").exec().is_err());
+        assert!(lua.load("return melody { }").exec().is_err());
+        assert!(lua
+            .load(r#"return melody { scale = scale("c4", "major"), range = { "c#4", "c#4" } }"#)
+            .exec()
+            .is_err());
+        assert!(lua
+            .load(r#"return melody { scale = scale("c4", "major"), avoid_repeats = -1 }"#)
+            .exec()
+            .is_err());
+        assert!(lua
+            .load(r#"return melody { scale = scale("c4", "major"), speed = 2 }"#)
+            .exec()
+            .is_err());
+
+        // melodies can be used in patterns
+        assert!(lua
+            .load(
+                r#"return pattern {
+                    pulse = { 1, 1 },
+                    event = melody { scale = scale("c4", "minor") }
+                }"#
+            )
+            .exec()
+            .is_ok());
+        Ok(())
+    }
+}
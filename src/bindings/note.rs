@@ -1,9 +1,11 @@
+use std::sync::Arc;
+
 use mlua::prelude::*;
 
 use super::unwrap::{
     amplify_array_from_value, bad_argument_error, chord_events_from_intervals,
     chord_events_from_mode, delay_array_from_value, instrument_array_from_value,
-    note_events_from_value, panning_array_from_value, sequence_from_value,
+    length_array_from_value, note_events_from_value, panning_array_from_value, sequence_from_value,
     transpose_steps_array_from_value, volume_array_from_value,
 };
 
@@ -211,6 +213,53 @@ impl LuaUserData for NoteUserData {
             drop(this);
             Ok(ud)
         });
+
+        methods.add_function("length", |lua, (ud, value): (LuaAnyUserData, LuaValue)| {
+            let mut this = ud.borrow_mut::<Self>()?;
+            let lengths = length_array_from_value(lua, value, this.notes.len())?;
+            for (note, length) in this.notes.iter_mut().zip(lengths.into_iter()) {
+                if let Some(note) = note {
+                    note.length = Some(length);
+                }
+            }
+            drop(this);
+            Ok(ud)
+        });
+
+        methods.add_function(
+            "attribute",
+            |lua, (ud, name, value): (LuaAnyUserData, String, LuaValue)| {
+                if matches!(
+                    name.as_str(),
+                    "key"
+                        | "instrument"
+                        | "volume"
+                        | "vol"
+                        | "panning"
+                        | "pan"
+                        | "delay"
+                        | "length"
+                ) {
+                    return Err(bad_argument_error(
+                        "attribute",
+                        "name",
+                        1,
+                        "note properties can not be set as attributes: use the note's \
+                         'instrument', 'volume', 'panning', 'delay' or 'length' functions instead",
+                    ));
+                }
+                let mut this = ud.borrow_mut::<Self>()?;
+                let values = attribute_array_from_value(lua, value, this.notes.len(), &name)?;
+                let name = Arc::<str>::from(name);
+                for (note, value) in this.notes.iter_mut().zip(values) {
+                    if let Some(note) = note {
+                        note.attributes.insert(Arc::clone(&name), value);
+                    }
+                }
+                drop(this);
+                Ok(ud)
+            },
+        );
     }
 }
 
@@ -546,6 +595,115 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn note_length() -> LuaResult<()> {
+        let (lua, _) = new_test_engine()?;
+
+        let lengths = |notes: Vec<Option<NoteEvent>>| {
+            notes
+                .into_iter()
+                .map(|note| note.and_then(|note| note.length))
+                .collect::<Vec<_>>()
+        };
+        // length
+        assert!(evaluate_note_userdata(&lua, r#"note("c4"):length(2.0)"#).is_ok());
+        assert!(evaluate_note_userdata(&lua, r#"note("c4"):length()"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note("c4"):length(-1)"#).is_err());
+        assert_eq!(
+            lengths(evaluate_note_userdata(&lua, r#"note("c4", "d4", "e4"):length({0.5})"#)?.notes),
+            vec![Some(0.5), None, None]
+        );
+        // string and table attributes
+        assert_eq!(
+            lengths(evaluate_note_userdata(&lua, r#"note("c4 l0.25", "d4'maj l1.5")"#)?.notes),
+            vec![Some(0.25), Some(1.5), Some(1.5), Some(1.5)]
+        );
+        assert_eq!(
+            lengths(evaluate_note_userdata(&lua, r#"note({ key = "c4", length = 0.5 })"#)?.notes),
+            vec![Some(0.5)]
+        );
+        assert!(evaluate_note_userdata(&lua, r#"note("c4 l-1")"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note({ key = "c4", length = "x" })"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn note_attributes() -> LuaResult<()> {
+        let (lua, _) = new_test_engine()?;
+
+        let attributes = |notes: Vec<Option<NoteEvent>>, name: &str| {
+            notes
+                .into_iter()
+                .map(|note| note.and_then(|note| note.attributes.get(name).copied()))
+                .collect::<Vec<_>>()
+        };
+        // attribute
+        assert!(evaluate_note_userdata(&lua, r#"note("c4"):attribute("cutoff")"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note("c4"):attribute("cutoff", "x")"#).is_err());
+        assert_eq!(
+            attributes(
+                evaluate_note_userdata(
+                    &lua,
+                    r#"note("c4", "d4"):attribute("cutoff", {0.5, 0.25})"#
+                )?
+                .notes,
+                "cutoff"
+            ),
+            vec![Some(0.5), Some(0.25)]
+        );
+        // string and table attributes
+        assert_eq!(
+            attributes(
+                evaluate_note_userdata(&lua, r#"note("c4 v0.5 mod=0.2", "d4'maj mod=1")"#)?.notes,
+                "mod"
+            ),
+            vec![Some(0.2), Some(1.0), Some(1.0), Some(1.0)]
+        );
+        assert_eq!(
+            attributes(
+                evaluate_note_userdata(&lua, r#"note({ key = "c4", pitch_bend = -0.5 })"#)?.notes,
+                "pitch_bend"
+            ),
+            vec![Some(-0.5)]
+        );
+        assert!(evaluate_note_userdata(&lua, r#"note("c4 mod=")"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note("c4 =1")"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note({ key = "c4", mod = "x" })"#).is_err());
+
+        // volume, panning and delay attributes alias the note properties
+        let properties = |notes: Vec<Option<NoteEvent>>| {
+            notes
+                .into_iter()
+                .flatten()
+                .map(|note| (note.volume, note.panning, note.delay, note.attributes.len()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            properties(
+                evaluate_note_userdata(
+                    &lua,
+                    r#"note("c4 vol=0.5 pan=-1 delay=0.25", { key = "d4", vol = 0.25, pan = 1 })"#
+                )?
+                .notes
+            ),
+            vec![(0.5, -1.0, 0.25, 0), (0.25, 1.0, 0.0, 0)]
+        );
+        assert!(evaluate_note_userdata(&lua, r#"note("c4 volume=2")"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note("c4'maj pan=-2")"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note({ key = "c4", vol = 2 })"#).is_err());
+        assert!(evaluate_note_userdata(&lua, r#"note("c4"):attribute("vol", 0.5)"#).is_err());
+
+        // attributes are passed to Lua as note table properties
+        assert_eq!(
+            lua.load(r#"return note("c4 cutoff=0.5").notes[1].cutoff"#)
+                .eval::<f32>()?,
+            0.5
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn note_numbers() -> LuaResult<()> {
         let (lua, _) = new_test_engine()?;
@@ -0,0 +1,33 @@
+use super::{LuaCallbackErrorChannel, ScriptLimits};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Options for Lua engines which evaluate pattern or sequence scripts.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptOptions {
+    /// Execution limits for the script and its callbacks.
+    pub limits: ScriptLimits,
+    /// Channel which receives runtime errors of the script's callbacks. When unset, a new
+    /// channel gets created for each script.
+    ///
+    /// When the channel has no pattern id set, errors are tagged with the script's name or path.
+    pub callback_errors: Option<LuaCallbackErrorChannel>,
+}
+
+impl ScriptOptions {
+    /// Return new options with the given execution limits.
+    #[must_use]
+    pub fn with_limits(self, limits: ScriptLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Return new options with the given callback error channel.
+    #[must_use]
+    pub fn with_callback_errors(self, callback_errors: LuaCallbackErrorChannel) -> Self {
+        let callback_errors = Some(callback_errors);
+        Self {
+            callback_errors,
+            ..self
+        }
+    }
+}
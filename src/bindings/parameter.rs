@@ -1,6 +1,11 @@
 use mlua::prelude::*;
 
-use crate::Parameter;
+use super::{
+    definitions::PARAMETER_AUTOMATION_PROPERTIES,
+    unwrap::{bad_argument_error, validate_table_properties},
+};
+
+use crate::{LfoShape, Note, Parameter, ParameterAutomation, ParameterType};
 
 // ---------------------------------------------------------------------------------------------
 
@@ -14,6 +19,184 @@ impl LuaUserData for ParameterUserData {}
 
 // ---------------------------------------------------------------------------------------------
 
+// Apply an optional automation table to the given parameter.
+pub(crate) fn automate_parameter_from_value(
+    parameter: Parameter,
+    value: &LuaValue,
+    function: &str,
+    arg_index: usize,
+) -> LuaResult<Parameter> {
+    let error = |message: &str| bad_argument_error(function, "automation", arg_index, message);
+    let table = match value {
+        LuaValue::Nil => return Ok(parameter),
+        LuaValue::Table(table) => table,
+        _ => return Err(error("expecting an automation table or nil")),
+    };
+    // error on unknown option keys
+    validate_table_properties(table, &PARAMETER_AUTOMATION_PROPERTIES)?;
+    // positive number properties
+    let positive_number = |key: &str, default: f64| -> LuaResult<f64> {
+        match table.get::<LuaValue>(key)? {
+            LuaValue::Nil => Ok(default),
+            value => match value.as_f64() {
+                Some(number) if number > 0.0 => Ok(number),
+                _ => Err(error(&format!("'{}' must be a number > 0", key))),
+            },
+        }
+    };
+    let sources = ["curve", "lfo", "steps"]
+        .into_iter()
+        .filter(|key| table.contains_key(*key).unwrap_or(false))
+        .collect::<Vec<_>>();
+    let automation = match sources.as_slice() {
+        ["curve"] => {
+            let mut points = Vec::new();
+            for point in table
+                .get::<LuaTable>("curve")?
+                .sequence_values::<LuaValue>()
+            {
+                let point_error = || error("curve points must be { time, value } tables");
+                let point = point?;
+                let point = point.as_table().ok_or_else(point_error)?;
+                let time = point.get::<LuaValue>(1)?.as_f64().ok_or_else(point_error)?;
+                let value = automation_value(&parameter, &point.get::<LuaValue>(2)?, &error)?;
+                points.push((time, value));
+            }
+            if points.is_empty() {
+                return Err(error("curves need at least one point"));
+            }
+            points.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+            let length = if table.contains_key("length")? {
+                Some(positive_number("length", 1.0)?)
+            } else {
+                None
+            };
+            ParameterAutomation::Curve { points, length }
+        }
+        ["lfo"] => {
+            let shape = LfoShape::try_from(table.get::<String>("lfo")?.as_str())
+                .map_err(|err| error(&err))?;
+            let period = positive_number("period", 1.0)?;
+            let phase = table.get::<Option<f64>>("phase")?.unwrap_or(0.0);
+            let (mut min, mut max) = (*parameter.range().start(), *parameter.range().end());
+            if let Some(range) = table.get::<Option<LuaTable>>("range")? {
+                min = automation_value(&parameter, &range.get::<LuaValue>(1)?, &error)?;
+                max = automation_value(&parameter, &range.get::<LuaValue>(2)?, &error)?;
+            }
+            ParameterAutomation::Lfo {
+                shape,
+                period,
+                phase,
+                min,
+                max,
+            }
+        }
+        ["steps"] => {
+            let values = table
+                .get::<LuaTable>("steps")?
+                .sequence_values::<LuaValue>()
+                .map(|value| automation_value(&parameter, &value?, &error))
+                .collect::<LuaResult<Vec<_>>>()?;
+            if values.is_empty() {
+                return Err(error("step sequences need at least one value"));
+            }
+            let step = positive_number("step", 1.0)?;
+            ParameterAutomation::Steps { values, step }
+        }
+        _ => return Err(error("expecting exactly one of 'curve', 'lfo' or 'steps'")),
+    };
+    Ok(parameter.with_automation(automation))
+}
+
+// Convert a Lua note number or note string to a note parameter value.
+pub(crate) fn note_parameter_value(
+    value: &LuaValue,
+    function: &str,
+    arg_name: &str,
+    arg_index: usize,
+) -> LuaResult<Note> {
+    let error = |message: &str| bad_argument_error(function, arg_name, arg_index, message);
+    match value {
+        LuaValue::Integer(value) if (0..=0x7f).contains(value) => Ok(Note::from(*value as u8)),
+        LuaValue::String(value) => {
+            let note =
+                Note::try_from(value.to_string_lossy().as_str()).map_err(|err| error(&err))?;
+            if !note.is_note_on() {
+                return Err(error(
+                    "expecting a note-on value, not a note-off or empty note",
+                ));
+            }
+            Ok(note)
+        }
+        _ => Err(error(
+            "expecting a note number in range [0..=0x7f] or a note string such as 'c4'",
+        )),
+    }
+}
+
+// Convert a Lua table with numbers to array parameter values.
+pub(crate) fn array_parameter_values(
+    value: &LuaValue,
+    integer: bool,
+    function: &str,
+    arg_name: &str,
+    arg_index: usize,
+) -> LuaResult<Vec<f64>> {
+    let error = |message: &str| bad_argument_error(function, arg_name, arg_index, message);
+    let expected = if integer {
+        "expecting a non empty table of integer values"
+    } else {
+        "expecting a non empty table of number values"
+    };
+    let table = value.as_table().ok_or_else(|| error(expected))?;
+    let mut values = Vec::with_capacity(table.raw_len());
+    for value in table.clone().sequence_values::<LuaValue>() {
+        let value = match value? {
+            LuaValue::Integer(value) => value as f64,
+            LuaValue::Number(value) if !integer => value,
+            _ => return Err(error(expected)),
+        };
+        values.push(value);
+    }
+    if values.is_empty() {
+        return Err(error(expected));
+    }
+    Ok(values)
+}
+
+// Convert a Lua automation value to a raw parameter value.
+fn automation_value(
+    parameter: &Parameter,
+    value: &LuaValue,
+    error: &dyn Fn(&str) -> LuaError,
+) -> LuaResult<f64> {
+    match (parameter.parameter_type(), value) {
+        (ParameterType::Boolean, LuaValue::Boolean(value)) => Ok(if *value { 1.0 } else { 0.0 }),
+        (ParameterType::Enum, LuaValue::String(value)) => {
+            let value = value.to_string_lossy();
+            parameter
+                .value_strings()
+                .iter()
+                .position(|v| v.eq_ignore_ascii_case(&value))
+                .map(|index| index as f64)
+                .ok_or_else(|| error(&format!("'{}' is not a valid enum value", value)))
+        }
+        (ParameterType::Note, LuaValue::String(value)) => {
+            Note::try_from(value.to_string_lossy().as_str())
+                .map(|note| u8::from(note) as f64)
+                .map_err(|err| error(&err))
+        }
+        (_, LuaValue::Integer(value)) => Ok(*value as f64),
+        (_, LuaValue::Number(value)) => Ok(*value),
+        _ => Err(error(&format!(
+            "invalid automation value of type '{}'",
+            value.type_name()
+        ))),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -148,6 +331,142 @@ mod test {
             .load(r#"parameter.enum("name", "value", {"value", "wurst"}, "Fancy Name", "Fancy Description")"#)
             .eval::<LuaValue>()
             .is_ok());
+
+        let parameter = |code: &str| -> LuaResult<Parameter> {
+            let user_data = lua.load(code).eval::<LuaAnyUserData>()?;
+            let parameter = user_data.borrow::<ParameterUserData>()?;
+            Ok(parameter.parameter.clone())
+        };
+
+        // note
+        for code in [
+            r#"parameter.note("name", "x4")"#,                // invalid note
+            r#"parameter.note("name", 200)"#,                 // invalid note number
+            r#"parameter.note("name", "off")"#,               // note offs are not valid
+            r#"parameter.note("name", "---")"#,               // empty notes are not valid
+            r#"parameter.note("name", "c4", {"off", "c5"})"#, // not in ranges either
+            r#"parameter.note("name", "c4", {"c5", 80})"#,    // out of range
+        ] {
+            assert!(parameter(code).is_err(), "expected an error for '{}'", code);
+        }
+        assert_eq!(
+            parameter(r#"parameter.note("name", "c4")"#)?.note_value(),
+            Some(Note::C4)
+        );
+        let note = parameter(
+            r#"parameter.note("name", 50, {"c4", "c5"}, nil, nil, { steps = {"c#4", 60} })"#,
+        )?;
+        assert_eq!(note.range(), &(48.0..=60.0));
+        assert_eq!(
+            note.automation(),
+            Some(&ParameterAutomation::Steps {
+                values: vec![49.0, 60.0],
+                step: 1.0
+            })
+        );
+
+        // string
+        assert!(parameter(r#"parameter.string("name", 1)"#).is_err());
+        assert!(parameter(r#"parameter.string("", "text")"#).is_err());
+        assert_eq!(
+            parameter(r#"parameter.string("name", "text", "Fancy Name")"#)?.text(),
+            "text"
+        );
+
+        // arrays
+        for code in [
+            r#"parameter.integer_array("name", {})"#,       // empty
+            r#"parameter.integer_array("name", {1, 0.5})"#, // not an integer
+            r#"parameter.integer_array("name", {1, 20}, {0, 10})"#, // out of range
+            r#"parameter.number_array("name", 0.5)"#,       // not a table
+            r#"parameter.number_array("name", {0.5, "1"})"#, // not a number
+            r#"parameter.number_array("name", {0.5, 2})"#,  // out of range
+        ] {
+            assert!(parameter(code).is_err(), "expected an error for '{}'", code);
+        }
+        assert_eq!(
+            parameter(r#"parameter.integer_array("name", {1, 0, 1}, {0, 1})"#)?.array(),
+            &[1.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            parameter(r#"parameter.number_array("name", {0.5, 1})"#)?.array(),
+            &[0.5, 1.0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn automation() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        let automation = |code: &str| -> LuaResult<Option<ParameterAutomation>> {
+            let user_data = lua.load(code).eval::<LuaAnyUserData>()?;
+            let parameter = user_data.borrow::<ParameterUserData>()?;
+            Ok(parameter.parameter.automation().cloned())
+        };
+
+        // invalid automations
+        for code in [
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, 1)"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, {})"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, { lfo = "sine", steps = {1} })"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, { lfo = "wurst" })"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, { lfo = "sine", period = 0 })"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, { curve = {} })"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, { curve = { 1, 2 } })"#,
+            r#"parameter.number("name", 0.5, {0, 1}, nil, nil, { steps = { "a" } })"#,
+            r#"parameter.enum("name", "a", {"a", "b"}, nil, nil, { steps = { "c" } })"#,
+            r#"parameter.boolean("name", false, nil, nil, { steps = {}, wurst = 1 })"#,
+        ] {
+            assert!(
+                automation(code).is_err(),
+                "expected an error for '{}'",
+                code
+            );
+        }
+
+        // valid automations
+        assert_eq!(automation(r#"parameter.number("name", 0.5)"#)?, None);
+        assert_eq!(
+            automation(
+                r#"parameter.number("name", 0.5, {0, 1}, nil, nil,
+                    { curve = { {4, 1}, {0, 0.5} }, length = 8 })"#
+            )?,
+            Some(ParameterAutomation::Curve {
+                points: vec![(0.0, 0.5), (4.0, 1.0)],
+                length: Some(8.0)
+            })
+        );
+        assert_eq!(
+            automation(
+                r#"parameter.integer("name", 5, {0, 10}, nil, nil, { lfo = "tri", period = 4 })"#
+            )?,
+            Some(ParameterAutomation::Lfo {
+                shape: LfoShape::Triangle,
+                period: 4.0,
+                phase: 0.0,
+                min: 0.0,
+                max: 10.0
+            })
+        );
+        assert_eq!(
+            automation(
+                r#"parameter.boolean("name", false, nil, nil, { steps = { true, false, 1 } })"#
+            )?,
+            Some(ParameterAutomation::Steps {
+                values: vec![1.0, 0.0, 1.0],
+                step: 1.0
+            })
+        );
+        assert_eq!(
+            automation(
+                r#"parameter.enum("name", "a", {"a", "b"}, nil, nil, { steps = { "B", "a" }, step = 0.5 })"#
+            )?,
+            Some(ParameterAutomation::Steps {
+                values: vec![1.0, 0.0],
+                step: 0.5
+            })
+        );
         Ok(())
     }
 }
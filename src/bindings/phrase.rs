@@ -0,0 +1,234 @@
+use mlua::prelude::*;
+
+use super::{
+    cycle::CycleUserData,
+    definitions::PHRASE_PROPERTIES,
+    rhythm::pattern_from_userdata,
+    unwrap::{bad_argument_error, beat_time_step_from_unit, validate_table_properties},
+    LuaTimeoutHook,
+};
+
+use crate::prelude::*;
+
+// ---------------------------------------------------------------------------------------------
+
+/// Phrase Userdata in bindings
+#[derive(Clone, Debug)]
+pub struct PhraseUserData {
+    pub phrase: Phrase,
+}
+
+impl PhraseUserData {
+    // create a phrase from the given Lua table value
+    pub(crate) fn from_table(
+        lua: &Lua,
+        timeout_hook: &LuaTimeoutHook,
+        time_base: &BeatTimeBase,
+        table: &LuaTable,
+    ) -> LuaResult<Self> {
+        // error on unknown option keys
+        validate_table_properties(table, &PHRASE_PROPERTIES)?;
+        // length
+        let mut length = 1.0;
+        if table.contains_key("length")? {
+            length = table.get::<f32>("length")?;
+            if length <= 0.0 {
+                return Err(bad_argument_error(
+                    "phrase",
+                    "length",
+                    1,
+                    "length must be > 0",
+                ));
+            }
+        }
+        // unit
+        let mut length_step = BeatTimeStep::Bar(length);
+        if table.contains_key("unit")? {
+            let unit = table.get::<String>("unit")?;
+            match beat_time_step_from_unit(unit.as_str(), length) {
+                Some(step) => length_step = step,
+                None => {
+                    return Err(bad_argument_error(
+                        "phrase",
+                        "unit",
+                        1,
+                        "expected one of 'bars|beats' or '1/1|1/2|1/4|1/8|1/16|1/32|1/64",
+                    ))
+                }
+            }
+        }
+        // patterns
+        let mut pattern_slots = Vec::new();
+        if table.contains_key("patterns")? {
+            let patterns = table.get::<LuaValue>("patterns")?;
+            if let Some(patterns) = patterns.as_table() {
+                for value in patterns.clone().sequence_values::<LuaValue>() {
+                    pattern_slots.push(pattern_slot_from_value(
+                        lua,
+                        timeout_hook,
+                        &value?,
+                        time_base,
+                    )?);
+                }
+            } else {
+                return Err(bad_argument_error(
+                    "phrase",
+                    "patterns",
+                    1,
+                    "expecting an array of patterns, cycles or 'stop'/'continue' slot strings",
+                ));
+            }
+        }
+        let phrase = Phrase::new(*time_base, pattern_slots, length_step);
+        Ok(Self { phrase })
+    }
+}
+
+impl LuaUserData for PhraseUserData {
+    // PhraseUserData is only passed through ATM
+}
+
+// ---------------------------------------------------------------------------------------------
+
+// Convert a pattern, cycle, phrase or slot mode string to a pattern slot.
+// Patterns and cycles are copied, so they can be used in multiple phrases.
+fn pattern_slot_from_value(
+    lua: &Lua,
+    timeout_hook: &LuaTimeoutHook,
+    value: &LuaValue,
+    time_base: &BeatTimeBase,
+) -> LuaResult<PatternSlot> {
+    let slot_error = || {
+        Err(LuaError::FromLuaConversionError {
+            from: value.type_name(),
+            to: "pattern slot".to_string(),
+            message: Some(
+                "expecting a pattern, cycle, phrase or one of 'stop' or 'continue'".to_string(),
+            ),
+        })
+    };
+    match value {
+        LuaValue::String(str) => match &*str.to_string_lossy() {
+            "stop" => Ok(PatternSlot::Stop),
+            "continue" => Ok(PatternSlot::Continue),
+            _ => slot_error(),
+        },
+        LuaValue::UserData(user_data) => {
+            if user_data.is::<BeatTimePattern>() {
+                let pattern = user_data.borrow::<BeatTimePattern>()?.clone();
+                Ok(PatternSlot::from(pattern))
+            } else if user_data.is::<SecondTimePattern>() {
+                let pattern = user_data.borrow::<SecondTimePattern>()?.clone();
+                Ok(PatternSlot::from(pattern))
+            } else if user_data.is::<TransformedPattern>() {
+                let pattern = user_data.borrow::<TransformedPattern>()?.clone();
+                Ok(PatternSlot::from(pattern))
+            } else if user_data.is::<PhraseUserData>() {
+                let phrase = user_data.borrow::<PhraseUserData>()?.phrase.clone();
+                Ok(PatternSlot::from(phrase))
+            } else if user_data.is::<CycleUserData>() {
+                // NB: pattern_from_userdata takes the cycle: pass a copy instead
+                let cycle = lua.create_userdata(user_data.borrow::<CycleUserData>()?.clone())?;
+                let pattern = pattern_from_userdata(
+                    lua,
+                    timeout_hook,
+                    &LuaValue::UserData(cycle),
+                    time_base,
+                    None,
+                )?;
+                Ok(PatternSlot::from(pattern))
+            } else {
+                slot_error()
+            }
+        }
+        _ => slot_error(),
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{bindings::*, BeatTimeStep, PatternSlot};
+
+    use super::PhraseUserData;
+
+    fn new_test_engine() -> Result<(Lua, LuaTimeoutHook), LuaError> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok((lua, timeout_hook))
+    }
+
+    fn evaluate_phrase_userdata(lua: &Lua, expression: &str) -> LuaResult<PhraseUserData> {
+        Ok(lua
+            .load(expression)
+            .eval::<LuaValue>()?
+            .as_userdata()
+            .ok_or(LuaError::RuntimeError("No user data".to_string()))?
+            .borrow::<PhraseUserData>()?
+            .clone())
+    }
+
+    #[test]
+    fn phrase() -> LuaResult<()> {
+        let (lua, _) = new_test_engine()?;
+
+        // invalid properties
+        assert!(evaluate_phrase_userdata(&lua, r#"phrase { length = 0 }"#).is_err());
+        assert!(evaluate_phrase_userdata(&lua, r#"phrase { unit = "ms" }"#).is_err());
+        assert!(evaluate_phrase_userdata(&lua, r#"phrase { patterns = 1 }"#).is_err());
+        assert!(evaluate_phrase_userdata(&lua, r#"phrase { patterns = { "c4" } }"#).is_err());
+        assert!(evaluate_phrase_userdata(&lua, r#"phrase { pattern = {} }"#).is_err());
+
+        // default length
+        let phrase = evaluate_phrase_userdata(&lua, r#"phrase { }"#)?.phrase;
+        assert_eq!(phrase.length(), BeatTimeStep::Bar(1.0));
+        assert!(phrase.pattern_slots().is_empty());
+
+        // slots
+        let phrase = evaluate_phrase_userdata(
+            &lua,
+            r#"
+            local kick = pattern { unit = "1/4", event = "c4" }
+            local hihat = cycle("c6*8")
+            return phrase {
+                unit = "beats",
+                length = 8,
+                patterns = { kick, hihat, "stop", "continue", phrase { patterns = { kick } } }
+            }
+            "#,
+        )?
+        .phrase;
+        assert_eq!(phrase.length(), BeatTimeStep::Beats(8.0));
+        let slots = phrase.pattern_slots();
+        assert_eq!(slots.len(), 5);
+        assert!(matches!(slots[0], PatternSlot::Pattern(_)));
+        assert!(matches!(slots[1], PatternSlot::Pattern(_)));
+        assert!(matches!(slots[2], PatternSlot::Stop));
+        assert!(matches!(slots[3], PatternSlot::Continue));
+        assert!(matches!(slots[4], PatternSlot::Pattern(_)));
+
+        // patterns and cycles can be reused in multiple phrases
+        assert!(lua
+            .load(
+                r#"
+                local c = cycle("c4 e4")
+                local p = pattern { event = "c4" }
+                return { phrase { patterns = { c, p } }, phrase { patterns = { c, p } } }
+                "#,
+            )
+            .eval::<LuaValue>()
+            .is_ok());
+
+        Ok(())
+    }
+}
@@ -5,7 +5,10 @@ use mlua::prelude::*;
 use crate::{
     bindings::{cycle::CycleUserData, unwrap::emitter_from_value, LuaTimeoutHook},
     event::InstrumentId,
-    pattern::{beat_time::BeatTimePattern, second_time::SecondTimePattern, Pattern},
+    pattern::{
+        beat_time::BeatTimePattern, second_time::SecondTimePattern,
+        transformed::TransformedPattern, Pattern,
+    },
     BeatTimeBase,
 };
 
@@ -13,10 +16,11 @@ use crate::{
 
 mod beat_time;
 mod second_time;
+mod transformed;
 
 // ---------------------------------------------------------------------------------------------
 
-// unwrap a BeatTimePattern or SecondTimePattern from the given LuaValue,
+// unwrap a BeatTimePattern, SecondTimePattern or TransformedPattern from the given LuaValue,
 // which is expected to be a user data
 pub(crate) fn pattern_from_userdata(
     lua: &Lua,
@@ -40,6 +44,13 @@ pub(crate) fn pattern_from_userdata(
                     .take::<SecondTimePattern>()?
                     .with_instrument(instrument),
             )))
+        } else if user_data.is::<TransformedPattern>() {
+            Ok(Rc::new(RefCell::new(
+                // NB: take instead of cloning: pattern userdata has no other usage than being defined
+                user_data
+                    .take::<TransformedPattern>()?
+                    .with_instrument(instrument),
+            )))
         } else if user_data.is::<CycleUserData>() {
             // create a default pattern from the given cycle
             Ok(Rc::new(RefCell::new(
@@ -72,7 +83,7 @@ pub(crate) fn pattern_from_userdata(
 mod test {
     use crate::{
         bindings::*,
-        event::{Event, NoteEvent},
+        event::{Event, NoteAttributes, NoteEvent},
         note::Note,
         pattern::{beat_time::BeatTimePattern, second_time::SecondTimePattern, PatternEvent},
         time::BeatTimeStep,
@@ -158,11 +169,92 @@ mod test {
                     note: Note::C6,
                     volume: 1.0,
                     panning: 0.0,
-                    delay: 0.0
+                    delay: 0.0,
+                    cents: 0.0,
+                    length: None,
+                    attributes: NoteAttributes::new()
                 })])),
                 duration: 11025
             })
         );
+
+        // BeatTimePattern with dynamics
+        let dynamics_pattern = lua
+            .load(
+                r#"
+                pattern {
+                    pulse = {1, 0.2, 0.6, 0.2},
+                    dynamics = {0.5, 1.0},
+                    probability = {1, 1, 1, 0},
+                    event = "c4"
+                }
+            "#,
+            )
+            .eval::<LuaValue>()
+            .unwrap();
+        let mut dynamics_pattern = dynamics_pattern
+            .as_userdata()
+            .unwrap()
+            .take::<BeatTimePattern>()?;
+        assert_eq!(
+            dynamics_pattern.dynamics(),
+            &crate::PulseDynamics::Accents(vec![0.5, 1.0])
+        );
+        let volumes = (0..4)
+            .map(
+                |_| match dynamics_pattern.next().and_then(|event| event.event) {
+                    Some(Event::NoteEvents(notes)) => notes[0].as_ref().map(|note| note.volume),
+                    _ => None,
+                },
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(volumes, vec![Some(1.0), Some(0.5), Some(1.0), None]);
+        assert!(lua
+            .load(r#"pattern { pulse = {1}, dynamics = "loud" }"#)
+            .eval::<LuaValue>()
+            .is_err());
+
+        // BeatTimePattern with weighted sub pulses
+        let weighted_pattern = lua
+            .load(r#"pattern { pulse = { {"1@3", 1}, { {1, 1, 1, weight = 2}, 0 } } }"#)
+            .eval::<LuaValue>()
+            .unwrap();
+        let mut weighted_pattern = weighted_pattern
+            .as_userdata()
+            .unwrap()
+            .take::<BeatTimePattern>()?;
+        let rhythm = weighted_pattern.rhythm_mut();
+        let step_times = (0..6)
+            .map(|_| rhythm.run().map(|event| event.step_time))
+            .collect::<Vec<_>>();
+        let step = 2.0 / 9.0;
+        assert_eq!(
+            step_times,
+            vec![
+                Some(0.75),
+                Some(0.25),
+                Some(step),
+                Some(step),
+                Some(step),
+                Some(1.0 / 3.0)
+            ]
+        );
+        assert!(lua
+            .load(r#"pattern { pulse = { {"1@0", 1} } }"#)
+            .eval::<LuaValue>()
+            .is_err());
+        assert!(lua
+            .load(r#"pattern { pulse = { {1, weight = "x"} } }"#)
+            .eval::<LuaValue>()
+            .is_err());
+        assert!(lua
+            .load(r#"pattern { pulse = { {"1@inf", 1} } }"#)
+            .eval::<LuaValue>()
+            .is_err());
+        assert!(lua
+            .load(r#"pattern { pulse = { {1, weight = math.huge} } }"#)
+            .eval::<LuaValue>()
+            .is_err());
         Ok(())
     }
 
@@ -176,6 +268,9 @@ mod test {
             volume: 0.5,
             panning: 0.0,
             delay: 0.25,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         })]);
 
         // BeatTimePattern function Context
@@ -277,7 +372,10 @@ mod test {
                     note: Note::C4,
                     volume: 1.0,
                     panning: 0.0,
-                    delay: 0.0
+                    delay: 0.0,
+                    cents: 0.0,
+                    length: None,
+                    attributes: NoteAttributes::new()
                 })])),
                 duration: 11025,
             })
@@ -354,6 +452,9 @@ mod test {
             volume: 0.25,
             panning: 0.5,
             delay: 0.75,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         })]);
 
         // SecondTimePattern function Context
@@ -395,7 +496,10 @@ mod test {
                     note: Note::C4,
                     volume: 1.0,
                     panning: 0.0,
-                    delay: 0.0
+                    delay: 0.0,
+                    cents: 0.0,
+                    length: None,
+                    attributes: NoteAttributes::new()
                 })],),),
                 duration: 48
             })
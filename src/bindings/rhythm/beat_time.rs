@@ -2,18 +2,22 @@ use mlua::prelude::*;
 
 use super::super::{
     unwrap::{
-        bad_argument_error, emitter_from_value, gate_from_value, parameters_from_value,
-        rhythm_from_value, rhythm_repeat_count_from_value,
+        bad_argument_error, beat_time_step_from_unit, emitter_from_value, gate_from_value,
+        parameters_from_value, pulse_dynamics_from_value, rhythm_from_value,
+        rhythm_repeat_count_from_value,
     },
     LuaTimeoutHook,
 };
+use super::transformed::add_transform_methods;
 
 use crate::prelude::*;
 
 // -------------------------------------------------------------------------------------------------
 
 impl LuaUserData for BeatTimePattern {
-    // BeatTimePattern is only passed through ATM
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        add_transform_methods(methods);
+    }
 }
 
 impl BeatTimePattern {
@@ -41,16 +45,9 @@ impl BeatTimePattern {
         let mut step = BeatTimeStep::Beats(resolution);
         if table.contains_key("unit")? {
             let unit = table.get::<String>("unit")?;
-            match unit.as_str() {
-                "bars" => step = BeatTimeStep::Bar(resolution),
-                "1/1" => step = BeatTimeStep::Whole(resolution),
-                "1/2" => step = BeatTimeStep::Half(resolution),
-                "beats" | "1/4" => step = BeatTimeStep::Beats(resolution),
-                "1/8" => step = BeatTimeStep::Eighth(resolution),
-                "1/16" => step = BeatTimeStep::Sixteenth(resolution),
-                "1/32" => step = BeatTimeStep::ThirtySecond(resolution),
-                "1/64" => step = BeatTimeStep::SixtyFourth(resolution),
-                _ => return Err(bad_argument_error("rhythm", "unit", 1, 
+            match beat_time_step_from_unit(unit.as_str(), resolution) {
+                Some(unit_step) => step = unit_step,
+                None => return Err(bad_argument_error("rhythm", "unit", 1,
                 "expected one of 'ms|seconds' or 'bars|beats' or '1/1|1/2|1/4|1/8|1/16|1/32|1/64"))
             }
         }
@@ -84,6 +81,18 @@ impl BeatTimePattern {
             let rhythm = rhythm_from_value(lua, timeout_hook, &value, time_base)?;
             pattern = pattern.with_rhythm_dyn(rhythm);
         }
+        // dynamics
+        if table.contains_key("dynamics")? {
+            let value = table.get::<LuaValue>("dynamics")?;
+            let dynamics = pulse_dynamics_from_value(&value)?;
+            pattern = pattern.with_dynamics(dynamics);
+        }
+        // probability
+        if table.contains_key("probability")? {
+            let value = table.get::<LuaValue>("probability")?;
+            let probability = rhythm_from_value(lua, timeout_hook, &value, time_base)?;
+            pattern = pattern.with_probability_dyn(probability);
+        }
         // gate
         if table.contains_key("gate")? {
             let value = table.get::<LuaValue>("gate")?;
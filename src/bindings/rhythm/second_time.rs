@@ -3,17 +3,20 @@ use mlua::prelude::*;
 use super::super::{
     unwrap::{
         bad_argument_error, emitter_from_value, gate_from_value, parameters_from_value,
-        rhythm_from_value, rhythm_repeat_count_from_value,
+        pulse_dynamics_from_value, rhythm_from_value, rhythm_repeat_count_from_value,
     },
     LuaTimeoutHook,
 };
+use super::transformed::add_transform_methods;
 
 use crate::prelude::*;
 
 // -------------------------------------------------------------------------------------------------
 
 impl LuaUserData for SecondTimePattern {
-    // SecondTimePattern is only passed through ATM
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        add_transform_methods(methods);
+    }
 }
 
 impl SecondTimePattern {
@@ -75,6 +78,18 @@ impl SecondTimePattern {
             let rhythm = rhythm_from_value(lua, timeout_hook, &value, time_base)?;
             pattern = pattern.with_rhythm_dyn(rhythm);
         }
+        // dynamics
+        if table.contains_key("dynamics")? {
+            let value = table.get::<LuaValue>("dynamics")?;
+            let dynamics = pulse_dynamics_from_value(&value)?;
+            pattern = pattern.with_dynamics(dynamics);
+        }
+        // probability
+        if table.contains_key("probability")? {
+            let value = table.get::<LuaValue>("probability")?;
+            let probability = rhythm_from_value(lua, timeout_hook, &value, time_base)?;
+            pattern = pattern.with_probability_dyn(probability);
+        }
         // gate
         if table.contains_key("gate")? {
             let value = table.get::<LuaValue>("gate")?;
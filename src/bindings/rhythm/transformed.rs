@@ -0,0 +1,361 @@
+use std::{cell::RefCell, rc::Rc};
+
+use mlua::prelude::*;
+use rand::Rng;
+
+use super::super::{unwrap::bad_argument_error, LuaAppData};
+
+use crate::prelude::*;
+
+// -------------------------------------------------------------------------------------------------
+
+impl LuaUserData for TransformedPattern {
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        add_transform_methods(methods);
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+// Register pattern transform functions for the given pattern user data type.
+// All transform functions consume the pattern they are called on and return a new pattern.
+pub(crate) fn add_transform_methods<T: LuaUserData, M: LuaUserDataMethods<T>>(methods: &mut M) {
+    // pattern:fast(factor)
+    methods.add_function("fast", |lua, (user_data, factor): (LuaAnyUserData, f64)| {
+        if factor <= 0.0 {
+            return Err(bad_argument_error(
+                "fast",
+                "factor",
+                1,
+                "factor must be > 0",
+            ));
+        }
+        let source = take_pattern(&user_data)?;
+        lua.create_userdata(TransformedPattern::with_speed(source, factor))
+    });
+    // pattern:slow(factor)
+    methods.add_function("slow", |lua, (user_data, factor): (LuaAnyUserData, f64)| {
+        if factor <= 0.0 {
+            return Err(bad_argument_error(
+                "slow",
+                "factor",
+                1,
+                "factor must be > 0",
+            ));
+        }
+        let source = take_pattern(&user_data)?;
+        lua.create_userdata(TransformedPattern::with_speed(source, 1.0 / factor))
+    });
+    // pattern:rev()
+    methods.add_function("rev", |lua, user_data: LuaAnyUserData| {
+        let source = take_pattern(&user_data)?;
+        lua.create_userdata(TransformedPattern::reversed(source))
+    });
+    // pattern:ply(count)
+    methods.add_function(
+        "ply",
+        |lua, (user_data, count): (LuaAnyUserData, LuaInteger)| {
+            if count <= 0 {
+                return Err(bad_argument_error("ply", "count", 1, "count must be > 0"));
+            }
+            let source = take_pattern(&user_data)?;
+            lua.create_userdata(TransformedPattern::plied(source, count as usize))
+        },
+    );
+    // pattern:degrade(amount?)
+    methods.add_function(
+        "degrade",
+        |lua, (user_data, amount): (LuaAnyUserData, Option<f64>)| {
+            let amount = amount.unwrap_or(0.5);
+            if !(0.0..=1.0).contains(&amount) {
+                return Err(bad_argument_error(
+                    "degrade",
+                    "amount",
+                    1,
+                    "amount must be in range [0, 1]",
+                ));
+            }
+            let source = take_pattern(&user_data)?;
+            let seed = random_seed(lua);
+            lua.create_userdata(TransformedPattern::degraded(source, amount, seed))
+        },
+    );
+    // pattern:every(count, fn)
+    methods.add_function(
+        "every",
+        |lua, (user_data, count, function): (LuaAnyUserData, LuaInteger, LuaFunction)| {
+            if count <= 0 {
+                return Err(bad_argument_error("every", "count", 1, "count must be > 0"));
+            }
+            let transformed = apply_function("every", copy_pattern(lua, &user_data)?, &function)?;
+            let source = take_pattern(&user_data)?;
+            lua.create_userdata(TransformedPattern::every(
+                source,
+                count as usize,
+                transformed,
+            ))
+        },
+    );
+    // pattern:sometimes(fn)
+    methods.add_function(
+        "sometimes",
+        |lua, (user_data, function): (LuaAnyUserData, LuaFunction)| {
+            // play the source pattern events or the transformed ones, never both
+            let amount = 0.5;
+            let seed = random_seed(lua);
+            let copy = take_pattern(&copy_pattern(lua, &user_data)?)?;
+            let transformed = apply_function(
+                "sometimes",
+                lua.create_userdata(TransformedPattern::undegraded(copy, amount, seed))?,
+                &function,
+            )?;
+            let source = take_pattern(&user_data)?;
+            let degraded = Rc::new(RefCell::new(TransformedPattern::degraded(
+                source, amount, seed,
+            )));
+            lua.create_userdata(TransformedPattern::overlaid(degraded, transformed))
+        },
+    );
+    // pattern:off(amount, fn?)
+    methods.add_function(
+        "off",
+        |lua, (user_data, amount, function): (LuaAnyUserData, f64, Option<LuaFunction>)| {
+            if amount < 0.0 {
+                return Err(bad_argument_error(
+                    "off",
+                    "amount",
+                    1,
+                    "amount must be >= 0",
+                ));
+            }
+            let copy = take_pattern(&copy_pattern(lua, &user_data)?)?;
+            let shifted = TransformedPattern::shifted(copy, amount);
+            let shifted = if let Some(function) = function {
+                apply_function("off", lua.create_userdata(shifted)?, &function)?
+            } else {
+                Rc::new(RefCell::new(shifted))
+            };
+            let source = take_pattern(&user_data)?;
+            lua.create_userdata(TransformedPattern::overlaid(source, shifted))
+        },
+    );
+}
+
+// -------------------------------------------------------------------------------------------------
+
+// Random seed for random transforms: uses the global seed, when set.
+fn random_seed(lua: &Lua) -> u64 {
+    lua.app_data_ref::<LuaAppData>()
+        .expect("Failed to access Lua app data")
+        .rand_seed
+        .unwrap_or(rand::rng().random())
+}
+
+// Take a pattern from the given pattern user data.
+fn take_pattern(user_data: &LuaAnyUserData) -> LuaResult<Rc<RefCell<dyn Pattern>>> {
+    if user_data.is::<BeatTimePattern>() {
+        Ok(Rc::new(RefCell::new(user_data.take::<BeatTimePattern>()?)))
+    } else if user_data.is::<SecondTimePattern>() {
+        Ok(Rc::new(RefCell::new(
+            user_data.take::<SecondTimePattern>()?,
+        )))
+    } else if user_data.is::<TransformedPattern>() {
+        Ok(Rc::new(RefCell::new(
+            user_data.take::<TransformedPattern>()?,
+        )))
+    } else {
+        Err(LuaError::FromLuaConversionError {
+            from: "userdata",
+            to: "pattern".to_string(),
+            message: Some("expecting a pattern".to_string()),
+        })
+    }
+}
+
+// Create a new user data instance with a copy of the given pattern user data.
+fn copy_pattern(lua: &Lua, user_data: &LuaAnyUserData) -> LuaResult<LuaAnyUserData> {
+    if user_data.is::<BeatTimePattern>() {
+        lua.create_userdata(user_data.borrow::<BeatTimePattern>()?.clone())
+    } else if user_data.is::<SecondTimePattern>() {
+        lua.create_userdata(user_data.borrow::<SecondTimePattern>()?.clone())
+    } else if user_data.is::<TransformedPattern>() {
+        lua.create_userdata(user_data.borrow::<TransformedPattern>()?.clone())
+    } else {
+        Err(LuaError::FromLuaConversionError {
+            from: "userdata",
+            to: "pattern".to_string(),
+            message: Some("expecting a pattern".to_string()),
+        })
+    }
+}
+
+// Call the given transform function with the given pattern, and unwrap the resulting pattern.
+fn apply_function(
+    name: &str,
+    pattern: LuaAnyUserData,
+    function: &LuaFunction,
+) -> LuaResult<Rc<RefCell<dyn Pattern>>> {
+    let result = function.call::<LuaValue>(pattern)?;
+    if let Some(user_data) = result.as_userdata() {
+        take_pattern(user_data)
+    } else {
+        Err(bad_argument_error(
+            name,
+            "fn",
+            2,
+            "function must return a pattern",
+        ))
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bindings::*, pattern::transformed::TransformedPattern, Event, Note, Pattern, PatternEvent,
+        SampleTime,
+    };
+
+    fn new_test_engine() -> Result<(Lua, LuaTimeoutHook), LuaError> {
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok((lua, timeout_hook))
+    }
+
+    fn evaluate_notes(
+        lua: &Lua,
+        expression: &str,
+        sample_time: SampleTime,
+    ) -> LuaResult<Vec<(SampleTime, Option<Note>)>> {
+        let value = lua.load(expression).eval::<LuaValue>()?;
+        let mut pattern = value
+            .as_userdata()
+            .ok_or(LuaError::RuntimeError("No user data".to_string()))?
+            .take::<TransformedPattern>()?;
+        let mut notes = Vec::new();
+        while let Some(PatternEvent { time, event, .. }) = pattern.run_until_time(sample_time) {
+            let note = match event {
+                Some(Event::NoteEvents(note_events)) => note_events[0].as_ref().map(|n| n.note),
+                _ => None,
+            };
+            notes.push((time, note));
+        }
+        Ok(notes)
+    }
+
+    #[test]
+    fn transforms() -> LuaResult<()> {
+        let (lua, _) = new_test_engine()?;
+
+        // invalid arguments
+        assert!(evaluate_notes(&lua, r#"pattern{}:fast(0)"#, 0).is_err());
+        assert!(evaluate_notes(&lua, r#"pattern{}:slow(-1)"#, 0).is_err());
+        assert!(evaluate_notes(&lua, r#"pattern{}:ply(0)"#, 0).is_err());
+        assert!(evaluate_notes(&lua, r#"pattern{}:degrade(2)"#, 0).is_err());
+        assert!(evaluate_notes(&lua, r#"pattern{}:every(2, function() end)"#, 0).is_err());
+
+        let source = r#"pattern { unit = "1/4", pulse = { 1, 1, 1, 1 }, event = { "c4", "d4", "e4", "f4" } }"#;
+
+        // fast, slow
+        assert_eq!(
+            evaluate_notes(&lua, &format!("{source}:fast(2)"), 44100)?,
+            vec![
+                (0, Some(Note::C4)),
+                (11025, Some(Note::D4)),
+                (22050, Some(Note::E4)),
+                (33075, Some(Note::F4)),
+            ]
+        );
+        assert_eq!(
+            evaluate_notes(&lua, &format!("{source}:slow(2)"), 88200)?,
+            vec![(0, Some(Note::C4)), (44100, Some(Note::D4))]
+        );
+
+        // rev, ply
+        assert_eq!(
+            evaluate_notes(&lua, &format!("{source}:rev()"), 88200)?
+                .into_iter()
+                .map(|(_, note)| note.unwrap())
+                .collect::<Vec<_>>(),
+            vec![Note::F4, Note::E4, Note::D4, Note::C4]
+        );
+        assert_eq!(
+            evaluate_notes(&lua, &format!("{source}:rev():ply(2)"), 22050)?,
+            vec![(0, Some(Note::F4)), (11025, Some(Note::F4))]
+        );
+
+        // every
+        assert_eq!(
+            evaluate_notes(
+                &lua,
+                &format!("{source}:every(2, function(p) return p:rev() end)"),
+                88200 * 2
+            )?
+            .into_iter()
+            .map(|(_, note)| note.unwrap())
+            .collect::<Vec<_>>(),
+            vec![
+                Note::F4,
+                Note::E4,
+                Note::D4,
+                Note::C4,
+                Note::C4,
+                Note::D4,
+                Note::E4,
+                Note::F4
+            ]
+        );
+
+        // degrade, sometimes
+        let notes = evaluate_notes(
+            &lua,
+            &format!("math.randomseed(1234); return {source}:degrade()"),
+            88200 * 8,
+        )?;
+        assert_eq!(notes.len(), 32);
+        assert!(notes.iter().any(|(_, note)| note.is_none()));
+        assert!(notes.iter().any(|(_, note)| note.is_some()));
+        let notes = evaluate_notes(
+            &lua,
+            &format!("{source}:sometimes(function(p) return p:fast(2) end)"),
+            88200 * 8,
+        )?;
+        assert!(notes.iter().any(|(_, note)| note.is_some()));
+
+        // off
+        assert_eq!(
+            evaluate_notes(&lua, &format!("{source}:off(0.125)"), 44100)?,
+            vec![
+                (0, Some(Note::C4)),
+                (11025, Some(Note::C4)),
+                (22050, Some(Note::D4)),
+                (33075, Some(Note::D4)),
+            ]
+        );
+        assert_eq!(
+            evaluate_notes(
+                &lua,
+                &format!("{source}:off(0.125, function(p) return p:degrade(1) end)"),
+                44100
+            )?,
+            vec![
+                (0, Some(Note::C4)),
+                (11025, None),
+                (22050, Some(Note::D4)),
+                (33075, None),
+            ]
+        );
+
+        Ok(())
+    }
+}
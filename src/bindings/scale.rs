@@ -14,6 +14,12 @@ impl LuaUserData for Scale {
         fields.add_field_method_get("notes", |lua, this| -> LuaResult<LuaTable> {
             lua.create_sequence_from(this.notes().iter().map(|n| LuaInteger::from(*n as u8)))
         });
+        fields.add_field_method_get("key", |_lua, this| -> LuaResult<LuaInteger> {
+            Ok(LuaInteger::from(this.root_note() as u8))
+        });
+        fields.add_field_method_get("mode", |_lua, this| -> LuaResult<String> {
+            Ok(this.mode_name().to_string())
+        });
     }
 
     fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
@@ -124,6 +130,24 @@ impl LuaUserData for Scale {
                 Ok(ret)
             },
         );
+
+        methods.add_method(
+            "progression",
+            |lua, this, progression: LuaString| -> LuaResult<LuaTable> {
+                let chords = this
+                    .progression(&progression.to_string_lossy())
+                    .map_err(|err| bad_argument_error("progression", "progression", 1, &err))?;
+                let chords = chords
+                    .iter()
+                    .map(|chord| {
+                        lua.create_sequence_from(
+                            chord.notes().iter().map(|n| LuaInteger::from(*n as u8)),
+                        )
+                    })
+                    .collect::<LuaResult<Vec<_>>>()?;
+                lua.create_sequence_from(chords)
+            },
+        );
     }
 }
 
@@ -233,6 +257,38 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn scale_progression() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        assert!(lua
+            .load(r#"scale("c4", "major"):progression("I IX")"#)
+            .eval::<LuaValue>()
+            .is_err());
+        assert!(lua
+            .load(r#"scale("c4", "pentatonic major"):progression("I")"#)
+            .eval::<LuaValue>()
+            .is_err());
+
+        assert_eq!(
+            lua.load(r#"scale("c4", "major"):progression("I vi ii7 V7/V")"#)
+                .eval::<Vec<Vec<i32>>>()?,
+            vec![
+                vec![48, 52, 55],
+                vec![57, 60, 64],
+                vec![50, 53, 57, 60],
+                vec![50, 54, 57, 60]
+            ]
+        );
+        // numerals in chord strings
+        assert_eq!(
+            lua.load(r#"note("c4'V7/V").notes[4].key"#)
+                .eval::<String>()?,
+            "C5"
+        );
+        Ok(())
+    }
+
     #[test]
     fn scale_degree() -> LuaResult<()> {
         let lua = new_test_engine()?;
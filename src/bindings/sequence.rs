@@ -1,9 +1,12 @@
+use std::sync::Arc;
+
 use mlua::prelude::*;
 
 use super::unwrap::{
     amplify_array_from_value, bad_argument_error, delay_array_from_value,
-    instrument_array_from_value, note_events_from_value, panning_array_from_value,
-    sequence_from_value, transpose_steps_array_from_value, volume_array_from_value,
+    instrument_array_from_value, length_array_from_value, note_events_from_value,
+    panning_array_from_value, sequence_from_value, transpose_steps_array_from_value,
+    volume_array_from_value,
 };
 
 use crate::{
@@ -193,6 +196,34 @@ impl LuaUserData for SequenceUserData {
             drop(this);
             Ok(ud)
         });
+
+        methods.add_function("length", |lua, (ud, value): (LuaAnyUserData, LuaValue)| {
+            let mut this = ud.borrow_mut::<Self>()?;
+            let lengths = length_array_from_value(lua, value, this.notes.len())?;
+            for (notes, length) in this.notes.iter_mut().zip(lengths) {
+                for note in notes.iter_mut().flatten() {
+                    note.length = Some(length);
+                }
+            }
+            drop(this);
+            Ok(ud)
+        });
+
+        methods.add_function(
+            "attribute",
+            |lua, (ud, name, value): (LuaAnyUserData, String, LuaValue)| {
+                let mut this = ud.borrow_mut::<Self>()?;
+                let values = attribute_array_from_value(lua, value, this.notes.len(), &name)?;
+                let name = Arc::<str>::from(name);
+                for (notes, value) in this.notes.iter_mut().zip(values) {
+                    for note in notes.iter_mut().flatten() {
+                        note.attributes.insert(Arc::clone(&name), value);
+                    }
+                }
+                drop(this);
+                Ok(ud)
+            },
+        );
     }
 }
 
@@ -1,55 +1,158 @@
-use std::{
-    cell::RefCell,
-    rc::Rc,
-    time::{Duration, Instant},
-};
+use std::{cell::RefCell, rc::Rc, time::Instant};
 
 use mlua::prelude::*;
 
+use super::limits::{ScriptLimitError, ScriptLimits};
+
 // -------------------------------------------------------------------------------------------------
 
-// Limits script execution time and aborts execution when a script runs too long. This way e.g.
-// never ending loops are stopped automatically with a timeout error.
+// Limits script execution time and instruction count, and aborts execution when a script runs
+// too long. This way e.g. never ending loops are stopped automatically with a timeout error.
 //
-// While constructed, it checks every few instructions if a timeout duration has been reached
-// and then aborts the script by firing an error.
+// While constructed, it checks every few instructions if a timeout duration or instruction
+// budget has been reached and then aborts the script by firing a `ScriptLimitError`.
 // When cloning and instance, it will use the existing hook, so ensure to call `reset` before
 // invoking new lua functions. The last instance that get's dropped will then remove the hook.
+//
+// Instances created via `with_limits` share the hook, but apply their own timeout and instruction
+// limits: `reset` activates the limits of the instance it gets called on.
 #[derive(Debug)]
 pub(crate) struct LuaTimeoutHook {
     active: Rc<RefCell<usize>>,
     start: Rc<RefCell<Instant>>,
+    instructions: Rc<RefCell<u64>>,
+    current_limits: Rc<RefCell<ScriptLimits>>,
+    installed_interval: Rc<RefCell<Option<u64>>>,
+    limits: ScriptLimits,
 }
 
 impl LuaTimeoutHook {
-    // default number of ms a script may run before a timeout error is fired.
-    // assumes scripts are running in a real-time alike context.
-    const DEFAULT_TIMEOUT: Duration = Duration::from_millis(200);
+    // max number of instructions between two limit checks.
+    #[cfg(not(any(feature = "luau", feature = "luau-jit")))]
+    const MAX_INSTRUCTION_INTERVAL: u64 = 1000;
 
     pub(crate) fn new(lua: &Lua) -> Self {
-        Self::new_with_timeout(lua, Self::DEFAULT_TIMEOUT)
+        Self::new_with_limits(lua, &ScriptLimits::default())
     }
 
-    pub(crate) fn new_with_timeout(lua: &Lua, timeout: Duration) -> Self {
+    pub(crate) fn new_with_limits(lua: &Lua, limits: &ScriptLimits) -> Self {
         let active = Rc::new(RefCell::new(1));
         let start = Rc::new(RefCell::new(Instant::now()));
-        let timeout_hook = {
-            let active = Rc::clone(&active);
-            let start = Rc::clone(&start);
+        let instructions = Rc::new(RefCell::new(0));
+        let current_limits = Rc::new(RefCell::new(*limits));
+        let installed_interval = Rc::new(RefCell::new(None));
+        let limits = *limits;
+        // apply memory limit for the entire engine
+        if let Some(memory_limit) = limits.memory_limit {
+            if let Err(err) = lua.set_memory_limit(memory_limit) {
+                log::warn!("Failed to apply Lua memory limit: {err}");
+            }
+        }
+        // install hook for timeout and instruction limits, if needed
+        let hook = Self {
+            active,
+            start,
+            instructions,
+            current_limits,
+            installed_interval,
+            limits,
+        };
+        hook.install(lua);
+        hook
+    }
+
+    // create a new hook instance which shares the hook with this instance, but applies the given
+    // timeout and instruction limits when it gets reset. The memory limit can not be overridden,
+    // as it applies to the entire engine.
+    pub(crate) fn with_limits(&self, lua: &Lua, limits: &ScriptLimits) -> Self {
+        let mut hook = self.clone();
+        hook.limits = ScriptLimits {
+            memory_limit: self.limits.memory_limit,
+            ..*limits
+        };
+        hook.install(lua);
+        hook
+    }
+
+    // timeout and instruction limits which get applied when resetting this instance.
+    pub(crate) fn limits(&self) -> &ScriptLimits {
+        &self.limits
+    }
+
+    // reset timestamp and instruction counter of the hook when running e.g. a callback again
+    // and activate this instance's limits.
+    pub(crate) fn reset(&mut self) {
+        *self.current_limits.borrow_mut() = self.limits;
+        *self.start.borrow_mut() = Instant::now();
+        *self.instructions.borrow_mut() = 0;
+    }
+
+    // number of instructions between two limit checks for the given limits.
+    #[cfg(not(any(feature = "luau", feature = "luau-jit")))]
+    fn instruction_interval(limits: &ScriptLimits) -> u64 {
+        let timeout_interval = limits.timeout.map_or(u64::MAX, |timeout| {
+            (timeout.as_millis() as u64).saturating_mul(10).max(1)
+        });
+        let instruction_interval = limits.instruction_limit.map_or(u64::MAX, |limit| {
+            limit.clamp(1, Self::MAX_INSTRUCTION_INTERVAL)
+        });
+        timeout_interval
+            .min(instruction_interval)
+            .min(u32::MAX as u64)
+    }
+
+    // NB: Luau has no instruction hooks: count interrupts instead
+    #[cfg(any(feature = "luau", feature = "luau-jit"))]
+    fn instruction_interval(_limits: &ScriptLimits) -> u64 {
+        1
+    }
+
+    // install the hook, if this instance's limits need one and the installed hook, if any,
+    // does not check the limits often enough.
+    fn install(&self, lua: &Lua) {
+        if self.limits.timeout.is_none() && self.limits.instruction_limit.is_none() {
+            return;
+        }
+        let instruction_interval = Self::instruction_interval(&self.limits);
+        if self
+            .installed_interval
+            .borrow()
+            .is_some_and(|interval| interval <= instruction_interval)
+        {
+            return;
+        }
+        *self.installed_interval.borrow_mut() = Some(instruction_interval);
+        let limit_hook = {
+            let active = Rc::clone(&self.active);
+            let start = Rc::clone(&self.start);
+            let instructions = Rc::clone(&self.instructions);
+            let current_limits = Rc::clone(&self.current_limits);
+            let installed_interval = Rc::clone(&self.installed_interval);
             move || {
                 if *active.borrow() > 0 {
-                    if start.borrow().elapsed() > timeout {
+                    let ScriptLimits {
+                        timeout,
+                        instruction_limit,
+                        ..
+                    } = *current_limits.borrow();
+                    *instructions.borrow_mut() += instruction_interval;
+                    if let Some(limit) =
+                        instruction_limit.filter(|limit| *instructions.borrow() > *limit)
+                    {
+                        *instructions.borrow_mut() = 0;
+                        Err(LuaError::external(ScriptLimitError::InstructionLimit(
+                            limit,
+                        )))
+                    } else if let Some(timeout) =
+                        timeout.filter(|timeout| start.borrow().elapsed() > *timeout)
+                    {
                         *start.borrow_mut() = Instant::now();
-                        Err(LuaError::RuntimeError(
-                            String::from("Script timeout. ")
-                                + &format!("Execution took longer than {} ms to complete.\n", timeout.as_millis())
-                                + "Please avoid overhead and check for never ending loops in your script. "
-                                + "Also note that the script is running in real-time thread!",
-                        ))
+                        Err(LuaError::external(ScriptLimitError::Timeout(timeout)))
                     } else {
                         Ok(false) // continue running
                     }
                 } else {
+                    *installed_interval.borrow_mut() = None;
                     Ok(true) // remove hook
                 }
             }
@@ -58,8 +161,8 @@ impl LuaTimeoutHook {
         #[cfg(not(any(feature = "luau", feature = "luau-jit")))]
         {
             lua.set_hook(
-                LuaHookTriggers::new().every_nth_instruction(timeout.as_millis() as u32 * 10),
-                move |lua, _debug| match timeout_hook() {
+                LuaHookTriggers::new().every_nth_instruction(instruction_interval as u32),
+                move |lua, _debug| match limit_hook() {
                     Ok(remove_hook) => {
                         if remove_hook {
                             lua.remove_hook();
@@ -73,7 +176,7 @@ impl LuaTimeoutHook {
         // Luau -> set_interrupt
         #[cfg(any(feature = "luau", feature = "luau-jit"))]
         {
-            lua.set_interrupt(move |lua| match timeout_hook() {
+            lua.set_interrupt(move |lua| match limit_hook() {
                 Ok(remove_hook) => {
                     if remove_hook {
                         lua.remove_interrupt();
@@ -83,12 +186,6 @@ impl LuaTimeoutHook {
                 Err(err) => Err(err),
             });
         }
-        Self { active, start }
-    }
-
-    // reset timestamp of the hook when running e.g. a callback again
-    pub(crate) fn reset(&mut self) {
-        *self.start.borrow_mut() = Instant::now();
     }
 }
 
@@ -100,6 +197,10 @@ impl Clone for LuaTimeoutHook {
         Self {
             active: Rc::clone(&self.active),
             start: Rc::clone(&self.start),
+            instructions: Rc::clone(&self.instructions),
+            current_limits: Rc::clone(&self.current_limits),
+            installed_interval: Rc::clone(&self.installed_interval),
+            limits: self.limits,
         }
     }
 }
@@ -1,13 +1,22 @@
 //! Various lua->rust conversion helpers
 
-use std::{cell::RefCell, ops::RangeBounds, rc::Rc, sync::Arc};
+use std::{cell::RefCell, ops::RangeBounds, rc::Rc, sync::Arc, time::Duration};
 
 use mlua::prelude::*;
 
 use crate::{
     bindings::{
-        callback::LuaCallback, cycle::CycleUserData, note::NoteUserData,
-        parameter::ParameterUserData, sequence::SequenceUserData, LuaTimeoutHook,
+        arpeggio::ArpeggioUserData,
+        callback::{lua_callback_error_channel, LuaCallback, LuaCallbackKind},
+        cycle::CycleUserData,
+        definitions::SCRIPT_LIMITS_PROPERTIES,
+        gate::GateUserData,
+        generative::RhythmUserData,
+        melody::MelodyUserData,
+        note::NoteUserData,
+        parameter::ParameterUserData,
+        sequence::SequenceUserData,
+        LuaTimeoutHook,
     },
     prelude::*,
 };
@@ -87,6 +96,10 @@ impl FromLua for Note {
 impl IntoLua for NoteEvent {
     fn into_lua(self, lua: &Lua) -> LuaResult<LuaValue> {
         let table = lua.create_table()?;
+        // set custom attributes first, so they can't overwrite the note properties
+        for (name, value) in self.attributes {
+            table.set(&*name, value as f64)?;
+        }
         table.set("key", self.note.into_lua(lua)?)?;
         if let Some(instrument) = self.instrument {
             table.set(
@@ -97,6 +110,9 @@ impl IntoLua for NoteEvent {
         table.set("volume", self.volume as f64)?;
         table.set("panning", self.panning as f64)?;
         table.set("delay", self.delay as f64)?;
+        if let Some(length) = self.length {
+            table.set("length", length as f64)?;
+        }
         Ok(LuaValue::Table(table))
     }
 }
@@ -309,8 +325,38 @@ pub(crate) fn delay_array_from_value(
     float_array_from_value(lua, value, array_len, "delay", 0.0..=1.0)
 }
 
+pub(crate) fn length_array_from_value(
+    lua: &Lua,
+    value: LuaValue,
+    array_len: usize,
+) -> LuaResult<Vec<f32>> {
+    float_array_from_value(lua, value, array_len, "length", 0.0..)
+}
+
+pub(crate) fn attribute_array_from_value(
+    lua: &Lua,
+    value: LuaValue,
+    array_len: usize,
+    name: &str,
+) -> LuaResult<Vec<f32>> {
+    float_array_from_value(lua, value, array_len, name, ..)
+}
+
 // ---------------------------------------------------------------------------------------------
 
+// Name of the first property alias which is set in the table, or the first name.
+fn property_name_from_table<const N: usize>(
+    table: &LuaTable,
+    names: [&'static str; N],
+) -> LuaResult<&'static str> {
+    for name in names {
+        if table.contains_key(name)? {
+            return Ok(name);
+        }
+    }
+    Ok(names[0])
+}
+
 fn float_value_from_table<Range>(
     table: &LuaTable,
     name: &'static str,
@@ -367,17 +413,64 @@ pub(crate) fn instrument_value_from_table(table: &LuaTable) -> LuaResult<Option<
 }
 
 pub(crate) fn volume_value_from_table(table: &LuaTable) -> LuaResult<f32> {
-    float_value_from_table(table, "volume", 0.0..=1.0, 1.0)
+    let name = property_name_from_table(table, ["volume", "vol"])?;
+    float_value_from_table(table, name, 0.0..=1.0, 1.0)
 }
 
 pub(crate) fn panning_value_from_table(table: &LuaTable) -> LuaResult<f32> {
-    float_value_from_table(table, "panning", -1.0..=1.0, 0.0)
+    let name = property_name_from_table(table, ["panning", "pan"])?;
+    float_value_from_table(table, name, -1.0..=1.0, 0.0)
 }
 
 pub(crate) fn delay_value_from_table(table: &LuaTable) -> LuaResult<f32> {
     float_value_from_table(table, "delay", 0.0..1.0, 0.0)
 }
 
+pub(crate) fn length_value_from_table(table: &LuaTable) -> LuaResult<Option<f32>> {
+    if table.get::<LuaValue>("length")?.is_nil() {
+        Ok(None)
+    } else {
+        Ok(Some(float_value_from_table(table, "length", 0.0.., 1.0)?))
+    }
+}
+
+pub(crate) fn attribute_values_from_table(table: &LuaTable) -> LuaResult<NoteAttributes> {
+    const NOTE_PROPERTIES: [&str; 8] = [
+        "key",
+        "instrument",
+        "volume",
+        "vol",
+        "panning",
+        "pan",
+        "delay",
+        "length",
+    ];
+    let mut attributes = NoteAttributes::new();
+    for pair in table.pairs::<LuaValue, LuaValue>() {
+        let (key, value) = pair?;
+        let LuaValue::String(name) = key else {
+            continue;
+        };
+        let name = name.to_string_lossy();
+        if NOTE_PROPERTIES.contains(&&*name) {
+            continue;
+        }
+        let value = match value {
+            LuaValue::Integer(value) => value as f32,
+            LuaValue::Number(value) => value as f32,
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "number".to_string(),
+                    message: Some(format!("'{}' attribute must be a number", name)),
+                })
+            }
+        };
+        attributes.insert(Arc::from(&*name), value);
+    }
+    Ok(attributes)
+}
+
 fn float_value_from_string<Range>(
     str: &str,
     name: &'static str,
@@ -445,6 +538,31 @@ pub(crate) fn delay_value_from_string(str: &str) -> LuaResult<f32> {
     float_value_from_string(str, "delay", 0.0..1.0, 0.0)
 }
 
+pub(crate) fn length_value_from_string(str: &str) -> LuaResult<Option<f32>> {
+    Ok(Some(float_value_from_string(str, "length", 0.0.., 1.0)?))
+}
+
+pub(crate) fn attribute_value_from_string(str: &str) -> LuaResult<(Arc<str>, f32)> {
+    let (name, value) = str.split_once('=').unwrap_or((str, ""));
+    if name.is_empty() || value.is_empty() {
+        return Err(LuaError::RuntimeError(format!(
+            "invalid attribute '{}': expecting a 'name=value' string",
+            str
+        )));
+    }
+    let value = value
+        .parse::<f32>()
+        .map_err(|_| LuaError::FromLuaConversionError {
+            from: "string",
+            to: "number".to_string(),
+            message: Some(format!(
+                "'{}' attribute value '{}' is not a number",
+                name, value
+            )),
+        })?;
+    Ok((Arc::from(name), value))
+}
+
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) fn is_empty_note_string(s: &str) -> bool {
@@ -507,8 +625,27 @@ pub(crate) fn note_event_from_string(str: &str) -> LuaResult<Option<NoteEvent>>
         let mut volume = 1.0;
         let mut panning = 0.0;
         let mut delay = 0.0;
+        let mut length = None;
+        let mut attributes = NoteAttributes::new();
         for split in white_space_splits {
-            if let Some(instrument_str) = split.strip_prefix('#') {
+            if let Some((name, value_str)) = split.split_once('=') {
+                // volume, panning and delay attributes alias the note properties
+                match name {
+                    "volume" | "vol" if !value_str.is_empty() => {
+                        volume = volume_value_from_string(value_str)?;
+                    }
+                    "panning" | "pan" if !value_str.is_empty() => {
+                        panning = panning_value_from_string(value_str)?;
+                    }
+                    "delay" if !value_str.is_empty() => {
+                        delay = delay_value_from_string(value_str)?;
+                    }
+                    _ => {
+                        let (name, value) = attribute_value_from_string(split)?;
+                        attributes.insert(name, value);
+                    }
+                }
+            } else if let Some(instrument_str) = split.strip_prefix('#') {
                 instrument = instrument_value_from_string(instrument_str)?;
             } else if let Some(volume_str) = split.strip_prefix('v') {
                 volume = volume_value_from_string(volume_str)?;
@@ -516,14 +653,22 @@ pub(crate) fn note_event_from_string(str: &str) -> LuaResult<Option<NoteEvent>>
                 panning = panning_value_from_string(panning_str)?;
             } else if let Some(delay_str) = split.strip_prefix('d') {
                 delay = delay_value_from_string(delay_str)?;
+            } else if let Some(length_str) = split.strip_prefix('l') {
+                length = length_value_from_string(length_str)?;
             } else {
                 return Err(LuaError::RuntimeError(
                     format!("invalid note string segment: '{}'. ", split) +
-                        "expecting only number values with '#' (instrument),'v' (volume), 'p' (panning) or 'd' (delay) prefixes here."),
+                        "expecting only number values with '#' (instrument),'v' (volume), 'p' (panning), 'd' (delay) or 'l' (length) prefixes or 'name=value' attributes here."),
                 );
             }
         }
-        Ok(new_note((note, instrument, volume, panning, delay)))
+        Ok(
+            new_note((note, instrument, volume, panning, delay)).map(|note_event| NoteEvent {
+                length,
+                attributes,
+                ..note_event
+            }),
+        )
     }
 }
 
@@ -541,7 +686,9 @@ pub(crate) fn note_event_from_table_map(table: &LuaTable) -> LuaResult<Option<No
         let volume = volume_value_from_table(table)?;
         let panning = panning_value_from_table(table)?;
         let delay = delay_value_from_table(table)?;
-        // { key = 60, [volume = 1.0, panning = 0.0, delay = 0.0] }
+        let length = length_value_from_table(table)?;
+        let attributes = attribute_values_from_table(table)?;
+        // { key = 60, [volume = 1.0, panning = 0.0, delay = 0.0, length = 1.0] }
         if let Some(note_value) = key.as_i32() {
             Ok(new_note((
                 Note::from(note_value as u8),
@@ -549,13 +696,24 @@ pub(crate) fn note_event_from_table_map(table: &LuaTable) -> LuaResult<Option<No
                 volume,
                 panning,
                 delay,
-            )))
+            ))
+            .map(|note_event| NoteEvent {
+                length,
+                attributes,
+                ..note_event
+            }))
         }
-        // { key = "C4", [instrument = 1, volume = 1.0, panning = 0.0, delay = 0.0] }
+        // { key = "C4", [instrument = 1, volume = 1.0, panning = 0.0, delay = 0.0, length = 1.0] }
         else if let Some(note_str) = key.as_str() {
             let note = Note::try_from(&*note_str)
                 .map_err(|err| LuaError::RuntimeError(err.to_string()))?;
-            Ok(new_note((note, instrument, volume, panning, delay)))
+            Ok(
+                new_note((note, instrument, volume, panning, delay)).map(|note_event| NoteEvent {
+                    length,
+                    attributes,
+                    ..note_event
+                }),
+            )
         } else {
             Err(LuaError::FromLuaConversionError {
                 from: key.type_name(),
@@ -658,8 +816,27 @@ pub(crate) fn chord_events_from_string(chord_string: &str) -> LuaResult<Vec<Opti
     let mut volume = 1.0;
     let mut panning = 0.0;
     let mut delay = 0.0;
+    let mut length = None;
+    let mut attributes = NoteAttributes::new();
     for split in white_space_splits {
-        if let Some(instrument_str) = split.strip_prefix('#') {
+        if let Some((name, value_str)) = split.split_once('=') {
+            // volume, panning and delay attributes alias the note properties
+            match name {
+                "volume" | "vol" if !value_str.is_empty() => {
+                    volume = volume_value_from_string(value_str)?;
+                }
+                "panning" | "pan" if !value_str.is_empty() => {
+                    panning = panning_value_from_string(value_str)?;
+                }
+                "delay" if !value_str.is_empty() => {
+                    delay = delay_value_from_string(value_str)?;
+                }
+                _ => {
+                    let (name, value) = attribute_value_from_string(split)?;
+                    attributes.insert(name, value);
+                }
+            }
+        } else if let Some(instrument_str) = split.strip_prefix('#') {
             instrument = instrument_value_from_string(instrument_str)?;
         } else if let Some(volume_str) = split.strip_prefix('v') {
             volume = volume_value_from_string(volume_str)?;
@@ -667,10 +844,12 @@ pub(crate) fn chord_events_from_string(chord_string: &str) -> LuaResult<Vec<Opti
             panning = panning_value_from_string(panning_str)?;
         } else if let Some(delay_str) = split.strip_prefix('d') {
             delay = delay_value_from_string(delay_str)?;
+        } else if let Some(length_str) = split.strip_prefix('l') {
+            length = length_value_from_string(length_str)?;
         } else {
             return Err(LuaError::RuntimeError(
                     format!("invalid note string segment: '{}'. ", split) +
-                        "expecting only number values with '#' (instrument),'v' (volume), 'p' (panning) or 'd' (delay) prefixes here."),
+                        "expecting only number values with '#' (instrument),'v' (volume), 'p' (panning), 'd' (delay) or 'l' (length) prefixes or 'name=value' attributes here."),
                 );
         }
     }
@@ -685,6 +864,11 @@ pub(crate) fn chord_events_from_string(chord_string: &str) -> LuaResult<Vec<Opti
                 panning,
                 delay,
             ))
+            .map(|note_event| NoteEvent {
+                length,
+                attributes: attributes.clone(),
+                ..note_event
+            })
         })
         .collect::<Vec<_>>())
 }
@@ -751,29 +935,23 @@ pub fn pulse_from_value(value: &LuaValue) -> LuaResult<Pulse> {
         LuaValue::Boolean(bool) => Ok(Pulse::from(*bool)),
         LuaValue::Integer(integer) => Ok(Pulse::from(*integer as u32)),
         LuaValue::Number(number) => Ok(Pulse::from(*number as f32)),
-        LuaValue::String(str) => {
-            let str = str.to_string_lossy();
-            if let Ok(number) = str.parse::<f32>() {
-                Ok(Pulse::from(number))
-            } else if let Ok(integer) = str.parse::<u32>() {
-                Ok(Pulse::from(integer))
-            } else if let Ok(bool) = str.parse::<bool>() {
-                Ok(Pulse::from(bool))
-            } else {
-                Err(LuaError::FromLuaConversionError {
-                    from: "string",
-                    to: "pulse".to_string(),
-                    message: Some("Invalid pulse string value".to_string()),
-                })
-            }
-        }
+        LuaValue::String(str) => pulse_from_str(&str.to_string_lossy()),
         LuaValue::Table(table) => {
             let sub_div = table
                 .clone()
                 .sequence_values()
                 .map(|result| pulse_from_value(&result?))
                 .collect::<LuaResult<Vec<Pulse>>>()?;
-            Ok(Pulse::from(sub_div))
+            match table.raw_get::<LuaValue>("weight")? {
+                LuaValue::Nil => Ok(Pulse::from(sub_div)),
+                LuaValue::Integer(weight) if weight > 0 => {
+                    Ok(Pulse::weighted(sub_div, weight as f64))
+                }
+                LuaValue::Number(weight) if weight.is_finite() && weight > 0.0 => {
+                    Ok(Pulse::weighted(sub_div, weight))
+                }
+                _ => Err(invalid_pulse_weight_error()),
+            }
         }
         _ => Err(LuaError::FromLuaConversionError {
             from: value.type_name(),
@@ -783,6 +961,56 @@ pub fn pulse_from_value(value: &LuaValue) -> LuaResult<Pulse> {
     }
 }
 
+// Parse a pulse string value, optionally weighted via "value@weight".
+fn pulse_from_str(str: &str) -> LuaResult<Pulse> {
+    if let Some((value, weight)) = str.split_once('@') {
+        match weight.trim().parse::<f64>() {
+            Ok(weight) if weight.is_finite() && weight > 0.0 => {
+                Ok(Pulse::weighted(pulse_from_str(value.trim())?, weight))
+            }
+            _ => Err(invalid_pulse_weight_error()),
+        }
+    } else if let Ok(number) = str.parse::<f32>() {
+        Ok(Pulse::from(number))
+    } else if let Ok(integer) = str.parse::<u32>() {
+        Ok(Pulse::from(integer))
+    } else if let Ok(bool) = str.parse::<bool>() {
+        Ok(Pulse::from(bool))
+    } else {
+        Err(LuaError::FromLuaConversionError {
+            from: "string",
+            to: "pulse".to_string(),
+            message: Some("Invalid pulse string value".to_string()),
+        })
+    }
+}
+
+fn invalid_pulse_weight_error() -> LuaError {
+    LuaError::FromLuaConversionError {
+        from: "weight",
+        to: "pulse".to_string(),
+        message: Some("Invalid pulse weight: expecting a finite number > 0".to_string()),
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+// Convert a beat time unit string and step amount to a beat time step, if it's a valid unit.
+pub(crate) fn beat_time_step_from_unit(unit: &str, amount: f32) -> Option<BeatTimeStep> {
+    match unit {
+        "bars" => Some(BeatTimeStep::Bar(amount)),
+        "1/1" => Some(BeatTimeStep::Whole(amount)),
+        "1/2" => Some(BeatTimeStep::Half(amount)),
+        "beats" => Some(BeatTimeStep::Beats(amount)),
+        "1/4" => Some(BeatTimeStep::Quarter(amount)),
+        "1/8" => Some(BeatTimeStep::Eighth(amount)),
+        "1/16" => Some(BeatTimeStep::Sixteenth(amount)),
+        "1/32" => Some(BeatTimeStep::ThirtySecond(amount)),
+        "1/64" => Some(BeatTimeStep::SixtyFourth(amount)),
+        _ => None,
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) fn rhythm_repeat_count_from_value(value: &LuaValue) -> LuaResult<Option<usize>> {
@@ -803,6 +1031,84 @@ pub(crate) fn rhythm_repeat_count_from_value(value: &LuaValue) -> LuaResult<Opti
     }
 }
 
+pub(crate) fn script_limits_from_value(
+    value: &LuaValue,
+    limits: &ScriptLimits,
+) -> LuaResult<ScriptLimits> {
+    let Some(table) = value.as_table() else {
+        return Err(LuaError::FromLuaConversionError {
+            from: value.type_name(),
+            to: "limits".to_string(),
+            message: Some("must be a table with 'timeout' and/or 'instructions'".to_string()),
+        });
+    };
+    validate_table_properties(table, &SCRIPT_LIMITS_PROPERTIES)?;
+    let mut limits = *limits;
+    match table.get::<LuaValue>("timeout")? {
+        LuaValue::Nil => (),
+        LuaValue::Boolean(false) => limits.timeout = None,
+        LuaValue::Integer(milliseconds) if milliseconds > 0 => {
+            limits.timeout = Some(Duration::from_millis(milliseconds as u64));
+        }
+        LuaValue::Number(milliseconds) if milliseconds.is_finite() && milliseconds > 0.0 => {
+            limits.timeout = Some(Duration::from_secs_f64(milliseconds / 1000.0));
+        }
+        _ => {
+            return Err(bad_argument_error(
+                "pattern",
+                "limits",
+                1,
+                "timeout must be a number of milliseconds > 0 or false",
+            ))
+        }
+    }
+    match table.get::<LuaValue>("instructions")? {
+        LuaValue::Nil => (),
+        LuaValue::Boolean(false) => limits.instruction_limit = None,
+        LuaValue::Integer(count) if count > 0 => limits.instruction_limit = Some(count as u64),
+        _ => {
+            return Err(bad_argument_error(
+                "pattern",
+                "limits",
+                1,
+                "instructions must be an integer > 0 or false",
+            ))
+        }
+    }
+    Ok(limits)
+}
+
+pub(crate) fn pulse_dynamics_from_value(value: &LuaValue) -> LuaResult<PulseDynamics> {
+    if let Some(str) = value.as_string() {
+        PulseDynamics::try_from(&*str.to_string_lossy()).map_err(|err| {
+            LuaError::FromLuaConversionError {
+                from: "string",
+                to: "dynamics".to_string(),
+                message: Some(err),
+            }
+        })
+    } else if let Some(table) = value.as_table() {
+        let mut levels = Vec::new();
+        for level in table.clone().sequence_values::<f32>() {
+            levels.push(level?);
+        }
+        if levels.is_empty() {
+            return Err(LuaError::FromLuaConversionError {
+                from: "table",
+                to: "dynamics".to_string(),
+                message: Some("accent level tables must not be empty".to_string()),
+            });
+        }
+        Ok(PulseDynamics::Accents(levels))
+    } else {
+        Err(LuaError::FromLuaConversionError {
+            from: value.type_name(),
+            to: "dynamics".to_string(),
+            message: Some("must be a mode name or a table of accent levels".to_string()),
+        })
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 pub fn gate_trigger_from_value(value: &LuaValue) -> LuaResult<bool> {
@@ -882,7 +1188,7 @@ pub(crate) fn rhythm_from_value(
 ) -> LuaResult<Box<dyn Rhythm>> {
     match value {
         LuaValue::Function(func) => {
-            let callback = LuaCallback::new(lua, func.clone())?;
+            let callback = LuaCallback::new(lua, func.clone(), LuaCallbackKind::Pulse)?;
             let rhythm = ScriptedRhythm::new(timeout_hook, callback, time_base)?;
             Ok(Box::new(rhythm))
         }
@@ -894,10 +1200,13 @@ pub(crate) fn rhythm_from_value(
                 .collect::<LuaResult<Vec<Pulse>>>()?;
             Ok(Box::new(pulses.to_rhythm()))
         }
+        LuaValue::UserData(userdata) if userdata.is::<RhythmUserData>() => {
+            Ok(userdata.borrow::<RhythmUserData>()?.rhythm.duplicate())
+        }
         _ => Err(LuaError::FromLuaConversionError {
             from: value.type_name(),
             to: "rhythm".to_string(),
-            message: Some("pulse must either be an array or a function".to_string()),
+            message: Some("pulse must either be an array, a function or a rhythm".to_string()),
         }),
     }
 }
@@ -912,14 +1221,17 @@ pub(crate) fn gate_from_value(
 ) -> LuaResult<Box<dyn Gate>> {
     match value {
         LuaValue::Function(func) => {
-            let callback = LuaCallback::new(lua, func.clone())?;
+            let callback = LuaCallback::new(lua, func.clone(), LuaCallbackKind::Gate)?;
             let gate = ScriptedGate::new(timeout_hook, callback, time_base)?;
             Ok(Box::new(gate))
         }
+        LuaValue::UserData(userdata) if userdata.is::<GateUserData>() => {
+            Ok(userdata.borrow::<GateUserData>()?.gate.duplicate())
+        }
         _ => Err(LuaError::FromLuaConversionError {
             from: value.type_name(),
             to: "gate".to_string(),
-            message: Some("gate must either be nil or a function".to_string()),
+            message: Some("gate must either be nil, a function or a gate".to_string()),
         }),
     }
 }
@@ -940,12 +1252,19 @@ pub(crate) fn emitter_from_value(
             } else if userdata.is::<SequenceUserData>() {
                 let sequence = userdata.borrow::<SequenceUserData>()?.clone();
                 Ok(Box::new(sequence.notes.to_sequence_emitter()))
+            } else if userdata.is::<ArpeggioUserData>() {
+                let arpeggio = userdata.borrow::<ArpeggioUserData>()?;
+                Ok(Box::new(arpeggio.emitter.clone()))
+            } else if userdata.is::<MelodyUserData>() {
+                let melody = userdata.borrow::<MelodyUserData>()?;
+                Ok(Box::new(melody.emitter.clone()))
             } else if userdata.is::<CycleUserData>() {
                 // NB: take instead of cloning: cycle userdata has no other usage than being defined
                 let userdata = userdata.take::<CycleUserData>()?;
                 let cycle = userdata.cycle;
                 if let Some(mapping_function) = userdata.mapping_function {
-                    let mapping_callback = LuaCallback::new(lua, mapping_function)?;
+                    let mapping_callback =
+                        LuaCallback::new(lua, mapping_function, LuaCallbackKind::CycleMap)?;
                     let emitter = ScriptedCycleEmitter::with_mapping_callback(
                         cycle,
                         timeout_hook,
@@ -955,19 +1274,22 @@ pub(crate) fn emitter_from_value(
                     Ok(Box::new(emitter))
                 } else {
                     let mappings = userdata.mappings;
-                    let emitter = ScriptedCycleEmitter::with_mappings(cycle, mappings);
+                    let emitter = ScriptedCycleEmitter::with_mappings(cycle, mappings)
+                        .with_callback_errors(lua_callback_error_channel(lua));
                     Ok(Box::new(emitter))
                 }
             } else {
                 Err(LuaError::FromLuaConversionError {
                     from: "userdata",
                     to: "notes".to_string(),
-                    message: Some("expecting a note, sequence or cycle here".to_string()),
+                    message: Some(
+                        "expecting a note, sequence, arpeggio, melody or cycle here".to_string(),
+                    ),
                 })
             }
         }
         LuaValue::Function(function) => {
-            let callback = LuaCallback::new(lua, function.clone())?;
+            let callback = LuaCallback::new(lua, function.clone(), LuaCallbackKind::Event)?;
             let emitter = ScriptedEmitter::new(timeout_hook, callback, time_base)?;
             Ok(Box::new(emitter))
         }
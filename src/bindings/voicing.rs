@@ -0,0 +1,243 @@
+use mlua::prelude::*;
+
+use super::{
+    definitions::VOICE_LEADING_PROPERTIES,
+    note::NoteUserData,
+    sequence::SequenceUserData,
+    unwrap::{bad_argument_error, note_event_from_value, validate_table_properties},
+};
+
+use crate::{event::NoteEvent, note::voicing::VoiceLeading};
+
+// ---------------------------------------------------------------------------------------------
+
+// create a voice leading from the given optional Lua options table
+pub(crate) fn voice_leading_from_value(value: &LuaValue) -> LuaResult<VoiceLeading> {
+    let mut voice_leading = VoiceLeading::new();
+    let table = match value {
+        LuaValue::Nil => return Ok(voice_leading),
+        LuaValue::Table(table) => table,
+        _ => {
+            return Err(bad_argument_error(
+                "voice_leading",
+                "options",
+                1,
+                "expecting an options table or nil",
+            ))
+        }
+    };
+    // error on unknown option keys
+    validate_table_properties(table, &VOICE_LEADING_PROPERTIES)?;
+    // range
+    if table.contains_key("range")? {
+        let range_error = || {
+            bad_argument_error(
+                "voice_leading",
+                "range",
+                1,
+                "expecting a { low, high } note table, spanning at least an octave",
+            )
+        };
+        let range = table.get::<LuaValue>("range")?;
+        let range = range
+            .as_table()
+            .ok_or_else(range_error)?
+            .clone()
+            .sequence_values::<LuaValue>()
+            .collect::<LuaResult<Vec<_>>>()?;
+        if range.len() != 2 {
+            return Err(range_error());
+        }
+        let low = note_event_from_value(&range[0], Some(0))?.map(|event| event.note);
+        let high = note_event_from_value(&range[1], Some(1))?.map(|event| event.note);
+        match (low, high) {
+            (Some(low), Some(high))
+                if low.is_note_on() && high.is_note_on() && high as u8 >= low as u8 + 12 =>
+            {
+                voice_leading = voice_leading.with_range(low, high);
+            }
+            _ => return Err(range_error()),
+        }
+    }
+    // voices
+    if table.contains_key("voices")? {
+        let voices = table.get::<LuaValue>("voices")?;
+        match voices.as_usize() {
+            Some(count) if (1..=VoiceLeading::MAX_VOICES).contains(&count) => {
+                voice_leading = voice_leading.with_voice_count(count);
+            }
+            _ => {
+                return Err(bad_argument_error(
+                    "voice_leading",
+                    "voices",
+                    1,
+                    &format!(
+                        "voices must be an integer in range [1..={}]",
+                        VoiceLeading::MAX_VOICES
+                    ),
+                ))
+            }
+        }
+    }
+    // fixed_bass
+    if table.contains_key("fixed_bass")? {
+        voice_leading = voice_leading.with_fixed_bass(table.get::<bool>("fixed_bass")?);
+    }
+    Ok(voice_leading)
+}
+
+// voice the given note events, keeping the note event properties of the voiced notes
+fn voice_note_events(
+    voice_leading: &mut VoiceLeading,
+    note_events: &[Option<NoteEvent>],
+) -> Vec<Option<NoteEvent>> {
+    let note_ons = note_events
+        .iter()
+        .flatten()
+        .filter(|event| event.note.is_note_on())
+        .collect::<Vec<_>>();
+    let notes = note_ons.iter().map(|event| event.note).collect::<Vec<_>>();
+    let voicing = voice_leading.voice(&notes);
+    if voicing.is_empty() {
+        // pass rests and note-offs as they are
+        return note_events.to_vec();
+    }
+    voicing
+        .into_iter()
+        .map(|note| {
+            let template = note_ons
+                .iter()
+                .find(|event| event.note.key() == note.key())
+                .unwrap_or(&note_ons[0]);
+            Some(NoteEvent {
+                note,
+                ..(*template).clone()
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------------------------
+
+impl LuaUserData for VoiceLeading {
+    fn add_fields<F: LuaUserDataFields<Self>>(fields: &mut F) {
+        fields.add_field_method_get("voicing", |lua, this| -> LuaResult<LuaTable> {
+            lua.create_sequence_from(this.voicing().iter().map(|n| LuaInteger::from(*n as u8)))
+        });
+    }
+
+    fn add_methods<M: LuaUserDataMethods<Self>>(methods: &mut M) {
+        methods.add_method_mut(
+            "voice",
+            |_lua, this, args: LuaMultiValue| -> LuaResult<NoteUserData> {
+                let notes = NoteUserData::from(args)?.notes;
+                Ok(NoteUserData {
+                    notes: voice_note_events(this, &notes),
+                })
+            },
+        );
+
+        methods.add_method_mut(
+            "voice_sequence",
+            |_lua, this, args: LuaMultiValue| -> LuaResult<SequenceUserData> {
+                let sequence = match args.front() {
+                    Some(LuaValue::UserData(userdata))
+                        if args.len() == 1 && userdata.is::<SequenceUserData>() =>
+                    {
+                        userdata.borrow::<SequenceUserData>()?.clone()
+                    }
+                    _ => SequenceUserData::from(args)?,
+                };
+                Ok(SequenceUserData {
+                    notes: sequence
+                        .notes
+                        .iter()
+                        .map(|note_events| voice_note_events(this, note_events))
+                        .collect(),
+                })
+            },
+        );
+
+        methods.add_method_mut("reset", |_lua, this, _args: ()| -> LuaResult<()> {
+            this.reset();
+            Ok(())
+        });
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::bindings::*;
+
+    fn new_test_engine() -> LuaResult<Lua> {
+        // create a new engine and register bindings
+        let (mut lua, mut timeout_hook) = new_engine()?;
+        register_bindings(
+            &mut lua,
+            &timeout_hook,
+            &BeatTimeBase {
+                beats_per_min: 120.0,
+                beats_per_bar: 4,
+                samples_per_sec: 44100,
+            },
+        )?;
+        timeout_hook.reset();
+        Ok(lua)
+    }
+
+    fn note_keys(notes: Vec<LuaValue>) -> Vec<String> {
+        notes
+            .iter()
+            .map(|note| {
+                note.as_table()
+                    .and_then(|table| table.get::<String>("key").ok())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn voice_leading() -> LuaResult<()> {
+        let lua = new_test_engine()?;
+
+        // invalid options
+        assert!(lua
+            .load(r#"voice_leading { voices = 0 }"#)
+            .eval::<LuaValue>()
+            .is_err());
+        assert!(lua
+            .load(r#"voice_leading { range = { "c4", "g4" } }"#)
+            .eval::<LuaValue>()
+            .is_err());
+        assert!(lua
+            .load(r#"voice_leading { wurst = 1 }"#)
+            .eval::<LuaValue>()
+            .is_err());
+
+        // voice notes, keeping note properties
+        let notes = lua
+            .load(
+                r#"local leading = voice_leading()
+                leading:voice("c4'major")
+                return leading:voice("f4 v0.5", "a4", "c5").notes"#,
+            )
+            .eval::<Vec<LuaValue>>()?;
+        assert_eq!(note_keys(notes.clone()), vec!["C4", "F4", "A4"]);
+        assert_eq!(notes[1].as_table().unwrap().get::<f32>("volume")?, 0.5_f32);
+
+        // voice sequences
+        let sequence = lua
+            .load(
+                r#"local leading = voice_leading { voices = 4, fixed_bass = true }
+                return leading:voice_sequence(sequence("c4'major", "---", "g4'dom7")).notes"#,
+            )
+            .eval::<Vec<Vec<LuaValue>>>()?;
+        assert_eq!(sequence.len(), 3);
+        assert_eq!(sequence[0].len(), 4);
+        assert_eq!(sequence[2].len(), 4);
+        assert_eq!(note_keys(sequence[0].clone())[0], "C4");
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+//! Playback clocks, which a `Sequence` or player can follow instead of the audio device's position.
+
+use std::fmt::Debug;
+
+use crate::SampleTime;
+
+// -------------------------------------------------------------------------------------------------
+
+pub mod internal;
+pub mod midi;
+pub mod simulated;
+
+// -------------------------------------------------------------------------------------------------
+
+/// State of a [`Clock`] at the time it got polled.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ClockState {
+    /// True when the clock is running, false when it's stopped or paused.
+    pub running: bool,
+    /// Current playback position in samples, relative to the clock's start.
+    pub sample_position: SampleTime,
+    /// Tempo reported or estimated by the clock, if known.
+    pub beats_per_min: Option<f32>,
+    /// True when the position jumped since the last poll, e.g. on start or when seeking.
+    pub position_changed: bool,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A playback clock which drives sequence playback.
+///
+/// Clocks report positions in samples with the sample rate they got created with. When following
+/// a clock, the clock's tempo replaces the sequence's time base tempo, so pattern beats stay in
+/// sync with the clock's beats.
+pub trait Clock: Debug {
+    /// Sample rate of the clock's sample positions.
+    fn samples_per_sec(&self) -> u32;
+
+    /// Fetch the clock's current state. Resets the `position_changed` flag of the state.
+    fn poll(&mut self) -> ClockState;
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        clock::{midi::MidiClock, simulated::SimulatedClock},
+        prelude::*,
+        Clock, ClockState,
+    };
+
+    #[test]
+    fn midi_clock() {
+        let clock = MidiClock::new(44100);
+        let mut follower = clock.clone();
+        let tick_duration = Duration::from_secs_f64(60.0 / 120.0 / 24.0);
+
+        // clock ticks while stopped only estimate the tempo
+        let mut time = Duration::ZERO;
+        for _ in 0..24 {
+            clock.handle_message(time, &[0xF8]);
+            time += tick_duration;
+        }
+        let state = follower.poll();
+        assert!(!state.running);
+        assert_eq!(state.sample_position, 0);
+        assert!((state.beats_per_min.unwrap() - 120.0).abs() < 0.01);
+
+        // first tick after start is the downbeat
+        clock.handle_message(time, &[0xFA]);
+        for _ in 0..25 {
+            clock.handle_message(time, &[0xF8]);
+            time += tick_duration;
+        }
+        let state = follower.poll();
+        assert!(state.running);
+        assert!(state.position_changed);
+        assert_eq!(state.sample_position, 22050);
+        assert!(!follower.poll().position_changed);
+
+        // stop, seek to the 3rd bar via song position pointer and continue
+        clock.handle_message(time, &[0xFC]);
+        assert!(!follower.poll().running);
+        clock.handle_message(time, &[0xF2, 32, 0]);
+        clock.handle_message(time, &[0xFB]);
+        let state = follower.poll();
+        assert!(state.running);
+        assert!(state.position_changed);
+        assert_eq!(state.sample_position, 22050 * 8);
+        for _ in 0..13 {
+            clock.handle_message(time, &[0xF8]);
+            time += tick_duration;
+        }
+        assert_eq!(follower.poll().sample_position, 22050 * 8 + 11025);
+
+        // non realtime messages are ignored
+        clock.handle_message(time, &[0x90, 60, 100]);
+        assert_eq!(follower.poll().sample_position, 22050 * 8 + 11025);
+    }
+
+    #[test]
+    fn follow_clock() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let phrase = Phrase::new(
+            time_base,
+            vec![time_base.every_nth_beat(1.0).emit(new_note_emitter("c4"))],
+            BeatTimeStep::Bar(4.0),
+        );
+        let mut sequence = Sequence::new(time_base, vec![phrase]);
+
+        let run = |sequence: &mut Sequence, state: ClockState| {
+            let mut times = Vec::new();
+            sequence.consume_events_until_clock(&state, 22050, &mut |_, event| {
+                if event.event.is_some() {
+                    times.push(event.time);
+                }
+            });
+            times
+        };
+
+        let mut clock = SimulatedClock::new(44100).with_states(vec![
+            // stopped: nothing is emitted
+            ClockState::default(),
+            // started: emit up to the lookahead
+            ClockState {
+                running: true,
+                position_changed: true,
+                ..Default::default()
+            },
+            // running: emit until position + lookahead
+            ClockState {
+                running: true,
+                sample_position: 22050,
+                ..Default::default()
+            },
+            // clock ran ahead: skip late events
+            ClockState {
+                running: true,
+                sample_position: 88200,
+                ..Default::default()
+            },
+            // jump back to the start
+            ClockState {
+                running: true,
+                sample_position: 0,
+                position_changed: true,
+                ..Default::default()
+            },
+            // tempo change: sequence follows the clock's tempo
+            ClockState {
+                running: false,
+                sample_position: 22050,
+                beats_per_min: Some(60.0),
+                position_changed: false,
+            },
+        ]);
+        assert_eq!(clock.samples_per_sec(), 44100);
+        assert_eq!(run(&mut sequence, clock.poll()), Vec::<SampleTime>::new());
+        assert_eq!(run(&mut sequence, clock.poll()), vec![0]);
+        assert_eq!(run(&mut sequence, clock.poll()), vec![22050]);
+        assert_eq!(run(&mut sequence, clock.poll()), vec![88200]);
+        assert_eq!(run(&mut sequence, clock.poll()), vec![0]);
+        assert_eq!(run(&mut sequence, clock.poll()), Vec::<SampleTime>::new());
+        assert_eq!(sequence.time_base().beats_per_min, 60.0);
+
+        // replayed states are consumed, then the last state sticks
+        let state = clock.poll();
+        assert_eq!(state.sample_position, 22050);
+        assert!(!state.position_changed);
+
+        // manual control
+        let mut clock = SimulatedClock::new(44100);
+        clock.start();
+        clock.advance(100);
+        let state = clock.poll();
+        assert!(state.running && state.position_changed);
+        assert_eq!(state.sample_position, 100);
+        clock.stop();
+        clock.advance(100);
+        assert_eq!(clock.poll().sample_position, 100);
+    }
+
+    #[test]
+    fn follow_clock_tempo_changes() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let phrase = Phrase::new(
+            time_base,
+            vec![time_base.every_nth_beat(1.0).emit(new_note_emitter("c4"))],
+            BeatTimeStep::Bar(1.0),
+        );
+        let mut sequence = Sequence::new(time_base, vec![phrase]);
+
+        let mut times = Vec::new();
+        let mut consumer = |_, event: PatternEvent| {
+            if event.event.is_some() {
+                times.push(event.time);
+            }
+        };
+
+        // speed up 100 samples before the end of a 88200 samples long phrase
+        sequence.consume_events_until_time(88100, &mut consumer);
+        let state = ClockState {
+            running: true,
+            sample_position: 88100,
+            beats_per_min: Some(121.0),
+            position_changed: false,
+        };
+        sequence.consume_events_until_clock(&state, 22050, &mut consumer);
+        // the rest of the phrase and the next phrase play with the new tempo
+        assert_eq!(times, vec![0, 22050, 44100, 66150, 88199, 110066]);
+    }
+}
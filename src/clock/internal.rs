@@ -0,0 +1,102 @@
+use std::time::Instant;
+
+use crate::{Clock, ClockState, SampleTime};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Clock which runs with the system's monotonic wall clock time.
+#[derive(Debug, Clone)]
+pub struct InternalClock {
+    samples_per_sec: u32,
+    beats_per_min: Option<f32>,
+    // start time and position of the current run, when running
+    start: Option<(Instant, SampleTime)>,
+    position: SampleTime,
+    position_changed: bool,
+}
+
+impl InternalClock {
+    /// Create a new stopped clock with the given sample rate.
+    pub fn new(samples_per_sec: u32) -> Self {
+        Self {
+            samples_per_sec,
+            beats_per_min: None,
+            start: None,
+            position: 0,
+            position_changed: false,
+        }
+    }
+
+    /// Return a new clock which reports the given tempo. By default the clock has no tempo, so
+    /// followers keep their own tempo.
+    #[must_use]
+    pub fn with_beats_per_min<T: Into<Option<f32>>>(mut self, beats_per_min: T) -> Self {
+        self.beats_per_min = beats_per_min.into();
+        self
+    }
+
+    /// Set or remove the clock's tempo.
+    pub fn set_beats_per_min<T: Into<Option<f32>>>(&mut self, beats_per_min: T) {
+        self.beats_per_min = beats_per_min.into();
+    }
+
+    /// Returns true when the clock is running.
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
+    /// Start running the clock from the beginning.
+    pub fn start(&mut self) {
+        self.seek(0);
+        self.resume();
+    }
+
+    /// Continue running the clock from its current position.
+    pub fn resume(&mut self) {
+        if self.start.is_none() {
+            self.start = Some((Instant::now(), self.position));
+        }
+    }
+
+    /// Stop running the clock.
+    pub fn stop(&mut self) {
+        self.position = self.current_position();
+        self.start = None;
+    }
+
+    /// Move the clock to the given sample position.
+    pub fn seek(&mut self, sample_position: SampleTime) {
+        self.position = sample_position;
+        if self.start.is_some() {
+            self.start = Some((Instant::now(), sample_position));
+        }
+        self.position_changed = true;
+    }
+
+    fn current_position(&self) -> SampleTime {
+        match self.start {
+            Some((instant, position)) => {
+                let elapsed = instant.elapsed().as_secs_f64();
+                position + (elapsed * self.samples_per_sec as f64) as SampleTime
+            }
+            None => self.position,
+        }
+    }
+}
+
+impl Clock for InternalClock {
+    fn samples_per_sec(&self) -> u32 {
+        self.samples_per_sec
+    }
+
+    fn poll(&mut self) -> ClockState {
+        let state = ClockState {
+            running: self.is_running(),
+            sample_position: self.current_position(),
+            beats_per_min: self.beats_per_min,
+            position_changed: self.position_changed,
+        };
+        self.position_changed = false;
+        state
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{Clock, ClockState};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Clock which follows incoming MIDI clock, start, continue, stop and song position pointer
+/// messages, e.g. from a DAW or drum machine.
+///
+/// MIDI clock sends 24 ticks per quarter note. The tempo is estimated from the tick intervals, and
+/// each tick advances the position by a 24th beat in the estimated tempo. Feed the clock with
+/// [`Self::handle_message`] from the MIDI input thread and poll it from a cloned instance in the
+/// playback thread: clones share the same state.
+#[derive(Debug, Clone)]
+pub struct MidiClock {
+    samples_per_sec: u32,
+    state: Arc<Mutex<MidiClockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MidiClockState {
+    running: bool,
+    // position in samples, advanced with each tick
+    position: f64,
+    position_changed: bool,
+    // true when the next tick marks the start or song position and should not advance
+    pending_downbeat: bool,
+    last_tick_time: Option<Duration>,
+    // smoothed tick interval in seconds
+    tick_interval: Option<f64>,
+}
+
+impl MidiClock {
+    /// MIDI clock ticks per quarter note.
+    pub const TICKS_PER_BEAT: u32 = 24;
+
+    // tempo which is used until the tempo could be estimated
+    const DEFAULT_BEATS_PER_MIN: f64 = 120.0;
+    // tick intervals larger than this are treated as gaps in the clock stream
+    const MAX_TICK_INTERVAL: f64 = 0.5;
+    // smoothing factor for the tick interval estimation
+    const TICK_INTERVAL_SMOOTHING: f64 = 0.1;
+
+    /// Create a new stopped MIDI clock with the given output sample rate.
+    pub fn new(samples_per_sec: u32) -> Self {
+        let state = Arc::new(Mutex::new(MidiClockState::default()));
+        Self {
+            samples_per_sec,
+            state,
+        }
+    }
+
+    /// Handle a raw MIDI message, received at the given monotonic timestamp. Timestamps only
+    /// need to be relative to each other, e.g. the time since the MIDI input port got opened.
+    /// Messages other than system realtime and song position messages are ignored.
+    pub fn handle_message(&self, timestamp: Duration, message: &[u8]) {
+        let mut state = self.state.lock().expect("Failed to lock MIDI clock state");
+        match message {
+            // timing clock
+            [0xF8, ..] => {
+                if let Some(last_tick_time) = state.last_tick_time {
+                    let interval = timestamp.saturating_sub(last_tick_time).as_secs_f64();
+                    if interval > 0.0 && interval <= Self::MAX_TICK_INTERVAL {
+                        state.tick_interval = Some(match state.tick_interval {
+                            Some(smoothed) => {
+                                smoothed + (interval - smoothed) * Self::TICK_INTERVAL_SMOOTHING
+                            }
+                            None => interval,
+                        });
+                    }
+                }
+                state.last_tick_time = Some(timestamp);
+                if state.running {
+                    if state.pending_downbeat {
+                        state.pending_downbeat = false;
+                    } else {
+                        state.position += self.samples_per_tick(&state);
+                    }
+                }
+            }
+            // start
+            [0xFA, ..] => {
+                state.running = true;
+                state.position = 0.0;
+                state.position_changed = true;
+                state.pending_downbeat = true;
+            }
+            // continue
+            [0xFB, ..] => {
+                state.running = true;
+            }
+            // stop
+            [0xFC, ..] => {
+                state.running = false;
+            }
+            // song position pointer in sixteenth notes
+            [0xF2, lsb, msb, ..] => {
+                let sixteenths = (*lsb as u32 & 0x7F) | ((*msb as u32 & 0x7F) << 7);
+                let ticks = sixteenths * Self::TICKS_PER_BEAT / 4;
+                state.position = ticks as f64 * self.samples_per_tick(&state);
+                state.position_changed = true;
+                state.pending_downbeat = true;
+            }
+            _ => (),
+        }
+    }
+
+    /// Estimated tempo of the incoming MIDI clock, if known.
+    pub fn beats_per_min(&self) -> Option<f32> {
+        let state = self.state.lock().expect("Failed to lock MIDI clock state");
+        Self::tempo(&state)
+    }
+
+    fn tempo(state: &MidiClockState) -> Option<f32> {
+        state
+            .tick_interval
+            .map(|interval| (60.0 / (interval * Self::TICKS_PER_BEAT as f64)) as f32)
+    }
+
+    fn samples_per_tick(&self, state: &MidiClockState) -> f64 {
+        let seconds_per_tick = state
+            .tick_interval
+            .unwrap_or(60.0 / (Self::DEFAULT_BEATS_PER_MIN * Self::TICKS_PER_BEAT as f64));
+        seconds_per_tick * self.samples_per_sec as f64
+    }
+}
+
+impl Clock for MidiClock {
+    fn samples_per_sec(&self) -> u32 {
+        self.samples_per_sec
+    }
+
+    fn poll(&mut self) -> ClockState {
+        let mut state = self.state.lock().expect("Failed to lock MIDI clock state");
+        let clock_state = ClockState {
+            running: state.running,
+            sample_position: state.position.round() as u64,
+            beats_per_min: Self::tempo(&state),
+            position_changed: state.position_changed,
+        };
+        state.position_changed = false;
+        clock_state
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+
+use crate::{Clock, ClockState, SampleTime};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Clock which is controlled manually or replays a list of recorded clock states, e.g. in tests.
+#[derive(Debug, Clone)]
+pub struct SimulatedClock {
+    samples_per_sec: u32,
+    state: ClockState,
+    states: VecDeque<ClockState>,
+}
+
+impl SimulatedClock {
+    /// Create a new stopped clock with the given sample rate.
+    pub fn new(samples_per_sec: u32) -> Self {
+        let state = ClockState::default();
+        let states = VecDeque::new();
+        Self {
+            samples_per_sec,
+            state,
+            states,
+        }
+    }
+
+    /// Return a new clock which replays the given states: each poll returns the next state. When
+    /// all states got consumed, the last state sticks.
+    #[must_use]
+    pub fn with_states(mut self, states: Vec<ClockState>) -> Self {
+        self.states = states.into();
+        self
+    }
+
+    /// Start running the clock from the beginning.
+    pub fn start(&mut self) {
+        self.state.running = true;
+        self.state.sample_position = 0;
+        self.state.position_changed = true;
+    }
+
+    /// Continue running the clock from its current position.
+    pub fn resume(&mut self) {
+        self.state.running = true;
+    }
+
+    /// Stop running the clock.
+    pub fn stop(&mut self) {
+        self.state.running = false;
+    }
+
+    /// Move the clock to the given sample position.
+    pub fn seek(&mut self, sample_position: SampleTime) {
+        self.state.sample_position = sample_position;
+        self.state.position_changed = true;
+    }
+
+    /// Advance the clock's position by the given amount of samples, when it's running.
+    pub fn advance(&mut self, samples: SampleTime) {
+        if self.state.running {
+            self.state.sample_position += samples;
+        }
+    }
+
+    /// Set or remove the clock's tempo.
+    pub fn set_beats_per_min<T: Into<Option<f32>>>(&mut self, beats_per_min: T) {
+        self.state.beats_per_min = beats_per_min.into();
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn samples_per_sec(&self) -> u32 {
+        self.samples_per_sec
+    }
+
+    fn poll(&mut self) -> ClockState {
+        if let Some(state) = self.states.pop_front() {
+            self.state = state;
+        }
+        let state = self.state;
+        self.state.position_changed = false;
+        state
+    }
+}
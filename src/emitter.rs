@@ -8,9 +8,11 @@ type Fraction = num_rational::Rational32;
 
 // -------------------------------------------------------------------------------------------------
 
+pub mod arpeggio;
 pub mod cycle;
 pub mod empty;
 pub mod fixed;
+pub mod melody;
 pub mod mutated;
 #[cfg(feature = "scripting")]
 pub mod scripted;
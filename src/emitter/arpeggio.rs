@@ -0,0 +1,421 @@
+use rand::{rng, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{
+    BeatTimeBase, Chord, Emitter, EmitterEvent, Event, NoteEvent, ParameterSet, RhythmEvent,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Order in which an [`ArpeggioEmitter`] plays the notes of its chord.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ArpeggioMode {
+    /// Lowest to highest note.
+    #[default]
+    Up,
+    /// Highest to lowest note.
+    Down,
+    /// Lowest to highest and back, without repeating the highest and lowest notes.
+    UpDown,
+    /// Randomly picked notes.
+    Random,
+    /// Notes in the order they got played or defined.
+    AsPlayed,
+    /// Outside in: lowest, highest, second lowest, second highest note and so on.
+    Converge,
+}
+
+impl ArpeggioMode {
+    /// All mode names, as used in `TryFrom<&str>`.
+    pub fn names() -> Vec<&'static str> {
+        vec!["up", "down", "updown", "random", "played", "converge"]
+    }
+}
+
+impl TryFrom<&str> for ArpeggioMode {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', '_'], "").as_str() {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            "updown" => Ok(Self::UpDown),
+            "random" => Ok(Self::Random),
+            "played" | "asplayed" => Ok(Self::AsPlayed),
+            "converge" => Ok(Self::Converge),
+            _ => Err(format!(
+                "invalid arpeggio mode '{}', valid modes are: {}",
+                name,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Emits the notes of a chord one after another as single notes.
+///
+/// Notes are taken from the pattern's trigger event, when the pattern got triggered with notes,
+/// else from the fixed notes the emitter got created with. When latched, the notes of the last
+/// trigger event keep playing after trigger events without notes (note-offs), else the arpeggio
+/// falls back to the fixed notes, if any.
+///
+/// ### Example
+///
+/// ```rust
+/// use pattrns::prelude::*;
+///
+/// let emitter = ArpeggioEmitter::from_chord(&Chord::try_from("c4'maj").unwrap())
+///     .with_mode(ArpeggioMode::UpDown)
+///     .with_octaves(2)
+///     .with_gate_length(0.5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArpeggioEmitter {
+    notes: Vec<NoteEvent>,
+    trigger_notes: Vec<NoteEvent>,
+    mode: ArpeggioMode,
+    octaves: usize,
+    repeats: usize,
+    gate_length: f64,
+    latch: bool,
+    sequence: Vec<NoteEvent>,
+    step: usize,
+    random_index: usize,
+    rand_gen: Xoshiro256PlusPlus,
+    seed: Option<u64>,
+}
+
+impl ArpeggioEmitter {
+    /// Maximum number of octaves an arpeggio can span.
+    pub const MAX_OCTAVES: usize = 10;
+
+    /// Create a new arpeggio with the given fixed notes. Note-offs and empty notes are ignored.
+    /// Use an empty note list to arpeggiate trigger event notes only.
+    pub fn new(notes: Vec<NoteEvent>) -> Self {
+        let notes = notes
+            .into_iter()
+            .filter(|note| note.note.is_note_on())
+            .collect::<Vec<_>>();
+        let trigger_notes = Vec::new();
+        let mode = ArpeggioMode::default();
+        let octaves = 1;
+        let repeats = 1;
+        let gate_length = 1.0;
+        let latch = false;
+        let sequence = Vec::new();
+        let step = 0;
+        let random_index = 0;
+        let seed = None;
+        let rand_gen = Xoshiro256PlusPlus::seed_from_u64(rng().random());
+        let mut emitter = Self {
+            notes,
+            trigger_notes,
+            mode,
+            octaves,
+            repeats,
+            gate_length,
+            latch,
+            sequence,
+            step,
+            random_index,
+            rand_gen,
+            seed,
+        };
+        emitter.update_sequence();
+        emitter
+    }
+
+    /// Create a new arpeggio with the notes of the given chord.
+    pub fn from_chord(chord: &Chord) -> Self {
+        Self::new(chord.notes().into_iter().map(NoteEvent::from).collect())
+    }
+
+    /// Return a new arpeggio with the given mode.
+    #[must_use]
+    pub fn with_mode(mut self, mode: ArpeggioMode) -> Self {
+        self.mode = mode;
+        self.update_sequence();
+        self
+    }
+
+    /// Return a new arpeggio which spans the given number of octaves. 1 plays the notes as they
+    /// are, 2 adds the notes transposed by one octave and so on.
+    ///
+    /// ### Panics
+    /// Panics if the given octave count is not in range `1..=MAX_OCTAVES`.
+    #[must_use]
+    pub fn with_octaves(mut self, octaves: usize) -> Self {
+        assert!(
+            (1..=Self::MAX_OCTAVES).contains(&octaves),
+            "Invalid octave count"
+        );
+        self.octaves = octaves;
+        self.update_sequence();
+        self
+    }
+
+    /// Return a new arpeggio which plays each note the given number of times.
+    ///
+    /// ### Panics
+    /// Panics if the given repeat count is 0.
+    #[must_use]
+    pub fn with_repeats(mut self, repeats: usize) -> Self {
+        assert!(repeats > 0, "Invalid repeat count");
+        self.repeats = repeats;
+        self
+    }
+
+    /// Return a new arpeggio with the given note length (gate time), relative to the pulse step
+    /// length, in range (0 - 1].
+    ///
+    /// ### Panics
+    /// Panics if the given length is not in range (0 - 1].
+    #[must_use]
+    pub fn with_gate_length(mut self, length: f64) -> Self {
+        assert!(length > 0.0 && length <= 1.0, "Invalid gate length");
+        self.gate_length = length;
+        self
+    }
+
+    /// Return a new arpeggio which keeps playing trigger notes after note-offs.
+    #[must_use]
+    pub fn with_latch(mut self, latch: bool) -> Self {
+        self.latch = latch;
+        self
+    }
+
+    /// Return a new arpeggio with the given random seed for the random mode.
+    #[must_use]
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self.rand_gen = Xoshiro256PlusPlus::seed_from_u64(seed.unwrap_or_else(|| rng().random()));
+        self
+    }
+
+    /// The arpeggio's mode.
+    pub fn mode(&self) -> ArpeggioMode {
+        self.mode
+    }
+
+    /// The notes the arpeggio currently steps through, including octaves.
+    pub fn sequence(&self) -> &[NoteEvent] {
+        &self.sequence
+    }
+
+    fn update_sequence(&mut self) {
+        let mut notes = if self.trigger_notes.is_empty() {
+            self.notes.clone()
+        } else {
+            self.trigger_notes.clone()
+        };
+        if self.mode != ArpeggioMode::AsPlayed {
+            notes.sort_by_key(|note| note.note);
+        }
+        let mut sequence = Vec::with_capacity(notes.len() * self.octaves);
+        for octave in 0..self.octaves {
+            for note in &notes {
+                let mut note = note.clone();
+                note.note = note.note.transposed(octave as i32 * 12);
+                sequence.push(note);
+            }
+        }
+        self.sequence = match self.mode {
+            ArpeggioMode::Up | ArpeggioMode::Random | ArpeggioMode::AsPlayed => sequence,
+            ArpeggioMode::Down => sequence.into_iter().rev().collect(),
+            ArpeggioMode::UpDown => {
+                let len = sequence.len();
+                let down = sequence
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .take(len.saturating_sub(2))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                sequence.into_iter().chain(down).collect()
+            }
+            ArpeggioMode::Converge => {
+                let mut lower = 0;
+                let mut upper = sequence.len();
+                let mut converged = Vec::with_capacity(sequence.len());
+                while lower < upper {
+                    converged.push(sequence[lower].clone());
+                    lower += 1;
+                    if lower < upper {
+                        upper -= 1;
+                        converged.push(sequence[upper].clone());
+                    }
+                }
+                converged
+            }
+        };
+        self.step = 0;
+        self.random_index = 0;
+    }
+
+    fn next_note(&mut self) -> Option<NoteEvent> {
+        if self.sequence.is_empty() {
+            return None;
+        }
+        let index = if self.mode == ArpeggioMode::Random {
+            if self.step.is_multiple_of(self.repeats) {
+                self.random_index = self.rand_gen.random_range(0..self.sequence.len());
+            }
+            self.random_index
+        } else {
+            self.step / self.repeats % self.sequence.len()
+        };
+        self.step += 1;
+        Some(self.sequence[index].clone())
+    }
+}
+
+impl Emitter for ArpeggioEmitter {
+    fn set_time_base(&mut self, _time_base: &BeatTimeBase) {
+        // nothing to do
+    }
+
+    fn set_trigger_event(&mut self, event: &Event) {
+        if let Event::NoteEvents(note_events) = event {
+            let notes = note_events
+                .iter()
+                .flatten()
+                .filter(|note| note.note.is_note_on())
+                .cloned()
+                .collect::<Vec<_>>();
+            if !notes.is_empty() || !self.latch {
+                self.trigger_notes = notes;
+                self.update_sequence();
+            }
+        }
+    }
+
+    fn set_parameters(&mut self, _parameters: ParameterSet) {
+        // nothing to do
+    }
+
+    fn run(&mut self, _pulse: RhythmEvent, emit_event: bool) -> Option<Vec<EmitterEvent>> {
+        if !emit_event {
+            return None;
+        }
+        let note = NoteEvent {
+            length: Some(self.gate_length as f32),
+            ..self.next_note()?
+        };
+        Some(vec![EmitterEvent::new(Event::NoteEvents(vec![Some(note)]))])
+    }
+
+    fn duplicate(&self) -> Box<dyn Emitter> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.step = 0;
+        // reset random number generator to its initial state, when the emitter is seeded
+        if let Some(seed) = self.seed {
+            self.rand_gen = Xoshiro256PlusPlus::seed_from_u64(seed);
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{event::new_note, Note};
+
+    fn run_notes(emitter: &mut ArpeggioEmitter, count: usize) -> Vec<Note> {
+        (0..count)
+            .filter_map(|_| emitter.run(RhythmEvent::default(), true))
+            .map(|events| match &events[0].event {
+                Event::NoteEvents(notes) => notes[0].as_ref().unwrap().note,
+                _ => panic!("unexpected event"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn modes() {
+        let notes = vec![Note::E4.into(), Note::C4.into(), Note::G4.into()];
+        let mut emitter = ArpeggioEmitter::new(notes.clone());
+        assert_eq!(
+            run_notes(&mut emitter, 4),
+            vec![Note::C4, Note::E4, Note::G4, Note::C4]
+        );
+        let mut emitter = ArpeggioEmitter::new(notes.clone()).with_mode(ArpeggioMode::Down);
+        assert_eq!(
+            run_notes(&mut emitter, 3),
+            vec![Note::G4, Note::E4, Note::C4]
+        );
+        let mut emitter = ArpeggioEmitter::new(notes.clone())
+            .with_mode(ArpeggioMode::UpDown)
+            .with_octaves(2);
+        assert_eq!(
+            run_notes(&mut emitter, 11),
+            vec![
+                Note::C4,
+                Note::E4,
+                Note::G4,
+                Note::C5,
+                Note::E5,
+                Note::G5,
+                Note::E5,
+                Note::C5,
+                Note::G4,
+                Note::E4,
+                Note::C4
+            ]
+        );
+        let mut emitter = ArpeggioEmitter::new(notes.clone()).with_mode(ArpeggioMode::AsPlayed);
+        assert_eq!(
+            run_notes(&mut emitter, 3),
+            vec![Note::E4, Note::C4, Note::G4]
+        );
+        let mut emitter = ArpeggioEmitter::new(notes.clone())
+            .with_mode(ArpeggioMode::Converge)
+            .with_octaves(2);
+        assert_eq!(
+            run_notes(&mut emitter, 6),
+            vec![Note::C4, Note::G5, Note::E4, Note::E5, Note::G4, Note::C5]
+        );
+        let mut emitter = ArpeggioEmitter::new(notes.clone())
+            .with_mode(ArpeggioMode::Random)
+            .with_repeats(2)
+            .with_seed(Some(1234));
+        let random_notes = run_notes(&mut emitter, 8);
+        assert!(random_notes.chunks(2).all(|pair| pair[0] == pair[1]));
+        emitter.reset();
+        assert_eq!(run_notes(&mut emitter, 8), random_notes);
+        assert!(ArpeggioMode::try_from("up-down").is_ok());
+        assert!(ArpeggioMode::try_from("sideways").is_err());
+    }
+
+    #[test]
+    fn trigger_and_latch() {
+        let mut emitter = ArpeggioEmitter::new(vec![Note::C4.into()]).with_gate_length(0.5);
+        let events = emitter.run(RhythmEvent::default(), true).unwrap();
+        assert!(matches!(
+            &events[0].event,
+            Event::NoteEvents(notes) if notes[0].as_ref().unwrap().length == Some(0.5)
+        ));
+
+        // trigger notes replace the fixed notes
+        emitter.set_trigger_event(&Event::NoteEvents(vec![
+            new_note(Note::A4),
+            new_note(Note::D4),
+        ]));
+        assert_eq!(run_notes(&mut emitter, 2), vec![Note::D4, Note::A4]);
+        // note-offs fall back to the fixed notes
+        emitter.set_trigger_event(&Event::NoteEvents(vec![new_note(Note::OFF)]));
+        assert_eq!(run_notes(&mut emitter, 2), vec![Note::C4, Note::C4]);
+
+        // latched trigger notes keep playing
+        let mut emitter = ArpeggioEmitter::new(vec![]).with_latch(true);
+        assert!(emitter.run(RhythmEvent::default(), true).is_none());
+        emitter.set_trigger_event(&Event::NoteEvents(vec![new_note(Note::A4)]));
+        emitter.set_trigger_event(&Event::NoteEvents(vec![new_note(Note::OFF)]));
+        assert_eq!(run_notes(&mut emitter, 2), vec![Note::A4, Note::A4]);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, ops::RangeBounds};
+use std::{collections::HashMap, ops::RangeBounds, sync::Arc};
 
 type Fraction = num_rational::Rational32;
 
@@ -99,11 +99,7 @@ pub(crate) fn apply_cycle_note_properties(
     for target in targets {
         match target {
             CycleTarget::Index(index) => {
-                let index = integer_value_in_range(
-                    *index,
-                    "instrument",
-                    0..,
-                )?;
+                let index = integer_value_in_range(*index, "instrument", 0..)?;
                 let instrument = InstrumentId::from(index as usize);
                 for note_event in note_events.iter_mut().flatten() {
                     note_event.instrument = Some(instrument);
@@ -111,27 +107,41 @@ pub(crate) fn apply_cycle_note_properties(
             }
             CycleTarget::Named(name, value) => {
                 match name.as_bytes() {
-                    b"v" => {
+                    b"v" | b"vol" | b"volume" => {
                         let volume = float_value_in_range(value, "volume", 0.0..=1.0)?;
                         for note_event in note_events.iter_mut().flatten() {
                             note_event.volume = volume;
                         }
                     }
-                    b"p" => {
+                    b"p" | b"pan" | b"panning" => {
                         let panning = float_value_in_range(value, "panning", -1.0..=1.0)?;
                         for note_event in note_events.iter_mut().flatten() {
                             note_event.panning = panning;
                         }
                     }
-                    b"d" => {
+                    b"d" | b"delay" => {
                         let delay = float_value_in_range(value, "delay", 0.0..1.0)?;
                         for note_event in note_events.iter_mut().flatten() {
                             note_event.delay = delay;
                         }
                     }
+                    b"l" => {
+                        let length = float_value_in_range(value, "length", 0.0..)?;
+                        for note_event in note_events.iter_mut().flatten() {
+                            note_event.length = Some(length);
+                        }
+                    }
                     _ => {
-                        return Err(format!("invalid note property: '{}'. ", name) + 
-                            "expecting number values with '#' (instrument),'v' (volume), 'p' (panning) or 'd' (delay) prefixes here.")
+                        // custom named attributes
+                        let Some(value) = value else {
+                            return Err(format!("invalid note property: '{}'. ", name) +
+                                "expecting number values with '#' (instrument),'v' (volume), 'p' (panning), 'd' (delay) or 'l' (length) prefixes or 'name=value' attributes here.");
+                        };
+                        for note_event in note_events.iter_mut().flatten() {
+                            note_event
+                                .attributes
+                                .insert(Arc::from(&**name), *value as f32);
+                        }
                     }
                 }
             }
@@ -376,3 +386,53 @@ pub fn new_cycle_emitter(input: &str) -> Result<CycleEmitter, String> {
 pub fn new_cycle_emitter_with_seed(input: &str, seed: u64) -> Result<CycleEmitter, String> {
     CycleEmitter::from_mini_with_seed(input, seed)
 }
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn note_properties() -> Result<(), String> {
+        let mut emitter = new_cycle_emitter("c4:v0.5:cutoff=0.25 e4:l0.5:x0.1 g4:pitch_bend=-1.0")?;
+        let notes = emitter
+            .run(RhythmEvent::default(), true)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|event| match event.event {
+                Event::NoteEvents(mut notes) => notes.remove(0),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(notes.len(), 3);
+        assert_eq!(notes[0].volume, 0.5);
+        assert_eq!(notes[0].attributes.get("cutoff"), Some(&0.25));
+        assert_eq!(notes[1].length, Some(0.5));
+        assert_eq!(notes[1].attributes.get("x"), Some(&0.1));
+        assert_eq!(notes[2].attributes.get("pitch_bend"), Some(&-1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn note_property_attributes() -> Result<(), String> {
+        let mut emitter = new_cycle_emitter("c4:v0.5:p0.5:d0.5 e4:vol=0.25:pan=-1:delay=0.75")?;
+        let notes = emitter
+            .run(RhythmEvent::default(), true)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|event| match event.event {
+                Event::NoteEvents(mut notes) => notes.remove(0),
+                _ => None,
+            })
+            .map(|note| (note.volume, note.panning, note.delay, note.attributes.len()))
+            .collect::<Vec<_>>();
+        // volume, panning and delay attributes alias the note's properties
+        assert_eq!(notes, vec![(0.5, 0.5, 0.5, 0), (0.25, -1.0, 0.75, 0)]);
+        // and get validated as note properties
+        let mut note_events = vec![new_note("g4")];
+        let targets = [CycleTarget::Named("volume".into(), Some(2.0))];
+        assert!(apply_cycle_note_properties(&mut note_events, &targets).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,367 @@
+use std::cmp::Ordering;
+
+use rand::{rng, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{
+    event::new_note, BeatTimeBase, Emitter, EmitterEvent, Event, Note, ParameterSet, RhythmEvent,
+    RhythmProperty, Scale,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Emits a constrained random melody from the notes of a [`Scale`].
+///
+/// The melody starts on the scale's tonic, closest to the center of the note range, and then
+/// moves by scale steps or leaps within the note range:
+///
+/// - `max_leap`: the maximum interval in scale steps. By default 4.
+/// - `step_probability`: probability of moving by a single scale step instead of leaping, in
+///   range \[0 - 1\]. By default 0.75.
+/// - `contour`: direction bias in range \[-1 - 1\]. Negative values favor descending, positive
+///   values ascending moves. By default 0.
+///
+/// Melodies avoid repeating the last played notes and optionally end phrases on the tonic.
+/// All numeric constraints can follow pattern parameters, see [`RhythmProperty`].
+///
+/// ### Example
+///
+/// ```rust
+/// use pattrns::prelude::*;
+///
+/// let emitter = MelodyEmitter::new(Scale::try_from((Note::C4, "minor")).unwrap())
+///     .with_range(Note::C4, Note::C6)
+///     .with_max_leap(3.0)
+///     .with_contour(RhythmProperty::with_parameter("contour", 0.0))
+///     .with_phrase_length(8)
+///     .with_seed(Some(1234));
+/// ```
+#[derive(Clone, Debug)]
+pub struct MelodyEmitter {
+    scale: Scale,
+    notes: Vec<Note>,
+    max_leap: RhythmProperty,
+    step_probability: RhythmProperty,
+    contour: RhythmProperty,
+    avoid_repeats: usize,
+    phrase_length: Option<usize>,
+    current: Option<usize>,
+    history: Vec<usize>,
+    step: usize,
+    rand_gen: Xoshiro256PlusPlus,
+    seed: Option<u64>,
+}
+
+impl MelodyEmitter {
+    /// Create a new melody from the given scale, which moves within one octave above the scale's
+    /// root note.
+    pub fn new(scale: Scale) -> Self {
+        let root_note = scale.root_note();
+        let notes = Self::scale_notes(&scale, root_note, root_note.transposed(12));
+        let max_leap = RhythmProperty::new(4.0);
+        let step_probability = RhythmProperty::new(0.75);
+        let contour = RhythmProperty::new(0.0);
+        let avoid_repeats = 1;
+        let phrase_length = None;
+        let current = None;
+        let history = Vec::new();
+        let step = 0;
+        let seed = None;
+        let rand_gen = Xoshiro256PlusPlus::seed_from_u64(rng().random());
+        Self {
+            scale,
+            notes,
+            max_leap,
+            step_probability,
+            contour,
+            avoid_repeats,
+            phrase_length,
+            current,
+            history,
+            step,
+            rand_gen,
+            seed,
+        }
+    }
+
+    /// Return a new melody which moves within the given inclusive note range.
+    ///
+    /// ### Panics
+    /// Panics if the range contains no notes of the scale.
+    #[must_use]
+    pub fn with_range(mut self, min: Note, max: Note) -> Self {
+        let notes = Self::scale_notes(&self.scale, min, max);
+        assert!(!notes.is_empty(), "Invalid note range");
+        self.notes = notes;
+        self
+    }
+
+    /// Return a new melody with the given maximum interval in scale steps. Values get clamped
+    /// to the number of notes in the melody's range and values < 1 are treated as 1.
+    #[must_use]
+    pub fn with_max_leap<P: Into<RhythmProperty>>(mut self, max_leap: P) -> Self {
+        self.max_leap = max_leap.into();
+        self
+    }
+
+    /// Return a new melody with the given probability of moving by a scale step instead of
+    /// leaping, in range \[0 - 1\].
+    #[must_use]
+    pub fn with_step_probability<P: Into<RhythmProperty>>(mut self, probability: P) -> Self {
+        self.step_probability = probability.into();
+        self
+    }
+
+    /// Return a new melody with the given direction bias in range \[-1 - 1\].
+    #[must_use]
+    pub fn with_contour<P: Into<RhythmProperty>>(mut self, contour: P) -> Self {
+        self.contour = contour.into();
+        self
+    }
+
+    /// Return a new melody which avoids repeating the given number of last played notes, when
+    /// other notes are available. When 0, notes may repeat as likely as they move by a step.
+    #[must_use]
+    pub fn with_avoid_repeats(mut self, count: usize) -> Self {
+        self.avoid_repeats = count;
+        self
+    }
+
+    /// Return a new melody which ends every phrase of the given number of notes on the tonic,
+    /// closest to the previous note. A length of 0 or None disables phrase endings.
+    #[must_use]
+    pub fn with_phrase_length<T: Into<Option<usize>>>(mut self, length: T) -> Self {
+        self.phrase_length = length.into().filter(|length| *length > 0);
+        self
+    }
+
+    /// Return a new melody with the given optional random seed. When None, the melody is
+    /// randomly seeded.
+    #[must_use]
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        if let Some(seed) = seed {
+            self.rand_gen = Xoshiro256PlusPlus::seed_from_u64(seed);
+        }
+        self
+    }
+
+    /// The scale the melody picks its notes from.
+    pub fn scale(&self) -> &Scale {
+        &self.scale
+    }
+
+    /// The scale notes within the melody's range, in ascending order.
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    // Collect all notes of the given scale within the given range.
+    fn scale_notes(scale: &Scale, min: Note, max: Note) -> Vec<Note> {
+        let steps = scale.steps();
+        (u8::from(min)..=u8::from(max).min(0x7f))
+            .filter(|note| {
+                let step = (*note as usize + 12 - scale.key() as usize) % 12;
+                steps.contains(&step)
+            })
+            .map(Note::from)
+            .collect()
+    }
+
+    // Index of the tonic, which is closest to the given note index.
+    fn closest_tonic(&self, index: usize) -> usize {
+        let is_tonic = |note: &Note| note.key() == self.scale.key();
+        let distance = |other: usize| other.abs_diff(index);
+        self.notes
+            .iter()
+            .enumerate()
+            .filter(|(_, note)| is_tonic(note))
+            .map(|(other, _)| other)
+            .min_by_key(|other| distance(*other))
+            .unwrap_or(index)
+    }
+
+    // Pick the next note index, based on the current note index and the melody's constraints.
+    fn next_index(&mut self, current: usize) -> usize {
+        let max_leap = (self.max_leap.value().round() as usize).clamp(1, self.notes.len());
+        let step_probability = self.step_probability.value().clamp(0.0, 1.0);
+        let contour = self.contour.value().clamp(-1.0, 1.0);
+        let candidates =
+            current.saturating_sub(max_leap)..=(current + max_leap).min(self.notes.len() - 1);
+        let weights = candidates
+            .clone()
+            .map(|index| {
+                if self.history.contains(&index) {
+                    return 0.0;
+                }
+                let interval_weight = match index.abs_diff(current) {
+                    0 | 1 => step_probability / 2.0,
+                    _ => (1.0 - step_probability) / (2 * (max_leap - 1)) as f64,
+                };
+                let direction_weight = match index.cmp(&current) {
+                    Ordering::Greater => 1.0 + contour,
+                    Ordering::Less => 1.0 - contour,
+                    Ordering::Equal => 1.0,
+                };
+                interval_weight * direction_weight
+            })
+            .collect::<Vec<_>>();
+        let total_weight = weights.iter().sum::<f64>();
+        if total_weight > 0.0 {
+            let mut value = self.rand_gen.random_range(0.0..total_weight);
+            for (index, weight) in candidates.clone().zip(weights) {
+                if value < weight {
+                    return index;
+                }
+                value -= weight;
+            }
+            current
+        } else {
+            // all candidates are blocked: pick any note which got not played recently
+            let mut pool = candidates
+                .clone()
+                .filter(|index| !self.history.contains(index))
+                .collect::<Vec<_>>();
+            if pool.is_empty() {
+                pool = candidates.collect();
+            }
+            pool[self.rand_gen.random_range(0..pool.len())]
+        }
+    }
+
+    // Generate the next note and update the melody's state.
+    fn next_note(&mut self) -> Note {
+        let is_phrase_end = self
+            .phrase_length
+            .is_some_and(|length| (self.step + 1).is_multiple_of(length));
+        let index = match self.current {
+            None => self.closest_tonic(self.notes.len() / 2),
+            Some(current) if is_phrase_end => self.closest_tonic(current),
+            Some(current) => self.next_index(current),
+        };
+        self.current = Some(index);
+        if self.avoid_repeats > 0 {
+            self.history.push(index);
+            if self.history.len() > self.avoid_repeats {
+                self.history.remove(0);
+            }
+        }
+        self.step += 1;
+        self.notes[index]
+    }
+}
+
+impl Emitter for MelodyEmitter {
+    fn set_time_base(&mut self, _time_base: &BeatTimeBase) {
+        // nothing to do
+    }
+
+    fn set_trigger_event(&mut self, _event: &Event) {
+        // nothing to do
+    }
+
+    fn set_parameters(&mut self, parameters: ParameterSet) {
+        for property in [
+            &mut self.max_leap,
+            &mut self.step_probability,
+            &mut self.contour,
+        ] {
+            property.set_parameters(&parameters);
+        }
+    }
+
+    fn run(&mut self, _pulse: RhythmEvent, emit_event: bool) -> Option<Vec<EmitterEvent>> {
+        if !emit_event {
+            return None;
+        }
+        let note = self.next_note();
+        Some(vec![EmitterEvent::new(Event::NoteEvents(vec![new_note(
+            note,
+        )]))])
+    }
+
+    fn duplicate(&self) -> Box<dyn Emitter> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.current = None;
+        self.history.clear();
+        self.step = 0;
+        // reset random number generator to its initial state, when the emitter is seeded
+        if let Some(seed) = self.seed {
+            self.rand_gen = Xoshiro256PlusPlus::seed_from_u64(seed);
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run_notes(emitter: &mut MelodyEmitter, count: usize) -> Vec<Note> {
+        (0..count)
+            .filter_map(|_| emitter.run(RhythmEvent::default(), true))
+            .map(|events| match &events[0].event {
+                Event::NoteEvents(notes) => notes[0].as_ref().unwrap().note,
+                _ => panic!("unexpected event"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn constraints() {
+        let scale = Scale::try_from((Note::C4, "major")).unwrap();
+        let mut emitter = MelodyEmitter::new(scale.clone())
+            .with_range(Note::C4, Note::C6)
+            .with_max_leap(2.0)
+            .with_seed(Some(1234));
+        let notes = run_notes(&mut emitter, 256);
+        // starts on the tonic closest to the range center
+        assert_eq!(notes[0], Note::C5);
+        // stays in range and scale
+        assert!(notes
+            .iter()
+            .all(|note| (Note::C4..=Note::C6).contains(note) && emitter.notes().contains(note)));
+        // moves by at most two scale steps and never repeats notes
+        let index = |note: &Note| emitter.notes().iter().position(|n| n == note).unwrap();
+        assert!(notes.windows(2).all(|pair| {
+            let distance = index(&pair[0]).abs_diff(index(&pair[1]));
+            distance > 0 && distance <= 2
+        }));
+        // seeded melodies repeat after resets
+        emitter.reset();
+        assert_eq!(run_notes(&mut emitter, 256), notes);
+
+        // contours
+        let mut emitter = MelodyEmitter::new(scale.clone())
+            .with_range(Note::C4, Note::C6)
+            .with_contour(1.0)
+            .with_seed(Some(1234));
+        let notes = run_notes(&mut emitter, 4);
+        assert!(notes.windows(2).all(|pair| pair[1] > pair[0]));
+
+        // huge leaps are limited to the range
+        let mut emitter = MelodyEmitter::new(scale.clone())
+            .with_range(Note::C4, Note::C6)
+            .with_max_leap(f64::INFINITY)
+            .with_seed(Some(1234));
+        let notes = run_notes(&mut emitter, 64);
+        assert!(notes
+            .iter()
+            .all(|note| (Note::C4..=Note::C6).contains(note)));
+
+        // phrase endings
+        let mut emitter = MelodyEmitter::new(scale)
+            .with_range(Note::C3, Note::C6)
+            .with_phrase_length(4)
+            .with_avoid_repeats(0)
+            .with_seed(Some(1234));
+        let notes = run_notes(&mut emitter, 16);
+        assert!(notes
+            .chunks(4)
+            .all(|phrase| phrase[3].key() == Note::C4.key()));
+    }
+}
@@ -6,8 +6,8 @@ use mlua::prelude::{LuaError, LuaResult};
 
 use crate::{
     bindings::{
-        add_lua_callback_error, note_events_from_value, ContextPlaybackState, LuaCallback,
-        LuaTimeoutHook,
+        note_events_from_value, ContextPlaybackState, LuaCallback, LuaCallbackErrorChannel,
+        LuaCallbackKind, LuaTimeoutHook,
     },
     emitter::cycle::{apply_cycle_note_properties, CycleNoteEvents},
     BeatTimeBase, Cycle, CycleEvent, CycleValue, Emitter, EmitterEvent, Event, NoteEvent,
@@ -29,6 +29,7 @@ pub struct ScriptedCycleEmitter {
     mappings: HashMap<String, Vec<Option<NoteEvent>>>,
     mapping_callback: Option<LuaCallback>,
     timeout_hook: Option<LuaTimeoutHook>,
+    callback_errors: Option<LuaCallbackErrorChannel>,
     channel_steps: Vec<usize>,
 }
 
@@ -38,16 +39,28 @@ impl ScriptedCycleEmitter {
         let mappings = mappings.into_iter().collect();
         let mapping_callback = None;
         let timeout_hook = None;
+        let callback_errors = None;
         let channel_steps = vec![];
         Self {
             cycle,
             mappings,
             mapping_callback,
             timeout_hook,
+            callback_errors,
             channel_steps,
         }
     }
 
+    /// Return a new cycle which reports errors to the given error channel.
+    /// Errors of mapping callbacks are reported to the callback's Lua engine channel.
+    pub(crate) fn with_callback_errors(self, callback_errors: LuaCallbackErrorChannel) -> Self {
+        let callback_errors = Some(callback_errors);
+        Self {
+            callback_errors,
+            ..self
+        }
+    }
+
     /// Return a new cycle with the given mapping callback applied.
     pub(crate) fn with_mapping_callback(
         cycle: Cycle,
@@ -72,16 +85,29 @@ impl ScriptedCycleEmitter {
             step,
             step_length,
         )?;
+        let callback_errors = None;
         let channel_steps = vec![];
         Ok(Self {
             cycle,
             mappings,
             mapping_callback: Some(mapping_callback),
             timeout_hook: Some(timeout_hook),
+            callback_errors,
             channel_steps,
         })
     }
 
+    /// Report errors which are not caused by mapping callbacks.
+    fn handle_error(&self, err: &LuaError) {
+        if let Some(callback) = &self.mapping_callback {
+            callback.handle_error(err);
+        } else if let Some(callback_errors) = &self.callback_errors {
+            callback_errors.report(LuaCallbackKind::Cycle, None, None, err);
+        } else {
+            log::warn!("Cycle failed to evaluate:\n{}", err);
+        }
+    }
+
     /// Generate a note event stack from a single cycle event, applying mappings if necessary.
     fn cycle_to_note_event(
         &mut self,
@@ -134,7 +160,7 @@ impl ScriptedCycleEmitter {
             match self.cycle.generate() {
                 Ok(events) => events,
                 Err(err) => {
-                    add_lua_callback_error("cycle", &LuaError::RuntimeError(err));
+                    self.handle_error(&LuaError::RuntimeError(err));
                     // skip processing events
                     return vec![];
                 }
@@ -166,13 +192,7 @@ impl ScriptedCycleEmitter {
                 let length = event.span().length();
                 let step_length = length.to_f64().unwrap_or(0.0);
                 match self.cycle_to_note_event(channel_index, channel_step, step_length, event) {
-                    Err(err) => {
-                        if let Some(callback) = &self.mapping_callback {
-                            callback.handle_error(&err)
-                        } else {
-                            add_lua_callback_error("map", &err)
-                        }
-                    }
+                    Err(err) => self.handle_error(&err),
                     Ok(note_events) => {
                         if !note_events.is_empty() {
                             timed_note_events.add(channel_index, start, length, note_events);
@@ -194,7 +214,7 @@ impl ScriptedCycleEmitter {
                 match self.cycle.generate() {
                     Ok(events) => events,
                     Err(err) => {
-                        add_lua_callback_error("cycle", &LuaError::RuntimeError(err));
+                        mapping_callback.handle_error(&LuaError::RuntimeError(err));
                         return;
                     }
                 }
@@ -225,12 +245,12 @@ impl ScriptedCycleEmitter {
                             channel_step,
                             step_length,
                         ) {
-                            add_lua_callback_error("cycle", &err);
+                            mapping_callback.handle_error(&err);
                             return;
                         }
                         // call mapping function
                         if let Err(err) = mapping_callback.call_with_arg(event.string()) {
-                            add_lua_callback_error("cycle", &err);
+                            mapping_callback.handle_error(&err);
                             return;
                         }
                     }
@@ -1,11 +1,13 @@
 //! Events and event iterators which get emitted by a `Pattern`.
 
 use std::{
+    collections::BTreeMap,
     fmt::{Debug, Display},
     rc::Rc,
+    sync::Arc,
 };
 
-use crate::Note;
+use crate::{note::tuning::frequency_from_note, Note};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -37,24 +39,54 @@ pub struct InstrumentId(usize);
 )]
 pub struct ParameterId(usize);
 
+/// Custom, named note attributes in a [`NoteEvent`], such as `cutoff`, `pitch_bend` or `mod`
+/// values. Attributes are not interpreted by pattrns: it's up to the player or output, which
+/// consumes the events, to apply them. Volume, panning and delay attributes in note strings,
+/// tables or cycles, such as `vol=0.5`, are no attributes, but set the note's properties.
+pub type NoteAttributes = BTreeMap<Arc<str>, f32>;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Single note event in a [`Event`].
+///
+/// A note's `length` is relative to the duration of the pattern event which emitted it. When set,
+/// a [`Phrase`](crate::Phrase) emits a note-off for the note's voice after the note's delay and
+/// length (staccato). When `None`, the note is tied: it plays until the next note or note-off in
+/// the same voice arrives (legato).
 #[derive(Clone, PartialEq, Debug)]
 pub struct NoteEvent {
     pub note: Note,
     pub instrument: Option<InstrumentId>,
-    pub volume: f32,  // [0 - INF]
-    pub panning: f32, // [-1 - 1]
-    pub delay: f32,   // [0 - 1]
+    pub volume: f32,                // [0 - INF]
+    pub panning: f32,               // [-1 - 1]
+    pub delay: f32,                 // [0 - 1]
+    pub cents: f32,                 // fine tuning relative to the note's 12-TET pitch
+    pub length: Option<f32>,        // [0 - INF] relative to the event's duration or None (tied)
+    pub attributes: NoteAttributes, // custom named attributes
 }
 
 impl NoteEvent {
+    /// Frequency of the note in Hz, including its fine tuning. Note values are interpreted as
+    /// MIDI note numbers. Returns `None` for note-offs and empty notes.
+    pub fn frequency(&self) -> Option<f64> {
+        if self.note.is_note_on() {
+            let note = u8::from(self.note) as f64 + self.cents as f64 / 100.0;
+            Some(frequency_from_note(note))
+        } else {
+            None
+        }
+    }
+
     pub fn to_string(&self, show_instruments: bool) -> String {
-        if show_instruments {
+        let note = if self.cents != 0.0 {
+            format!("{}{:+.1}ct", self.note, self.cents)
+        } else {
+            self.note.to_string()
+        };
+        let mut string = if show_instruments {
             format!(
                 "{} {} {:.2} {:.2} {:.2}",
-                self.note,
+                note,
                 if let Some(instrument) = self.instrument {
                     format!("#{:02}", instrument)
                 } else {
@@ -67,9 +99,13 @@ impl NoteEvent {
         } else {
             format!(
                 "{} {:.2} {:.2} {:.2}",
-                self.note, self.volume, self.panning, self.delay
+                note, self.volume, self.panning, self.delay
             )
+        };
+        for (name, value) in &self.attributes {
+            string += &format!(" {}={:.2}", name, value);
         }
+        string
     }
 }
 
@@ -86,6 +122,9 @@ where
             volume: 1.0,
             panning: 0.0,
             delay: 0.0,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         }
     }
 }
@@ -104,6 +143,9 @@ where
             volume: 1.0,
             panning: 0.0,
             delay: 0.0,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         }
     }
 }
@@ -123,6 +165,9 @@ where
             volume,
             panning: 0.0,
             delay: 0.0,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         }
     }
 }
@@ -143,6 +188,9 @@ where
             volume,
             panning,
             delay: 0.0,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         }
     }
 }
@@ -164,6 +212,9 @@ where
             volume,
             panning,
             delay,
+            cents: 0.0,
+            length: None,
+            attributes: NoteAttributes::new(),
         }
     }
 }
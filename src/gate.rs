@@ -1,11 +1,16 @@
 //! Defines if an `Event` should be triggered or not for a given `Pulse`.
 
-use std::fmt::Debug;
+use std::{fmt::Debug, rc::Rc};
 
-use crate::{BeatTimeBase, Event, ParameterSet, RhythmEvent};
+use crate::{BeatTimeBase, BeatTimeStep, Event, ParameterSet, RhythmEvent, TempoMap};
 
 // -------------------------------------------------------------------------------------------------
 
+pub mod bar;
+pub mod cycle;
+pub mod density;
+pub mod logic;
+pub mod mute;
 pub mod probability;
 #[cfg(feature = "scripting")]
 pub mod scripted;
@@ -15,6 +20,12 @@ pub mod threshold;
 
 /// Defines if an [`Event`] should be triggered or not, depending on an incoming
 /// [`Pulse`](crate::Pulse) value.
+///
+/// ### Probabilities
+///
+/// Only the [`ProbabilityGate`](crate::gates::ProbabilityGate) treats pulse values in range
+/// (0, 1) as probabilities: all other gates handle them like any other pulse value > 0. To
+/// apply probabilities with another gate, combine both via an [`AndGate`](crate::gates::AndGate).
 pub trait Gate: Debug {
     /// Set or update the gate's internal beat or second time base with the new time base.
     fn set_time_base(&mut self, time_base: &BeatTimeBase);
@@ -25,6 +36,19 @@ pub trait Gate: Debug {
     /// Set or update optional, parameter map for callbacks.
     fn set_parameters(&mut self, parameters: ParameterSet);
 
+    /// Set or update the length of the pattern's pulse steps in beats and the number of pulses
+    /// in the pattern's rhythm, which is 0 for endless rhythms. Used by gates which locate pulses
+    /// in bars or rhythm cycles. By default does nothing.
+    fn set_pulse_steps(&mut self, _step_in_beats: f64, _rhythm_length: usize) {
+        // nothing to do
+    }
+
+    /// Set or update the tempo map which the pattern follows, if any. Gates which locate pulses
+    /// in bars then follow the map's meter instead of the time base's. By default does nothing.
+    fn set_tempo_map(&mut self, _tempo_map: Option<&GateTempoMap>) {
+        // nothing to do
+    }
+
     /// Returns if the event should be triggered.
     fn run(&mut self, pulse: &RhythmEvent) -> bool;
 
@@ -36,3 +60,166 @@ pub trait Gate: Debug {
     /// Resets the gate's internal state.
     fn reset(&mut self);
 }
+
+// -------------------------------------------------------------------------------------------------
+
+/// Tempo map of a pattern, as passed to gates via [`Gate::set_tempo_map`].
+///
+/// Converts pulse positions, which are based on the pattern time base's constant tempo and meter,
+/// to quarter note beat and bar positions in the tempo map.
+#[derive(Debug, Clone)]
+pub struct GateTempoMap {
+    tempo_map: Rc<TempoMap>,
+    step: BeatTimeStep,
+    step_in_beats: f64,
+    start_beat: f64,
+}
+
+impl GateTempoMap {
+    /// Create a new gate tempo map for a pattern with the given time base and step, which starts
+    /// at the given quarter note beat position in the tempo map.
+    pub fn new(
+        tempo_map: Rc<TempoMap>,
+        time_base: &BeatTimeBase,
+        step: BeatTimeStep,
+        start_beat: f64,
+    ) -> Self {
+        let step_in_beats = step.samples_per_step(time_base) / time_base.samples_per_beat();
+        Self {
+            tempo_map,
+            step,
+            step_in_beats,
+            start_beat,
+        }
+    }
+
+    /// The pattern's tempo map.
+    pub fn tempo_map(&self) -> &TempoMap {
+        &self.tempo_map
+    }
+
+    /// Start position of the pattern in the tempo map in quarter note beats.
+    pub fn start_beat(&self) -> f64 {
+        self.start_beat
+    }
+
+    /// Convert a position in beats of the pattern's time base, relative to the start of the
+    /// pattern, to a quarter note beat position in the tempo map.
+    pub fn map_beats(&self, beats: f64) -> f64 {
+        let start_steps = self.tempo_map.beats_to_steps(&self.step, self.start_beat);
+        self.tempo_map
+            .steps_to_beats(&self.step, start_steps + beats / self.step_in_beats)
+    }
+
+    /// Convert a quarter note beat position in the tempo map to a bar position.
+    pub fn beats_to_bars(&self, beats: f64) -> f64 {
+        self.tempo_map.meter().beats_to_bars(beats)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Position of a pulse in a pattern, as tracked by [`PulseTimer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PulsePosition {
+    /// Start time of the pulse in beats, relative to the start of the pattern.
+    pub start: f64,
+    /// End time of the pulse in beats, relative to the start of the pattern.
+    pub end: f64,
+    /// Bar position of the pulse's start. Follows the tempo map's meter, if any.
+    pub start_bar: f64,
+    /// Bar position of the pulse's end. Follows the tempo map's meter, if any.
+    pub end_bar: f64,
+    /// Index of the rhythm cycle, or the bar index for endless rhythms.
+    pub cycle: usize,
+}
+
+/// Tracks the positions of the pulses which are passed to a gate.
+#[derive(Debug, Clone)]
+pub(crate) struct PulseTimer {
+    step_in_beats: f64,
+    rhythm_length: usize,
+    beats_per_bar: f64,
+    tempo_map: Option<GateTempoMap>,
+    pulse_index: usize,
+    pulse_time: f64,
+}
+
+impl PulseTimer {
+    /// Tolerance for comparing beat times.
+    pub const EPSILON: f64 = 1e-9;
+
+    pub fn new() -> Self {
+        Self {
+            step_in_beats: 1.0,
+            rhythm_length: 0,
+            beats_per_bar: 4.0,
+            tempo_map: None,
+            pulse_index: 0,
+            pulse_time: 0.0,
+        }
+    }
+
+    /// Index of the bar at the given bar position.
+    pub fn bar_index(bar: f64) -> usize {
+        (bar + Self::EPSILON).floor() as usize
+    }
+
+    pub fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.beats_per_bar = time_base.beats_per_bar.max(1) as f64;
+    }
+
+    pub fn set_pulse_steps(&mut self, step_in_beats: f64, rhythm_length: usize) {
+        self.step_in_beats = step_in_beats;
+        self.rhythm_length = rhythm_length;
+    }
+
+    pub fn set_tempo_map(&mut self, tempo_map: Option<&GateTempoMap>) {
+        self.tempo_map = tempo_map.cloned();
+    }
+
+    /// Returns the position of the given pulse and moves on to the next one.
+    pub fn advance(&mut self, pulse: &RhythmEvent) -> PulsePosition {
+        let start = self.pulse_time * self.step_in_beats;
+        let end = (self.pulse_time + pulse.step_time) * self.step_in_beats;
+        let (start, end, start_bar, end_bar, first_bar) = match &self.tempo_map {
+            Some(tempo_map) => {
+                // bars are absolute bars in the tempo map's meter
+                let start_beat = tempo_map.start_beat();
+                let (start, end) = (tempo_map.map_beats(start), tempo_map.map_beats(end));
+                (
+                    start - start_beat,
+                    end - start_beat,
+                    tempo_map.beats_to_bars(start),
+                    tempo_map.beats_to_bars(end),
+                    Self::bar_index(tempo_map.beats_to_bars(start_beat)),
+                )
+            }
+            None => (
+                start,
+                end,
+                start / self.beats_per_bar,
+                end / self.beats_per_bar,
+                0,
+            ),
+        };
+        let cycle = self
+            .pulse_index
+            .checked_div(self.rhythm_length)
+            .unwrap_or_else(|| Self::bar_index(start_bar).saturating_sub(first_bar));
+        self.pulse_index += 1;
+        self.pulse_time += pulse.step_time;
+        PulsePosition {
+            start,
+            end,
+            start_bar,
+            end_bar,
+            cycle,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pulse_index = 0;
+        self.pulse_time = 0.0;
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    gate::{GateTempoMap, PulseTimer},
+    BeatTimeBase, Event, Gate, ParameterSet, RhythmEvent,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Position of pulses in a bar, as used by [`BarGate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarPosition {
+    /// The first pulse which starts in a bar.
+    First,
+    /// The last pulse which starts in a bar.
+    Last,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Gate implementation which passes pulse values > 0 only when the pulse is the first or last
+/// pulse in a bar.
+///
+/// Pulse values are not treated as probabilities, see [`Gate`](crate::Gate#probabilities).
+#[derive(Debug, Clone)]
+pub struct BarGate {
+    position: BarPosition,
+    timer: PulseTimer,
+    bar_ended: bool,
+}
+
+impl BarGate {
+    pub fn new(position: BarPosition) -> Self {
+        let timer = PulseTimer::new();
+        let bar_ended = true;
+        Self {
+            position,
+            timer,
+            bar_ended,
+        }
+    }
+
+    /// Create a new gate which passes the first pulse of each bar.
+    pub fn first() -> Self {
+        Self::new(BarPosition::First)
+    }
+
+    /// Create a new gate which passes the last pulse of each bar.
+    pub fn last() -> Self {
+        Self::new(BarPosition::Last)
+    }
+}
+
+impl Gate for BarGate {
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.timer.set_time_base(time_base);
+    }
+
+    fn set_trigger_event(&mut self, _event: &Event) {
+        // nothing to do
+    }
+
+    fn set_parameters(&mut self, _parameters: ParameterSet) {
+        // nothing to do
+    }
+
+    fn set_pulse_steps(&mut self, step_in_beats: f64, rhythm_length: usize) {
+        self.timer.set_pulse_steps(step_in_beats, rhythm_length);
+    }
+
+    fn set_tempo_map(&mut self, tempo_map: Option<&GateTempoMap>) {
+        self.timer.set_tempo_map(tempo_map);
+    }
+
+    fn run(&mut self, pulse: &RhythmEvent) -> bool {
+        let position = self.timer.advance(pulse);
+        // a pulse is the last one in a bar, when it reaches or crosses the next bar
+        let is_first = self.bar_ended;
+        let is_last =
+            PulseTimer::bar_index(position.end_bar) > PulseTimer::bar_index(position.start_bar);
+        self.bar_ended = is_last;
+        pulse.value > 0.0
+            && match self.position {
+                BarPosition::First => is_first,
+                BarPosition::Last => is_last,
+            }
+    }
+
+    fn duplicate(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.timer.reset();
+        self.bar_ended = true;
+    }
+}
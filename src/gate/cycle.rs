@@ -0,0 +1,66 @@
+use crate::{
+    gate::{GateTempoMap, PulseTimer},
+    BeatTimeBase, Event, Gate, ParameterSet, RhythmEvent,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Gate implementation which passes pulse values > 0 in every nth cycle of the pattern's rhythm
+/// only. For endless rhythms, a cycle is a bar.
+///
+/// Pulse values are not treated as probabilities, see [`Gate`](crate::Gate#probabilities).
+#[derive(Debug, Clone)]
+pub struct CycleGate {
+    every: usize,
+    offset: usize,
+    timer: PulseTimer,
+}
+
+impl CycleGate {
+    /// Create a new gate which passes pulses in every `every`th cycle, starting with the cycle
+    /// at index `offset`. An `every` value of 0 is treated as 1.
+    pub fn new(every: usize, offset: usize) -> Self {
+        let every = every.max(1);
+        let timer = PulseTimer::new();
+        Self {
+            every,
+            offset,
+            timer,
+        }
+    }
+}
+
+impl Gate for CycleGate {
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.timer.set_time_base(time_base);
+    }
+
+    fn set_trigger_event(&mut self, _event: &Event) {
+        // nothing to do
+    }
+
+    fn set_parameters(&mut self, _parameters: ParameterSet) {
+        // nothing to do
+    }
+
+    fn set_pulse_steps(&mut self, step_in_beats: f64, rhythm_length: usize) {
+        self.timer.set_pulse_steps(step_in_beats, rhythm_length);
+    }
+
+    fn set_tempo_map(&mut self, tempo_map: Option<&GateTempoMap>) {
+        self.timer.set_tempo_map(tempo_map);
+    }
+
+    fn run(&mut self, pulse: &RhythmEvent) -> bool {
+        let position = self.timer.advance(pulse);
+        pulse.value > 0.0 && position.cycle % self.every == self.offset % self.every
+    }
+
+    fn duplicate(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{BeatTimeBase, Event, Gate, ParameterSet, RhythmEvent, RhythmProperty};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Gate implementation which thins out pulse values > 0 to the given density: with a density of
+/// 0.5, every other pulse passes, with a density of 0.25 every fourth one and so on. Skipped
+/// pulses are spread evenly, so the result is deterministic.
+///
+/// Pulse values are not treated as probabilities, see [`Gate`](crate::Gate#probabilities).
+///
+/// The density can follow a pattern parameter, see [`RhythmProperty`].
+#[derive(Debug, Clone)]
+pub struct DensityGate {
+    density: RhythmProperty,
+    accumulator: f64,
+}
+
+impl DensityGate {
+    /// Create a new gate with the given density in range \[0 - 1\].
+    pub fn new<P: Into<RhythmProperty>>(density: P) -> Self {
+        let density = density.into();
+        let accumulator = 0.0;
+        Self {
+            density,
+            accumulator,
+        }
+    }
+
+    /// The gate's density property.
+    pub fn density(&self) -> &RhythmProperty {
+        &self.density
+    }
+}
+
+impl Gate for DensityGate {
+    fn set_time_base(&mut self, _time_base: &BeatTimeBase) {
+        // nothing to do
+    }
+
+    fn set_trigger_event(&mut self, _event: &Event) {
+        // nothing to do
+    }
+
+    fn set_parameters(&mut self, parameters: ParameterSet) {
+        self.density.set_parameters(&parameters);
+    }
+
+    fn run(&mut self, pulse: &RhythmEvent) -> bool {
+        if pulse.value <= 0.0 {
+            return false;
+        }
+        // error diffusion: pass pulses when the accumulated density reaches a half pulse
+        self.accumulator += self.density.value().clamp(0.0, 1.0);
+        if self.accumulator >= 0.5 {
+            self.accumulator -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn duplicate(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
@@ -0,0 +1,359 @@
+use crate::{gate::GateTempoMap, BeatTimeBase, Event, Gate, ParameterSet, RhythmEvent};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Gate combinator which passes pulses when all of its gates pass them.
+///
+/// All gates always run, so gates which track pulse positions or states stay in sync.
+#[derive(Debug)]
+pub struct AndGate {
+    gates: Vec<Box<dyn Gate>>,
+}
+
+/// Gate combinator which passes pulses when any of its gates passes them.
+///
+/// All gates always run, so gates which track pulse positions or states stay in sync.
+#[derive(Debug)]
+pub struct OrGate {
+    gates: Vec<Box<dyn Gate>>,
+}
+
+/// Gate combinator which inverts the result of another gate.
+#[derive(Debug)]
+pub struct NotGate {
+    gate: Box<dyn Gate>,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+macro_rules! impl_gate_list_combinator {
+    ($type:ident, $combine:ident) => {
+        impl $type {
+            pub fn new(gates: Vec<Box<dyn Gate>>) -> Self {
+                Self { gates }
+            }
+
+            /// Combined gates.
+            pub fn gates(&self) -> &[Box<dyn Gate>] {
+                &self.gates
+            }
+        }
+
+        impl Clone for $type {
+            fn clone(&self) -> Self {
+                Self {
+                    gates: self.gates.iter().map(|gate| gate.duplicate()).collect(),
+                }
+            }
+        }
+
+        impl Gate for $type {
+            fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+                for gate in &mut self.gates {
+                    gate.set_time_base(time_base);
+                }
+            }
+
+            fn set_trigger_event(&mut self, event: &Event) {
+                for gate in &mut self.gates {
+                    gate.set_trigger_event(event);
+                }
+            }
+
+            fn set_parameters(&mut self, parameters: ParameterSet) {
+                for gate in &mut self.gates {
+                    gate.set_parameters(parameters.clone());
+                }
+            }
+
+            fn set_pulse_steps(&mut self, step_in_beats: f64, rhythm_length: usize) {
+                for gate in &mut self.gates {
+                    gate.set_pulse_steps(step_in_beats, rhythm_length);
+                }
+            }
+
+            fn set_tempo_map(&mut self, tempo_map: Option<&GateTempoMap>) {
+                for gate in &mut self.gates {
+                    gate.set_tempo_map(tempo_map);
+                }
+            }
+
+            fn run(&mut self, pulse: &RhythmEvent) -> bool {
+                // NB: don't short-circuit: run all gates
+                let results = self
+                    .gates
+                    .iter_mut()
+                    .map(|gate| gate.run(pulse))
+                    .collect::<Vec<_>>();
+                results.into_iter().$combine(|result| result)
+            }
+
+            fn duplicate(&self) -> Box<dyn Gate> {
+                Box::new(self.clone())
+            }
+
+            fn reset(&mut self) {
+                for gate in &mut self.gates {
+                    gate.reset();
+                }
+            }
+        }
+    };
+}
+
+impl_gate_list_combinator!(AndGate, all);
+impl_gate_list_combinator!(OrGate, any);
+
+// -------------------------------------------------------------------------------------------------
+
+impl NotGate {
+    pub fn new(gate: Box<dyn Gate>) -> Self {
+        Self { gate }
+    }
+
+    /// Inverted gate.
+    pub fn gate(&self) -> &dyn Gate {
+        self.gate.as_ref()
+    }
+}
+
+impl Clone for NotGate {
+    fn clone(&self) -> Self {
+        Self {
+            gate: self.gate.duplicate(),
+        }
+    }
+}
+
+impl Gate for NotGate {
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.gate.set_time_base(time_base);
+    }
+
+    fn set_trigger_event(&mut self, event: &Event) {
+        self.gate.set_trigger_event(event);
+    }
+
+    fn set_parameters(&mut self, parameters: ParameterSet) {
+        self.gate.set_parameters(parameters);
+    }
+
+    fn set_pulse_steps(&mut self, step_in_beats: f64, rhythm_length: usize) {
+        self.gate.set_pulse_steps(step_in_beats, rhythm_length);
+    }
+
+    fn set_tempo_map(&mut self, tempo_map: Option<&GateTempoMap>) {
+        self.gate.set_tempo_map(tempo_map);
+    }
+
+    fn run(&mut self, pulse: &RhythmEvent) -> bool {
+        !self.gate.run(pulse)
+    }
+
+    fn duplicate(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.gate.reset();
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        gate::GateTempoMap,
+        gates::{
+            AndGate, BarGate, CycleGate, DensityGate, MuteGate, NotGate, OrGate, ProbabilityGate,
+            ThresholdGate,
+        },
+        BeatTimeBase, BeatTimeStep, Gate, Parameter, RhythmEvent, RhythmProperty, TempoMap,
+        TimeSignature,
+    };
+
+    fn run_gate(gate: &mut dyn Gate, pulses: &[f32]) -> Vec<bool> {
+        pulses
+            .iter()
+            .map(|value| {
+                gate.run(&RhythmEvent {
+                    value: *value,
+                    step_time: 1.0,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn native_gates() {
+        // cycles
+        let mut gate = CycleGate::new(2, 1);
+        gate.set_pulse_steps(1.0, 2);
+        assert_eq!(
+            run_gate(&mut gate, &[1.0, 1.0, 1.0, 0.0, 1.0, 1.0]),
+            vec![false, false, true, false, false, false]
+        );
+        // bars: 4/4 with 1.5 beat steps
+        let mut gate = BarGate::first();
+        gate.set_pulse_steps(1.5, 0);
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 6]),
+            vec![true, false, false, true, false, false]
+        );
+        let mut gate = BarGate::last();
+        gate.set_pulse_steps(1.5, 0);
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 6]),
+            vec![false, false, true, false, false, true]
+        );
+        gate.reset();
+        assert_eq!(run_gate(&mut gate, &[1.0; 3]), vec![false, false, true]);
+        // mute ranges
+        let mut gate = MuteGate::new(vec![1.0..2.0], 3.0);
+        gate.set_pulse_steps(0.5, 0);
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 8]),
+            vec![true, true, false, false, true, true, true, true]
+        );
+        // density
+        let mut gate = DensityGate::new(0.5);
+        assert_eq!(
+            run_gate(&mut gate, &[1.0, 1.0, 0.0, 1.0, 1.0]),
+            vec![true, false, false, true, false]
+        );
+        let parameter = Rc::new(RefCell::new(Parameter::with_float(
+            "density",
+            "",
+            "",
+            0.0..=1.0,
+            0.0,
+        )));
+        let mut gate = DensityGate::new(RhythmProperty::with_parameter("density", 1.0));
+        gate.set_parameters(vec![Rc::clone(&parameter)]);
+        assert_eq!(run_gate(&mut gate, &[1.0; 3]), vec![false; 3]);
+        parameter.borrow_mut().set_value(1.0);
+        assert_eq!(run_gate(&mut gate, &[1.0; 3]), vec![true; 3]);
+    }
+
+    #[test]
+    fn tempo_mapped_gates() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        // 4/4, then 3/4 from the second bar on
+        let tempo_map =
+            Rc::new(TempoMap::new(&time_base).with_time_signature(1, TimeSignature::new(3, 4)));
+        let gate_tempo_map = |start_beat: f64| {
+            GateTempoMap::new(
+                Rc::clone(&tempo_map),
+                &time_base,
+                BeatTimeStep::Beats(1.0),
+                start_beat,
+            )
+        };
+
+        // bars follow the meter
+        let mut gate = BarGate::first();
+        gate.set_pulse_steps(1.0, 0);
+        gate.set_tempo_map(Some(&gate_tempo_map(0.0)));
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 11]),
+            vec![true, false, false, false, true, false, false, true, false, false, true]
+        );
+        let mut gate = CycleGate::new(2, 1);
+        gate.set_pulse_steps(1.0, 0);
+        gate.set_tempo_map(Some(&gate_tempo_map(0.0)));
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 10]),
+            vec![false, false, false, false, true, true, true, false, false, false]
+        );
+
+        // patterns which start within a bar follow the map's bars
+        let mut gate = BarGate::last();
+        gate.set_pulse_steps(1.0, 0);
+        gate.set_tempo_map(Some(&gate_tempo_map(2.0)));
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 6]),
+            vec![false, true, false, false, true, false]
+        );
+        let mut gate = MuteGate::new(vec![0.0..1.0], None);
+        gate.set_pulse_steps(1.0, 0);
+        gate.set_tempo_map(Some(&gate_tempo_map(2.0)));
+        assert_eq!(run_gate(&mut gate, &[1.0; 3]), vec![false, true, true]);
+
+        // without a tempo map, bars follow the time base
+        let mut gate = BarGate::first();
+        gate.set_pulse_steps(1.0, 0);
+        assert_eq!(
+            run_gate(&mut gate, &[1.0; 8]),
+            vec![true, false, false, false, true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn probabilities() {
+        // native gates pass pulse values > 0, regardless of their value
+        let pulses = [0.5, 0.25, 0.0, 0.75, 0.5, 0.25, 1.0, 0.5];
+        let mut gate = BarGate::first();
+        gate.set_pulse_steps(1.0, 0);
+        assert_eq!(
+            run_gate(&mut gate, &pulses),
+            vec![true, false, false, false, true, false, false, false]
+        );
+        let mut gate = DensityGate::new(1.0);
+        assert_eq!(
+            run_gate(&mut gate, &pulses),
+            pulses.iter().map(|v| *v > 0.0).collect::<Vec<_>>()
+        );
+
+        // combined with a probability gate, values get applied as probabilities
+        let probabilities = run_gate(&mut ProbabilityGate::new(Some(1234)), &pulses);
+        assert!(probabilities.contains(&false) && probabilities.contains(&true));
+        let mut gate = AndGate::new(vec![
+            Box::new(ProbabilityGate::new(Some(1234))),
+            Box::new(MuteGate::new(vec![1.0..2.0], 4.0)),
+        ]);
+        assert_eq!(
+            run_gate(&mut gate, &pulses),
+            probabilities
+                .iter()
+                .enumerate()
+                .map(|(index, passed)| *passed && index % 4 != 1)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn combinators() {
+        let pulses = [1.0, 0.0, 1.0, 1.0];
+        let mut gate = AndGate::new(vec![
+            Box::new(ThresholdGate::new()),
+            Box::new(DensityGate::new(0.5)),
+        ]);
+        assert_eq!(run_gate(&mut gate, &pulses), vec![true, false, false, true]);
+        let mut gate = OrGate::new(vec![
+            Box::new(ThresholdGate::with_threshold(0.5)),
+            Box::new(NotGate::new(Box::new(ThresholdGate::new()))),
+        ]);
+        assert_eq!(run_gate(&mut gate, &pulses), vec![true; 4]);
+
+        // all gates run, even when the result is known, and get duplicated and reset
+        let mut gate = AndGate::new(vec![
+            Box::new(ThresholdGate::new()),
+            Box::new(CycleGate::new(2, 0)),
+        ]);
+        gate.set_pulse_steps(1.0, 1);
+        assert_eq!(run_gate(&mut gate, &pulses), vec![true, false, true, false]);
+        let mut duplicate = gate.duplicate();
+        duplicate.reset();
+        assert_eq!(
+            run_gate(duplicate.as_mut(), &pulses),
+            vec![true, false, true, false]
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::ops::Range;
+
+use crate::{
+    gate::{GateTempoMap, PulseTimer},
+    BeatTimeBase, Event, Gate, ParameterSet, RhythmEvent,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Gate implementation which passes pulse values > 0, unless the pulse starts within one of the
+/// given muted beat ranges. Beat times are relative to the start of the pattern and optionally
+/// loop after a given number of beats.
+///
+/// Pulse values are not treated as probabilities, see [`Gate`](crate::Gate#probabilities).
+#[derive(Debug, Clone)]
+pub struct MuteGate {
+    ranges: Vec<Range<f64>>,
+    length: Option<f64>,
+    timer: PulseTimer,
+}
+
+impl MuteGate {
+    /// Create a new gate which mutes pulses in the given beat time ranges. When a length in beats
+    /// is given, the ranges repeat after the given number of beats.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use pattrns::gates::MuteGate;
+    /// // mute the third beat in every 4/4 bar
+    /// let gate = MuteGate::new(vec![2.0..3.0], 4.0);
+    /// ```
+    pub fn new<L: Into<Option<f64>>>(ranges: Vec<Range<f64>>, length: L) -> Self {
+        let length = length.into().filter(|length| *length > 0.0);
+        let timer = PulseTimer::new();
+        Self {
+            ranges,
+            length,
+            timer,
+        }
+    }
+
+    /// Muted beat time ranges.
+    pub fn ranges(&self) -> &[Range<f64>] {
+        &self.ranges
+    }
+
+    /// Optional loop length of the ranges in beats.
+    pub fn length(&self) -> Option<f64> {
+        self.length
+    }
+}
+
+impl Gate for MuteGate {
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.timer.set_time_base(time_base);
+    }
+
+    fn set_trigger_event(&mut self, _event: &Event) {
+        // nothing to do
+    }
+
+    fn set_parameters(&mut self, _parameters: ParameterSet) {
+        // nothing to do
+    }
+
+    fn set_pulse_steps(&mut self, step_in_beats: f64, rhythm_length: usize) {
+        self.timer.set_pulse_steps(step_in_beats, rhythm_length);
+    }
+
+    fn set_tempo_map(&mut self, tempo_map: Option<&GateTempoMap>) {
+        self.timer.set_tempo_map(tempo_map);
+    }
+
+    fn run(&mut self, pulse: &RhythmEvent) -> bool {
+        let mut time = self.timer.advance(pulse).start;
+        if let Some(length) = self.length {
+            time %= length;
+            if length - time < PulseTimer::EPSILON {
+                time = 0.0;
+            }
+        }
+        pulse.value > 0.0
+            && !self.ranges.iter().any(|range| {
+                time >= range.start - PulseTimer::EPSILON && time < range.end - PulseTimer::EPSILON
+            })
+    }
+
+    fn duplicate(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.timer.reset();
+    }
+}
@@ -33,6 +33,7 @@ compile_error!(
 // -------------------------------------------------------------------------------------------------
 
 // Internal mods
+mod clock;
 mod emitter;
 mod event;
 mod gate;
@@ -48,28 +49,56 @@ mod time;
 
 // Re-Exported basic Traits and Types
 pub use crate::{
+    clock::{Clock, ClockState},
     emitter::{Emitter, EmitterEvent},
-    event::{Event, EventTransform, InstrumentId, NoteEvent, ParameterChangeEvent, ParameterId},
-    gate::Gate,
-    note::{chord::Chord, scale::Scale, Note},
-    parameter::{Parameter, ParameterSet, ParameterType},
-    pattern::{Pattern, PatternEvent},
+    event::{
+        Event, EventTransform, InstrumentId, NoteAttributes, NoteEvent, ParameterChangeEvent,
+        ParameterId,
+    },
+    gate::{Gate, GateTempoMap},
+    note::{
+        chord::Chord,
+        scale::Scale,
+        tuning::{KeyboardMapping, Tuning},
+        voicing::VoiceLeading,
+        Note,
+    },
+    parameter::{
+        automation::{LfoShape, ParameterAutomation},
+        preset::{ParameterPreset, ParameterSetPresets, ParameterValue},
+        Parameter, ParameterSet, ParameterType,
+    },
+    pattern::{dynamics::PulseDynamics, Pattern, PatternEvent},
     phrase::{PatternSlot, Phrase},
     pulse::Pulse,
-    rhythm::{Rhythm, RhythmEvent},
+    rhythm::{generative::RhythmProperty, transform::PulseTransform, Rhythm, RhythmEvent},
     sequence::Sequence,
     tidal::{
         Cycle, Event as CycleEvent, Span as CycleSpan, Target as CycleTarget, Value as CycleValue,
     },
     time::{
-        BeatTimeBase, BeatTimeStep, ExactSampleTime, SampleTime, SampleTimeBase, SampleTimeDisplay,
-        SecondTimeBase,
+        BeatTimeBase, BeatTimeStep, ExactSampleTime, MeterMap, SampleTime, SampleTimeBase,
+        SampleTimeDisplay, SecondTimeBase, TempoChange, TempoMap, TempoRamp, TimeSignature,
     },
 };
 
+/// Default [`Clock`] impls.
+pub mod clocks {
+    pub use super::clock::{internal::InternalClock, midi::MidiClock, simulated::SimulatedClock};
+}
+
 /// Default [`Rhythm`] impls.
 pub mod rhythms {
-    pub use super::rhythm::{empty::EmptyRhythm, fixed::FixedRhythm};
+    pub use super::rhythm::{
+        automaton::{CellularAutomatonGenerator, CellularAutomatonRhythm},
+        empty::EmptyRhythm,
+        fixed::FixedRhythm,
+        generative::{GenerativeRhythm, PulseGenerator},
+        lsystem::{LSystemGenerator, LSystemRhythm},
+        markov::{MarkovGenerator, MarkovRhythm},
+        random_walk::{RandomWalkGenerator, RandomWalkRhythm},
+        timeline::Timeline,
+    };
 
     #[cfg(feature = "scripting")]
     pub use crate::rhythm::scripted::ScriptedRhythm;
@@ -77,7 +106,15 @@ pub mod rhythms {
 
 /// Default [`Gate`] impls.
 pub mod gates {
-    pub use super::gate::{probability::ProbabilityGate, threshold::ThresholdGate};
+    pub use super::gate::{
+        bar::{BarGate, BarPosition},
+        cycle::CycleGate,
+        density::DensityGate,
+        logic::{AndGate, NotGate, OrGate},
+        mute::MuteGate,
+        probability::ProbabilityGate,
+        threshold::ThresholdGate,
+    };
 
     #[cfg(feature = "scripting")]
     pub use super::gate::scripted::ScriptedGate;
@@ -86,7 +123,12 @@ pub mod gates {
 /// Default [`Emitter`] impls.
 pub mod emitters {
     pub use super::emitter::{
-        cycle::CycleEmitter, empty::EmptyEmitter, fixed::FixedEmitter, mutated::MutatedEmitter,
+        arpeggio::{ArpeggioEmitter, ArpeggioMode},
+        cycle::CycleEmitter,
+        empty::EmptyEmitter,
+        fixed::FixedEmitter,
+        melody::MelodyEmitter,
+        mutated::MutatedEmitter,
     };
 
     #[cfg(feature = "scripting")]
@@ -10,6 +10,8 @@ use std::{
 
 pub mod chord;
 pub mod scale;
+pub mod tuning;
+pub mod voicing;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -1,9 +1,9 @@
 //! Musical chords as list of `Note` with intervals.
 
 use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
-use crate::note::Note;
+use crate::{note::Note, Scale};
 
 // --------------------------------------------------------------------------------------------------
 
@@ -177,6 +177,56 @@ lazy_static! {
     };
 }
 
+// canonical chord names, in the order they are preferred when naming or detecting chords
+const CANONICAL_CHORD_NAMES: [&str; 39] = [
+    "major",
+    "minor",
+    "diminished",
+    "augmented",
+    "sus2",
+    "sus4",
+    "five",
+    "major7",
+    "dom7",
+    "minor7",
+    "minor7b5",
+    "diminished7",
+    "minorMajor7",
+    "six",
+    "minor6",
+    "minor#5",
+    "7b5",
+    "7#5",
+    "minor7#5",
+    "7sus2",
+    "7sus4",
+    "add9",
+    "major9",
+    "nine",
+    "minor9",
+    "sixNine",
+    "minor69",
+    "7b9",
+    "minor7b9",
+    "9sus2",
+    "9sus4",
+    "add11",
+    "major11",
+    "eleven",
+    "minor11",
+    "add13",
+    "major13",
+    "thirteen",
+    "minor13",
+];
+
+// pitch class set of the given intervals as bit mask
+fn pitch_class_mask(intervals: impl IntoIterator<Item = u8>) -> u16 {
+    intervals
+        .into_iter()
+        .fold(0, |mask, interval| mask | (1 << (interval % 12)))
+}
+
 // --------------------------------------------------------------------------------------------------
 
 /// Note vector, created from a root [`Note`] and intervals.
@@ -226,17 +276,112 @@ impl Chord {
 
     /// Try converting the given string to a chord string in the form:
     /// `$note'$chord` where `$note` is a root key or note string and
-    /// `$mode` is one of `Chord::names()`
+    /// `$mode` is one of `Chord::names()` or a roman numeral chord symbol in the major key of
+    /// `$note`, see [`Scale::chord_from_numeral`].
     pub fn from_string(str: &str) -> Result<Self, String> {
         Self::try_from(str)
     }
 
     /// Try converting the given string to a note and mode string tuple.
-    /// mode must be one of `Chord::names()`
+    /// mode must be one of `Chord::names()` or a roman numeral chord symbol.
     pub fn from_mode_string<N: Into<Note>>((note, mode): (N, &str)) -> Result<Self, String> {
         Self::try_from((note, mode))
     }
 
+    /// Name the chord which is formed by the given notes: the inverse of [`Self::from_string`].
+    /// Notes may be given in any order, octave and inversion. Note offs and empty notes are
+    /// ignored. Returns `None` when the notes' pitch classes don't exactly form a known chord.
+    ///
+    /// Root positions, with the lowest note as root, are preferred over inversions, unless the
+    /// inversion forms a much more common chord. The detected chord's root note is placed at or
+    /// below the lowest note.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use pattrns::{Chord, Note};
+    /// let chord = Chord::detect(&[Note::E4, Note::G4, Note::C5]).unwrap();
+    /// assert_eq!(chord.to_string(), "C4'major");
+    /// ```
+    pub fn detect(notes: &[Note]) -> Option<Self> {
+        let mut notes = notes
+            .iter()
+            .copied()
+            .filter(|note| note.is_note_on())
+            .collect::<Vec<_>>();
+        notes.sort();
+        let bass = *notes.first()?;
+        let mask = pitch_class_mask(notes.iter().map(|note| note.key()));
+        // try bass note as root first, then all other notes for inversions. inversions are
+        // only preferred when they form a more common chord than the root position.
+        const INVERSION_PENALTY: usize = 8;
+        let mut roots = notes.iter().map(|note| note.key()).collect::<Vec<_>>();
+        let mut tried_roots = 0_u16;
+        roots.retain(|root| {
+            let retain = tried_roots & (1 << root) == 0;
+            tried_roots |= 1 << root;
+            retain
+        });
+        let mut best_match: Option<(usize, u8, &Vec<u8>)> = None;
+        for (root_index, root) in roots.into_iter().enumerate() {
+            let relative_mask = ((mask >> root) | (mask << (12 - root))) & 0xfff;
+            for (name_index, name) in CANONICAL_CHORD_NAMES.iter().enumerate() {
+                let intervals = &CHORD_TABLE[name];
+                let cost = name_index + if root_index > 0 { INVERSION_PENALTY } else { 0 };
+                if pitch_class_mask(intervals.iter().copied()) == relative_mask
+                    && best_match.is_none_or(|(best_cost, _, _)| cost < best_cost)
+                {
+                    best_match = Some((cost, root, intervals));
+                }
+            }
+        }
+        let (_, root, intervals) = best_match?;
+        let offset = (bass.key() + 12 - root) % 12;
+        let root_note = if bass as u8 >= offset {
+            bass as u8 - offset
+        } else {
+            bass as u8 + 12 - offset
+        };
+        Some(Self::new(root_note, intervals.clone()))
+    }
+
+    /// Name of the chord's intervals, one of [`Self::names`], if the intervals are known.
+    pub fn mode_name(&self) -> Option<&'static str> {
+        CANONICAL_CHORD_NAMES
+            .iter()
+            .copied()
+            .find(|name| CHORD_TABLE[name] == self.intervals)
+            .or_else(|| {
+                let mut names = CHORD_TABLE
+                    .iter()
+                    .filter(|(_, intervals)| **intervals == self.intervals)
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>();
+                names.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+                names.first().copied()
+            })
+    }
+
+    // Create a chord from a chord name or a roman numeral in the note's major key.
+    fn from_mode(note: Note, mode: &str) -> Result<Self, String> {
+        if let Some(intervals) = CHORD_TABLE.get(mode) {
+            Ok(Self::new(note, intervals.clone()))
+        } else if mode
+            .trim_start_matches(['b', '#'])
+            .starts_with(['I', 'V', 'i', 'v'])
+        {
+            Scale::try_from((note, "major"))
+                .and_then(|scale| scale.chord_from_numeral(mode))
+                .map_err(|err| format!("invalid roman numeral chord mode '{}': {}", mode, err))
+        } else {
+            Err(format!(
+                "invalid chord mode '{}', valid modes are roman numerals such as 'V7/V' or: {}",
+                mode,
+                Chord::names().join(",")
+            ))
+        }
+    }
+
     /// Root note.
     pub fn note(&self) -> Note {
         self.note
@@ -246,6 +391,25 @@ impl Chord {
     pub fn intervals(&self) -> &[u8] {
         &self.intervals
     }
+
+    /// Chord notes: the root note transposed by all intervals.
+    pub fn notes(&self) -> Vec<Note> {
+        self.intervals
+            .iter()
+            .map(|i| self.note.transposed(*i as i32))
+            .collect()
+    }
+}
+
+impl Display for Chord {
+    /// Chord string in the form `$note'$mode` when the chord's intervals are known, else the
+    /// root note and the raw intervals.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mode_name() {
+            Some(name) => write!(f, "{}'{}", self.note, name),
+            None => write!(f, "{}'{:?}", self.note, self.intervals),
+        }
+    }
 }
 
 impl TryFrom<&str> for Chord {
@@ -261,11 +425,7 @@ impl TryFrom<&str> for Chord {
                     );
                 }
                 let note = Note::try_from(note_part)?;
-                let intervals = CHORD_TABLE.get(chord_part).ok_or(format!(
-                    "invalid chord mode, valid modes are: {}",
-                    Chord::names().join(",")
-                ))?;
-                return Ok(Self::new(note, intervals.clone()));
+                return Self::from_mode(note, chord_part);
             }
         }
         Err("invalid chord string: \
@@ -281,11 +441,7 @@ where
     type Error = String;
 
     fn try_from((note, mode): (N, &str)) -> Result<Self, String> {
-        let intervals = CHORD_TABLE.get(mode).ok_or(format!(
-            "Invalid chord mode, valid chords are: {}",
-            Chord::names().join(",")
-        ))?;
-        Ok(Self::new(note, intervals.clone()))
+        Self::from_mode(note.into(), mode)
     }
 }
 
@@ -380,4 +536,66 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn chord_numerals() -> Result<(), String> {
+        assert_eq!(
+            Chord::try_from("c4'V7/V")?.notes(),
+            vec![Note::D4, Note::Fs4, Note::A4, Note::C5]
+        );
+        assert_eq!(
+            Chord::try_from((Note::A3, "ii65"))?.notes(),
+            vec![Note::D4, Note::Fs4, Note::A4, Note::B4]
+        );
+        assert!(Chord::try_from("c4'VIII").is_err());
+        // numeral errors are passed through, other modes list the valid chord names
+        assert!(Chord::try_from("c4'VIII")
+            .unwrap_err()
+            .starts_with("invalid roman numeral chord mode 'VIII'"));
+        assert!(Chord::try_from("c4'foo")
+            .unwrap_err()
+            .starts_with("invalid chord mode 'foo'"));
+        Ok(())
+    }
+
+    #[test]
+    fn chord_detect() -> Result<(), String> {
+        assert_eq!(Chord::detect(&[]), None);
+        assert_eq!(Chord::detect(&[Note::C4, Note::Cs4, Note::D4]), None);
+
+        // root position
+        assert_eq!(
+            Chord::detect(&[Note::G4, Note::E4, Note::C4]),
+            Some(Chord::from_string("c4'maj")?)
+        );
+        assert_eq!(
+            Chord::detect(&[Note::A3, Note::C4, Note::E4, Note::G4]),
+            Some(Chord::from_string("a3'm7")?)
+        );
+        assert_eq!(
+            Chord::detect(&[Note::C4, Note::E4, Note::G4, Note::A4]),
+            Some(Chord::from_string("c4'six")?)
+        );
+        // inversions and octave doublings
+        assert_eq!(
+            Chord::detect(&[Note::D4, Note::F4, Note::B4, Note::G5]),
+            Some(Chord::from_string("g3'7")?)
+        );
+        assert_eq!(
+            Chord::detect(&[Note::Ds4, Note::C5, Note::Gs4, Note::C4, Note::OFF]),
+            Some(Chord::from_string("g#3'maj")?)
+        );
+        // extended chords
+        assert_eq!(
+            Chord::detect(&[Note::C4, Note::E4, Note::G4, Note::As4, Note::D5]),
+            Some(Chord::from_string("c4'9")?)
+        );
+
+        // naming round trip
+        for name in Chord::unique_names() {
+            let chord = Chord::from_mode_string((Note::D4, name.as_str()))?;
+            assert_eq!(Chord::from_string(&chord.to_string())?, chord);
+        }
+        Ok(())
+    }
 }
@@ -1,6 +1,12 @@
 //! Musical scales based on `Note` and custom intervals or common scale names.
 
-use crate::Note;
+use crate::{Chord, Note};
+
+use self::numeral::RomanNumeral;
+
+// -------------------------------------------------------------------------------------------------
+
+mod numeral;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -378,6 +384,74 @@ impl Scale {
         SCALE_MODES.iter().map(|mode| mode.name).collect()
     }
 
+    /// Find the most likely scales for the given notes, ranked against all known modes in all
+    /// keys. Returns scales with their scores in range `[0..=1]`, best matches first, or an
+    /// empty list when no notes are given. Note offs and empty notes are ignored.
+    ///
+    /// Scales score higher the more of the given notes they contain and the more of their own
+    /// steps are used by the notes. Equally scored scales prefer the lowest note as key, then
+    /// the order of [`Self::mode_names`].
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use pattrns::{Note, Scale};
+    /// let notes = [Note::A3, Note::C4, Note::D4, Note::E4, Note::G4];
+    /// let (scale, _score) = &Scale::detect(&notes)[0];
+    /// assert_eq!((scale.key(), scale.mode_name()), (9, "pentatonic minor"));
+    /// ```
+    pub fn detect(notes: &[Note]) -> Vec<(Self, f32)> {
+        let notes = notes
+            .iter()
+            .filter(|note| note.is_note_on())
+            .collect::<Vec<_>>();
+        let Some(lowest_note) = notes.iter().min().copied() else {
+            return vec![];
+        };
+        // pitch class histogram
+        let mut weights = [0.0_f32; 12];
+        for note in &notes {
+            weights[note.key() as usize] += 1.0;
+        }
+        let total_weight = notes.len() as f32;
+        // rate all modes in all keys, starting with the lowest note as key
+        let mut scales = Vec::with_capacity(12 * SCALE_MODES.len());
+        for key_offset in 0..12 {
+            let key = (lowest_note.key() + key_offset) % 12;
+            for mode in &SCALE_MODES {
+                let steps = mode.steps();
+                let mut matched_weight = 0.0;
+                let mut matched_keys = 0;
+                for step in &steps {
+                    let weight = weights[(key as usize + step) % 12];
+                    if weight > 0.0 {
+                        matched_weight += weight;
+                        matched_keys += 1;
+                    }
+                }
+                let coverage = matched_weight / total_weight;
+                let usage = matched_keys as f32 / steps.len() as f32;
+                let score = coverage * usage;
+                let octave = if key >= lowest_note.key() {
+                    lowest_note.octave()
+                } else {
+                    lowest_note.octave() + 1
+                };
+                scales.push((
+                    Self {
+                        key,
+                        octave,
+                        mode: mode.clone(),
+                    },
+                    score,
+                ));
+            }
+        }
+        // stable sort: keeps key and mode order for equal scores
+        scales.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        scales
+    }
+
     #[allow(dead_code)] // used in tests only
     fn new(note: Note, mode: Mode) -> Self {
         let key = note.key();
@@ -413,11 +487,56 @@ impl Scale {
             .collect()
     }
 
+    /// Create a voiced chord from a roman numeral chord symbol in the scale's key, such as `I`,
+    /// `vi`, `ii7`, `V65`, `bVII`, `V7/V` or `I/E`. The scale must have 7 notes.
+    ///
+    /// Uppercase numerals are major, lowercase numerals minor chords. `o` or `°` marks diminished,
+    /// `ø` half-diminished and `+` augmented chords. `7`, `9`, `11` and `13` add the scale's
+    /// diatonic seventh and extensions, `maj7` or `M7` a major seventh. Inversions use figured
+    /// bass notation: `6` and `64` for triads, `65`, `43` and `42` for seventh chords. `sus2`
+    /// and `sus4` replace the third. Accidentals `b` and `#` before the numeral alter the root,
+    /// e.g. for borrowed chords. Secondary chords are appended with `/` and the tonicized
+    /// numeral, e.g. `V7/V`, slash chords with `/` and a bass note name, e.g. `I/E`.
+    ///
+    /// The chord's root is placed in the octave above the scale's root note. The returned
+    /// chord's note is the lowest note of the voiced chord.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// use pattrns::{Note, Scale};
+    /// let scale = Scale::try_from((Note::C4, "major")).unwrap();
+    /// let chord = scale.chord_from_numeral("V7/V").unwrap();
+    /// assert_eq!(chord.notes(), vec![Note::D4, Note::Fs4, Note::A4, Note::C5]);
+    /// ```
+    pub fn chord_from_numeral(&self, numeral: &str) -> Result<Chord, String> {
+        RomanNumeral::parse(numeral)?.chord(self)
+    }
+
+    /// Create voiced chords from a whitespace separated list of roman numeral chord symbols,
+    /// e.g. `"I vi ii7 V7/V"`. See [`Self::chord_from_numeral`] for the numeral syntax.
+    pub fn progression(&self, progression: &str) -> Result<Vec<Chord>, String> {
+        progression
+            .split_whitespace()
+            .map(|numeral| self.chord_from_numeral(numeral))
+            .collect()
+    }
+
     /// Key note as number [0..12].
     pub fn key(&self) -> u8 {
         self.key
     }
 
+    /// Root note of the scale: the key note in the scale's octave.
+    pub fn root_note(&self) -> Note {
+        Note::from((self.key as usize + 12 * self.octave as usize).min(0x7f) as u8)
+    }
+
+    /// Name of the scale's mode, one of [`Self::mode_names`] or "custom scale".
+    pub fn mode_name(&self) -> &'static str {
+        self.mode.name
+    }
+
     /// List of raw degrees where 0 indicates no step.
     pub fn degrees(&self) -> Vec<usize> {
         self.mode.degrees.to_vec()
@@ -674,4 +793,133 @@ mod test {
         assert!(gmaj7 == vec![Note::G4, Note::B4, Note::D5, Note::F5]);
         Ok(())
     }
+
+    #[test]
+    fn progression() -> Result<(), String> {
+        let notes = |chord: Chord| chord.notes();
+        let c_major = Scale::new(Note::C4, Mode::try_from("major")?);
+        assert_eq!(
+            c_major
+                .progression("I vi ii7 V7/V")?
+                .into_iter()
+                .map(notes)
+                .collect::<Vec<_>>(),
+            vec![
+                vec![Note::C4, Note::E4, Note::G4],
+                vec![Note::A4, Note::C5, Note::E5],
+                vec![Note::D4, Note::F4, Note::A4, Note::C5],
+                vec![Note::D4, Note::Fs4, Note::A4, Note::C5],
+            ]
+        );
+        // qualities, sevenths and extensions
+        let chord = |numeral: &str| c_major.chord_from_numeral(numeral).map(notes);
+        assert_eq!(chord("IV7")?, vec![Note::F4, Note::A4, Note::C5, Note::E5]);
+        assert_eq!(
+            chord("viio7")?,
+            vec![Note::B4, Note::D5, Note::F5, Note::Gs5]
+        );
+        assert_eq!(chord("viiø")?, vec![Note::B4, Note::D5, Note::F5, Note::A5]);
+        assert_eq!(chord("III+")?, vec![Note::E4, Note::Gs4, Note::C5]);
+        assert_eq!(chord("Imaj9")?, chord("IM9")?);
+        assert_eq!(
+            chord("V9")?,
+            vec![Note::G4, Note::B4, Note::D5, Note::F5, Note::A5]
+        );
+        assert_eq!(
+            chord("V7sus4")?,
+            vec![Note::G4, Note::C5, Note::D5, Note::F5]
+        );
+        // borrowed chords
+        assert_eq!(chord("bVII")?, vec![Note::As4, Note::D5, Note::F5]);
+        assert_eq!(chord("iv")?, vec![Note::F4, Note::Gs4, Note::C5]);
+        // inversions and slash chords
+        assert_eq!(chord("I6")?, vec![Note::E4, Note::G4, Note::C5]);
+        assert_eq!(chord("I64")?, vec![Note::G4, Note::C5, Note::E5]);
+        assert_eq!(chord("V65")?, vec![Note::B4, Note::D5, Note::F5, Note::G5]);
+        assert_eq!(chord("V42")?, vec![Note::F5, Note::G5, Note::B5, Note::D6]);
+        assert_eq!(chord("I/E")?, chord("I6")?);
+        assert_eq!(
+            chord("I/bb")?,
+            vec![Note::As3, Note::C4, Note::E4, Note::G4]
+        );
+        assert_eq!(
+            chord("V7/V/V")?,
+            vec![Note::A4, Note::Cs5, Note::E5, Note::G5]
+        );
+        // minor keys
+        let c_minor = Scale::new(Note::C4, Mode::try_from("minor")?);
+        assert_eq!(
+            c_minor
+                .progression("i iv V7 III")?
+                .into_iter()
+                .map(notes)
+                .collect::<Vec<_>>(),
+            vec![
+                vec![Note::C4, Note::Ds4, Note::G4],
+                vec![Note::F4, Note::Gs4, Note::C5],
+                vec![Note::G4, Note::B4, Note::D5, Note::F5],
+                vec![Note::Ds4, Note::G4, Note::As4],
+            ]
+        );
+        // errors
+        assert!(chord("").is_err());
+        assert!(chord("VIII").is_err());
+        assert!(chord("Vi").is_err());
+        assert!(chord("V8").is_err());
+        assert!(chord("V7x").is_err());
+        assert!(chord("I/x").is_err());
+        assert!(chord("I/E/V").is_err());
+        assert!(chord("V643").is_err());
+        assert!(Scale::new(Note::C4, Mode::try_from("pentatonic major")?)
+            .chord_from_numeral("I")
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn detect() {
+        assert!(Scale::detect(&[]).is_empty());
+        assert!(Scale::detect(&[Note::OFF, Note::EMPTY]).is_empty());
+
+        let detect = |notes: &[Note]| {
+            let (scale, score) = Scale::detect(notes)[0].clone();
+            (scale.root_note(), scale.mode_name(), score)
+        };
+        // full scales
+        let c_major = Scale::new(Note::C4, Mode::try_from("major").unwrap()).notes();
+        assert_eq!(detect(&c_major), (Note::C4, "natural major", 1.0));
+        let a_minor = [
+            Note::A3,
+            Note::B3,
+            Note::C4,
+            Note::D4,
+            Note::E4,
+            Note::F4,
+            Note::G4,
+        ];
+        assert_eq!(detect(&a_minor), (Note::A3, "natural minor", 1.0));
+        let d_dorian = Scale::new(Note::D4, Mode::try_from("dorian").unwrap()).notes();
+        assert_eq!(detect(&d_dorian), (Note::D4, "dorian", 1.0));
+        // repeated and out of scale notes
+        let (note, mode, score) = detect(&[
+            Note::E4,
+            Note::Fs4,
+            Note::Gs4,
+            Note::A4,
+            Note::B4,
+            Note::Cs5,
+            Note::Ds5,
+            Note::E5,
+            Note::Gs4,
+            Note::B4,
+            Note::E4,
+            Note::C5,
+        ]);
+        assert_eq!((note, mode), (Note::E4, "natural major"));
+        assert!(score < 1.0);
+        // ranking
+        let scales = Scale::detect(&[Note::C4, Note::E4, Note::G4]);
+        assert_eq!(scales.len(), 12 * SCALE_MODES.len());
+        assert!(scales.windows(2).all(|s| s[0].1 >= s[1].1));
+    }
 }
@@ -0,0 +1,308 @@
+//! Roman numeral chord symbols, as used in functional harmony progressions.
+
+use crate::{Chord, Note};
+
+use super::Scale;
+
+// -------------------------------------------------------------------------------------------------
+
+// scale steps of keys which are used for secondary chords
+const MAJOR_STEPS: [usize; 7] = [0, 2, 4, 5, 7, 9, 11];
+const MINOR_STEPS: [usize; 7] = [0, 2, 3, 5, 7, 8, 10];
+
+// roman numerals, longest first, to match them greedily
+const NUMERALS: [(&str, usize); 7] = [
+    ("vii", 7),
+    ("vi", 6),
+    ("v", 5),
+    ("iv", 4),
+    ("iii", 3),
+    ("ii", 2),
+    ("i", 1),
+];
+
+// -------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Quality {
+    Major,
+    Minor,
+    Diminished,
+    HalfDiminished,
+    Augmented,
+}
+
+/// A scale degree with optional accidentals, e.g. `bVII`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Degree {
+    degree: usize,
+    alteration: i32,
+    lowercase: bool,
+}
+
+impl Degree {
+    // Parse a degree from the start of the given string and return the remaining string.
+    fn parse(str: &str) -> Option<(Self, &str)> {
+        let mut alteration = 0;
+        let mut rest = str;
+        loop {
+            if let Some(r) = rest.strip_prefix('b') {
+                alteration -= 1;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix('#') {
+                alteration += 1;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        for (numeral, degree) in NUMERALS {
+            if let Some(prefix) = rest.get(..numeral.len()) {
+                let lowercase = prefix == numeral;
+                if lowercase || prefix == numeral.to_ascii_uppercase() {
+                    let degree = Self {
+                        degree,
+                        alteration,
+                        lowercase,
+                    };
+                    return Some((degree, &rest[numeral.len()..]));
+                }
+            }
+        }
+        None
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A parsed roman numeral chord symbol such as `V7/V`, `bVII`, `ii65` or `I/E`.
+///
+/// Uppercase numerals are major, lowercase numerals minor chords. `o` or `°` marks diminished,
+/// `ø` half-diminished and `+` augmented chords. `7`, `9`, `11` and `13` add diatonic sevenths and
+/// extensions, `maj7` or `M7` a major seventh. Inversions use figured bass notation: `6` and
+/// `64` for triads, `65`, `43` and `42` for seventh chords. `sus2` and `sus4` replace the third.
+/// Secondary chords are appended with `/`, followed by the tonicized numeral, e.g. `V7/V`, and
+/// slash chords with a bass note name, e.g. `I/E`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct RomanNumeral {
+    degree: Degree,
+    quality: Quality,
+    extension: Option<u8>,
+    major_seventh: bool,
+    suspension: Option<u8>,
+    inversion: usize,
+    secondary: Vec<Degree>,
+    bass: Option<u8>,
+}
+
+impl RomanNumeral {
+    /// Parse a roman numeral chord symbol.
+    pub fn parse(symbol: &str) -> Result<Self, String> {
+        let error = |message: &str| format!("invalid roman numeral '{}': {}", symbol, message);
+        let mut parts = symbol.split('/');
+        let (degree, mut rest) = Degree::parse(parts.next().unwrap_or_default().trim())
+            .ok_or_else(|| error("expecting a roman numeral in range I-VII or i-vii"))?;
+        // quality
+        let mut quality = if degree.lowercase {
+            Quality::Minor
+        } else {
+            Quality::Major
+        };
+        for (mark, mark_quality) in [
+            ("o", Quality::Diminished),
+            ("°", Quality::Diminished),
+            ("ø", Quality::HalfDiminished),
+            ("+", Quality::Augmented),
+        ] {
+            if let Some(r) = rest.strip_prefix(mark) {
+                quality = mark_quality;
+                rest = r;
+                break;
+            }
+        }
+        // sevenths, extensions and inversions
+        let mut major_seventh = false;
+        for mark in ["maj", "M"] {
+            if let Some(r) = rest.strip_prefix(mark) {
+                major_seventh = true;
+                rest = r;
+                break;
+            }
+        }
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (extension, inversion) = match &rest[..digits] {
+            "" => (None, 0),
+            "7" => (Some(7), 0),
+            "9" => (Some(9), 0),
+            "11" => (Some(11), 0),
+            "13" => (Some(13), 0),
+            "6" if !major_seventh => (None, 1),
+            "64" if !major_seventh => (None, 2),
+            "65" if !major_seventh => (Some(7), 1),
+            "43" if !major_seventh => (Some(7), 2),
+            "42" | "2" if !major_seventh => (Some(7), 3),
+            _ => return Err(error("invalid extension or inversion")),
+        };
+        if major_seventh && extension.is_none() {
+            return Err(error("expecting a 7, 9, 11 or 13 after 'maj'"));
+        }
+        rest = &rest[digits..];
+        // half-diminished chords always are seventh chords
+        let extension = match (quality, extension) {
+            (Quality::HalfDiminished, None) => Some(7),
+            _ => extension,
+        };
+        // suspensions
+        let suspension = match rest {
+            "" => None,
+            "sus2" => Some(2),
+            "sus4" | "sus" => Some(4),
+            _ => return Err(error(&format!("unexpected suffix '{}'", rest))),
+        };
+        // secondary chords and slash chord bass note
+        let mut secondary = Vec::new();
+        let mut bass = None;
+        for part in parts {
+            if bass.is_some() {
+                return Err(error("the bass note must be the last part"));
+            }
+            match Degree::parse(part) {
+                Some((degree, "")) => secondary.push(degree),
+                _ => {
+                    let note = Note::try_from(part).map_err(|_| {
+                        error(&format!(
+                            "expecting a roman numeral or bass note name after '/', got '{}'",
+                            part
+                        ))
+                    })?;
+                    bass = Some(note.key());
+                }
+            }
+        }
+        Ok(Self {
+            degree,
+            quality,
+            extension,
+            major_seventh,
+            suspension,
+            inversion,
+            secondary,
+            bass,
+        })
+    }
+
+    /// Resolve the numeral to a voiced chord in the given scale's key. The chord's root is placed
+    /// within the octave above the scale's root note, and the chord's note is its lowest note.
+    pub fn chord(&self, scale: &Scale) -> Result<Chord, String> {
+        let steps = scale.steps();
+        if steps.len() != 7 {
+            return Err(format!(
+                "roman numerals need a scale with 7 notes, but '{}' has {} notes",
+                scale.mode_name(),
+                steps.len()
+            ));
+        }
+        // tonicize secondary degrees, from right to left
+        let mut key = 0_i32;
+        let mut key_steps = [0; 7];
+        key_steps.copy_from_slice(&steps);
+        for degree in self.secondary.iter().rev() {
+            key += key_steps[degree.degree - 1] as i32 + degree.alteration;
+            key_steps = if degree.lowercase {
+                MINOR_STEPS
+            } else {
+                MAJOR_STEPS
+            };
+        }
+        let root = key + key_steps[self.degree.degree - 1] as i32 + self.degree.alteration;
+        // interval from the root to the given scale degree above the root
+        let diatonic = self.degree.alteration == 0;
+        let scale_interval = |offset: usize, default: i32| -> i32 {
+            if diatonic {
+                let index = self.degree.degree - 1 + offset;
+                let step = key_steps[index % 7] as i32 + 12 * (index / 7) as i32;
+                step - key_steps[self.degree.degree - 1] as i32
+            } else {
+                default
+            }
+        };
+        // stack intervals
+        let third = match (self.suspension, self.quality) {
+            (Some(2), _) => 2,
+            (Some(_), _) => 5,
+            (None, Quality::Major | Quality::Augmented) => 4,
+            (None, _) => 3,
+        };
+        let fifth = match self.quality {
+            Quality::Diminished | Quality::HalfDiminished => 6,
+            Quality::Augmented => 8,
+            _ => 7,
+        };
+        let mut intervals = vec![0, third, fifth];
+        if let Some(extension) = self.extension {
+            let seventh = if self.major_seventh {
+                11
+            } else {
+                match self.quality {
+                    Quality::Diminished => 9,
+                    Quality::HalfDiminished => 10,
+                    _ => scale_interval(6, 10),
+                }
+            };
+            intervals.push(seventh);
+            if extension >= 9 {
+                intervals.push(scale_interval(8, 14));
+            }
+            if extension >= 11 {
+                intervals.push(scale_interval(10, 17));
+            }
+            if extension >= 13 {
+                intervals.push(scale_interval(12, 21));
+            }
+        }
+        // invert
+        if self.inversion >= intervals.len() {
+            return Err(format!(
+                "inversion {} is not possible for a chord with {} notes",
+                self.inversion,
+                intervals.len()
+            ));
+        }
+        intervals.rotate_left(self.inversion);
+        stack_ascending(&mut intervals);
+        // add slash chord bass note or invert to it
+        let root_note = scale.root_note() as i32 + root.rem_euclid(12);
+        if let Some(bass) = self.bass.map(i32::from) {
+            if let Some(index) = intervals
+                .iter()
+                .position(|i| (root_note + i).rem_euclid(12) == bass)
+            {
+                intervals.rotate_left(index);
+                stack_ascending(&mut intervals);
+            } else {
+                let lowest = root_note + intervals[0];
+                intervals.insert(0, intervals[0] - (lowest - bass).rem_euclid(12));
+            }
+        }
+        // convert to a chord with the lowest note as root
+        let lowest = root_note + intervals[0];
+        if !(0..=0x7f).contains(&lowest)
+            || !(0..=0x7f).contains(&(root_note + intervals.iter().max().unwrap_or(&0)))
+        {
+            return Err("chord notes are out of the valid note range".to_string());
+        }
+        Ok(Chord::new(
+            Note::from(lowest as u8),
+            intervals.iter().map(|i| (i - intervals[0]) as u8).collect(),
+        ))
+    }
+}
+
+// Move intervals up by octaves until they are in ascending order.
+fn stack_ascending(intervals: &mut [i32]) {
+    for index in 1..intervals.len() {
+        while intervals[index] <= intervals[index - 1] {
+            intervals[index] += 12;
+        }
+    }
+}
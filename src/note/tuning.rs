@@ -0,0 +1,425 @@
+//! Microtonal tunings and keyboard mappings, as used by Scala `.scl` and `.kbm` files.
+
+use std::{path::Path, rc::Rc};
+
+use crate::{Event, EventTransform, Note, NoteEvent};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Maps note numbers to degrees of a [`Tuning`] and defines the tuning's reference pitch.
+///
+/// Note numbers are the raw [`Note`] values, which are interpreted as MIDI note numbers:
+/// note 69 is the 440 Hz A.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyboardMapping {
+    first_note: u8,
+    last_note: u8,
+    middle_note: u8,
+    reference_note: u8,
+    reference_frequency: f64,
+    octave_degree: Option<usize>,
+    degrees: Vec<Option<usize>>,
+}
+
+impl Default for KeyboardMapping {
+    /// Linear mapping, where note 60 is the first scale degree with its 12-TET frequency.
+    fn default() -> Self {
+        Self::linear(60, 60, frequency_from_note(60.0))
+    }
+}
+
+impl KeyboardMapping {
+    /// Create a linear mapping, where every note is mapped to the next scale degree.
+    /// The first scale degree is mapped to `middle_note`, and `reference_note` is tuned to
+    /// `reference_frequency`.
+    pub fn linear(middle_note: u8, reference_note: u8, reference_frequency: f64) -> Self {
+        Self {
+            first_note: 0,
+            last_note: 127,
+            middle_note,
+            reference_note,
+            reference_frequency,
+            octave_degree: None,
+            degrees: Vec::new(),
+        }
+    }
+
+    /// Parse a keyboard mapping from the contents of a Scala `.kbm` file.
+    pub fn from_kbm(content: &str) -> Result<Self, String> {
+        let values = content
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('!'))
+            .filter_map(|line| line.split_whitespace().next())
+            .collect::<Vec<_>>();
+        let value = |index: usize, name: &str| -> Result<&str, String> {
+            values
+                .get(index)
+                .copied()
+                .ok_or_else(|| format!("missing {name} in keyboard mapping"))
+        };
+        fn parse<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, String> {
+            value
+                .parse::<T>()
+                .map_err(|_| format!("invalid {name} '{value}' in keyboard mapping"))
+        }
+        let size = parse::<usize>(value(0, "map size")?, "map size")?;
+        let first_note = parse::<u8>(value(1, "first note")?, "first note")?;
+        let last_note = parse::<u8>(value(2, "last note")?, "last note")?;
+        let middle_note = parse::<u8>(value(3, "middle note")?, "middle note")?;
+        let reference_note = parse::<u8>(value(4, "reference note")?, "reference note")?;
+        let reference_frequency =
+            parse::<f64>(value(5, "reference frequency")?, "reference frequency")?;
+        let octave_degree = parse::<usize>(value(6, "octave degree")?, "octave degree")?;
+        let mut degrees = Vec::with_capacity(size);
+        for index in 0..size {
+            // trailing unmapped keys may be omitted
+            match values.get(7 + index) {
+                Some(&"x") | None => degrees.push(None),
+                Some(value) => degrees.push(Some(parse::<usize>(value, "mapping")?)),
+            }
+        }
+        if first_note > 127 || last_note > 127 || first_note > last_note {
+            return Err(format!(
+                "invalid note range {first_note}..{last_note} in keyboard mapping"
+            ));
+        }
+        if middle_note > 127 || reference_note > 127 {
+            return Err("middle and reference notes must be in range [0 - 127]".to_string());
+        }
+        if reference_frequency <= 0.0 {
+            return Err("reference frequency must be > 0".to_string());
+        }
+        let mapping = Self {
+            first_note,
+            last_note,
+            middle_note,
+            reference_note,
+            reference_frequency,
+            octave_degree: (octave_degree > 0).then_some(octave_degree),
+            degrees,
+        };
+        if mapping.degree(reference_note, 0).is_none() {
+            return Err("reference note must be mapped to a scale degree".to_string());
+        }
+        Ok(mapping)
+    }
+
+    /// Read a keyboard mapping from the given Scala `.kbm` file.
+    pub fn from_kbm_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|err| format!("failed to read file '{}': {err}", path.as_ref().display()))?;
+        Self::from_kbm(&content)
+    }
+
+    /// Tuned note range.
+    pub fn note_range(&self) -> (u8, u8) {
+        (self.first_note, self.last_note)
+    }
+
+    /// Note which is tuned to the reference frequency.
+    pub fn reference_note(&self) -> u8 {
+        self.reference_note
+    }
+
+    /// Frequency of the reference note in Hz.
+    pub fn reference_frequency(&self) -> f64 {
+        self.reference_frequency
+    }
+
+    // Absolute scale degree of the given note, relative to the middle note, or None when the
+    // note is not mapped. `tuning_size` is the default octave degree.
+    fn degree(&self, note: u8, tuning_size: usize) -> Option<i64> {
+        let offset = note as i64 - self.middle_note as i64;
+        if self.degrees.is_empty() {
+            Some(offset)
+        } else {
+            let size = self.degrees.len() as i64;
+            let octave_degree = self.octave_degree.unwrap_or(tuning_size) as i64;
+            let degree = self.degrees[offset.rem_euclid(size) as usize]?;
+            Some(offset.div_euclid(size) * octave_degree + degree as i64)
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A periodic microtonal tuning with a keyboard mapping, e.g. N-EDO or just intonation scales.
+///
+/// Tunings are defined as in Scala `.scl` files: a list of pitches in cents above the tuning's
+/// root, where the last pitch is the tuning's period (usually the octave).
+///
+/// Apply tunings to note events via [`Tuning::retune`] or as [`EventTransform`] via
+/// [`Tuning::event_transform`]. Tuned note events keep their nearest 12-TET note and store the
+/// remaining offset in [`NoteEvent::cents`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tuning {
+    description: String,
+    pitches: Vec<f64>,
+    mapping: KeyboardMapping,
+}
+
+impl Default for Tuning {
+    /// 12-tone equal temperament.
+    fn default() -> Self {
+        Self::edo(12).expect("Failed to create 12-EDO tuning")
+    }
+}
+
+impl Tuning {
+    /// Create a new tuning from the given pitches in cents. The last pitch is the period.
+    pub fn from_cents(description: &str, pitches: Vec<f64>) -> Result<Self, String> {
+        if pitches.is_empty() {
+            return Err("tuning needs at least one pitch".to_string());
+        }
+        if pitches.iter().any(|pitch| !pitch.is_finite()) {
+            return Err("tuning pitches must be finite".to_string());
+        }
+        if *pitches.last().unwrap() <= 0.0 {
+            return Err("tuning period must be > 0 cents".to_string());
+        }
+        let description = description.to_string();
+        let mapping = KeyboardMapping::default();
+        Ok(Self {
+            description,
+            pitches,
+            mapping,
+        })
+    }
+
+    /// Create an equal division of the octave tuning with the given number of divisions,
+    /// e.g. `Tuning::edo(19)` for 19-EDO.
+    pub fn edo(divisions: usize) -> Result<Self, String> {
+        if divisions == 0 {
+            return Err("EDO divisions must be > 0".to_string());
+        }
+        let pitches = (1..=divisions)
+            .map(|step| step as f64 * 1200.0 / divisions as f64)
+            .collect();
+        Self::from_cents(&format!("{divisions}-EDO"), pitches)
+    }
+
+    /// Parse a tuning from the contents of a Scala `.scl` file.
+    pub fn from_scl(content: &str) -> Result<Self, String> {
+        let mut lines = content.lines().filter(|line| !line.starts_with('!'));
+        let description = lines
+            .next()
+            .ok_or("missing description in scale file")?
+            .trim()
+            .to_string();
+        let count = lines
+            .next()
+            .and_then(|line| line.split_whitespace().next())
+            .ok_or("missing pitch count in scale file")?;
+        let count = count
+            .parse::<usize>()
+            .map_err(|_| format!("invalid pitch count '{count}' in scale file"))?;
+        let mut pitches = Vec::with_capacity(count);
+        for _ in 0..count {
+            let pitch = lines
+                .next()
+                .and_then(|line| line.split_whitespace().next())
+                .ok_or_else(|| format!("expected {count} pitches in scale file"))?;
+            pitches.push(parse_scl_pitch(pitch)?);
+        }
+        Self::from_cents(&description, pitches)
+    }
+
+    /// Read a tuning from the given Scala `.scl` file.
+    pub fn from_scl_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|err| format!("failed to read file '{}': {err}", path.as_ref().display()))?;
+        Self::from_scl(&content)
+    }
+
+    /// Return a copy of the tuning with the given keyboard mapping.
+    #[must_use]
+    pub fn with_keyboard_mapping(self, mapping: KeyboardMapping) -> Self {
+        Self { mapping, ..self }
+    }
+
+    /// Tuning description, as given in the scale file.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Pitches in cents above the root. The last pitch is the tuning's period.
+    pub fn pitches(&self) -> &[f64] {
+        &self.pitches
+    }
+
+    /// The tuning's keyboard mapping.
+    pub fn keyboard_mapping(&self) -> &KeyboardMapping {
+        &self.mapping
+    }
+
+    /// Frequency in Hz of the given note, or `None` when the note is not mapped.
+    pub fn frequency<N: Into<u8>>(&self, note: N) -> Option<f64> {
+        let note = note.into();
+        let (first_note, last_note) = self.mapping.note_range();
+        if note < first_note || note > last_note {
+            return None;
+        }
+        let size = self.pitches.len();
+        let cents = self.cents(self.mapping.degree(note, size)?);
+        let reference_cents = self.cents(self.mapping.degree(self.mapping.reference_note, size)?);
+        Some(self.mapping.reference_frequency * ((cents - reference_cents) / 1200.0).exp2())
+    }
+
+    /// Retune the given note event: sets the nearest 12-TET note and the remaining offset in
+    /// cents. Returns false when the note is not mapped by the tuning, true otherwise.
+    /// Note-offs and other non-note-on events are left untouched.
+    pub fn retune(&self, note_event: &mut NoteEvent) -> bool {
+        if !note_event.note.is_note_on() {
+            return true;
+        }
+        match self.frequency(note_event.note) {
+            Some(frequency) => {
+                let pitch = note_from_frequency(frequency) + note_event.cents as f64 / 100.0;
+                let note = pitch.round().clamp(0.0, 127.0);
+                note_event.note = Note::from(note as u8);
+                note_event.cents = ((pitch - note) * 100.0) as f32;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Retune all note events in the given event. Unmapped notes are removed from the event.
+    pub fn retune_event(&self, event: &mut Event) {
+        if let Event::NoteEvents(note_events) = event {
+            for note_event in note_events.iter_mut() {
+                if let Some(note) = note_event {
+                    if !self.retune(note) {
+                        *note_event = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Create an [`EventTransform`] which retunes events with this tuning.
+    /// Use e.g. with [`BeatTimePattern::with_event_transform`](crate::prelude::BeatTimePattern).
+    pub fn event_transform(self) -> EventTransform {
+        Rc::new(move |event| self.retune_event(event))
+    }
+
+    // Pitch in cents of the given absolute scale degree, relative to the tuning's root.
+    fn cents(&self, degree: i64) -> f64 {
+        let size = self.pitches.len() as i64;
+        let period = *self.pitches.last().unwrap();
+        let (octave, degree) = (degree.div_euclid(size), degree.rem_euclid(size) as usize);
+        let pitch = if degree == 0 {
+            0.0
+        } else {
+            self.pitches[degree - 1]
+        };
+        octave as f64 * period + pitch
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// 12-TET frequency in Hz of the given fractional MIDI note number.
+pub(crate) fn frequency_from_note(note: f64) -> f64 {
+    440.0 * ((note - 69.0) / 12.0).exp2()
+}
+
+/// Fractional MIDI note number of the given frequency in Hz.
+pub(crate) fn note_from_frequency(frequency: f64) -> f64 {
+    69.0 + 12.0 * (frequency / 440.0).log2()
+}
+
+// Parse a single pitch line of a Scala scale file: cents values contain a period,
+// everything else is a ratio or integer.
+fn parse_scl_pitch(pitch: &str) -> Result<f64, String> {
+    let invalid = || format!("invalid pitch '{pitch}' in scale file");
+    if pitch.contains('.') {
+        pitch.parse::<f64>().map_err(|_| invalid())
+    } else {
+        let (numerator, denominator) = pitch.split_once('/').unwrap_or((pitch, "1"));
+        let numerator = numerator.parse::<u64>().map_err(|_| invalid())?;
+        let denominator = denominator.parse::<u64>().map_err(|_| invalid())?;
+        if numerator == 0 || denominator == 0 {
+            return Err(invalid());
+        }
+        Ok(1200.0 * (numerator as f64 / denominator as f64).log2())
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_frequency(tuning: &Tuning, note: u8, frequency: f64) {
+        let tuned = tuning.frequency(note).unwrap();
+        assert!(
+            (tuned - frequency).abs() < 0.01,
+            "expected {frequency} Hz for note {note}, got {tuned} Hz"
+        );
+    }
+
+    #[test]
+    fn edo() -> Result<(), String> {
+        // 12-EDO matches 12-TET
+        let tuning = Tuning::default();
+        assert_frequency(&tuning, 69, 440.0);
+        assert_frequency(&tuning, 60, 261.6256);
+        assert_frequency(&tuning, 48, 130.8128);
+
+        // 19-EDO: 19 notes per octave around note 60
+        let tuning = Tuning::edo(19)?;
+        assert_frequency(&tuning, 60, 261.6256);
+        assert_frequency(&tuning, 79, 523.2511);
+        assert_frequency(&tuning, 41, 130.8128);
+        assert_frequency(&tuning, 61, 261.6256 * (1.0f64 / 19.0).exp2());
+
+        assert!(Tuning::edo(0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn scala() -> Result<(), String> {
+        let scl = "! just.scl\n\
+            !\n\
+            5-limit just intonation major\n \
+            7\n\
+            !\n \
+            9/8\n \
+            5/4\n \
+            4/3\n \
+            3/2\n \
+            5/3\n \
+            15/8\n \
+            2/1\n";
+        let kbm = "! white keys only\n\
+            12\n0\n127\n60\n69\n440.0\n7\n\
+            0\nx\n1\nx\n2\n3\nx\n4\nx\n5\nx\n6\n";
+        let tuning = Tuning::from_scl(scl)?;
+        assert_eq!(tuning.description(), "5-limit just intonation major");
+        assert_eq!(tuning.pitches().len(), 7);
+        assert!((tuning.pitches()[3] - 701.955).abs() < 0.001);
+
+        let tuning = tuning.with_keyboard_mapping(KeyboardMapping::from_kbm(kbm)?);
+        assert_frequency(&tuning, 69, 440.0);
+        assert_frequency(&tuning, 60, 440.0 * 3.0 / 5.0);
+        assert_frequency(&tuning, 67, 440.0 * 3.0 / 5.0 * 3.0 / 2.0);
+        assert_frequency(&tuning, 72, 440.0 * 6.0 / 5.0);
+        assert_eq!(tuning.frequency(61), None);
+
+        // retune note events
+        let mut note_event = NoteEvent::from(Note::E5);
+        assert!(tuning.retune(&mut note_event));
+        assert_eq!(note_event.note, Note::E5);
+        assert!((note_event.cents - 1.955).abs() < 0.01);
+        let mut event = Event::NoteEvents(vec![Some(Note::Cs5.into()), Some(Note::OFF.into())]);
+        tuning.retune_event(&mut event);
+        assert_eq!(event, Event::NoteEvents(vec![None, Some(Note::OFF.into())]));
+
+        assert!(Tuning::from_scl("invalid\n2\n3/2\n").is_err());
+        assert!(Tuning::from_scl("invalid\n1\n0/2\n").is_err());
+        assert!(KeyboardMapping::from_kbm("12\n0\n127\n60\n69\n440.0\n7\n0\n").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,408 @@
+//! Automatic voice leading between successive chords.
+
+use crate::{Chord, Note};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Voices successive chords so that the total voice movement between them is minimal.
+///
+/// Each chord's pitch classes get distributed to a fixed or chord-dependent number of voices
+/// within a register range. Chords with more pitch classes than voices drop their fifth first,
+/// then their topmost notes. Chords with fewer pitch classes double their bass, then their fifth.
+/// The first chord is voiced in close position around the center of the range, all following
+/// chords relative to the previous voicing.
+///
+/// ### Example
+/// ```rust
+/// use pattrns::prelude::*;
+///
+/// let mut voice_leading = VoiceLeading::new()
+///     .with_range(Note::C3, Note::C6)
+///     .with_voice_count(4);
+/// for chord in Scale::try_from((Note::C4, "major"))?.progression("I vi ii7 V7")? {
+///     let voicing = voice_leading.voice_chord(&chord);
+///     assert_eq!(voicing.len(), 4);
+/// }
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceLeading {
+    low: Note,
+    high: Note,
+    voice_count: Option<usize>,
+    fixed_bass: bool,
+    voicing: Vec<Note>,
+}
+
+impl Default for VoiceLeading {
+    fn default() -> Self {
+        Self {
+            low: Note::C3,
+            high: Note::C6,
+            voice_count: None,
+            fixed_bass: false,
+            voicing: Vec::new(),
+        }
+    }
+}
+
+impl VoiceLeading {
+    /// Maximum number of voices a voice leading can use.
+    pub const MAX_VOICES: usize = 8;
+
+    /// Create a new voice leading with default options: a range of C3 to C6, as many voices as
+    /// each chord has pitch classes and no fixed bass.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return a new voice leading which places all notes within the given note range.
+    /// Panics when the range is not a valid note range or smaller than an octave.
+    #[must_use]
+    pub fn with_range(self, low: Note, high: Note) -> Self {
+        assert!(
+            low.is_note_on() && high.is_note_on() && high as u8 >= low as u8 + 12,
+            "voice leading range must span at least an octave"
+        );
+        Self {
+            low,
+            high,
+            voicing: Vec::new(),
+            ..self
+        }
+    }
+
+    /// Return a new voice leading with the given fixed number of voices. When `None`, each chord
+    /// uses as many voices as it has distinct pitch classes.
+    /// Panics when the voice count is not in range `1..=MAX_VOICES`.
+    #[must_use]
+    pub fn with_voice_count<T: Into<Option<usize>>>(self, voice_count: T) -> Self {
+        let voice_count = voice_count.into();
+        if let Some(count) = voice_count {
+            assert!(
+                (1..=Self::MAX_VOICES).contains(&count),
+                "voice count must be in range [1..={}]",
+                Self::MAX_VOICES
+            );
+        }
+        Self {
+            voice_count,
+            ..self
+        }
+    }
+
+    /// Return a new voice leading which always keeps the chord's lowest note in the bass.
+    /// Upper voices then only move above the bass.
+    #[must_use]
+    pub fn with_fixed_bass(self, fixed_bass: bool) -> Self {
+        Self { fixed_bass, ..self }
+    }
+
+    /// Lowest and highest note of the voicing range.
+    pub fn range(&self) -> (Note, Note) {
+        (self.low, self.high)
+    }
+
+    /// Fixed voice count, if any.
+    pub fn voice_count(&self) -> Option<usize> {
+        self.voice_count
+    }
+
+    /// True when the chord's lowest note stays in the bass.
+    pub fn fixed_bass(&self) -> bool {
+        self.fixed_bass
+    }
+
+    /// The most recently voiced chord's notes, in ascending order.
+    pub fn voicing(&self) -> &[Note] {
+        &self.voicing
+    }
+
+    /// Forget the previous voicing, so the next chord is voiced in close position again.
+    pub fn reset(&mut self) {
+        self.voicing.clear();
+    }
+
+    /// Voice the given chord, relative to the previously voiced chord.
+    pub fn voice_chord(&mut self, chord: &Chord) -> Vec<Note> {
+        self.voice(&chord.notes())
+    }
+
+    /// Voice the given chord notes, relative to the previously voiced chord. The lowest given
+    /// note is the chord's bass note. Note-offs and empty notes are ignored. When no note-on
+    /// is given, an empty voicing is returned and the previous voicing is kept.
+    pub fn voice(&mut self, notes: &[Note]) -> Vec<Note> {
+        let mut notes = notes
+            .iter()
+            .filter(|note| note.is_note_on())
+            .map(|note| *note as u8)
+            .collect::<Vec<_>>();
+        if notes.is_empty() {
+            return Vec::new();
+        }
+        notes.sort();
+        // distinct pitch classes, starting with the bass
+        let mut pitch_classes = Vec::with_capacity(notes.len());
+        for note in notes {
+            if !pitch_classes.contains(&(note % 12)) {
+                pitch_classes.push(note % 12);
+            }
+        }
+        let voice_count = self
+            .voice_count
+            .unwrap_or(pitch_classes.len())
+            .min(Self::MAX_VOICES);
+        let classes = Self::select_pitch_classes(&pitch_classes, voice_count);
+        let voicing = if self.voicing.is_empty() {
+            self.close_voicing(&classes)
+        } else {
+            self.closest_voicing(&classes)
+        };
+        self.voicing = voicing.iter().map(|note| Note::from(*note)).collect();
+        self.voicing.clone()
+    }
+
+    /// Voice all given chords in a row, relative to the previously voiced chord.
+    pub fn voice_progression<I, N>(&mut self, chords: I) -> Vec<Vec<Note>>
+    where
+        I: IntoIterator<Item = N>,
+        N: AsRef<[Note]>,
+    {
+        chords
+            .into_iter()
+            .map(|chord| self.voice(chord.as_ref()))
+            .collect()
+    }
+
+    // Select pitch classes for the given number of voices. The bass always stays first.
+    fn select_pitch_classes(pitch_classes: &[u8], voice_count: usize) -> Vec<u8> {
+        let bass = pitch_classes[0];
+        let fifth = (bass + 7) % 12;
+        let mut classes = pitch_classes.to_vec();
+        while classes.len() > voice_count {
+            match classes.iter().skip(1).position(|class| *class == fifth) {
+                Some(index) if classes.len() > 2 => {
+                    classes.remove(index + 1);
+                }
+                _ => {
+                    classes.pop();
+                }
+            }
+        }
+        let mut doublings = vec![bass];
+        if pitch_classes.contains(&fifth) {
+            doublings.push(fifth);
+        }
+        doublings.extend(pitch_classes.iter().filter(|c| **c != bass && **c != fifth));
+        for index in 0..voice_count.saturating_sub(classes.len()) {
+            classes.push(doublings[index % doublings.len()]);
+        }
+        classes
+    }
+
+    // Stack the pitch classes upwards, starting around the center of the range.
+    fn close_voicing(&self, classes: &[u8]) -> Vec<u8> {
+        let (low, high) = (self.low as u8, self.high as u8);
+        let center = (low as i32 + high as i32) / 2;
+        let mut voicing = Vec::with_capacity(classes.len());
+        let mut previous = None;
+        for class in classes {
+            let note = match previous {
+                None => self.nearest(*class, center - 6, low),
+                Some(previous) => {
+                    let mut note = previous - previous % 12 + class;
+                    while note <= previous {
+                        note += 12;
+                    }
+                    note
+                }
+            };
+            voicing.push(note);
+            previous = Some(note);
+        }
+        // move the whole voicing down into the range, when possible
+        while voicing.iter().any(|note| *note > high) && voicing[0] >= low + 12 {
+            voicing.iter_mut().for_each(|note| *note -= 12);
+        }
+        for note in &mut voicing {
+            if *note > high {
+                *note = self.nearest(*note % 12, high as i32, low);
+            }
+        }
+        voicing.sort();
+        voicing
+    }
+
+    // Find the voicing with the least movement relative to the previous voicing.
+    fn closest_voicing(&self, classes: &[u8]) -> Vec<u8> {
+        let targets = Self::resampled(&self.voicing, classes.len());
+        let (low, high) = (self.low as u8, self.high as u8);
+        // fixed bass: place the bass first, keeping an octave above it for the upper voices
+        let (bass, upper_classes, upper_targets) = if self.fixed_bass {
+            let bass = self.nearest(classes[0], targets[0].round() as i32, low);
+            let lowered = bass.saturating_sub(12);
+            let bass = if bass + 12 > high && lowered >= low {
+                lowered
+            } else {
+                bass
+            };
+            (Some(bass.clamp(low, high)), &classes[1..], &targets[1..])
+        } else {
+            (None, classes, &targets[..])
+        };
+        let min = bass.map_or(low, |bass| bass + 1);
+        let mut best: Option<(f32, Vec<u8>)> = None;
+        let mut permutation = upper_classes.to_vec();
+        for_each_permutation(&mut permutation, &mut |permutation| {
+            let mut voicing = permutation
+                .iter()
+                .zip(upper_targets)
+                .map(|(class, target)| self.nearest(*class, target.round() as i32, min))
+                .collect::<Vec<_>>();
+            voicing.sort();
+            // sum of voice movements, penalizing voices which collapse into unisons
+            let mut cost = voicing
+                .iter()
+                .zip(upper_targets)
+                .map(|(note, target)| (*note as f32 - target).abs())
+                .sum::<f32>();
+            cost += 12.0 * voicing.windows(2).filter(|n| n[0] == n[1]).count() as f32;
+            if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                best = Some((cost, voicing));
+            }
+        });
+        let mut voicing = best.map(|(_, voicing)| voicing).unwrap_or_default();
+        if let Some(bass) = bass {
+            voicing.insert(0, bass);
+        }
+        voicing
+    }
+
+    // Note with the given pitch class that's closest to the target within [min, high].
+    // Falls back to the range's low note as minimum when there's no such note.
+    fn nearest(&self, class: u8, target: i32, min: u8) -> u8 {
+        let high = self.high as u8;
+        let min = if min > high { self.low as u8 } else { min };
+        let first = min + (class + 12 - min % 12) % 12;
+        let mut best = None;
+        let mut note = if first > high { first - 12 } else { first };
+        while note <= high {
+            let distance = (note as i32 - target).abs();
+            if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+                best = Some((distance, note));
+            }
+            note += 12;
+        }
+        best.map(|(_, note)| note).unwrap_or(min)
+    }
+
+    // Resample the given ascending voicing to the given number of voices.
+    fn resampled(voicing: &[Note], count: usize) -> Vec<f32> {
+        let notes = voicing.iter().map(|n| *n as u8 as f32).collect::<Vec<_>>();
+        if notes.len() == count {
+            return notes;
+        }
+        (0..count)
+            .map(|index| {
+                let position = if count > 1 {
+                    index as f32 * (notes.len() - 1) as f32 / (count - 1) as f32
+                } else {
+                    (notes.len() - 1) as f32 / 2.0
+                };
+                let (lower, fraction) = (position.floor() as usize, position.fract());
+                let upper = (lower + 1).min(notes.len() - 1);
+                notes[lower] + (notes[upper] - notes[lower]) * fraction
+            })
+            .collect()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+// Call the given function with all permutations of the given items (Heap's algorithm).
+fn for_each_permutation<F: FnMut(&[u8])>(items: &mut [u8], f: &mut F) {
+    fn permute<F: FnMut(&[u8])>(count: usize, items: &mut [u8], f: &mut F) {
+        if count <= 1 {
+            f(items);
+            return;
+        }
+        for index in 0..count - 1 {
+            permute(count - 1, items, f);
+            if count.is_multiple_of(2) {
+                items.swap(index, count - 1);
+            } else {
+                items.swap(0, count - 1);
+            }
+        }
+        permute(count - 1, items, f);
+    }
+    permute(items.len(), items, f);
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{note::voicing::VoiceLeading, Chord, Note, Scale};
+
+    #[test]
+    fn voice_leading() -> Result<(), String> {
+        let chords = Scale::try_from((Note::C4, "major"))?
+            .progression("I vi IV V")?
+            .iter()
+            .map(Chord::notes)
+            .collect::<Vec<_>>();
+
+        // minimal movement, common tones stay
+        let mut voice_leading = VoiceLeading::new();
+        let voicings = voice_leading.voice_progression(&chords);
+        assert_eq!(
+            voicings,
+            vec![
+                vec![Note::C4, Note::E4, Note::G4],
+                vec![Note::C4, Note::E4, Note::A4],
+                vec![Note::C4, Note::F4, Note::A4],
+                vec![Note::D4, Note::G4, Note::B4],
+            ]
+        );
+        assert_eq!(voice_leading.voicing(), voicings[3].as_slice());
+
+        // note-offs are ignored
+        assert_eq!(voice_leading.voice(&[Note::OFF]), vec![]);
+        assert_eq!(voice_leading.voicing(), voicings[3].as_slice());
+
+        // fixed voice count and bass
+        let mut voice_leading = VoiceLeading::new()
+            .with_voice_count(4)
+            .with_fixed_bass(true);
+        for (chord, voicing) in chords.iter().zip(voice_leading.voice_progression(&chords)) {
+            assert_eq!(voicing.len(), 4);
+            assert_eq!(voicing[0].key(), chord[0].key());
+            assert!(voicing[1..].iter().all(|note| *note > voicing[0]));
+            let keys = voicing.iter().map(Note::key).collect::<Vec<_>>();
+            assert!(chord.iter().all(|note| keys.contains(&note.key())));
+        }
+
+        // fewer voices than pitch classes drop the fifth
+        let mut voice_leading = VoiceLeading::new().with_voice_count(3);
+        let voicing = voice_leading.voice_chord(&Chord::try_from((Note::G4, "dom7"))?);
+        assert_eq!(voicing, vec![Note::G3, Note::B3, Note::F4]);
+
+        // range limits
+        let mut voice_leading = VoiceLeading::new().with_range(Note::C5, Note::C6);
+        for voicing in voice_leading.voice_progression(&chords) {
+            assert!(voicing
+                .iter()
+                .all(|note| (Note::C5..=Note::C6).contains(note)));
+        }
+        let mut voice_leading = VoiceLeading::new()
+            .with_range(Note::C0, Note::C1)
+            .with_fixed_bass(true);
+        for voicing in voice_leading.voice_progression(&chords) {
+            assert!(voicing
+                .iter()
+                .all(|note| (Note::C0..=Note::C1).contains(note)));
+        }
+        Ok(())
+    }
+}
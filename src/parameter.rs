@@ -3,6 +3,15 @@ use std::{cell::RefCell, ops::RangeInclusive, rc::Rc};
 #[cfg(feature = "scripting")]
 use mlua::prelude::{IntoLua, Lua, LuaInteger, LuaResult, LuaValue};
 
+use crate::Note;
+
+// -------------------------------------------------------------------------------------------------
+
+pub mod automation;
+pub mod preset;
+
+use self::automation::ParameterAutomation;
+
 // -------------------------------------------------------------------------------------------------
 
 /// Value representation of a parameter.
@@ -13,6 +22,14 @@ pub enum ParameterType {
     Float,
     Integer,
     Enum,
+    /// A note number, displayed as note name.
+    Note,
+    /// A free-form text value.
+    String,
+    /// A fixed-length list of float values.
+    FloatArray,
+    /// A fixed-length list of integer values.
+    IntegerArray,
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -27,6 +44,9 @@ pub type ParameterSet = Vec<Rc<RefCell<Parameter>>>;
 ///
 /// Parameter values can be changed by the user during playback, and will usually be applied in
 /// scripted callbacks only, as those are the only runtime dynamic components in patterns.
+///
+/// Parameters optionally can be automated via a [`ParameterAutomation`]. Patterns then update the
+/// parameter's value with each pulse, before running their rhythm, gate and emitter.
 #[derive(Debug, Clone)]
 pub struct Parameter {
     id: String,
@@ -37,6 +57,11 @@ pub struct Parameter {
     default: f64,
     value: f64,
     value_strings: Vec<String>,
+    default_text: String,
+    text: String,
+    default_array: Vec<f64>,
+    array: Vec<f64>,
+    automation: Option<ParameterAutomation>,
 }
 
 impl Parameter {
@@ -62,6 +87,11 @@ impl Parameter {
         };
         let value = default;
         let value_strings = vec![];
+        let default_text = String::new();
+        let text = default_text.clone();
+        let default_array = vec![];
+        let array = default_array.clone();
+        let automation = None;
         Self {
             id,
             name,
@@ -71,6 +101,11 @@ impl Parameter {
             default,
             value,
             value_strings,
+            default_text,
+            text,
+            default_array,
+            array,
+            automation,
         }
     }
 
@@ -101,6 +136,11 @@ impl Parameter {
         let default = default as f64;
         let value = default;
         let value_strings = vec![];
+        let default_text = String::new();
+        let text = default_text.clone();
+        let default_array = vec![];
+        let array = default_array.clone();
+        let automation = None;
         Self {
             id,
             name,
@@ -110,6 +150,11 @@ impl Parameter {
             default,
             value,
             value_strings,
+            default_text,
+            text,
+            default_array,
+            array,
+            automation,
         }
     }
 
@@ -138,6 +183,11 @@ impl Parameter {
         let parameter_type = ParameterType::Float;
         let value = default;
         let value_strings = vec![];
+        let default_text = String::new();
+        let text = default_text.clone();
+        let default_array = vec![];
+        let array = default_array.clone();
+        let automation = None;
         Self {
             id,
             name,
@@ -147,6 +197,11 @@ impl Parameter {
             default,
             value,
             value_strings,
+            default_text,
+            text,
+            default_array,
+            array,
+            automation,
         }
     }
 
@@ -183,6 +238,11 @@ impl Parameter {
             .unwrap_or(0) as f64;
         let value = default;
         let value_strings = values;
+        let default_text = String::new();
+        let text = default_text.clone();
+        let default_array = vec![];
+        let array = default_array.clone();
+        let automation = None;
         Self {
             id,
             name,
@@ -192,9 +252,129 @@ impl Parameter {
             default,
             value,
             value_strings,
+            default_text,
+            text,
+            default_array,
+            array,
+            automation,
+        }
+    }
+
+    /// Create a new note parameter with the given properties. The parameter's value is the
+    /// note's number. Note offs and empty notes are not valid parameter values.
+    ///
+    /// Name and description are optional and may be empty, all other values
+    /// must be valid.
+    ///
+    /// ### Panics
+    /// Panics if the default value is not in the specified range.
+    pub fn with_note(
+        id: &str,
+        name: &str,
+        description: &str,
+        range: RangeInclusive<Note>,
+        default: Note,
+    ) -> Self {
+        debug_assert!(
+            *range.end() <= Note::G10,
+            "Invalid parameter range: note offs and empty notes are not supported"
+        );
+        debug_assert!(range.contains(&default), "Invalid parameter default value");
+
+        let range = RangeInclusive::new(
+            u8::from(*range.start()) as i32,
+            u8::from(*range.end()) as i32,
+        );
+        let default = u8::from(default) as i32;
+        Self {
+            parameter_type: ParameterType::Note,
+            ..Self::with_integer(id, name, description, range, default)
+        }
+    }
+
+    /// Create a new free-form string parameter with the given properties.
+    ///
+    /// Name and description are optional and may be empty.
+    pub fn with_string(id: &str, name: &str, description: &str, default: &str) -> Self {
+        let default_text = default.to_string();
+        let text = default_text.clone();
+        Self {
+            parameter_type: ParameterType::String,
+            default_text,
+            text,
+            ..Self::with_float(id, name, description, 0.0..=0.0, 0.0)
+        }
+    }
+
+    /// Create a new fixed-length float array parameter with the given properties. The array's
+    /// length is the length of the given default values.
+    ///
+    /// Name and description are optional and may be empty, all other values
+    /// must be valid.
+    ///
+    /// ### Panics
+    /// Panics if the default values are empty or not in the specified range.
+    pub fn with_float_array(
+        id: &str,
+        name: &str,
+        description: &str,
+        range: RangeInclusive<f64>,
+        default: Vec<f64>,
+    ) -> Self {
+        debug_assert!(!default.is_empty(), "Invalid parameter default value");
+        debug_assert!(
+            default.iter().all(|v| range.contains(v)),
+            "Invalid parameter default value"
+        );
+
+        let default_array = default;
+        let array = default_array.clone();
+        Self {
+            parameter_type: ParameterType::FloatArray,
+            default_array,
+            array,
+            ..Self::with_float(id, name, description, range.clone(), *range.start())
+        }
+    }
+
+    /// Create a new fixed-length integer array parameter with the given properties. The array's
+    /// length is the length of the given default values.
+    ///
+    /// Name and description are optional and may be empty, all other values
+    /// must be valid.
+    ///
+    /// ### Panics
+    /// Panics if the default values are empty or not in the specified range.
+    pub fn with_integer_array(
+        id: &str,
+        name: &str,
+        description: &str,
+        range: RangeInclusive<i32>,
+        default: Vec<i32>,
+    ) -> Self {
+        debug_assert!(!default.is_empty(), "Invalid parameter default value");
+        debug_assert!(
+            default.iter().all(|v| range.contains(v)),
+            "Invalid parameter default value"
+        );
+
+        let default_array = default.into_iter().map(|v| v as f64).collect::<Vec<_>>();
+        let array = default_array.clone();
+        Self {
+            parameter_type: ParameterType::IntegerArray,
+            default_array,
+            array,
+            ..Self::with_integer(id, name, description, range.clone(), *range.start())
         }
     }
 
+    /// Return a new parameter which automates its value with the given automation.
+    #[must_use]
+    pub fn with_automation<T: Into<Option<ParameterAutomation>>>(self, automation: T) -> Self {
+        let automation = automation.into();
+        Self { automation, ..self }
+    }
+
     /// Unique id of the parameter. The id will be used in callback context tables as key.
     pub fn id(&self) -> &str {
         &self.id
@@ -215,7 +395,8 @@ impl Parameter {
         self.parameter_type
     }
 
-    /// Valid internal value range. Falls back to (0..=1) when unspecified.
+    /// Valid internal value range. Falls back to (0..=1) when unspecified. For array parameters,
+    /// this is the value range of each array item.
     pub fn range(&self) -> &RangeInclusive<f64> {
         &self.range
     }
@@ -244,9 +425,110 @@ impl Parameter {
         self.value = value;
     }
 
+    /// Actual value of note parameters as [`Note`]. None for all other parameter types.
+    pub fn note_value(&self) -> Option<Note> {
+        match self.parameter_type {
+            ParameterType::Note => Some(Note::from(self.value.round() as u8)),
+            _ => None,
+        }
+    }
+
+    /// Default value of string parameters. Empty for all other parameter types.
+    pub fn default_text(&self) -> &str {
+        &self.default_text
+    }
+
+    /// Actual value of string parameters. Empty for all other parameter types.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Set a new string parameter value.
+    ///
+    /// ### Panics
+    /// Panics if the parameter is not a string parameter.
+    pub fn set_text(&mut self, text: &str) {
+        assert!(
+            self.parameter_type == ParameterType::String,
+            "Not a string parameter"
+        );
+        text.clone_into(&mut self.text);
+    }
+
+    /// Default values of array parameters. Empty for all other parameter types.
+    pub fn default_array(&self) -> &[f64] {
+        &self.default_array
+    }
+
+    /// Actual values of array parameters. Empty for all other parameter types.
+    pub fn array(&self) -> &[f64] {
+        &self.array
+    }
+
+    /// Set a new array parameter item value. Value must be in the specified range.
+    ///
+    /// ### Panics
+    /// Panics if the index or the passed value exceeds the specified range.
+    pub fn set_array_value(&mut self, index: usize, value: f64) {
+        assert!(index < self.array.len(), "Invalid array index");
+        assert!(self.range.contains(&value), "Invalid value");
+        self.array[index] = value;
+    }
+
+    /// Set all array parameter values. Values must be in the specified range and the
+    /// number of values must match the array's length.
+    ///
+    /// ### Panics
+    /// Panics if the length or one of the passed values exceeds the specified range.
+    pub fn set_array(&mut self, values: &[f64]) {
+        assert!(values.len() == self.array.len(), "Invalid array length");
+        assert!(
+            values.iter().all(|v| self.range.contains(v)),
+            "Invalid value"
+        );
+        self.array.copy_from_slice(values);
+    }
+
     /// Reset the value to the default value.
     pub fn reset(&mut self) {
-        self.value = self.default
+        self.value = self.default;
+        self.text.clone_from(&self.default_text);
+        self.array.clone_from(&self.default_array);
+    }
+
+    /// Optional automation of the parameter's value.
+    pub fn automation(&self) -> Option<&ParameterAutomation> {
+        self.automation.as_ref()
+    }
+
+    /// Set or remove the parameter's automation. When removing an automation, the parameter keeps
+    /// its last automated value.
+    pub fn set_automation<T: Into<Option<ParameterAutomation>>>(&mut self, automation: T) {
+        self.automation = automation.into();
+    }
+
+    /// Apply the automation value at the given pattern time in beats, if the parameter is
+    /// automated. Automation values get clamped into the parameter's range and rounded for
+    /// non float parameters. Automated values overwrite values which got set via `set_value`.
+    /// String and array parameters can not be automated: their automation is ignored.
+    pub fn update_automation(&mut self, time_in_beats: f64) {
+        if matches!(
+            self.parameter_type,
+            ParameterType::String | ParameterType::FloatArray | ParameterType::IntegerArray
+        ) {
+            return;
+        }
+        if let Some(value) = self
+            .automation
+            .as_ref()
+            .and_then(|automation| automation.value_at(time_in_beats))
+        {
+            let value = value.clamp(*self.range.start(), *self.range.end());
+            self.value = match self.parameter_type {
+                ParameterType::Float => value,
+                _ => value.round(),
+            };
+        }
     }
 
     /// String representation of the value, depending on the parameter type.
@@ -262,6 +544,20 @@ impl Parameter {
             ParameterType::Float => self.value.to_string(),
             ParameterType::Integer => (self.value.round() as i64).to_string(),
             ParameterType::Enum => self.value_strings[self.value.round() as usize].clone(),
+            ParameterType::Note => Note::from(self.value.round() as u8).to_string(),
+            ParameterType::String => self.text.clone(),
+            ParameterType::FloatArray => self
+                .array
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            ParameterType::IntegerArray => self
+                .array
+                .iter()
+                .map(|v| (v.round() as i64).to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
         }
     }
 
@@ -281,6 +577,14 @@ impl Parameter {
             ParameterType::Enum => self.value_strings[self.value.round() as usize]
                 .clone()
                 .into_lua(lua),
+            ParameterType::Note => (self.value.round() as LuaInteger).into_lua(lua),
+            ParameterType::String => self.text.clone().into_lua(lua),
+            ParameterType::FloatArray => {
+                lua.create_sequence_from(self.array.clone())?.into_lua(lua)
+            }
+            ParameterType::IntegerArray => lua
+                .create_sequence_from(self.array.iter().map(|v| v.round() as LuaInteger))?
+                .into_lua(lua),
         }
     }
 }
@@ -295,5 +599,48 @@ impl PartialEq for Parameter {
             && self.default == other.default
             // SKIP value
             && self.value_strings == other.value_strings
+            && self.default_text == other.default_text
+            && self.default_array == other.default_array
+            && self.automation == other.automation
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parameter_types() {
+        let mut note = Parameter::with_note("root", "", "", Note::C3..=Note::C5, Note::C4);
+        assert_eq!(note.parameter_type(), ParameterType::Note);
+        assert_eq!(note.value(), 48.0);
+        assert_eq!(note.note_value(), Some(Note::C4));
+        assert_eq!(note.string_value(), "C4");
+        note.set_value(51.0);
+        assert_eq!(note.string_value(), "D#4");
+        note.update_automation(0.0);
+        assert_eq!(note.note_value(), Some(Note::Ds4));
+
+        let mut string = Parameter::with_string("sample", "", "", "kick.wav");
+        assert_eq!(string.text(), "kick.wav");
+        string.set_text("snare.wav");
+        assert_eq!(string.string_value(), "snare.wav");
+        string.reset();
+        assert_eq!(string.text(), "kick.wav");
+        assert_eq!(string.note_value(), None);
+
+        let mut floats = Parameter::with_float_array("gains", "", "", 0.0..=1.0, vec![0.5, 1.0]);
+        assert_eq!(floats.array(), &[0.5, 1.0]);
+        floats.set_array_value(1, 0.25);
+        assert_eq!(floats.string_value(), "0.5, 0.25");
+        floats.reset();
+        assert_eq!(floats.array(), floats.default_array());
+
+        let mut integers = Parameter::with_integer_array("steps", "", "", 0..=4, vec![1, 0, 4]);
+        integers.set_array(&[2.0, 2.0, 3.0]);
+        assert_eq!(integers.string_value(), "2, 2, 3");
+        assert_eq!(integers.text(), "");
     }
 }
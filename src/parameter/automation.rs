@@ -0,0 +1,254 @@
+//! Automation lanes and modulation sources for [`Parameter`](crate::Parameter) values.
+
+use std::f64::consts::PI;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Waveform of a [`ParameterAutomation::Lfo`]. All shapes start at the LFO's minimum value.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum LfoShape {
+    #[default]
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+}
+
+impl LfoShape {
+    /// All shapes names, as used in `TryFrom<&str>`.
+    pub fn names() -> Vec<&'static str> {
+        vec!["sine", "triangle", "saw", "square"]
+    }
+
+    /// Normalized shape value in range [0, 1] at the given phase in range [0, 1).
+    pub fn value_at(&self, phase: f64) -> f64 {
+        match self {
+            LfoShape::Sine => 0.5 - 0.5 * (2.0 * PI * phase).cos(),
+            LfoShape::Triangle => 1.0 - (2.0 * phase - 1.0).abs(),
+            LfoShape::Saw => phase,
+            LfoShape::Square => {
+                if phase < 0.5 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<&str> for LfoShape {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "sine" | "sin" => Ok(Self::Sine),
+            "triangle" | "tri" => Ok(Self::Triangle),
+            "saw" | "ramp" => Ok(Self::Saw),
+            "square" | "pulse" => Ok(Self::Square),
+            _ => Err(format!(
+                "invalid lfo shape '{}', valid shapes are: {}",
+                name,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Automation of a [`Parameter`](crate::Parameter)'s value over time.
+///
+/// Automation times are pattern beat times, relative to the start of the pattern which uses the
+/// parameter. Values are raw parameter values: `0` or `1` for booleans and value indices for enums.
+/// Values outside of the parameter's range get clamped, and integer, boolean and enum values are
+/// rounded when they are applied to the parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterAutomation {
+    /// A curve which linearly interpolates between `(beat time, value)` points, sorted by time.
+    /// Before the first and after the last point, the first or last point's value is held.
+    /// When a loop length in beats is set, the curve repeats after the given number of beats.
+    Curve {
+        points: Vec<(f64, f64)>,
+        length: Option<f64>,
+    },
+    /// A low frequency oscillator which moves between `min` and `max` once every `period` beats,
+    /// starting at the given phase in range [0, 1).
+    Lfo {
+        shape: LfoShape,
+        period: f64,
+        phase: f64,
+        min: f64,
+        max: f64,
+    },
+    /// A looping step sequence which holds each value for `step` beats.
+    Steps { values: Vec<f64>, step: f64 },
+}
+
+impl ParameterAutomation {
+    /// Automation value at the given time in beats. None for curves or step sequences
+    /// without values.
+    pub fn value_at(&self, time_in_beats: f64) -> Option<f64> {
+        match self {
+            ParameterAutomation::Curve { points, length } => {
+                let time = match length {
+                    Some(length) if *length > 0.0 => time_in_beats.rem_euclid(*length),
+                    _ => time_in_beats,
+                };
+                let index = points.iter().position(|(point_time, _)| *point_time > time);
+                match index {
+                    None => points.last().map(|(_, value)| *value),
+                    Some(0) => Some(points[0].1),
+                    Some(index) => {
+                        let (start_time, start) = points[index - 1];
+                        let (end_time, end) = points[index];
+                        let fraction = (time - start_time) / (end_time - start_time);
+                        Some(start + (end - start) * fraction)
+                    }
+                }
+            }
+            ParameterAutomation::Lfo {
+                shape,
+                period,
+                phase,
+                min,
+                max,
+            } => {
+                if *period <= 0.0 {
+                    return Some(*min);
+                }
+                let phase = (time_in_beats / period + phase).rem_euclid(1.0);
+                Some(min + (max - min) * shape.value_at(phase))
+            }
+            ParameterAutomation::Steps { values, step } => {
+                if values.is_empty() {
+                    return None;
+                }
+                let index = if *step > 0.0 {
+                    (time_in_beats / step).floor() as i64
+                } else {
+                    0
+                };
+                Some(values[index.rem_euclid(values.len() as i64) as usize])
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::prelude::*;
+
+    #[test]
+    fn automation_values() {
+        let curve = ParameterAutomation::Curve {
+            points: vec![(1.0, 0.0), (3.0, 1.0)],
+            length: None,
+        };
+        assert_eq!(curve.value_at(0.0), Some(0.0));
+        assert_eq!(curve.value_at(2.0), Some(0.5));
+        assert_eq!(curve.value_at(8.0), Some(1.0));
+
+        let looped_curve = ParameterAutomation::Curve {
+            points: vec![(0.0, 0.0), (4.0, 1.0)],
+            length: Some(4.0),
+        };
+        assert_eq!(looped_curve.value_at(3.0), Some(0.75));
+        assert_eq!(looped_curve.value_at(5.0), Some(0.25));
+
+        let lfo = ParameterAutomation::Lfo {
+            shape: LfoShape::Triangle,
+            period: 4.0,
+            phase: 0.0,
+            min: -1.0,
+            max: 1.0,
+        };
+        assert_eq!(lfo.value_at(0.0), Some(-1.0));
+        assert_eq!(lfo.value_at(1.0), Some(0.0));
+        assert_eq!(lfo.value_at(2.0), Some(1.0));
+        assert_eq!(lfo.value_at(4.0), Some(-1.0));
+
+        let steps = ParameterAutomation::Steps {
+            values: vec![1.0, 2.0, 3.0],
+            step: 0.5,
+        };
+        assert_eq!(steps.value_at(0.0), Some(1.0));
+        assert_eq!(steps.value_at(1.2), Some(3.0));
+        assert_eq!(steps.value_at(1.5), Some(1.0));
+
+        assert!(LfoShape::try_from("wurst").is_err());
+        assert_eq!(LfoShape::try_from("Saw"), Ok(LfoShape::Saw));
+    }
+
+    #[test]
+    fn automated_parameters() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let parameter = Rc::new(RefCell::new(
+            Parameter::with_integer("steps", "", "", 0..=10, 0).with_automation(
+                ParameterAutomation::Steps {
+                    values: vec![2.0, 4.0, 20.0],
+                    step: 1.0,
+                },
+            ),
+        ));
+        let mut pattern = time_base
+            .every_nth_beat(1.0)
+            .with_parameters(vec![Rc::clone(&parameter)])
+            .emit(new_note_emitter("c4"));
+
+        // values update with each pulse and get clamped into the parameter's range
+        pattern.next();
+        assert_eq!(parameter.borrow().value(), 2.0);
+        pattern.next();
+        assert_eq!(parameter.borrow().value(), 4.0);
+        pattern.next();
+        assert_eq!(parameter.borrow().value(), 10.0);
+
+        // values restart with the pattern
+        pattern.reset();
+        pattern.next();
+        assert_eq!(parameter.borrow().value(), 2.0);
+    }
+
+    #[test]
+    fn tempo_mapped_automation() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let tempo_map = TempoMap::new(&time_base)
+            .with_time_signature(0, TimeSignature::new(6, 8))
+            .with_tempo_change(1.0, 60.0, TempoRamp::Linear);
+        let parameter = Rc::new(RefCell::new(
+            Parameter::with_integer("steps", "", "", 0..=10, 0).with_automation(
+                ParameterAutomation::Steps {
+                    values: vec![1.0, 2.0, 3.0],
+                    step: 1.0,
+                },
+            ),
+        ));
+        let mut pattern = time_base
+            .every_nth_beat(1.0)
+            .with_tempo_map(Rc::new(tempo_map))
+            .with_parameters(vec![Rc::clone(&parameter)])
+            .emit(new_note_emitter("c4"));
+        pattern.set_sample_offset(1000);
+
+        // automation times are quarter note beats: beats in 6/8 are eighth notes
+        let mut values = Vec::new();
+        for _ in 0..5 {
+            pattern.next();
+            values.push(parameter.borrow().value());
+        }
+        assert_eq!(values, vec![1.0, 1.0, 2.0, 2.0, 3.0]);
+    }
+}
@@ -0,0 +1,601 @@
+//! Named snapshots of [`Parameter`] values, which can be restored, morphed and serialized.
+
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+use crate::{Parameter, ParameterType};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A single parameter value in a [`ParameterPreset`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterValue {
+    Boolean(bool),
+    /// Integer or note parameter value.
+    Integer(i32),
+    Float(f64),
+    /// Enum or string parameter value.
+    String(String),
+    /// Float or integer array parameter values.
+    Array(Vec<f64>),
+}
+
+impl ParameterValue {
+    /// Capture the given parameter's current value.
+    pub fn from_parameter(parameter: &Parameter) -> Self {
+        match parameter.parameter_type() {
+            ParameterType::Boolean => Self::Boolean(parameter.value() > 0.5),
+            ParameterType::Integer | ParameterType::Note => {
+                Self::Integer(parameter.value().round() as i32)
+            }
+            ParameterType::Float => Self::Float(parameter.value()),
+            ParameterType::Enum => Self::String(parameter.string_value()),
+            ParameterType::String => Self::String(parameter.text().to_string()),
+            ParameterType::FloatArray | ParameterType::IntegerArray => {
+                Self::Array(parameter.array().to_vec())
+            }
+        }
+    }
+
+    /// Convert the value to a raw value of the given parameter, clamped into the parameter's
+    /// range. Returns None when the value is an enum string the parameter does not know, and
+    /// for string or array values or parameters, which have no raw value.
+    pub fn to_parameter_value(&self, parameter: &Parameter) -> Option<f64> {
+        if matches!(
+            parameter.parameter_type(),
+            ParameterType::String | ParameterType::FloatArray | ParameterType::IntegerArray
+        ) {
+            return None;
+        }
+        let value = match self {
+            Self::Boolean(value) => {
+                if *value {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Integer(value) => *value as f64,
+            Self::Float(value) => *value,
+            Self::String(value) => parameter
+                .value_strings()
+                .iter()
+                .position(|v| v.eq_ignore_ascii_case(value))?
+                as f64,
+            Self::Array(_) => return None,
+        };
+        let range = parameter.range();
+        let value = value.clamp(*range.start(), *range.end());
+        match parameter.parameter_type() {
+            ParameterType::Float => Some(value),
+            _ => Some(value.round()),
+        }
+    }
+
+    /// Apply the value to the given parameter. Values are clamped into the parameter's range.
+    /// Array values apply to the first items only when their length does not match the
+    /// parameter's array length. Values which do not match the parameter's type are ignored.
+    pub fn apply_to(&self, parameter: &mut Parameter) {
+        match (parameter.parameter_type(), self) {
+            (ParameterType::String, Self::String(text)) => parameter.set_text(text),
+            (ParameterType::FloatArray | ParameterType::IntegerArray, Self::Array(values)) => {
+                let range = parameter.range().clone();
+                let round = parameter.parameter_type() == ParameterType::IntegerArray;
+                let mut array = parameter.array().to_vec();
+                for (item, value) in array.iter_mut().zip(values) {
+                    let value = value.clamp(*range.start(), *range.end());
+                    *item = if round { value.round() } else { value };
+                }
+                parameter.set_array(&array);
+            }
+            _ => {
+                if let Some(value) = self.to_parameter_value(parameter) {
+                    parameter.set_value(value);
+                }
+            }
+        }
+    }
+
+    /// Interpolate between this and the given other value. Numbers and arrays of equal length
+    /// get interpolated linearly, all other values switch to the other value at the midpoint.
+    pub fn morph(&self, other: &Self, amount: f64) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        let lerp = |a: f64, b: f64| a + (b - a) * amount;
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => {
+                Self::Integer(lerp(*a as f64, *b as f64).round() as i32)
+            }
+            (Self::Float(a), Self::Float(b)) => Self::Float(lerp(*a, *b)),
+            (Self::Integer(a), Self::Float(b)) => Self::Float(lerp(*a as f64, *b)),
+            (Self::Float(a), Self::Integer(b)) => Self::Float(lerp(*a, *b as f64)),
+            (Self::Array(a), Self::Array(b)) if a.len() == b.len() => {
+                Self::Array(a.iter().zip(b).map(|(a, b)| lerp(*a, *b)).collect())
+            }
+            _ => {
+                if amount < 0.5 {
+                    self.clone()
+                } else {
+                    other.clone()
+                }
+            }
+        }
+    }
+}
+
+impl Display for ParameterValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Boolean(value) => write!(f, "{}", value),
+            Self::Integer(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{:?}", value),
+            Self::String(value) => write!(f, "\"{}\"", escape(value)),
+            Self::Array(values) => write!(
+                f,
+                "[{}]",
+                values
+                    .iter()
+                    .map(|v| format!("{:?}", v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl TryFrom<&str> for ParameterValue {
+    type Error = String;
+
+    fn try_from(str: &str) -> Result<Self, String> {
+        let str = str.trim();
+        if str == "true" {
+            Ok(Self::Boolean(true))
+        } else if str == "false" {
+            Ok(Self::Boolean(false))
+        } else if str.len() >= 2 && str.starts_with('"') && str.ends_with('"') {
+            unescape(&str[1..str.len() - 1]).map(Self::String)
+        } else if let Some(values) = str.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            values
+                .split(',')
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| {
+                    v.trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|v| v.is_finite())
+                        .ok_or_else(|| format!("invalid array value '{}'", v.trim()))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Self::Array)
+        } else if let Ok(value) = str.parse::<i32>() {
+            Ok(Self::Integer(value))
+        } else if let Some(value) = str.parse::<f64>().ok().filter(|v| v.is_finite()) {
+            Ok(Self::Float(value))
+        } else {
+            Err(format!(
+                "invalid parameter value '{}': expecting true, false, a number, \
+                 a quoted string or a [number, ...] list",
+                str
+            ))
+        }
+    }
+}
+
+// Escape backslashes, quotes and line breaks in preset names and string values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+// Resolve escape sequences written by `escape`.
+fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('"') => unescaped.push('"'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(other) => return Err(format!("invalid escape sequence '\\{}'", other)),
+            None => return Err("unterminated escape sequence '\\'".to_string()),
+        }
+    }
+    Ok(unescaped)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A named snapshot of parameter values.
+///
+/// Presets get captured from and applied to a parameter set via [`ParameterSetPresets`]. Two
+/// presets can be morphed into a new preset, and presets can be serialized to and from a simple
+/// INI like text format:
+///
+/// ```text
+/// [Verse]
+/// density = 0.25
+/// enabled = true
+/// steps = 12
+/// mode = "up"
+/// velocities = [1.0, 0.5, 0.75, 0.5]
+/// ```
+///
+/// Backslashes, quotes and line breaks in preset names and string values are escaped with a
+/// backslash: `\\`, `\"`, `\n`, `\r` and `\t`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParameterPreset {
+    name: String,
+    values: Vec<(String, ParameterValue)>,
+}
+
+impl ParameterPreset {
+    /// Create a new empty preset with the given name.
+    pub fn new(name: &str) -> Self {
+        let name = name.to_string();
+        let values = Vec::new();
+        Self { name, values }
+    }
+
+    /// Return a new preset with the given parameter value added or replaced.
+    #[must_use]
+    pub fn with_value(self, id: &str, value: ParameterValue) -> Self {
+        let mut new = self;
+        new.set_value(id, value);
+        new
+    }
+
+    /// The preset's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// All parameter ids and values of the preset.
+    pub fn values(&self) -> &[(String, ParameterValue)] {
+        &self.values
+    }
+
+    /// Value for the given parameter id, if the preset has one.
+    pub fn value(&self, id: &str) -> Option<&ParameterValue> {
+        self.values
+            .iter()
+            .find(|(value_id, _)| value_id == id)
+            .map(|(_, value)| value)
+    }
+
+    /// Add or replace the value for the given parameter id.
+    pub fn set_value(&mut self, id: &str, value: ParameterValue) {
+        if let Some((_, existing)) = self.values.iter_mut().find(|(value_id, _)| value_id == id) {
+            *existing = value;
+        } else {
+            self.values.push((id.to_string(), value));
+        }
+    }
+
+    /// Interpolate between this and the given other preset with a morph amount in range [0, 1].
+    /// Number values and arrays of equal length are interpolated linearly, all other values
+    /// switch at the midpoint.
+    /// Values which only exist in one of the presets are taken over as they are. The morphed
+    /// preset is named after the preset which is closer to the morph amount.
+    pub fn morph(&self, other: &Self, amount: f64) -> Self {
+        let amount = amount.clamp(0.0, 1.0);
+        let name = if amount < 0.5 {
+            self.name.clone()
+        } else {
+            other.name.clone()
+        };
+        let mut values = Vec::with_capacity(self.values.len());
+        for (id, value) in &self.values {
+            let value = match other.value(id) {
+                Some(other_value) => value.morph(other_value, amount),
+                None => value.clone(),
+            };
+            values.push((id.clone(), value));
+        }
+        for (id, value) in &other.values {
+            if self.value(id).is_none() {
+                values.push((id.clone(), value.clone()));
+            }
+        }
+        Self { name, values }
+    }
+
+    /// Parse a list of presets from the preset text format.
+    pub fn parse_list(text: &str) -> Result<Vec<Self>, String> {
+        let mut presets = Vec::<Self>::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: &str| format!("line {}: {}", line_index + 1, message);
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            } else if let Some(name) = line.strip_prefix('[') {
+                let name = name
+                    .strip_suffix(']')
+                    .ok_or_else(|| error("expecting a ']' after the preset name"))?;
+                presets.push(Self::new(&unescape(name.trim()).map_err(|e| error(&e))?));
+            } else if let Some((id, value)) = line.split_once('=') {
+                let preset = presets
+                    .last_mut()
+                    .ok_or_else(|| error("expecting a '[name]' preset header before values"))?;
+                let id = id.trim();
+                if id.is_empty() {
+                    return Err(error("parameter ids can not be empty"));
+                }
+                preset.set_value(id, ParameterValue::try_from(value).map_err(|e| error(&e))?);
+            } else {
+                return Err(error(&format!(
+                    "expecting a '[name]' header or 'id = value' pair, got '{}'",
+                    line
+                )));
+            }
+        }
+        Ok(presets)
+    }
+
+    /// Serialize a list of presets to the preset text format.
+    pub fn list_to_string(presets: &[Self]) -> String {
+        presets
+            .iter()
+            .map(|preset| preset.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Display for ParameterPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[{}]", escape(&self.name))?;
+        for (id, value) in &self.values {
+            writeln!(f, "{} = {}", id, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&str> for ParameterPreset {
+    type Error = String;
+
+    /// Parse a single preset from the preset text format.
+    fn try_from(text: &str) -> Result<Self, String> {
+        let mut presets = Self::parse_list(text)?;
+        if presets.len() != 1 {
+            return Err(format!(
+                "expecting a single preset, but got {} presets",
+                presets.len()
+            ));
+        }
+        Ok(presets.remove(0))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Capture, restore and morph parameter values of a [`ParameterSet`](crate::ParameterSet), e.g.
+/// the parameters of a [`Pattern`](crate::Pattern), [`Phrase`](crate::Phrase) or
+/// [`Sequence`](crate::Sequence), as [`ParameterPreset`]s.
+///
+/// Automated parameters will overwrite restored values with their next automation update.
+pub trait ParameterSetPresets {
+    /// Capture all current parameter values into a new preset with the given name.
+    fn capture_preset(&self, name: &str) -> ParameterPreset;
+
+    /// Restore the given preset's values. Values of parameters which are not part of the preset,
+    /// and preset values without a matching parameter, are ignored. Values are clamped into the
+    /// parameter's value range.
+    fn apply_preset(&self, preset: &ParameterPreset);
+
+    /// Restore the morphed values of the two given presets. See [`ParameterPreset::morph`].
+    fn apply_morph(&self, from: &ParameterPreset, to: &ParameterPreset, amount: f64) {
+        self.apply_preset(&from.morph(to, amount));
+    }
+}
+
+impl ParameterSetPresets for [Rc<RefCell<Parameter>>] {
+    fn capture_preset(&self, name: &str) -> ParameterPreset {
+        let mut preset = ParameterPreset::new(name);
+        for parameter in self {
+            let parameter = parameter.borrow();
+            preset.set_value(parameter.id(), ParameterValue::from_parameter(&parameter));
+        }
+        preset
+    }
+
+    fn apply_preset(&self, preset: &ParameterPreset) {
+        for parameter in self {
+            let mut parameter = parameter.borrow_mut();
+            if let Some(value) = preset.value(parameter.id()) {
+                value.apply_to(&mut parameter);
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        parameter::preset::{ParameterPreset, ParameterSetPresets, ParameterValue},
+        Note, Parameter, ParameterSet,
+    };
+
+    fn new_parameters() -> ParameterSet {
+        [
+            Parameter::with_boolean("enabled", "", "", false),
+            Parameter::with_integer("steps", "", "", 1..=16, 4),
+            Parameter::with_float("density", "", "", 0.0..=1.0, 0.5),
+            Parameter::with_enum(
+                "mode",
+                "",
+                "",
+                vec!["up".to_string(), "down".to_string()],
+                "up".to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|parameter| Rc::new(RefCell::new(parameter)))
+        .collect()
+    }
+
+    #[test]
+    fn presets() {
+        let parameters = new_parameters();
+
+        // capture and restore
+        let intro = parameters.capture_preset("Intro");
+        parameters[0].borrow_mut().set_value(1.0);
+        parameters[1].borrow_mut().set_value(16.0);
+        parameters[2].borrow_mut().set_value(1.0);
+        parameters[3].borrow_mut().set_value(1.0);
+        let drop = parameters.capture_preset("Drop");
+        assert_eq!(
+            drop.values(),
+            &[
+                ("enabled".to_string(), ParameterValue::Boolean(true)),
+                ("steps".to_string(), ParameterValue::Integer(16)),
+                ("density".to_string(), ParameterValue::Float(1.0)),
+                (
+                    "mode".to_string(),
+                    ParameterValue::String("down".to_string())
+                ),
+            ]
+        );
+        parameters.apply_preset(&intro);
+        assert_eq!(parameters.capture_preset("Intro"), intro);
+
+        // unknown and out of range values are ignored or clamped
+        parameters.apply_preset(
+            &ParameterPreset::new("Invalid")
+                .with_value("wurst", ParameterValue::Integer(1))
+                .with_value("steps", ParameterValue::Integer(100))
+                .with_value("mode", ParameterValue::String("sideways".to_string())),
+        );
+        assert_eq!(parameters[1].borrow().value(), 16.0);
+        assert_eq!(parameters[3].borrow().string_value(), "up");
+
+        // morphing
+        parameters.apply_morph(&intro, &drop, 0.25);
+        assert_eq!(parameters[0].borrow().value(), 0.0);
+        assert_eq!(parameters[1].borrow().value(), 7.0);
+        assert_eq!(parameters[2].borrow().value(), 0.625);
+        assert_eq!(parameters[3].borrow().string_value(), "up");
+        parameters.apply_morph(&intro, &drop, 0.5);
+        assert_eq!(parameters[0].borrow().value(), 1.0);
+        assert_eq!(parameters[3].borrow().string_value(), "down");
+        assert_eq!(intro.morph(&drop, 0.75).name(), "Drop");
+    }
+
+    #[test]
+    fn preset_text() -> Result<(), String> {
+        let parameters = new_parameters();
+        let presets = vec![
+            parameters.capture_preset("Intro"),
+            ParameterPreset::new("Drop")
+                .with_value("enabled", ParameterValue::Boolean(true))
+                .with_value("density", ParameterValue::Float(1.0))
+                .with_value("mode", ParameterValue::String("down".to_string())),
+        ];
+        let text = ParameterPreset::list_to_string(&presets);
+        assert_eq!(
+            text,
+            "[Intro]\nenabled = false\nsteps = 4\ndensity = 0.5\nmode = \"up\"\n\n\
+             [Drop]\nenabled = true\ndensity = 1.0\nmode = \"down\"\n"
+        );
+        assert_eq!(ParameterPreset::parse_list(&text)?, presets);
+
+        // comments and whitespace
+        assert_eq!(
+            ParameterPreset::try_from("# comment\n [ Verse ] \n  steps=  12 \n")?,
+            ParameterPreset::new("Verse").with_value("steps", ParameterValue::Integer(12))
+        );
+
+        // errors
+        assert!(ParameterPreset::try_from("").is_err());
+        assert!(ParameterPreset::try_from("[A]\n[B]").is_err());
+        assert!(ParameterPreset::parse_list("steps = 12").is_err());
+        assert!(ParameterPreset::parse_list("[A\nsteps = 12").is_err());
+        assert!(ParameterPreset::parse_list("[A]\nsteps = twelve").is_err());
+        assert!(ParameterPreset::parse_list("[A]\n = 1").is_err());
+        assert!(ParameterPreset::parse_list("[A]\nsteps").is_err());
+        assert!(ParameterPreset::parse_list("[A]\nsteps = [1, x]").is_err());
+        assert!(ParameterPreset::parse_list("[A]\nmode = \"a\\x\"").is_err());
+        assert!(ParameterPreset::parse_list("[A]\nmode = \"a\\\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn preset_text_escapes() -> Result<(), String> {
+        let presets = vec![ParameterPreset::new("Say \"Hi\"\n[2]")
+            .with_value("path", ParameterValue::String("C:\\Samples\\".to_string()))
+            .with_value(
+                "lyrics",
+                ParameterValue::String("one \"two\"\nthree\r\tfour".to_string()),
+            )];
+        let text = ParameterPreset::list_to_string(&presets);
+        assert_eq!(
+            text,
+            "[Say \\\"Hi\\\"\\n[2]]\n\
+             path = \"C:\\\\Samples\\\\\"\n\
+             lyrics = \"one \\\"two\\\"\\nthree\\r\\tfour\"\n"
+        );
+        assert_eq!(text.lines().count(), 3);
+        assert_eq!(ParameterPreset::parse_list(&text)?, presets);
+        Ok(())
+    }
+
+    #[test]
+    fn preset_value_types() -> Result<(), String> {
+        let parameters: ParameterSet = [
+            Parameter::with_note("root", "", "", Note::C0..=Note::G10, Note::C4),
+            Parameter::with_string("sample", "", "", "kick"),
+            Parameter::with_integer_array("steps", "", "", 0..=4, vec![0, 0, 0]),
+        ]
+        .into_iter()
+        .map(|parameter| Rc::new(RefCell::new(parameter)))
+        .collect();
+
+        let a =
+            ParameterPreset::try_from("[A]\nroot = 60\nsample = \"snare\"\nsteps = [4, 2, 10]")?;
+        parameters.apply_preset(&a);
+        assert_eq!(parameters[0].borrow().note_value(), Some(Note::C5));
+        assert_eq!(parameters[1].borrow().text(), "snare");
+        assert_eq!(parameters[2].borrow().array(), &[4.0, 2.0, 4.0]);
+
+        let b = parameters.capture_preset("B");
+        assert_eq!(
+            b.to_string(),
+            "[B]\nroot = 60\nsample = \"snare\"\nsteps = [4.0, 2.0, 4.0]\n"
+        );
+        assert_eq!(ParameterPreset::try_from(b.to_string().as_str())?, b);
+
+        // arrays morph item-wise and may be shorter than the parameter's array
+        parameters.apply_morph(
+            &b,
+            &ParameterPreset::new("C").with_value("steps", ParameterValue::Array(vec![0.0, 0.0])),
+            1.0,
+        );
+        assert_eq!(parameters[2].borrow().array(), &[0.0, 0.0, 4.0]);
+        parameters.apply_morph(
+            &b,
+            &ParameterPreset::new("C").with_value("steps", ParameterValue::Array(vec![0.0; 3])),
+            0.25,
+        );
+        assert_eq!(parameters[2].borrow().array(), &[3.0, 2.0, 3.0]);
+        Ok(())
+    }
+}
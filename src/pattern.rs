@@ -3,14 +3,18 @@
 
 use std::{cell::RefCell, fmt::Debug, rc::Rc};
 
-use crate::{BeatTimeBase, Event, EventTransform, ExactSampleTime, Parameter, SampleTime};
+use crate::{
+    BeatTimeBase, Event, EventTransform, ExactSampleTime, Parameter, SampleTime, TempoMap,
+};
 
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) mod generic;
 
 pub mod beat_time;
+pub mod dynamics;
 pub mod second_time;
+pub mod transformed;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -52,6 +56,9 @@ pub trait Pattern: Debug {
     fn time_base(&self) -> &BeatTimeBase;
     /// Update the pattern beat time bases with a new time base (e.g. on tempo changes).
     fn set_time_base(&mut self, time_base: &BeatTimeBase);
+    /// Set an optional tempo map, which changes the time base's constant tempo over time.
+    /// Patterns with wall-clock time steps ignore tempo maps. By default tempo maps are ignored.
+    fn set_tempo_map(&mut self, _tempo_map: Option<Rc<TempoMap>>) {}
 
     /// Length in *samples* of a *single time step* in the pattern's rhythm.
     fn step_length(&self) -> ExactSampleTime;
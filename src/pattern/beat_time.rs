@@ -19,6 +19,11 @@ impl GenericPatternTimeStep for BeatTimeStep {
         Self::Beats(1.0)
     }
 
+    #[inline]
+    fn beat_time_step(&self) -> Option<BeatTimeStep> {
+        Some(*self)
+    }
+
     #[inline]
     fn to_samples(&self, time_base: &BeatTimeBase) -> f64 {
         BeatTimeStep::to_samples(self, time_base)
@@ -52,6 +57,7 @@ impl BeatTimeBase {
     }
     generate_step_funcs!(sixteenth, BeatTimeStep::Sixteenth);
     generate_step_funcs!(eighth, BeatTimeStep::Eighth);
+    generate_step_funcs!(quarter, BeatTimeStep::Quarter);
     generate_step_funcs!(beat, BeatTimeStep::Beats);
     generate_step_funcs!(half, BeatTimeStep::Half);
     generate_step_funcs!(bar, BeatTimeStep::Bar);
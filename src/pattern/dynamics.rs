@@ -0,0 +1,157 @@
+//! Map pulse values of a `Rhythm` to note dynamics.
+
+// -------------------------------------------------------------------------------------------------
+
+/// Defines how a pattern interprets the pulse values of its [`Rhythm`](crate::Rhythm).
+///
+/// By default, pulse values are trigger probabilities, which get evaluated by the pattern's
+/// [`Gate`](crate::Gate). With the other modes, all pulse values > 0 trigger, and the pulse
+/// values define the volume of the emitted note events instead. Trigger probabilities then
+/// can be set via a separate probability rhythm in the pattern.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum PulseDynamics {
+    /// Pulse values are trigger probabilities.
+    #[default]
+    Probability,
+    /// Pulse values > 0 trigger and scale the volume of emitted note events.
+    Volume,
+    /// Pulse values > 0 trigger and select one of the given accent levels, which scale the
+    /// volume of emitted note events. The pulse value range (0 - 1] is split into equally sized
+    /// ranges, one for each level: e.g. with levels `[0.3, 1.0]`, pulse values <= 0.5 play ghost
+    /// notes and values > 0.5 play accents.
+    Accents(Vec<f32>),
+}
+
+impl PulseDynamics {
+    /// All dynamics mode names, as used in `TryFrom<&str>`.
+    pub fn names() -> Vec<&'static str> {
+        vec!["probability", "volume", "accents"]
+    }
+
+    /// Returns true when pulse values are used as dynamics instead of trigger probabilities.
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, PulseDynamics::Probability)
+    }
+
+    /// Value which gets passed to the pattern's gate for the given pulse value and probability.
+    pub fn gate_value(&self, pulse_value: f32, probability: f32) -> f32 {
+        if self.is_enabled() {
+            if pulse_value > 0.0 {
+                probability
+            } else {
+                0.0
+            }
+        } else {
+            pulse_value * probability
+        }
+    }
+
+    /// Volume factor for note events, which got triggered by the given pulse value.
+    /// Returns None when pulse values are not used as dynamics.
+    pub fn volume_factor(&self, pulse_value: f32) -> Option<f32> {
+        let value = pulse_value.clamp(0.0, 1.0);
+        match self {
+            PulseDynamics::Probability => None,
+            PulseDynamics::Volume => Some(value),
+            PulseDynamics::Accents(levels) => {
+                if levels.is_empty() {
+                    None
+                } else {
+                    let index = (value * levels.len() as f32).ceil() as usize;
+                    Some(levels[index.clamp(1, levels.len()) - 1])
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<&str> for PulseDynamics {
+    type Error = String;
+
+    /// Converts a mode name to dynamics. Accents use three default levels: ghost notes,
+    /// regular notes and accents.
+    fn try_from(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "probability" => Ok(Self::Probability),
+            "volume" | "velocity" => Ok(Self::Volume),
+            "accents" | "accent" => Ok(Self::Accents(vec![0.3, 0.7, 1.0])),
+            _ => Err(format!(
+                "invalid pulse dynamics '{}', valid modes are: {}",
+                name,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{prelude::*, PulseDynamics};
+
+    fn note_volumes(pattern: BeatTimePattern, count: usize) -> Vec<Option<f32>> {
+        pattern
+            .take(count)
+            .map(|event| match event.event {
+                Some(Event::NoteEvents(notes)) => notes[0].as_ref().map(|note| note.volume),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dynamics() {
+        let dynamics = PulseDynamics::try_from("accents").unwrap();
+        assert_eq!(dynamics.volume_factor(0.2), Some(0.3));
+        assert_eq!(dynamics.volume_factor(0.5), Some(0.7));
+        assert_eq!(dynamics.volume_factor(1.0), Some(1.0));
+        assert_eq!(dynamics.gate_value(0.2, 1.0), 1.0);
+        assert_eq!(dynamics.gate_value(0.0, 1.0), 0.0);
+        assert_eq!(PulseDynamics::Probability.volume_factor(0.5), None);
+        assert_eq!(PulseDynamics::Probability.gate_value(0.5, 0.5), 0.25);
+        assert!(PulseDynamics::try_from("loud").is_err());
+
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+
+        // by default, pulse values only drive the gate
+        let pattern = time_base
+            .every_nth_sixteenth(1.0)
+            .with_rhythm([1.0, 0.0, 0.5, 0.25].to_rhythm())
+            .with_gate(ThresholdGate::new())
+            .emit(new_note_emitter("c4"));
+        assert_eq!(
+            note_volumes(pattern, 4),
+            vec![Some(1.0), None, Some(1.0), Some(1.0)]
+        );
+
+        // with dynamics, pulse values scale volumes
+        let pattern = time_base
+            .every_nth_sixteenth(1.0)
+            .with_rhythm([1.0, 0.0, 0.5, 0.25].to_rhythm())
+            .with_dynamics(PulseDynamics::Volume)
+            .with_gate(ThresholdGate::new())
+            .emit(new_note_emitter("c4"));
+        assert_eq!(
+            note_volumes(pattern, 4),
+            vec![Some(1.0), None, Some(0.5), Some(0.25)]
+        );
+
+        // and a separate probability rhythm drives the gate
+        let pattern = time_base
+            .every_nth_sixteenth(1.0)
+            .with_rhythm([1.0, 0.0, 0.5, 0.25].to_rhythm())
+            .with_dynamics(PulseDynamics::Accents(vec![0.5, 1.0]))
+            .with_probability([1.0, 1.0, 0.0, 1.0].to_rhythm())
+            .with_gate(ThresholdGate::new())
+            .emit(new_note_emitter("c4"));
+        assert_eq!(
+            note_volumes(pattern, 4),
+            vec![Some(1.0), None, None, Some(0.5)]
+        );
+    }
+}
@@ -12,10 +12,11 @@ use crate::{
     emitter::{fixed::FixedEmitter, Emitter, EmitterEvent},
     event::{Event, InstrumentId},
     gate::threshold::ThresholdGate,
+    pattern::dynamics::PulseDynamics,
     rhythm::{fixed::FixedRhythm, Rhythm},
-    time::BeatTimeBase,
-    EventTransform, ExactSampleTime, Gate, Parameter, ParameterSet, Pattern, PatternEvent,
-    RhythmEvent, SampleTime,
+    time::{BeatTimeBase, BeatTimeStep},
+    EventTransform, ExactSampleTime, Gate, GateTempoMap, Parameter, ParameterSet, Pattern,
+    PatternEvent, RhythmEvent, SampleTime, TempoMap,
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -27,6 +28,10 @@ pub trait GenericPatternTimeStep: Debug + Clone + Copy + 'static {
     /// The step value of the `PatternTimeStep`. Usually some non `0` value.
     fn default_step() -> Self;
 
+    /// The time step as beat time step, when it's relative to the tempo and meter and thus
+    /// should follow tempo maps. None when it's an absolute wall-clock time (e.g. seconds).
+    fn beat_time_step(&self) -> Option<BeatTimeStep>;
+
     /// Converts the `PatternTimeStep` to an exact sample time.
     fn to_samples(&self, time_base: &BeatTimeBase) -> ExactSampleTime;
 }
@@ -39,6 +44,7 @@ pub trait GenericPatternTimeStep: Debug + Clone + Copy + 'static {
 /// Internal time units are generic, and will usually be beats or seconds.
 pub struct GenericPattern<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> {
     time_base: BeatTimeBase,
+    tempo_map: Option<Rc<TempoMap>>,
     step: Step,
     offset: Offset,
     instrument: Option<InstrumentId>,
@@ -47,6 +53,8 @@ pub struct GenericPattern<Step: GenericPatternTimeStep, Offset: GenericPatternTi
     rhythm_event: RhythmEvent,
     rhythm_repeat_count: Option<usize>,
     rhythm_playback_finished: bool,
+    dynamics: PulseDynamics,
+    probability: Option<Box<dyn Rhythm>>,
     gate: Box<dyn Gate>,
     emitter: Box<dyn Emitter>,
     emitter_sample_time: SampleTime,
@@ -62,6 +70,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Debug
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("GenericPattern")
             .field("time_base", &self.time_base)
+            .field("tempo_map", &self.tempo_map)
             .field("step", &self.step)
             .field("offset", &self.offset)
             .field("instrument", &self.instrument)
@@ -69,6 +78,8 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Debug
             .field("rhythm", &self.rhythm)
             .field("rhythm_repeat_count", &self.rhythm_repeat_count)
             .field("rhythm_playback_finished", &self.rhythm_playback_finished)
+            .field("dynamics", &self.dynamics)
+            .field("probability", &self.probability)
             .field("gate", &self.gate)
             .field("emitter", &self.emitter)
             // Skip event_transform, which has no Debug impl and event_iter state to reduce noise
@@ -80,6 +91,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Debug
 impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPattern<Step, Offset> {
     /// Create a new pattern which emits events every `beat_time_base` `step`.
     pub fn new(time_base: BeatTimeBase, step: Step) -> Self {
+        let tempo_map = None;
         let offset = Offset::default_offset();
         let instrument = None;
         let parameters = ParameterSet::new();
@@ -87,6 +99,8 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         let rhythm_event = RhythmEvent::default();
         let rhythm_repeat_count = None;
         let rhythm_playback_finished = false;
+        let dynamics = PulseDynamics::default();
+        let probability = None;
         let gate = Box::new(ThresholdGate::new());
         let emitter = Box::<FixedEmitter>::default();
         let emitter_sample_time = 0;
@@ -96,6 +110,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         let sample_offset = 0;
         Self {
             time_base,
+            tempo_map,
             step,
             offset,
             instrument,
@@ -104,6 +119,8 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
             rhythm_event,
             rhythm_repeat_count,
             rhythm_playback_finished,
+            dynamics,
+            probability,
             gate,
             emitter,
             emitter_sample_time,
@@ -118,6 +135,10 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
     pub fn time_base(&self) -> BeatTimeBase {
         self.time_base
     }
+    /// Get current tempo map, if any.
+    pub fn tempo_map(&self) -> Option<&TempoMap> {
+        self.tempo_map.as_deref()
+    }
     /// Get current step.
     pub fn step(&self) -> Step {
         self.step
@@ -130,6 +151,10 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
     pub fn rhythm(&self) -> &dyn Rhythm {
         self.rhythm.borrow()
     }
+    /// Get current pulse dynamics mode.
+    pub fn dynamics(&self) -> &PulseDynamics {
+        &self.dynamics
+    }
     /// Mut access the current rhythm (only allowed in tests).
     #[cfg(test)]
     pub(crate) fn rhythm_mut(&mut self) -> &mut dyn Rhythm {
@@ -164,8 +189,12 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         let mut new = self;
         new.parameters.clone_from(&parameters);
         new.rhythm.set_parameters(parameters.clone());
+        if let Some(probability) = &mut new.probability {
+            probability.set_parameters(parameters.clone());
+        }
         new.gate.set_parameters(parameters.clone());
         new.emitter.set_parameters(parameters);
+        new.update_gate_pulse_steps();
         new
     }
 
@@ -186,6 +215,34 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         new.rhythm.set_time_base(&time_base);
         new.rhythm.set_parameters(parameters);
         new.rhythm.set_repeat_count(repeat_count);
+        new.update_gate_pulse_steps();
+        new
+    }
+
+    /// Return a new pattern instance which interprets pulse values with the given dynamics mode.
+    #[must_use]
+    pub fn with_dynamics(self, dynamics: PulseDynamics) -> Self {
+        Self { dynamics, ..self }
+    }
+
+    /// Return a new pattern instance which uses the given [`Rhythm`] as probability channel:
+    /// each pulse of the pattern's rhythm consumes one pulse value from the probability rhythm,
+    /// which then scales the value that gets passed to the gate. See [`PulseDynamics`].
+    #[must_use]
+    pub fn with_probability<T: Rhythm + Sized + 'static>(self, probability: T) -> Self {
+        self.with_probability_dyn(Box::new(probability))
+    }
+
+    /// Return a new pattern instance which uses the given dyn [`Rhythm`] as probability channel.
+    #[must_use]
+    pub fn with_probability_dyn(self, probability: Box<dyn Rhythm>) -> Self {
+        let time_base = self.time_base;
+        let parameters = self.parameters.clone();
+        let mut new = self;
+        let mut probability = probability;
+        probability.set_time_base(&time_base);
+        probability.set_parameters(parameters);
+        new.probability = Some(probability);
         new
     }
 
@@ -196,6 +253,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         let mut new = self;
         new.rhythm_repeat_count = count;
         new.rhythm.set_repeat_count(count);
+        new.update_gate_pulse_steps();
         new
     }
 
@@ -214,6 +272,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         new.gate = gate;
         new.gate.set_time_base(&time_base);
         new.gate.set_parameters(parameters);
+        new.update_gate_pulse_steps();
         new
     }
 
@@ -235,6 +294,16 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         new
     }
 
+    /// Return a new pattern instance which follows the given tempo map instead of the time base's
+    /// constant tempo. Patterns with wall-clock time steps ignore tempo maps.
+    #[must_use]
+    pub fn with_tempo_map<T: Into<Option<Rc<TempoMap>>>>(self, tempo_map: T) -> Self {
+        let mut new = self;
+        new.tempo_map = tempo_map.into();
+        new.update_gate_pulse_steps();
+        new
+    }
+
     /// Return a new pattern instance which uses the given event transform function
     #[must_use]
     pub fn with_event_transform(self, transform: EventTransform) -> Self {
@@ -250,21 +319,91 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
         self.step.to_samples(&self.time_base) * self.rhythm_event.step_time
     }
 
+    /// Pass our step length in beats, the rhythm's length and the tempo map to the gate. Must be
+    /// called whenever the step, rhythm, tempo map or sample offset may have changed.
+    fn update_gate_pulse_steps(&mut self) {
+        let step_in_beats =
+            self.step.to_samples(&self.time_base) / self.time_base.samples_per_beat();
+        self.gate.set_pulse_steps(step_in_beats, self.rhythm.len());
+        let sample_offset = self.sample_offset as ExactSampleTime;
+        let tempo_map = self.tempo_map.clone().zip(self.step.beat_time_step());
+        let gate_tempo_map = tempo_map.map(|(tempo_map, step)| {
+            let start_beat = tempo_map.samples_to_beats(sample_offset);
+            GateTempoMap::new(tempo_map, &self.time_base, step, start_beat)
+        });
+        self.gate.set_tempo_map(gate_tempo_map.as_ref());
+    }
+
+    /// Return the tempo map and our step as beat time step, when the pattern should follow one.
+    #[inline]
+    fn active_tempo_map(&self) -> Option<(&TempoMap, BeatTimeStep)> {
+        self.tempo_map.as_deref().zip(self.step.beat_time_step())
+    }
+
+    /// Convert a pattern time, which is relative to the sample offset and based on the time
+    /// base's constant tempo, to an absolute sample time which follows the tempo map, if any.
+    #[inline]
+    fn sample_time(&self, time: ExactSampleTime) -> ExactSampleTime {
+        if let Some((tempo_map, step)) = self.active_tempo_map() {
+            let time_base = &self.time_base;
+            let offset = tempo_map.unmap_sample_time(time_base, &step, self.sample_offset as f64);
+            tempo_map.map_sample_time(time_base, &step, offset + time)
+        } else {
+            self.sample_offset as ExactSampleTime + time
+        }
+    }
+
+    /// Current pattern time in beats, relative to the sample offset, as used by parameter
+    /// automation. Follows the tempo map's meter, if any.
+    fn automation_time_in_beats(&self) -> f64 {
+        if let Some((tempo_map, step)) = self.active_tempo_map() {
+            let offset = tempo_map.samples_to_beats(self.sample_offset as ExactSampleTime);
+            let steps = self.emitter_next_sample_time / step.samples_per_step(&self.time_base);
+            tempo_map.steps_to_beats(&step, tempo_map.beats_to_steps(&step, offset) + steps)
+                - offset
+        } else {
+            self.emitter_next_sample_time / self.time_base.samples_per_beat()
+        }
+    }
+
     /// Return start sample time of the given emitter event start time.
     #[inline]
     fn event_iter_item_start_time(&self, start: &Fraction) -> SampleTime {
         let step_time = self.current_steps_sample_duration();
-        let event_iter_time = self.sample_offset as f64 + self.emitter_next_sample_time;
         let start = start.to_f64().unwrap_or(0.0);
-        (event_iter_time + (step_time * start)) as SampleTime
+        self.sample_time(self.emitter_next_sample_time + (step_time * start)) as SampleTime
     }
 
-    /// Return duration in sample time of the given emitter event length.
+    /// Return duration in sample time of the given emitter event start time and length.
     #[inline]
-    fn event_iter_item_duration(&self, length: &Fraction) -> SampleTime {
+    fn event_iter_item_duration(&self, start: &Fraction, length: &Fraction) -> SampleTime {
         let step_time = self.current_steps_sample_duration();
         let length = length.to_f64().unwrap_or(1.0);
-        (step_time * length) as SampleTime
+        if self.active_tempo_map().is_some() {
+            let start = self.emitter_next_sample_time + step_time * start.to_f64().unwrap_or(0.0);
+            let end = start + step_time * length;
+            (self.sample_time(end) - self.sample_time(start)) as SampleTime
+        } else {
+            (step_time * length) as SampleTime
+        }
+    }
+
+    /// Run the emitter with the given pulse and apply pulse dynamics to the emitted events.
+    fn run_emitter(&mut self, pulse: RhythmEvent, emit_event: bool) -> VecDeque<EmitterEvent> {
+        let mut events = self
+            .emitter
+            .run(pulse, emit_event)
+            .map_or_else(VecDeque::default, VecDeque::from);
+        if let Some(volume) = self.dynamics.volume_factor(pulse.value) {
+            for event_item in &mut events {
+                if let Event::NoteEvents(note_events) = &mut event_item.event {
+                    for note_event in note_events.iter_mut().flatten() {
+                        note_event.volume *= volume;
+                    }
+                }
+            }
+        }
+        events
     }
 
     /// Set a default instrument, if set, and apply event transform functions.
@@ -286,8 +425,24 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
             self.events.is_empty(),
             "Should only run rhythms when there are no pending emitter items"
         );
+        // update automated parameters before the rhythm, gate and emitter read them
+        if !self.parameters.is_empty() {
+            let time_in_beats = self.automation_time_in_beats();
+            for parameter in &self.parameters {
+                RefCell::borrow_mut(parameter).update_automation(time_in_beats);
+            }
+        }
         if let Some(event) = self.rhythm.run() {
-            let emit_event = self.gate.run(&event);
+            let probability = self
+                .probability
+                .as_mut()
+                .and_then(|probability| probability.run())
+                .map_or(1.0, |probability| probability.value);
+            let gate_event = RhythmEvent {
+                value: self.dynamics.gate_value(event.value, probability),
+                ..event
+            };
+            let emit_event = self.gate.run(&gate_event);
             self.rhythm_event = event;
             Some((event, emit_event))
         } else {
@@ -301,7 +456,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
             return None;
         }
         // quickly check if the next event is due before the given target time
-        let next_sample_time = self.sample_offset + self.emitter_next_sample_time as SampleTime;
+        let next_sample_time = self.sample_time(self.emitter_next_sample_time) as SampleTime;
         if next_sample_time >= sample_time {
             // next event is not yet due
             return None;
@@ -315,10 +470,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
             // generate a pulse from the pattern and pass the pulse to the gate
             if let Some((pulse, emit_event)) = self.run_rhythm() {
                 // generate new events from the gated pulse
-                self.events = self
-                    .emitter
-                    .run(pulse, emit_event)
-                    .map_or_else(VecDeque::default, VecDeque::from);
+                self.events = self.run_emitter(pulse, emit_event);
             } else {
                 // pattern playback finished
                 self.rhythm_playback_finished = true;
@@ -338,7 +490,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
                 return None;
             }
             let event = Some(event_item.event);
-            let duration = self.event_iter_item_duration(&event_item.length);
+            let duration = self.event_iter_item_duration(&event_item.start, &event_item.length);
             // advance to the next pulse in the next iteration when all events got consumed
             if self.events.is_empty() {
                 self.emitter_next_sample_time += self.current_steps_sample_duration();
@@ -354,7 +506,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> GenericPatter
             let time = self.event_iter_item_start_time(&Fraction::ZERO);
             debug_assert!(time < sample_time, "Event should be due here");
             let event = None;
-            let duration = self.event_iter_item_duration(&Fraction::ONE);
+            let duration = self.event_iter_item_duration(&Fraction::ZERO, &Fraction::ONE);
             // advance to the next pulse in the next iteration
             self.emitter_next_sample_time += self.current_steps_sample_duration();
             // return event as rhythm iter item
@@ -374,10 +526,16 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Clone
         Self {
             parameters: self.parameters.clone(),
             rhythm: self.rhythm.duplicate(),
+            dynamics: self.dynamics.clone(),
+            probability: self
+                .probability
+                .as_ref()
+                .map(|probability| probability.duplicate()),
             emitter: self.emitter.duplicate(),
             events: self.events.clone(),
             event_transform: self.event_transform.clone(),
             gate: self.gate.duplicate(),
+            tempo_map: self.tempo_map.clone(),
             ..*self
         }
     }
@@ -410,9 +568,17 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Pattern
         self.time_base.clone_from(time_base);
         // update pattern, gate and emitter
         self.rhythm.set_time_base(time_base);
+        if let Some(probability) = &mut self.probability {
+            probability.set_time_base(time_base);
+        }
         self.gate.set_time_base(time_base);
+        self.update_gate_pulse_steps();
         self.emitter.set_time_base(time_base);
     }
+    fn set_tempo_map(&mut self, tempo_map: Option<Rc<TempoMap>>) {
+        self.tempo_map = tempo_map;
+        self.update_gate_pulse_steps();
+    }
 
     fn step_length(&self) -> ExactSampleTime {
         self.step.to_samples(&self.time_base)
@@ -427,7 +593,11 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Pattern
 
     fn set_trigger_event(&mut self, event: &Event) {
         self.rhythm.set_trigger_event(event);
+        if let Some(probability) = &mut self.probability {
+            probability.set_trigger_event(event);
+        }
         self.gate.set_trigger_event(event);
+        self.update_gate_pulse_steps();
         self.emitter.set_trigger_event(event);
     }
 
@@ -440,6 +610,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Pattern
     }
     fn set_sample_offset(&mut self, sample_offset: SampleTime) {
         self.sample_offset = sample_offset;
+        self.update_gate_pulse_steps();
     }
 
     fn run_until_time(&mut self, sample_time: SampleTime) -> Option<PatternEvent> {
@@ -471,8 +642,7 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Pattern
         // batch advance events in full pulse steps
         loop {
             // quickly check if the next event is due before the given target time
-            let next_sample_time =
-                self.sample_offset as ExactSampleTime + self.emitter_next_sample_time;
+            let next_sample_time = self.sample_time(self.emitter_next_sample_time);
             if (next_sample_time as SampleTime) >= sample_time {
                 // next event is not yet due: we're done
                 return;
@@ -481,16 +651,15 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Pattern
             if let Some((pulse, emit_event)) = self.run_rhythm() {
                 // test if the event crosses the target time
                 let step_duration = self.current_steps_sample_duration();
-                if ((next_sample_time + step_duration) as SampleTime) < sample_time {
+                let next_step_sample_time =
+                    self.sample_time(self.emitter_next_sample_time + step_duration);
+                if (next_step_sample_time as SampleTime) < sample_time {
                     // skip all events from the gated pulse
                     self.emitter.advance(pulse, emit_event);
                     self.emitter_next_sample_time += step_duration;
                 } else {
                     // generate new events from the gated pulse
-                    self.events = self
-                        .emitter
-                        .run(pulse, emit_event)
-                        .map_or_else(VecDeque::default, VecDeque::from);
+                    self.events = self.run_emitter(pulse, emit_event);
                     // when the remaining step is empty advance to next step
                     if self.events.is_empty() {
                         self.emitter_next_sample_time += self.current_steps_sample_duration();
@@ -523,7 +692,11 @@ impl<Step: GenericPatternTimeStep, Offset: GenericPatternTimeStep> Pattern
         // reset pattern and gate
         self.rhythm.reset();
         self.rhythm_playback_finished = false;
+        if let Some(probability) = &mut self.probability {
+            probability.reset();
+        }
         self.gate.reset();
+        self.update_gate_pulse_steps();
         // reset iterator state
         self.emitter.reset();
         self.emitter_sample_time = 0;
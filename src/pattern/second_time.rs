@@ -3,7 +3,7 @@
 use crate::{
     pattern::generic::{GenericPattern, GenericPatternTimeStep},
     prelude::SampleTimeBase,
-    time::SecondTimeStep,
+    time::{BeatTimeStep, SecondTimeStep},
     BeatTimeBase, ExactSampleTime,
 };
 
@@ -20,6 +20,11 @@ impl GenericPatternTimeStep for SecondTimeStep {
         1.0
     }
 
+    #[inline]
+    fn beat_time_step(&self) -> Option<BeatTimeStep> {
+        None
+    }
+
     #[inline]
     fn to_samples(&self, time_base: &BeatTimeBase) -> ExactSampleTime {
         *self as ExactSampleTime * time_base.samples_per_second() as ExactSampleTime
@@ -0,0 +1,888 @@
+//! Transform the event stream of a `Pattern` cycle by cycle.
+
+use std::{cell::RefCell, collections::VecDeque, fmt::Debug, rc::Rc};
+
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{
+    BeatTimeBase, BeatTimeStep, Event, EventTransform, ExactSampleTime, InstrumentId, Parameter,
+    ParameterSet, Pattern, PatternEvent, SampleTime, TempoMap,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Transforms the events of a source [`Pattern`] cycle by cycle.
+///
+/// A cycle has the length of a full source pattern's rhythm run:
+/// `source.step_count() * source.step_length()`. Transforms get called with the cycle's sample
+/// time range and must return all events within that range, sorted by time.
+pub trait PatternTransform: Debug {
+    /// Speed factor which gets applied to the source pattern's step length. By default 1.
+    fn speed(&self) -> f64 {
+        1.0
+    }
+
+    /// Run the source pattern, and return transformed events for the given cycle time range.
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent>;
+
+    /// Collect parameters of additional patterns the transform uses, if any.
+    fn parameters(&self) -> ParameterSet {
+        ParameterSet::new()
+    }
+    /// Update time bases of additional patterns the transform uses, if any.
+    fn set_time_base(&mut self, _time_base: &BeatTimeBase) {}
+    /// Update trigger events of additional patterns the transform uses, if any.
+    fn set_trigger_event(&mut self, _event: &Event) {}
+
+    /// Create a new cloned instance of this transform.
+    fn duplicate(&self) -> Box<dyn PatternTransform>;
+    /// Resets the transform and additional patterns it uses, if any, to their initial state.
+    fn reset(&mut self) {}
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A [`Pattern`] which applies a [`PatternTransform`] to the events of a source pattern,
+/// e.g. to speed up, reverse or randomly thin out the source pattern's events.
+pub struct TransformedPattern {
+    source: Rc<RefCell<dyn Pattern>>,
+    transform: Box<dyn PatternTransform>,
+    time_base: BeatTimeBase,
+    tempo_map: Option<Rc<TempoMap>>,
+    instrument: Option<InstrumentId>,
+    parameters: ParameterSet,
+    cycle: u64,
+    events: VecDeque<PatternEvent>,
+    event_transform: Option<EventTransform>,
+    sample_offset: SampleTime,
+}
+
+impl TransformedPattern {
+    /// Max number of empty cycles to run in a single `run_until_time` call before giving up.
+    /// Source patterns which finished playback no longer emit events.
+    const MAX_EMPTY_CYCLES: usize = 256;
+
+    /// Step unit which is used to apply tempo maps. Transformed patterns follow tempo changes,
+    /// but not the bar lengths of time signature changes.
+    const TEMPO_MAP_STEP: BeatTimeStep = BeatTimeStep::Sixteenth(1.0);
+
+    /// Create a new transformed pattern with the given source and transform.
+    pub fn new(source: Rc<RefCell<dyn Pattern>>, transform: Box<dyn PatternTransform>) -> Self {
+        source.borrow_mut().reset();
+        let time_base = *source.borrow().time_base();
+        let tempo_map = None;
+        let instrument = None;
+        let mut parameters = ParameterSet::new();
+        for param in source
+            .borrow()
+            .parameters()
+            .iter()
+            .chain(transform.parameters().iter())
+        {
+            // silently skip duplicate parameter ids
+            if !parameters
+                .iter()
+                .any(|p| p.borrow().id() == param.borrow().id())
+            {
+                parameters.push(Rc::clone(param));
+            }
+        }
+        let cycle = 0;
+        let events = VecDeque::new();
+        let event_transform = None;
+        let sample_offset = 0;
+        Self {
+            source,
+            transform,
+            time_base,
+            tempo_map,
+            instrument,
+            parameters,
+            cycle,
+            events,
+            event_transform,
+            sample_offset,
+        }
+    }
+
+    /// Speed up (factor > 1) or slow down (factor < 1) the source pattern by the given factor.
+    ///
+    /// ### Panics
+    /// Panics if the given factor is not > 0.
+    pub fn with_speed(source: Rc<RefCell<dyn Pattern>>, factor: f64) -> Self {
+        assert!(factor > 0.0, "Invalid speed factor");
+        Self::new(source, Box::new(SpeedTransform { factor }))
+    }
+
+    /// Reverse the source pattern's events within each cycle.
+    pub fn reversed(source: Rc<RefCell<dyn Pattern>>) -> Self {
+        Self::new(source, Box::new(ReverseTransform {}))
+    }
+
+    /// Repeat each source pattern event `count` times within the event's duration.
+    ///
+    /// ### Panics
+    /// Panics if the given count is 0.
+    pub fn plied(source: Rc<RefCell<dyn Pattern>>, count: usize) -> Self {
+        assert!(count > 0, "Invalid ply count");
+        Self::new(source, Box::new(PlyTransform { count }))
+    }
+
+    /// Randomly replace source pattern events with rests, using the given `amount` as
+    /// probability. The random choice is seeded by the given seed and the event's time.
+    pub fn degraded(source: Rc<RefCell<dyn Pattern>>, amount: f64, seed: u64) -> Self {
+        let inverted = false;
+        Self::new(
+            source,
+            Box::new(DegradeTransform {
+                amount,
+                seed,
+                inverted,
+            }),
+        )
+    }
+
+    /// Inverse of [`Self::degraded`]: keeps exactly those events which `degraded` would drop,
+    /// when using the same amount and seed.
+    pub fn undegraded(source: Rc<RefCell<dyn Pattern>>, amount: f64, seed: u64) -> Self {
+        let inverted = true;
+        Self::new(
+            source,
+            Box::new(DegradeTransform {
+                amount,
+                seed,
+                inverted,
+            }),
+        )
+    }
+
+    /// Delay the source pattern's events by the given fraction of a cycle.
+    ///
+    /// ### Panics
+    /// Panics if the given fraction is negative.
+    pub fn shifted(source: Rc<RefCell<dyn Pattern>>, fraction: f64) -> Self {
+        assert!(fraction >= 0.0, "Invalid shift amount");
+        Self::new(source, Box::new(ShiftTransform { fraction }))
+    }
+
+    /// Use events from the `transformed` pattern every `count` cycles, starting with the first
+    /// cycle, and the source pattern's events in all other cycles.
+    ///
+    /// ### Panics
+    /// Panics if the given count is 0.
+    pub fn every(
+        source: Rc<RefCell<dyn Pattern>>,
+        count: usize,
+        transformed: Rc<RefCell<dyn Pattern>>,
+    ) -> Self {
+        assert!(count > 0, "Invalid every count");
+        transformed.borrow_mut().reset();
+        Self::new(source, Box::new(EveryTransform { count, transformed }))
+    }
+
+    /// Play the source and the other pattern together.
+    pub fn overlaid(source: Rc<RefCell<dyn Pattern>>, other: Rc<RefCell<dyn Pattern>>) -> Self {
+        other.borrow_mut().reset();
+        Self::new(source, Box::new(OverlayTransform { other }))
+    }
+
+    /// Return a new pattern instance which uses the given instrument for all note events
+    /// which have no instrument set.
+    #[must_use]
+    pub fn with_instrument<I: Into<Option<InstrumentId>>>(self, instrument: I) -> Self {
+        let instrument = instrument.into();
+        Self { instrument, ..self }
+    }
+
+    /// Length of a single cycle in samples, with the transform's speed applied.
+    fn cycle_length(&self) -> ExactSampleTime {
+        self.step_count().max(1) as ExactSampleTime * self.step_length()
+    }
+
+    /// Convert the given absolute sample time to a local time, relative to the sample offset.
+    /// Source patterns run with the time base's constant tempo, so this applies the tempo map.
+    fn local_time(&self, sample_time: SampleTime) -> SampleTime {
+        if let Some(tempo_map) = &self.tempo_map {
+            let (time_base, step) = (&self.time_base, &Self::TEMPO_MAP_STEP);
+            let time = tempo_map.unmap_sample_time(time_base, step, sample_time as f64);
+            let offset = tempo_map.unmap_sample_time(time_base, step, self.sample_offset as f64);
+            (time - offset).max(0.0) as SampleTime
+        } else {
+            sample_time - self.sample_offset
+        }
+    }
+
+    /// Convert the given local event time and duration to absolute sample times.
+    fn global_event(&self, event: PatternEvent) -> PatternEvent {
+        if let Some(tempo_map) = &self.tempo_map {
+            let (time_base, step) = (&self.time_base, &Self::TEMPO_MAP_STEP);
+            let offset = tempo_map.unmap_sample_time(time_base, step, self.sample_offset as f64);
+            let start = offset + event.time as ExactSampleTime;
+            let end = start + event.duration as ExactSampleTime;
+            let time = tempo_map.map_sample_time(time_base, step, start) as SampleTime;
+            let end = tempo_map.map_sample_time(time_base, step, end) as SampleTime;
+            PatternEvent {
+                time,
+                duration: end.saturating_sub(time),
+                ..event
+            }
+        } else {
+            event.with_offset(self.sample_offset)
+        }
+    }
+
+    /// Set a default instrument, if set, and apply event transform functions.
+    fn apply_event_transform(&self, pattern_event: &mut PatternEvent) {
+        if let Some(event) = &mut pattern_event.event {
+            if let Some(instrument) = self.instrument {
+                if let Event::NoteEvents(note_events) = event {
+                    for note_event in note_events.iter_mut().flatten() {
+                        note_event.instrument = note_event.instrument.or(Some(instrument));
+                    }
+                }
+            }
+            if let Some(transform) = &self.event_transform {
+                transform(event);
+            }
+        }
+    }
+}
+
+impl Clone for TransformedPattern {
+    fn clone(&self) -> Self {
+        Self {
+            source: self.source.borrow().duplicate(),
+            transform: self.transform.duplicate(),
+            time_base: self.time_base,
+            tempo_map: self.tempo_map.clone(),
+            instrument: self.instrument,
+            parameters: self.parameters.clone(),
+            cycle: self.cycle,
+            events: self.events.clone(),
+            event_transform: self.event_transform.clone(),
+            sample_offset: self.sample_offset,
+        }
+    }
+}
+
+impl Debug for TransformedPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransformedPattern")
+            .field("source", &self.source)
+            .field("transform", &self.transform)
+            .field("instrument", &self.instrument)
+            .field("parameters", &self.parameters)
+            .field("cycle", &self.cycle)
+            // Skip event_transform, which has no Debug impl and events to reduce noise
+            .field("sample_offset", &self.sample_offset)
+            .finish()
+    }
+}
+
+impl Pattern for TransformedPattern {
+    fn time_base(&self) -> &BeatTimeBase {
+        &self.time_base
+    }
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.time_base = *time_base;
+        self.source.borrow_mut().set_time_base(time_base);
+        self.transform.set_time_base(time_base);
+    }
+    fn set_tempo_map(&mut self, tempo_map: Option<Rc<TempoMap>>) {
+        // source patterns keep running with the constant tempo: the map is applied to our events
+        self.tempo_map = tempo_map;
+    }
+
+    fn step_length(&self) -> ExactSampleTime {
+        self.source.borrow().step_length() / self.transform.speed()
+    }
+    fn step_count(&self) -> usize {
+        self.source.borrow().step_count()
+    }
+
+    fn parameters(&self) -> &[Rc<RefCell<Parameter>>] {
+        &self.parameters
+    }
+
+    fn set_trigger_event(&mut self, event: &Event) {
+        self.source.borrow_mut().set_trigger_event(event);
+        self.transform.set_trigger_event(event);
+    }
+
+    fn set_event_transform(&mut self, transform: Option<EventTransform>) {
+        self.event_transform = transform;
+    }
+
+    fn sample_offset(&self) -> SampleTime {
+        self.sample_offset
+    }
+    fn set_sample_offset(&mut self, sample_offset: SampleTime) {
+        self.sample_offset = sample_offset;
+    }
+
+    fn run_until_time(&mut self, sample_time: SampleTime) -> Option<PatternEvent> {
+        if sample_time <= self.sample_offset {
+            return None;
+        }
+        let local_time = self.local_time(sample_time);
+        // run cycles until we got a due event, or until the next cycle is not yet due
+        let cycle_length = self.cycle_length();
+        let mut empty_cycles = 0;
+        while self
+            .events
+            .front()
+            .is_none_or(|event| event.time >= local_time)
+        {
+            let cycle_start = (self.cycle as ExactSampleTime * cycle_length) as SampleTime;
+            if cycle_start >= local_time || empty_cycles >= Self::MAX_EMPTY_CYCLES {
+                break;
+            }
+            let cycle_end = ((self.cycle + 1) as ExactSampleTime * cycle_length) as SampleTime;
+            let events = self.transform.run(
+                &mut *self.source.borrow_mut(),
+                self.cycle,
+                (cycle_start, cycle_end),
+            );
+            if events.is_empty() {
+                empty_cycles += 1;
+            }
+            self.events.extend(events);
+            self.cycle += 1;
+        }
+        // emit next due event
+        if self
+            .events
+            .front()
+            .is_some_and(|event| event.time < local_time)
+        {
+            let mut event = self.events.pop_front().unwrap();
+            self.apply_event_transform(&mut event);
+            Some(self.global_event(event))
+        } else {
+            None
+        }
+    }
+
+    fn duplicate(&self) -> Rc<RefCell<dyn Pattern>> {
+        Rc::new(RefCell::new(self.clone()))
+    }
+
+    fn reset(&mut self) {
+        // reset sample offset
+        self.sample_offset = 0;
+        // reset iterator state
+        self.cycle = 0;
+        self.events.clear();
+        // reset source and transform
+        self.source.borrow_mut().reset();
+        self.transform.reset();
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Run the given pattern until the given sample time and collect all emitted events.
+fn events_until_time(pattern: &mut dyn Pattern, sample_time: SampleTime) -> Vec<PatternEvent> {
+    let mut events = Vec::new();
+    while let Some(event) = pattern.run_until_time(sample_time) {
+        events.push(event);
+    }
+    events
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Speeds up or slows down the source pattern.
+#[derive(Debug, Clone)]
+struct SpeedTransform {
+    factor: f64,
+}
+
+impl PatternTransform for SpeedTransform {
+    fn speed(&self) -> f64 {
+        self.factor
+    }
+
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        _cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        let source_end = (range.1 as f64 * self.factor) as SampleTime;
+        events_until_time(source, source_end)
+            .into_iter()
+            .map(|event| PatternEvent {
+                time: (event.time as f64 / self.factor) as SampleTime,
+                duration: (event.duration as f64 / self.factor) as SampleTime,
+                ..event
+            })
+            .collect()
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Reverses the source pattern events within each cycle.
+#[derive(Debug, Clone)]
+struct ReverseTransform {}
+
+impl PatternTransform for ReverseTransform {
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        _cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        let (start, end) = range;
+        let mut events = events_until_time(source, end)
+            .into_iter()
+            .map(|event| {
+                let event_end = (event.time + event.duration).min(end);
+                PatternEvent {
+                    time: start + (end - event_end),
+                    ..event
+                }
+            })
+            .collect::<Vec<_>>();
+        // NB: stable sort: keep order of events with the same time
+        events.sort_by_key(|event| event.time);
+        events
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Repeats each source pattern event within the event's duration.
+#[derive(Debug, Clone)]
+struct PlyTransform {
+    count: usize,
+}
+
+impl PatternTransform for PlyTransform {
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        _cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        let mut events = Vec::new();
+        for event in events_until_time(source, range.1) {
+            if event.event.is_some() {
+                let duration = event.duration / self.count as SampleTime;
+                for index in 0..self.count {
+                    events.push(PatternEvent {
+                        time: event.time + index as SampleTime * duration,
+                        event: event.event.clone(),
+                        duration,
+                    });
+                }
+            } else {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Randomly replaces source pattern events with rests.
+#[derive(Debug, Clone)]
+struct DegradeTransform {
+    amount: f64,
+    seed: u64,
+    inverted: bool,
+}
+
+impl DegradeTransform {
+    /// Deterministic random number in range \[0, 1) for the given event time.
+    fn random(&self, time: SampleTime) -> f64 {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(self.seed ^ time);
+        rng.random()
+    }
+}
+
+impl PatternTransform for DegradeTransform {
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        _cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        events_until_time(source, range.1)
+            .into_iter()
+            .map(|event| {
+                let dropped = self.random(event.time) < self.amount;
+                if dropped != self.inverted {
+                    PatternEvent {
+                        event: None,
+                        ..event
+                    }
+                } else {
+                    event
+                }
+            })
+            .collect()
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Delays the source pattern events by a fraction of a cycle.
+#[derive(Debug, Clone)]
+struct ShiftTransform {
+    fraction: f64,
+}
+
+impl PatternTransform for ShiftTransform {
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        _cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        let (start, end) = range;
+        let cycle_length = (end - start) as f64;
+        let shift = (self.fraction * cycle_length) as SampleTime;
+        events_until_time(source, end.saturating_sub(shift))
+            .into_iter()
+            .map(|event| event.with_offset(shift))
+            .collect()
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(self.clone())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Uses events of a transformed pattern every nth cycle.
+#[derive(Debug)]
+struct EveryTransform {
+    count: usize,
+    transformed: Rc<RefCell<dyn Pattern>>,
+}
+
+impl PatternTransform for EveryTransform {
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        let mut transformed = self.transformed.borrow_mut();
+        if cycle.is_multiple_of(self.count as u64) {
+            source.advance_until_time(range.1);
+            events_until_time(&mut *transformed, range.1)
+        } else {
+            transformed.advance_until_time(range.1);
+            events_until_time(source, range.1)
+        }
+    }
+
+    fn parameters(&self) -> ParameterSet {
+        self.transformed.borrow().parameters().to_vec()
+    }
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.transformed.borrow_mut().set_time_base(time_base);
+    }
+    fn set_trigger_event(&mut self, event: &Event) {
+        self.transformed.borrow_mut().set_trigger_event(event);
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(Self {
+            count: self.count,
+            transformed: self.transformed.borrow().duplicate(),
+        })
+    }
+    fn reset(&mut self) {
+        self.transformed.borrow_mut().reset();
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Plays another pattern together with the source pattern.
+#[derive(Debug)]
+struct OverlayTransform {
+    other: Rc<RefCell<dyn Pattern>>,
+}
+
+impl PatternTransform for OverlayTransform {
+    fn run(
+        &mut self,
+        source: &mut dyn Pattern,
+        _cycle: u64,
+        range: (SampleTime, SampleTime),
+    ) -> Vec<PatternEvent> {
+        let mut events = events_until_time(source, range.1);
+        events.append(&mut events_until_time(
+            &mut *self.other.borrow_mut(),
+            range.1,
+        ));
+        // NB: stable sort: source events come first when times are equal
+        events.sort_by_key(|event| event.time);
+        events
+    }
+
+    fn parameters(&self) -> ParameterSet {
+        self.other.borrow().parameters().to_vec()
+    }
+    fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        self.other.borrow_mut().set_time_base(time_base);
+    }
+    fn set_trigger_event(&mut self, event: &Event) {
+        self.other.borrow_mut().set_trigger_event(event);
+    }
+
+    fn duplicate(&self) -> Box<dyn PatternTransform> {
+        Box::new(Self {
+            other: self.other.borrow().duplicate(),
+        })
+    }
+    fn reset(&mut self) {
+        self.other.borrow_mut().reset();
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{prelude::*, PatternEvent};
+
+    fn new_source() -> Rc<RefCell<dyn Pattern>> {
+        let beat_time = BeatTimeBase {
+            samples_per_sec: 44100,
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+        };
+        let pattern = beat_time
+            .every_nth_beat(1.0)
+            .with_rhythm([1, 1, 1, 1].to_rhythm())
+            .emit(new_note_sequence_emitter(vec![
+                Some("c4"),
+                Some("d4"),
+                Some("e4"),
+                Some("f4"),
+            ]));
+        Rc::new(RefCell::new(pattern))
+    }
+
+    fn run_pattern(pattern: &mut dyn Pattern, sample_time: SampleTime) -> Vec<PatternEvent> {
+        let mut events = Vec::new();
+        while let Some(event) = pattern.run_until_time(sample_time) {
+            events.push(event);
+        }
+        events
+    }
+
+    fn notes(events: &[PatternEvent]) -> Vec<(SampleTime, Option<Note>)> {
+        events
+            .iter()
+            .map(|event| {
+                let note = match &event.event {
+                    Some(Event::NoteEvents(notes)) => notes[0].as_ref().map(|n| n.note),
+                    _ => None,
+                };
+                (event.time, note)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn speed() {
+        let mut fast = TransformedPattern::with_speed(new_source(), 2.0);
+        assert_eq!(fast.step_length(), 11025.0);
+        assert_eq!(
+            notes(&run_pattern(&mut fast, 88200)),
+            vec![
+                (0, Some(Note::C4)),
+                (11025, Some(Note::D4)),
+                (22050, Some(Note::E4)),
+                (33075, Some(Note::F4)),
+                (44100, Some(Note::C4)),
+                (55125, Some(Note::D4)),
+                (66150, Some(Note::E4)),
+                (77175, Some(Note::F4)),
+            ]
+        );
+        let mut slow = TransformedPattern::with_speed(new_source(), 0.5);
+        assert_eq!(
+            notes(&run_pattern(&mut slow, 88200 * 2)),
+            vec![
+                (0, Some(Note::C4)),
+                (44100, Some(Note::D4)),
+                (88200, Some(Note::E4)),
+                (132300, Some(Note::F4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reverse_and_ply() {
+        let mut reversed = TransformedPattern::reversed(new_source());
+        assert_eq!(
+            notes(&run_pattern(&mut reversed, 88200 * 2)),
+            vec![
+                (0, Some(Note::F4)),
+                (22050, Some(Note::E4)),
+                (44100, Some(Note::D4)),
+                (66150, Some(Note::C4)),
+                (88200, Some(Note::F4)),
+                (110250, Some(Note::E4)),
+                (132300, Some(Note::D4)),
+                (154350, Some(Note::C4)),
+            ]
+        );
+        let mut plied = TransformedPattern::plied(new_source(), 2);
+        let events = run_pattern(&mut plied, 44100);
+        assert_eq!(
+            notes(&events),
+            vec![
+                (0, Some(Note::C4)),
+                (11025, Some(Note::C4)),
+                (22050, Some(Note::D4)),
+                (33075, Some(Note::D4)),
+            ]
+        );
+        assert!(events.iter().all(|event| event.duration == 11025));
+    }
+
+    #[test]
+    fn degrade() {
+        let seed = 1234;
+        let mut degraded = TransformedPattern::degraded(new_source(), 0.5, seed);
+        let mut undegraded = TransformedPattern::undegraded(new_source(), 0.5, seed);
+        let degraded_events = run_pattern(&mut degraded, 88200 * 8);
+        let undegraded_events = run_pattern(&mut undegraded, 88200 * 8);
+        assert_eq!(degraded_events.len(), 32);
+        assert_eq!(undegraded_events.len(), 32);
+        // each event is played either by the degraded or the undegraded pattern
+        for (a, b) in degraded_events.iter().zip(undegraded_events.iter()) {
+            assert_eq!(a.time, b.time);
+            assert!(a.event.is_some() != b.event.is_some());
+        }
+        assert!(degraded_events.iter().any(|event| event.event.is_none()));
+        assert!(degraded_events.iter().any(|event| event.event.is_some()));
+        // degrading is deterministic
+        degraded.reset();
+        assert_eq!(run_pattern(&mut degraded, 88200 * 8), degraded_events);
+        // amounts of 0 and 1 keep or drop all events
+        let mut all = TransformedPattern::degraded(new_source(), 0.0, seed);
+        assert!(run_pattern(&mut all, 88200)
+            .iter()
+            .all(|event| event.event.is_some()));
+        let mut none = TransformedPattern::degraded(new_source(), 1.0, seed);
+        assert!(run_pattern(&mut none, 88200)
+            .iter()
+            .all(|event| event.event.is_none()));
+    }
+
+    #[test]
+    fn every_and_off() {
+        let source = new_source();
+        let reversed = Rc::new(RefCell::new(TransformedPattern::reversed(
+            source.borrow().duplicate(),
+        )));
+        let mut every = TransformedPattern::every(source, 2, reversed);
+        let every_notes = notes(&run_pattern(&mut every, 88200 * 3))
+            .into_iter()
+            .map(|(_, note)| note.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            every_notes,
+            vec![
+                Note::F4,
+                Note::E4,
+                Note::D4,
+                Note::C4,
+                Note::C4,
+                Note::D4,
+                Note::E4,
+                Note::F4,
+                Note::F4,
+                Note::E4,
+                Note::D4,
+                Note::C4,
+            ]
+        );
+
+        let source = new_source();
+        let shifted = Rc::new(RefCell::new(TransformedPattern::shifted(
+            source.borrow().duplicate(),
+            0.125,
+        )));
+        let mut off = TransformedPattern::overlaid(source, shifted);
+        off.set_sample_offset(100);
+        assert_eq!(
+            notes(&run_pattern(&mut off, 100 + 44100)),
+            vec![
+                (100, Some(Note::C4)),
+                (100 + 11025, Some(Note::C4)),
+                (100 + 22050, Some(Note::D4)),
+                (100 + 33075, Some(Note::D4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn speed_and_every() {
+        let fast = Rc::new(RefCell::new(TransformedPattern::with_speed(
+            new_source(),
+            2.0,
+        )));
+        let reversed = Rc::new(RefCell::new(TransformedPattern::reversed(
+            fast.borrow().duplicate(),
+        )));
+        let mut every = TransformedPattern::every(fast, 2, reversed);
+        assert_eq!(every.cycle_length(), 44100.0);
+        assert_eq!(
+            notes(&run_pattern(&mut every, 88200)),
+            vec![
+                (0, Some(Note::F4)),
+                (11025, Some(Note::E4)),
+                (22050, Some(Note::D4)),
+                (33075, Some(Note::C4)),
+                (44100, Some(Note::C4)),
+                (55125, Some(Note::D4)),
+                (66150, Some(Note::E4)),
+                (77175, Some(Note::F4)),
+            ]
+        );
+        // speed transformed cycles run on the speed adjusted period
+        let mut fast = TransformedPattern::with_speed(new_source(), 2.0);
+        assert_eq!(fast.cycle_length(), 44100.0);
+        run_pattern(&mut fast, 88200);
+        assert_eq!(fast.cycle, 2);
+        let slow = TransformedPattern::with_speed(new_source(), 0.5);
+        assert_eq!(slow.cycle_length(), 176400.0);
+    }
+}
@@ -3,8 +3,8 @@
 use std::{cell::RefCell, cmp::Ordering, fmt::Debug, rc::Rc};
 
 use crate::{
-    BeatTimeBase, BeatTimeStep, Event, EventTransform, ExactSampleTime, Parameter, ParameterSet,
-    Pattern, PatternEvent, SampleTime,
+    BeatTimeBase, BeatTimeStep, Event, EventTransform, ExactSampleTime, InstrumentId, Note,
+    NoteEvent, Parameter, ParameterSet, Pattern, PatternEvent, SampleTime, TempoMap,
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -49,7 +49,21 @@ pub type PhraseEvent = (PatternIndex, PatternEvent);
 
 // -------------------------------------------------------------------------------------------------
 
+/// A pending note-off for a note with a length, as scheduled by a [`Phrase`].
+#[derive(Clone, Debug, PartialEq)]
+struct PendingNoteOff {
+    pattern_index: PatternIndex,
+    voice_index: usize,
+    instrument: Option<InstrumentId>,
+    time: SampleTime,
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Combines multiple [`Pattern`]s into a new pattern stack.
+///
+/// Phrases emit note-offs for notes with a [`length`](crate::NoteEvent::length), unless the note's
+/// voice got stopped or replaced by a new note before the note ended.
 #[derive(Clone)]
 pub struct Phrase {
     time_base: BeatTimeBase,
@@ -57,6 +71,7 @@ pub struct Phrase {
     parameters: ParameterSet,
     pattern_slots: Vec<PatternSlot>,
     next_events: Vec<Option<PhraseEvent>>,
+    note_offs: Vec<PendingNoteOff>,
     event_transform: Option<EventTransform>,
     sample_offset: SampleTime,
 }
@@ -68,7 +83,7 @@ impl Debug for Phrase {
             .field("length", &self.length)
             .field("parameters", &self.parameters)
             .field("pattern_slots", &self.pattern_slots)
-            // Skip event_transform, which has no Debug impl and next_events to reduce noise
+            // Skip event_transform, which has no Debug impl and next_events, note_offs (noise)
             .field("sample_offset", &self.sample_offset)
             .finish()
     }
@@ -104,6 +119,7 @@ impl Phrase {
             }
         }
         let next_events = vec![None; pattern_slots.len()];
+        let note_offs = Vec::new();
         let event_transform = None;
         let sample_offset = 0;
         Self {
@@ -112,6 +128,7 @@ impl Phrase {
             parameters,
             pattern_slots,
             next_events,
+            note_offs,
             event_transform,
             sample_offset,
         }
@@ -165,6 +182,9 @@ impl Phrase {
 
     /// Move patterns until a given sample time is reached, ignoring all events until that time.
     pub fn advance_until_time(&mut self, sample_time: SampleTime) {
+        // skip pending note-offs
+        self.note_offs
+            .retain(|note_off| note_off.time >= sample_time);
         // skip next events in all patterns
         for (pattern_slot, next_event) in self
             .pattern_slots
@@ -188,6 +208,8 @@ impl Phrase {
     /// reset playback status and shift events to the given sample position.
     /// Further take over patterns from the passed previously playing phrase for `PatternSlot::Continue` slots.   
     pub fn reset_with_offset(&mut self, sample_offset: SampleTime, previous_phrase: &Phrase) {
+        // take over pending note-offs of notes which got emitted by the previous phrase
+        self.note_offs.clone_from(&previous_phrase.note_offs);
         // reset pattern iters, unless they are in continue mode. in continue mode, copy the slot
         // from the previously playing phrase and adjust sample offsets to fit.
         for (pattern_index, pattern_slot) in self.pattern_slots.iter_mut().enumerate() {
@@ -245,6 +267,21 @@ impl Phrase {
                 }
             }
         }
+        // emit pending note-offs which are due before or with the next event
+        if let Some(note_off_time) = self.note_offs.iter().map(|note_off| note_off.time).min() {
+            let next_event_time = self
+                .next_events
+                .iter()
+                .flatten()
+                .map(|(_, event)| event.time)
+                .min();
+            if note_off_time < sample_time
+                && next_event_time.is_none_or(|event_time| note_off_time <= event_time)
+            {
+                let (pattern_index, event) = self.take_note_offs(note_off_time);
+                return Some((pattern_index, event.with_offset(self.sample_offset)));
+            }
+        }
         // select the next from all pre-fetched events with the smallest sample time
         let next_due = self.next_events.iter_mut().reduce(|min, next| {
             if let Some((_, min_event)) = min {
@@ -264,6 +301,7 @@ impl Phrase {
             if let Some((pattern_index, event)) = next_due.clone() {
                 if event.time < sample_time {
                     *next_due = None; // consume
+                    self.schedule_note_offs(pattern_index, &event);
                     Some((pattern_index, event.with_offset(self.sample_offset)))
                 } else {
                     None // not yet due
@@ -275,6 +313,64 @@ impl Phrase {
             None
         }
     }
+
+    // Update pending note-offs with the given, just emitted pattern event: new notes and note-offs
+    // replace pending note-offs in their voice and notes with a length schedule new note-offs.
+    fn schedule_note_offs(&mut self, pattern_index: PatternIndex, pattern_event: &PatternEvent) {
+        if let Some(Event::NoteEvents(note_events)) = &pattern_event.event {
+            for (voice_index, note_event) in note_events.iter().enumerate() {
+                let Some(note_event) = note_event else {
+                    continue;
+                };
+                if !(note_event.note.is_note_on() || note_event.note.is_note_off()) {
+                    continue;
+                }
+                self.note_offs.retain(|note_off| {
+                    note_off.pattern_index != pattern_index || note_off.voice_index != voice_index
+                });
+                if let (true, Some(length)) = (note_event.note.is_note_on(), note_event.length) {
+                    let offset = (note_event.delay as f64 + length.max(0.0) as f64)
+                        * pattern_event.duration as f64;
+                    self.note_offs.push(PendingNoteOff {
+                        pattern_index,
+                        voice_index,
+                        instrument: note_event.instrument,
+                        time: pattern_event.time + offset as SampleTime,
+                    });
+                }
+            }
+        }
+    }
+
+    // Remove and return all pending note-offs of the first pattern with note-offs at the given
+    // time as a single pattern event.
+    fn take_note_offs(&mut self, time: SampleTime) -> PhraseEvent {
+        let pattern_index = self
+            .note_offs
+            .iter()
+            .find(|note_off| note_off.time == time)
+            .map(|note_off| note_off.pattern_index)
+            .expect("Expecting a pending note-off at the given time");
+        let mut note_events: Vec<Option<NoteEvent>> = Vec::new();
+        self.note_offs.retain(|note_off| {
+            if note_off.time == time && note_off.pattern_index == pattern_index {
+                if note_events.len() <= note_off.voice_index {
+                    note_events.resize(note_off.voice_index + 1, None);
+                }
+                note_events[note_off.voice_index] =
+                    Some(NoteEvent::from((Note::OFF, note_off.instrument)));
+                false
+            } else {
+                true
+            }
+        });
+        let event = PatternEvent {
+            time,
+            event: Some(Event::NoteEvents(note_events)),
+            duration: 0,
+        };
+        (pattern_index, event)
+    }
 }
 
 impl Pattern for Phrase {
@@ -289,6 +385,13 @@ impl Pattern for Phrase {
             }
         }
     }
+    fn set_tempo_map(&mut self, tempo_map: Option<Rc<TempoMap>>) {
+        for pattern_slot in &mut self.pattern_slots {
+            if let PatternSlot::Pattern(pattern) = pattern_slot {
+                pattern.borrow_mut().set_tempo_map(tempo_map.clone());
+            }
+        }
+    }
 
     fn step_length(&self) -> ExactSampleTime {
         // use our length's step, likely won't be used anyway for phrases
@@ -340,6 +443,7 @@ impl Pattern for Phrase {
         self.sample_offset = 0;
         // reset iterator state
         self.next_events.fill(None);
+        self.note_offs.clear();
         // reset all patterns in all slots as well
         for pattern_slot in &mut self.pattern_slots {
             if let PatternSlot::Pattern(pattern) = pattern_slot {
@@ -570,4 +674,51 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn note_lengths() {
+        let beat_time = BeatTimeBase {
+            samples_per_sec: 1000,
+            beats_per_min: 60.0,
+            beats_per_bar: 4,
+        };
+        let pattern = beat_time
+            .every_nth_beat(1.0)
+            .emit(new_note_sequence_emitter(vec![
+                Some(NoteEvent {
+                    length: Some(0.5),
+                    ..Note::C4.into()
+                }),
+                Some(NoteEvent {
+                    delay: 0.5,
+                    length: Some(1.0),
+                    ..Note::D4.into()
+                }),
+                Some(NoteEvent::from(Note::E4)),
+            ]));
+        let phrase = Phrase::new(beat_time, vec![pattern], BeatTimeStep::Bar(1.0));
+
+        let events = phrase
+            .take(6)
+            .map(|(_, event)| match event.event {
+                Some(Event::NoteEvents(notes)) => (
+                    event.time,
+                    notes.into_iter().flatten().map(|n| n.note).collect(),
+                ),
+                _ => (event.time, vec![]),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                (0, vec![Note::C4]),
+                (500, vec![Note::OFF]),
+                // the tied E4 replaces D4's pending note-off
+                (1000, vec![Note::D4]),
+                (2000, vec![Note::E4]),
+                (3000, vec![Note::C4]),
+                (3500, vec![Note::OFF]),
+            ]
+        );
+    }
 }
@@ -23,7 +23,7 @@ use phonic::{
 
 use crate::{
     time::{SampleTimeBase, SampleTimeDisplay},
-    BeatTimeBase, Event, InstrumentId, Note, PatternEvent, SampleTime, Sequence,
+    Clock, Event, InstrumentId, Note, PatternEvent, SampleTime, Sequence,
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -159,6 +159,9 @@ impl SamplePlaybackContext {
 /// using the default audio output device using plain samples loaded from a file as instruments.
 ///
 /// Works on an existing sample pool, which can be used outside of the player as well.
+///
+/// Custom note [`attributes`](crate::NoteAttributes) have no sample playback equivalent, so they
+/// are ignored: they only show up in printed events.
 pub struct SamplePlayer {
     player: PhonicPlayer,
     sample_pool: Arc<SamplePool>,
@@ -336,6 +339,62 @@ impl SamplePlayer {
         }
     }
 
+    /// Run the given sequence, following the given clock instead of the audio device's playback
+    /// position, until the passed stop condition function returns true. Use this to sync playback
+    /// to an external clock, e.g. a [`MidiClock`](crate::clocks::MidiClock).
+    ///
+    /// The sequence follows the clock's tempo and position: it gets seeked when the clock's
+    /// position jumps and all playing sources get stopped when the clock stops or jumps.
+    ///
+    /// The clock's and sequence's sample rates must match: when they don't, an error gets logged
+    /// and nothing is played.
+    pub fn run_until_clock<StopFn: Fn() -> bool>(
+        &mut self,
+        sequence: &mut Sequence,
+        clock: &mut dyn Clock,
+        stop_fn: StopFn,
+    ) {
+        if clock.samples_per_sec() != sequence.time_base().samples_per_sec {
+            log::error!(target: "Player",
+                "Clock and sequence sample rates must match: got {} and {} Hz",
+                clock.samples_per_sec(), sequence.time_base().samples_per_sec);
+            return;
+        }
+        self.reset_playback_position(sequence);
+        sequence.reset();
+        // interval in which the clock gets polled
+        const CLOCK_POLL_INTERVAL: Duration = Duration::from_millis(5);
+        let mut was_running = false;
+        while !stop_fn() {
+            let clock_state = clock.poll();
+            if clock_state.position_changed || (was_running && !clock_state.running) {
+                log::debug!(target: "Player", "Clock stopped or moved: stopping all sources");
+                self.stop_all_sources();
+            }
+            was_running = clock_state.running;
+            // the clock's position is the device's current playback position
+            let time_offset = self.player.output_sample_frame_position() as i64
+                - clock_state.sample_position as i64;
+            let lookahead = sequence
+                .time_base()
+                .seconds_to_samples(PLAYBACK_PRELOAD_SECONDS);
+            let time_base = *sequence.time_base();
+            sequence.consume_events_until_clock(
+                &clock_state,
+                lookahead,
+                &mut |pattern_index, pattern_event| {
+                    self.handle_pattern_event(
+                        pattern_index,
+                        pattern_event,
+                        &time_base,
+                        time_offset,
+                    );
+                },
+            );
+            std::thread::sleep(CLOCK_POLL_INTERVAL);
+        }
+    }
+
     /// Initialize the given sequence for playback with `run_until_time`.
     /// This seeks the sequence to the given position and keeps track of internal playback state.
     pub fn prepare_run_until_time(&mut self, sequence: &mut Sequence, sample_time: u64) {
@@ -361,8 +420,18 @@ impl SamplePlayer {
         time: SampleTime,
     ) {
         let time_base = *sequence.time_base();
+        let tempo_map = sequence.tempo_map().cloned();
+        let time_display: &dyn SampleTimeDisplay = match &tempo_map {
+            Some(tempo_map) => tempo_map.as_ref(),
+            None => &time_base,
+        };
         sequence.consume_events_until_time(time, &mut |pattern_index, pattern_event| {
-            self.handle_pattern_event(pattern_index, pattern_event, time_base, time_offset);
+            self.handle_pattern_event(
+                pattern_index,
+                pattern_event,
+                time_display,
+                time_offset as i64,
+            );
         });
     }
 
@@ -371,15 +440,17 @@ impl SamplePlayer {
         &mut self,
         pattern_index: usize,
         pattern_event: PatternEvent,
-        time_base: BeatTimeBase,
-        time_offset: SampleTime,
+        time_display: &dyn SampleTimeDisplay,
+        time_offset: i64,
     ) {
+        // convert event times to the player's output sample time
+        let output_time = |time: SampleTime| (time as i64 + time_offset).max(0) as SampleTime;
         // Print event if enabled
         if self.show_events {
             const SHOW_INSTRUMENTS_AND_PARAMETERS: bool = true;
             println!(
                 "{}: {}",
-                time_base.display(pattern_event.time),
+                time_display.display(pattern_event.time),
                 match &pattern_event.event {
                     Some(event) => event.to_string(SHOW_INSTRUMENTS_AND_PARAMETERS),
                     None => "---".to_string(),
@@ -403,7 +474,7 @@ impl SamplePlayer {
                     if let Some((playback_id, _)) = playing_notes_in_pattern.get(&voice_index) {
                         let _ = self.player.stop_source_at_sample_time(
                             *playback_id,
-                            time_offset + pattern_event.time,
+                            output_time(pattern_event.time),
                         );
                         playing_notes_in_pattern.remove(&voice_index);
                     }
@@ -412,13 +483,17 @@ impl SamplePlayer {
                 if !note_event.note.is_note_on() {
                     continue;
                 }
+                // NB: custom note attributes are not applied to sample playback
                 if let Some(instrument) = note_event.instrument {
                     let midi_note = (note_event.note as i32 + 60 - self.sample_root_note as i32)
                         .clamp(0, 127) as u8;
                     let volume = note_event.volume.max(0.0);
                     let panning = note_event.panning.clamp(-1.0, 1.0);
+                    // apply microtonal fine tuning on top of the note's 12-TET speed
+                    let speed =
+                        speed_from_note(midi_note) * (note_event.cents as f64 / 1200.0).exp2();
                     let mut playback_options = FilePlaybackOptions::default()
-                        .speed(speed_from_note(midi_note))
+                        .speed(speed)
                         .volume(volume)
                         .panning(panning)
                         .playback_pos_emit_rate(self.playback_pos_emit_rate);
@@ -437,7 +512,7 @@ impl SamplePlayer {
                     ) {
                         let sample_delay =
                             (note_event.delay * pattern_event.duration as f32) as SampleTime;
-                        let start_time = Some(time_offset + pattern_event.time + sample_delay);
+                        let start_time = Some(output_time(pattern_event.time + sample_delay));
 
                         let context: Option<PlaybackStatusContext> =
                             Some(Arc::new(SamplePlaybackContext {
@@ -7,22 +7,42 @@
 
 pub use super::{
     // all public types to create emitters, gates and patterns
+    clock::{internal::InternalClock, midi::MidiClock, simulated::SimulatedClock},
     emitter::{
+        arpeggio::{ArpeggioEmitter, ArpeggioMode},
         cycle::{new_cycle_emitter, new_cycle_emitter_with_seed, CycleEmitter},
         fixed::{ToFixedEmitter, ToFixedEmitterSequence},
+        melody::MelodyEmitter,
         mutated::ToMutatedEmitter,
         new_empty_note_emitter, new_note_emitter, new_note_sequence_emitter,
         new_parameter_change_emitter, new_polyphonic_note_emitter,
         new_polyphonic_note_sequence_emitter,
     },
-    event::{new_empty_note, new_note, InstrumentId, NoteEvent, ParameterChangeEvent, ParameterId},
-    gate::{probability::ProbabilityGate, threshold::ThresholdGate},
-    pattern::{beat_time::BeatTimePattern, second_time::SecondTimePattern},
-    rhythm::{euclidean, fixed::ToFixedRhythm},
+    event::{
+        new_empty_note, new_note, InstrumentId, NoteAttributes, NoteEvent, ParameterChangeEvent,
+        ParameterId,
+    },
+    gate::{
+        bar::{BarGate, BarPosition},
+        cycle::CycleGate,
+        density::DensityGate,
+        logic::{AndGate, NotGate, OrGate},
+        mute::MuteGate,
+        probability::ProbabilityGate,
+        threshold::ThresholdGate,
+    },
+    pattern::{
+        beat_time::BeatTimePattern,
+        second_time::SecondTimePattern,
+        transformed::{PatternTransform, TransformedPattern},
+    },
+    rhythm::{euclidean, fixed::ToFixedRhythm, timeline, transform::PulseTransform},
     time::{BeatTimeStep, SecondTimeStep},
     // all public basic types
     BeatTimeBase,
     Chord,
+    Clock,
+    ClockState,
     Cycle,
     CycleEvent,
     CycleSpan,
@@ -33,29 +53,46 @@ pub use super::{
     Event,
     EventTransform,
     Gate,
+    GateTempoMap,
+    KeyboardMapping,
+    LfoShape,
     Note,
     Parameter,
+    ParameterAutomation,
+    ParameterPreset,
     ParameterSet,
+    ParameterSetPresets,
     ParameterType,
+    ParameterValue,
     Pattern,
     PatternEvent,
     PatternSlot,
     Phrase,
     Pulse,
+    PulseDynamics,
     Rhythm,
+    RhythmProperty,
     SampleTime,
     SampleTimeBase,
     Scale,
     SecondTimeBase,
     Sequence,
+    TempoMap,
+    TempoRamp,
+    TimeSignature,
+    Tuning,
+    VoiceLeading,
 };
 
 #[cfg(feature = "scripting")]
 // all public scripting types
 pub use super::{
     bindings::{
-        clear_lua_callback_errors, has_lua_callback_errors, lua_callback_errors,
-        new_pattern_from_file, new_pattern_from_string,
+        new_pattern_from_file, new_pattern_from_file_with_options, new_pattern_from_string,
+        new_pattern_from_string_with_options, new_sequence_from_file,
+        new_sequence_from_file_with_options, new_sequence_from_string,
+        new_sequence_from_string_with_options, LuaCallbackError, LuaCallbackErrorChannel,
+        LuaCallbackKind, ScriptLimitError, ScriptLimits, ScriptOptions,
     },
     emitter::{scripted::ScriptedEmitter, scripted_cycle::ScriptedCycleEmitter},
     gate::scripted::ScriptedGate,
@@ -11,6 +11,10 @@
 /// By using pulses with sub divisions, complex sub rhythms can be created without increasing
 /// the pattern's base time resolution.
 ///
+/// Pulses in sub divisions can be weighted, like the `@` weight in mini-notation, to split a
+/// step unevenly: a pulse with weight 3 lasts three times as long as an unweighted pulse in the
+/// same sub division. Weights of top level pulses are ignored: each covers exactly one step.
+///
 /// ### Example
 ///
 /// ```rust
@@ -18,14 +22,41 @@
 /// // Assuming pulse step is 1 beat.
 /// // Defines a pulse rhythm with one quater note followed by a 16th note triplet.
 /// let pulses = vec![Pulse::from(1), Pulse::from(vec![1, 1, 1])];
+/// // Defines a dotted 8th note followed by a 16th note.
+/// let pulses = vec![Pulse::from(vec![Pulse::weighted(1, 3.0), Pulse::from(1)])];
 /// ````
 #[derive(Clone, Debug, PartialEq, PartialOrd)]
 pub enum Pulse {
     Pulse(f32),
     SubDivision(Vec<Pulse>),
+    Weighted(Box<Pulse>, f64),
 }
 
 impl Pulse {
+    /// Create a new pulse with the given relative weight within its sub division.
+    ///
+    /// ### Panics
+    /// Panics if the given weight is not a finite number > 0.
+    pub fn weighted<T>(pulse: T, weight: f64) -> Self
+    where
+        Pulse: From<T>,
+    {
+        assert!(weight.is_finite() && weight > 0.0, "Invalid pulse weight");
+        match Pulse::from(pulse) {
+            Pulse::Weighted(pulse, _) => Pulse::Weighted(pulse, weight),
+            pulse => Pulse::Weighted(Box::new(pulse), weight),
+        }
+    }
+
+    /// Returns the pulse's relative weight within its sub division: 1 for unweighted pulses.
+    /// Invalid weights, which are not finite numbers > 0, count as 1 as well.
+    pub fn weight(&self) -> f64 {
+        match self {
+            Pulse::Weighted(_, weight) if weight.is_finite() && *weight > 0.0 => *weight,
+            _ => 1.0,
+        }
+    }
+
     /// Returns true when the pulse is a sub division and empty, else false.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -36,6 +67,7 @@ impl Pulse {
         match self {
             Pulse::Pulse(_) => 1,
             Pulse::SubDivision(sub_div) => sub_div.iter().fold(0, |sum, pulse| sum + pulse.len()),
+            Pulse::Weighted(pulse, _) => pulse.len(),
         }
     }
 }
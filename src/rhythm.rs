@@ -4,11 +4,18 @@ use std::fmt::Debug;
 
 use crate::{BeatTimeBase, Event, ParameterSet, Pulse};
 
+pub mod automaton;
 pub mod empty;
 pub mod euclidean;
 pub mod fixed;
+pub mod generative;
+pub mod lsystem;
+pub mod markov;
+pub mod random_walk;
 #[cfg(feature = "scripting")]
 pub mod scripted;
+pub mod timeline;
+pub mod transform;
 
 // -------------------------------------------------------------------------------------------------
 
@@ -91,11 +98,15 @@ impl Pulse {
                 result.push(RhythmEvent { value, step_time });
             }
             Pulse::SubDivision(ref sub_pulses) => {
+                let total_weight = sub_pulses.iter().map(Pulse::weight).sum::<f64>();
                 for sub_pulse in sub_pulses {
-                    let sub_step_time = step_time / sub_pulses.len() as f64;
+                    let sub_step_time = step_time * sub_pulse.weight() / total_weight;
                     sub_pulse.expand_into(result, sub_step_time);
                 }
             }
+            Pulse::Weighted(ref pulse, _) => {
+                pulse.expand_into(result, step_time);
+            }
         }
     }
 }
@@ -0,0 +1,131 @@
+use crate::{
+    rhythm::generative::{GenerativeRhythm, PulseGenerator, RhythmProperty},
+    ParameterSet,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates pulses from an elementary (1D) cellular automaton: emits the cells of the current
+/// generation one by one, then evolves the cells with the automaton's rule to the next generation.
+/// Cells wrap around at the row's edges.
+#[derive(Clone, Debug)]
+pub struct CellularAutomatonGenerator {
+    rule: RhythmProperty,
+    seed: Vec<bool>,
+    cells: Vec<bool>,
+    index: usize,
+}
+
+impl CellularAutomatonGenerator {
+    /// Create a new cellular automaton with the given Wolfram rule number in range \[0 - 255\]
+    /// and the given initial cells. The number of cells is the number of pulses per generation.
+    ///
+    /// ### Panics
+    /// Panics if the given seed row is empty.
+    pub fn new(rule: RhythmProperty, seed: Vec<bool>) -> Self {
+        assert!(!seed.is_empty(), "Cellular automata need at least one cell");
+        let cells = seed.clone();
+        let index = 0;
+        Self {
+            rule,
+            seed,
+            cells,
+            index,
+        }
+    }
+
+    /// Evolve the given cells by one generation with the given rule.
+    pub fn evolve(cells: &[bool], rule: u8) -> Vec<bool> {
+        let len = cells.len();
+        (0..len)
+            .map(|index| {
+                let left = cells[(index + len - 1) % len] as u8;
+                let center = cells[index] as u8;
+                let right = cells[(index + 1) % len] as u8;
+                let neighbourhood = (left << 2) | (center << 1) | right;
+                (rule >> neighbourhood) & 1 == 1
+            })
+            .collect()
+    }
+
+    /// Cells of the current generation.
+    pub fn cells(&self) -> &[bool] {
+        &self.cells
+    }
+
+    fn rule_number(&self) -> u8 {
+        self.rule.value().round().clamp(0.0, 255.0) as u8
+    }
+}
+
+impl PulseGenerator for CellularAutomatonGenerator {
+    fn set_parameters(&mut self, parameters: &ParameterSet) {
+        self.rule.set_parameters(parameters);
+    }
+
+    fn generate(&mut self) -> f32 {
+        if self.index >= self.cells.len() {
+            self.cells = Self::evolve(&self.cells, self.rule_number());
+            self.index = 0;
+        }
+        let value = if self.cells[self.index] { 1.0 } else { 0.0 };
+        self.index += 1;
+        value
+    }
+
+    fn reset(&mut self) {
+        self.cells.clone_from(&self.seed);
+        self.index = 0;
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A rhythm which emits pulses from a [`CellularAutomatonGenerator`].
+pub type CellularAutomatonRhythm = GenerativeRhythm<CellularAutomatonGenerator>;
+
+impl CellularAutomatonRhythm {
+    /// Create a new cellular automaton rhythm. See [`CellularAutomatonGenerator::new`].
+    pub fn new(rule: RhythmProperty, seed: Vec<bool>) -> Self {
+        Self::from_generator(CellularAutomatonGenerator::new(rule, seed))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{rhythm::automaton::CellularAutomatonGenerator, rhythms::CellularAutomatonRhythm};
+    use crate::{Rhythm, RhythmProperty};
+
+    #[test]
+    fn automaton() {
+        // rule 90: sierpinski triangle
+        let seed = vec![false, false, true, false, false];
+        assert_eq!(
+            CellularAutomatonGenerator::evolve(&seed, 90),
+            vec![false, true, false, true, false]
+        );
+        // rule 30 with wrapping edges
+        assert_eq!(
+            CellularAutomatonGenerator::evolve(&[true, false, false, false], 30),
+            vec![true, true, false, true]
+        );
+
+        let mut rhythm = CellularAutomatonRhythm::new(RhythmProperty::new(90.0), seed);
+        let values = (0..10)
+            .map(|_| rhythm.run().unwrap().value)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0]
+        );
+        rhythm.reset();
+        assert_eq!(
+            rhythm.generator().cells(),
+            &[false, false, true, false, false]
+        );
+        assert!(!rhythm.is_empty());
+        assert_eq!(rhythm.len(), 0);
+    }
+}
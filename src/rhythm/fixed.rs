@@ -1,6 +1,8 @@
 use crate::{
-    rhythm::euclidean::euclidean, rhythm::RhythmEventIterator, BeatTimeBase, Event, ParameterSet,
-    Pulse, Rhythm, RhythmEvent,
+    rhythm::euclidean::euclidean,
+    rhythm::timeline::{christoffel, maximally_even, rotate, weighted_maximally_even, Timeline},
+    rhythm::RhythmEventIterator,
+    BeatTimeBase, Event, ParameterSet, Pulse, Rhythm, RhythmEvent,
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -49,6 +51,42 @@ impl FixedRhythm {
     pub fn from_euclidean(steps: u32, pulses: u32, offset: i32) -> Self {
         Self::from_pulses(euclidean(steps, pulses, offset))
     }
+
+    /// Create from a named timeline, optionally rotated by the given offset.
+    pub fn from_timeline(timeline: Timeline, offset: i32) -> Self {
+        Self::from_pulses(rotate(&timeline.pattern(), offset))
+    }
+
+    /// Create from a maximally even (Bresenham) rhythm.
+    pub fn from_maximally_even(steps: u32, pulses: u32, offset: i32) -> Self {
+        Self::from_pulses(maximally_even(steps, pulses, offset))
+    }
+
+    /// Create from a maximally even rhythm over pulses with the given relative durations.
+    pub fn from_weighted_maximally_even(steps: u32, weights: &[f64]) -> Self {
+        Self::from_pulses(weighted_maximally_even(steps, weights))
+    }
+
+    /// Create from a lower or upper Christoffel word.
+    pub fn from_christoffel(steps: u32, pulses: u32, upper: bool) -> Self {
+        Self::from_pulses(christoffel(steps, pulses, upper))
+    }
+
+    /// Access to the rhythm's static pulses.
+    pub fn pulses(&self) -> &[Pulse] {
+        &self.pulses
+    }
+
+    /// Create a new rhythm with the given function applied to our pulses. Keeps the repeat
+    /// count, but resets the playback state.
+    pub(crate) fn with_pulses<F>(self, transform: F) -> Self
+    where
+        F: FnOnce(Vec<Pulse>) -> Vec<Pulse>,
+    {
+        let mut rhythm = Self::from_pulses(transform(self.pulses));
+        rhythm.repeat_count_option = self.repeat_count_option;
+        rhythm
+    }
 }
 
 impl Rhythm for FixedRhythm {
@@ -227,6 +265,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn weighted() {
+        // dotted 8th and 16th, followed by a 3:2 triplet
+        let mut rhythm = [
+            Pulse::from(vec![Pulse::weighted(1.0, 3.0), Pulse::from(0.5)]),
+            Pulse::from(vec![
+                Pulse::weighted(vec![1.0, 1.0, 1.0], 2.0),
+                Pulse::from(0.0),
+            ]),
+        ]
+        .to_rhythm();
+        assert_eq!(rhythm.len(), 6);
+        let step_times = (0..6)
+            .map(|_| rhythm.run().map(|event| event.step_time))
+            .collect::<Vec<_>>();
+        let step = 2.0 / 9.0;
+        assert_eq!(
+            step_times,
+            vec![
+                Some(0.75),
+                Some(0.25),
+                Some(step),
+                Some(step),
+                Some(step),
+                Some(1.0 / 3.0)
+            ]
+        );
+
+        // invalid weights count as 1
+        let mut rhythm = [Pulse::from(vec![
+            Pulse::Weighted(Box::new(Pulse::from(1.0)), 0.0),
+            Pulse::Weighted(Box::new(Pulse::from(1.0)), -1.0),
+            Pulse::Weighted(Box::new(Pulse::from(1.0)), f64::NAN),
+            Pulse::Weighted(Box::new(Pulse::from(1.0)), f64::INFINITY),
+        ])]
+        .to_rhythm();
+        let step_times = (0..4)
+            .map(|_| rhythm.run().map(|event| event.step_time))
+            .collect::<Vec<_>>();
+        assert_eq!(step_times, vec![Some(0.25); 4]);
+    }
+
     #[test]
     fn repeat() {
         let mut rhythm = [1.0, 0.0].to_rhythm();
@@ -0,0 +1,195 @@
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
+
+use crate::{BeatTimeBase, Event, Parameter, ParameterSet, Rhythm, RhythmEvent};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A numeric property of a generative rhythm, which either is a fixed value or follows the
+/// value of a [`Parameter`] with the given id. Parameters are resolved in
+/// [`Rhythm::set_parameters`]. Unresolved parameter properties use their fallback value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RhythmProperty {
+    value: f64,
+    parameter_id: Option<String>,
+    parameter: Option<Rc<RefCell<Parameter>>>,
+}
+
+impl RhythmProperty {
+    /// Create a new property with a fixed value.
+    pub fn new(value: f64) -> Self {
+        let parameter_id = None;
+        let parameter = None;
+        Self {
+            value,
+            parameter_id,
+            parameter,
+        }
+    }
+
+    /// Create a new property which follows the parameter with the given id.
+    pub fn with_parameter(id: &str, fallback: f64) -> Self {
+        let value = fallback;
+        let parameter_id = Some(id.to_string());
+        let parameter = None;
+        Self {
+            value,
+            parameter_id,
+            parameter,
+        }
+    }
+
+    /// Id of the parameter this property follows, if any.
+    pub fn parameter_id(&self) -> Option<&str> {
+        self.parameter_id.as_deref()
+    }
+
+    /// Actual value of the property.
+    pub fn value(&self) -> f64 {
+        match &self.parameter {
+            Some(parameter) => parameter.borrow().value(),
+            None => self.value,
+        }
+    }
+
+    /// Resolve the property's parameter from the given parameter set.
+    pub fn set_parameters(&mut self, parameters: &ParameterSet) {
+        if let Some(id) = &self.parameter_id {
+            self.parameter = parameters
+                .iter()
+                .find(|parameter| parameter.borrow().id() == id)
+                .cloned();
+        }
+    }
+}
+
+impl From<f64> for RhythmProperty {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates pulse values for a [`GenerativeRhythm`].
+pub trait PulseGenerator: Debug + Clone + 'static {
+    /// Number of pulses after which the generator naturally repeats, if any.
+    fn period(&self) -> Option<usize> {
+        None
+    }
+
+    /// Resolve parameter bound properties from the given parameter set.
+    fn set_parameters(&mut self, parameters: &ParameterSet);
+
+    /// Generate the next pulse value in range \[0 - 1\].
+    fn generate(&mut self) -> f32;
+
+    /// Reset the generator to its initial state.
+    fn reset(&mut self);
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A rhythm which endlessly emits pulses from a [`PulseGenerator`].
+///
+/// When a length is set, or the generator has a natural period, the generator gets reset after
+/// this number of pulses, so (seeded) generators repeat their pulses. Repeat counts only apply
+/// to rhythms with a length.
+#[derive(Clone, Debug)]
+pub struct GenerativeRhythm<G: PulseGenerator> {
+    generator: G,
+    length: Option<usize>,
+    step: usize,
+    repeat_count_option: Option<usize>,
+    repeat_count: usize,
+}
+
+impl<G: PulseGenerator> GenerativeRhythm<G> {
+    /// Create a new rhythm from the given pulse generator.
+    pub fn from_generator(generator: G) -> Self {
+        let length = None;
+        let step = 0;
+        let repeat_count_option = None;
+        let repeat_count = 0;
+        Self {
+            generator,
+            length,
+            step,
+            repeat_count_option,
+            repeat_count,
+        }
+    }
+
+    /// Return a new rhythm which repeats after the given number of pulses. A length of 0
+    /// or None falls back to the generator's natural period.
+    #[must_use]
+    pub fn with_length<T: Into<Option<usize>>>(self, length: T) -> Self {
+        let length = length.into().filter(|length| *length > 0);
+        Self { length, ..self }
+    }
+
+    /// Read-only access to the rhythm's generator.
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// Number of pulses after which the rhythm repeats, if any.
+    pub fn length(&self) -> Option<usize> {
+        self.length.or_else(|| self.generator.period())
+    }
+}
+
+impl<G: PulseGenerator> Rhythm for GenerativeRhythm<G> {
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn len(&self) -> usize {
+        self.length().unwrap_or(0)
+    }
+
+    fn run(&mut self) -> Option<RhythmEvent> {
+        if let Some(length) = self.length() {
+            if self.step >= length {
+                self.step = 0;
+                self.repeat_count += 1;
+                if self
+                    .repeat_count_option
+                    .is_some_and(|option| self.repeat_count > option)
+                {
+                    return None;
+                }
+                self.generator.reset();
+            }
+        }
+        self.step += 1;
+        let value = self.generator.generate().clamp(0.0, 1.0);
+        let step_time = 1.0;
+        Some(RhythmEvent { value, step_time })
+    }
+
+    fn set_time_base(&mut self, _time_base: &BeatTimeBase) {
+        // nothing to do
+    }
+
+    fn set_trigger_event(&mut self, _event: &Event) {
+        // nothing to do
+    }
+
+    fn set_parameters(&mut self, parameters: ParameterSet) {
+        self.generator.set_parameters(&parameters);
+    }
+
+    fn set_repeat_count(&mut self, count: Option<usize>) {
+        self.repeat_count_option = count;
+    }
+
+    fn duplicate(&self) -> Box<dyn Rhythm> {
+        Box::new(self.clone())
+    }
+
+    fn reset(&mut self) {
+        self.step = 0;
+        self.repeat_count = 0;
+        self.generator.reset();
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use crate::{
+    rhythm::generative::{GenerativeRhythm, PulseGenerator, RhythmProperty},
+    ParameterSet,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates pulses from an L-system: the axiom string gets rewritten with the given rules for
+/// the given number of iterations. The resulting string's characters then get mapped to pulse
+/// values. Characters without a pulse value emit 0 pulses.
+#[derive(Clone, Debug)]
+pub struct LSystemGenerator {
+    axiom: String,
+    rules: HashMap<char, String>,
+    values: HashMap<char, f32>,
+    iterations: RhythmProperty,
+    expanded_iterations: usize,
+    expanded: Vec<f32>,
+    index: usize,
+}
+
+impl LSystemGenerator {
+    /// Maximum number of pulses an expanded L-system string may have. Expansions get cut off
+    /// at this length to avoid running out of memory with fast growing rules.
+    pub const MAX_LENGTH: usize = 0x10000;
+    /// Maximum number of rewriting iterations. Larger iteration counts get clamped.
+    pub const MAX_ITERATIONS: usize = 64;
+
+    /// Create a new L-system with the given axiom, rewriting rules, pulse values for characters
+    /// and iteration count. When `values` is empty, `1` and `x` characters emit 1 pulses.
+    pub fn new(
+        axiom: &str,
+        rules: HashMap<char, String>,
+        values: HashMap<char, f32>,
+        iterations: RhythmProperty,
+    ) -> Self {
+        let axiom = axiom.to_string();
+        let values = if values.is_empty() {
+            HashMap::from([('1', 1.0), ('x', 1.0)])
+        } else {
+            values
+        };
+        let mut generator = Self {
+            axiom,
+            rules,
+            values,
+            iterations,
+            expanded_iterations: 0,
+            expanded: Vec::new(),
+            index: 0,
+        };
+        generator.expand();
+        generator
+    }
+
+    /// Rewrite the axiom with the given rules and number of iterations. Iterations are clamped
+    /// to [`Self::MAX_ITERATIONS`] and stop early when the string no longer changes.
+    pub fn rewrite(axiom: &str, rules: &HashMap<char, String>, iterations: usize) -> String {
+        let mut string = axiom.chars().take(Self::MAX_LENGTH).collect::<String>();
+        for _ in 0..iterations.min(Self::MAX_ITERATIONS) {
+            let mut rewritten = String::with_capacity(string.len() * 2);
+            for char in string.chars() {
+                match rules.get(&char) {
+                    Some(replacement) => rewritten.push_str(replacement),
+                    None => rewritten.push(char),
+                }
+                if rewritten.len() >= Self::MAX_LENGTH {
+                    break;
+                }
+            }
+            let rewritten = rewritten.chars().take(Self::MAX_LENGTH).collect::<String>();
+            if rewritten == string {
+                break;
+            }
+            string = rewritten;
+        }
+        string
+    }
+
+    /// Expanded pulse values of the L-system.
+    pub fn pulses(&self) -> &[f32] {
+        &self.expanded
+    }
+
+    fn iteration_count(&self) -> usize {
+        (self.iterations.value().round().max(0.0) as usize).min(Self::MAX_ITERATIONS)
+    }
+
+    fn expand(&mut self) {
+        self.expanded_iterations = self.iteration_count();
+        self.expanded = Self::rewrite(&self.axiom, &self.rules, self.expanded_iterations)
+            .chars()
+            .map(|char| self.values.get(&char).copied().unwrap_or(0.0))
+            .collect();
+        self.index = 0;
+    }
+}
+
+impl PulseGenerator for LSystemGenerator {
+    fn period(&self) -> Option<usize> {
+        Some(self.expanded.len()).filter(|len| *len > 0)
+    }
+
+    fn set_parameters(&mut self, parameters: &ParameterSet) {
+        self.iterations.set_parameters(parameters);
+        if self.iteration_count() != self.expanded_iterations {
+            self.expand();
+        }
+    }
+
+    fn generate(&mut self) -> f32 {
+        if self.expanded.is_empty() {
+            return 0.0;
+        }
+        let value = self.expanded[self.index % self.expanded.len()];
+        self.index += 1;
+        value
+    }
+
+    fn reset(&mut self) {
+        // apply changed iteration counts from parameters
+        if self.iteration_count() != self.expanded_iterations {
+            self.expand();
+        }
+        self.index = 0;
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A rhythm which emits pulses from an [`LSystemGenerator`]. The rhythm repeats after all pulses
+/// of the expanded L-system string got emitted.
+pub type LSystemRhythm = GenerativeRhythm<LSystemGenerator>;
+
+impl LSystemRhythm {
+    /// Create a new L-system rhythm. See [`LSystemGenerator::new`].
+    pub fn new(
+        axiom: &str,
+        rules: HashMap<char, String>,
+        values: HashMap<char, f32>,
+        iterations: RhythmProperty,
+    ) -> Self {
+        Self::from_generator(LSystemGenerator::new(axiom, rules, values, iterations))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{
+        rhythm::lsystem::LSystemGenerator, rhythms::LSystemRhythm, Rhythm, RhythmProperty,
+    };
+
+    #[test]
+    fn lsystem() {
+        // fibonacci rhythm
+        let rules = HashMap::from([('a', "ab".to_string()), ('b', "a".to_string())]);
+        assert_eq!(LSystemGenerator::rewrite("a", &rules, 4), "abaababa");
+
+        let values = HashMap::from([('a', 1.0), ('b', 0.0)]);
+        let mut rhythm = LSystemRhythm::new("a", rules.clone(), values, 4.0.into());
+        assert_eq!(rhythm.len(), 8);
+        let values = (0..10)
+            .map(|_| rhythm.run().unwrap().value)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0]
+        );
+
+        // default values
+        let rules = HashMap::from([('1', "1.".to_string())]);
+        let mut rhythm = LSystemRhythm::new("1", rules, HashMap::new(), RhythmProperty::new(2.0));
+        rhythm.set_repeat_count(Some(0));
+        let values = (0..4)
+            .map(|_| rhythm.run().map(|event| event.value))
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![Some(1.0), Some(0.0), Some(0.0), None]);
+
+        // fast growing rules get cut off
+        let rules = HashMap::from([('x', "xxxx".to_string())]);
+        let rhythm = LSystemRhythm::new("x", rules, HashMap::new(), 20.0.into());
+        assert_eq!(rhythm.len(), LSystemGenerator::MAX_LENGTH);
+
+        // iterations are capped
+        let rules = HashMap::from([('a', "ax".to_string())]);
+        assert_eq!(
+            LSystemGenerator::rewrite("a", &rules, usize::MAX).len(),
+            LSystemGenerator::MAX_ITERATIONS + 1
+        );
+        let rhythm = LSystemRhythm::new("a", rules, HashMap::new(), 1e12.into());
+        assert_eq!(rhythm.len(), LSystemGenerator::MAX_ITERATIONS + 1);
+
+        // rewriting stops when the string no longer changes
+        let rules = HashMap::from([('a', "b".to_string())]);
+        assert_eq!(LSystemGenerator::rewrite("aa", &rules, usize::MAX), "bb");
+    }
+}
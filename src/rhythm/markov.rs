@@ -0,0 +1,202 @@
+use rand::{rng, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{
+    rhythm::generative::{GenerativeRhythm, PulseGenerator, RhythmProperty},
+    ParameterSet,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates pulses from a Markov chain: each state emits a pulse value and moves on to the next
+/// state, randomly picked with the current state's transition weights.
+#[derive(Clone, Debug)]
+pub struct MarkovGenerator {
+    states: Vec<f32>,
+    transitions: Vec<Vec<RhythmProperty>>,
+    start: usize,
+    state: usize,
+    rand_gen: Xoshiro256PlusPlus,
+    seed: Option<u64>,
+}
+
+impl MarkovGenerator {
+    /// Create a new Markov chain with the given state pulse values and transition weights.
+    /// `transitions[a][b]` is the weight of moving from state `a` to state `b`. Negative and
+    /// non-finite weights are treated as 0. States without any positive transition weight stay
+    /// in their state.
+    ///
+    /// ### Panics
+    /// Panics if states are empty, the transition matrix does not match the number of states
+    /// or the start state is out of range.
+    pub fn new(
+        states: Vec<f32>,
+        transitions: Vec<Vec<RhythmProperty>>,
+        start: usize,
+        seed: Option<u64>,
+    ) -> Self {
+        assert!(!states.is_empty(), "Markov chains need at least one state");
+        assert!(
+            transitions.len() == states.len()
+                && transitions.iter().all(|row| row.len() == states.len()),
+            "Markov transitions must be a square matrix with a row for each state"
+        );
+        assert!(start < states.len(), "Invalid Markov start state");
+        let state = start;
+        let rand_seed = seed.unwrap_or_else(|| rng().random());
+        let rand_gen = Xoshiro256PlusPlus::seed_from_u64(rand_seed);
+        Self {
+            states,
+            transitions,
+            start,
+            state,
+            rand_gen,
+            seed,
+        }
+    }
+
+    /// Pulse values of all states.
+    pub fn states(&self) -> &[f32] {
+        &self.states
+    }
+
+    /// Index of the state which emits the next pulse.
+    pub fn state(&self) -> usize {
+        self.state
+    }
+}
+
+impl PulseGenerator for MarkovGenerator {
+    fn set_parameters(&mut self, parameters: &ParameterSet) {
+        for property in self.transitions.iter_mut().flatten() {
+            property.set_parameters(parameters);
+        }
+    }
+
+    fn generate(&mut self) -> f32 {
+        let value = self.states[self.state];
+        let weights = self.transitions[self.state]
+            .iter()
+            .map(|weight| weight.value())
+            .map(|weight| {
+                if weight.is_finite() {
+                    weight.max(0.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect::<Vec<_>>();
+        let sum = weights.iter().sum::<f64>();
+        if sum > 0.0 && sum.is_finite() {
+            let mut random = self.rand_gen.random_range(0.0..sum);
+            for (index, weight) in weights.into_iter().enumerate() {
+                if random < weight {
+                    self.state = index;
+                    break;
+                }
+                random -= weight;
+            }
+        }
+        value
+    }
+
+    fn reset(&mut self) {
+        self.state = self.start;
+        // reset random number generator to its initial state, when the generator is seeded
+        if let Some(seed) = self.seed {
+            self.rand_gen = Xoshiro256PlusPlus::seed_from_u64(seed);
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A rhythm which emits pulses from a seeded or random [`MarkovGenerator`].
+pub type MarkovRhythm = GenerativeRhythm<MarkovGenerator>;
+
+impl MarkovRhythm {
+    /// Create a new Markov chain rhythm. See [`MarkovGenerator::new`].
+    pub fn new(
+        states: Vec<f32>,
+        transitions: Vec<Vec<RhythmProperty>>,
+        start: usize,
+        seed: Option<u64>,
+    ) -> Self {
+        Self::from_generator(MarkovGenerator::new(states, transitions, start, seed))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{rhythms::MarkovRhythm, Parameter, Rhythm, RhythmProperty};
+
+    fn run_values(rhythm: &mut dyn Rhythm, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|_| rhythm.run().map(|event| event.value).unwrap_or(-1.0))
+            .collect()
+    }
+
+    #[test]
+    fn markov() {
+        // deterministic chains alternate states
+        let mut rhythm = MarkovRhythm::new(
+            vec![1.0, 0.0],
+            vec![vec![0.0.into(), 1.0.into()], vec![1.0.into(), 0.0.into()]],
+            0,
+            None,
+        );
+        assert_eq!(run_values(&mut rhythm, 4), vec![1.0, 0.0, 1.0, 0.0]);
+
+        // seeded chains repeat after reset and loop with a length
+        let transitions = || vec![vec![0.5.into(), 0.5.into()], vec![0.5.into(), 0.5.into()]];
+        let mut rhythm = MarkovRhythm::new(vec![1.0, 0.0], transitions(), 0, Some(1234));
+        let values = run_values(&mut rhythm, 16);
+        assert!(values.contains(&1.0) && values.contains(&0.0));
+        rhythm.reset();
+        assert_eq!(run_values(&mut rhythm, 16), values);
+
+        let mut rhythm =
+            MarkovRhythm::new(vec![1.0, 0.0], transitions(), 0, Some(1234)).with_length(4);
+        rhythm.set_repeat_count(Some(1));
+        assert_eq!(rhythm.len(), 4);
+        assert_eq!(run_values(&mut rhythm, 4), run_values(&mut rhythm, 4));
+        assert_eq!(rhythm.run(), None);
+
+        // transition weights can follow parameters
+        let parameter = Rc::new(RefCell::new(Parameter::with_float(
+            "stay",
+            "",
+            "",
+            0.0..=1.0,
+            1.0,
+        )));
+        let mut rhythm = MarkovRhythm::new(
+            vec![1.0, 0.0],
+            vec![
+                vec![RhythmProperty::with_parameter("stay", 0.0), 1.0.into()],
+                vec![1.0.into(), 0.0.into()],
+            ],
+            0,
+            Some(1234),
+        );
+        rhythm.set_parameters(vec![Rc::clone(&parameter)]);
+        parameter.borrow_mut().set_value(0.0);
+        assert_eq!(run_values(&mut rhythm, 4), vec![1.0, 0.0, 1.0, 0.0]);
+
+        // non-finite weights are ignored
+        let mut rhythm = MarkovRhythm::new(
+            vec![1.0, 0.0],
+            vec![
+                vec![f64::INFINITY.into(), 1.0.into()],
+                vec![1.0.into(), f64::NAN.into()],
+            ],
+            0,
+            Some(1234),
+        );
+        assert_eq!(run_values(&mut rhythm, 4), vec![1.0, 0.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,143 @@
+use rand::{rng, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::{
+    rhythm::generative::{GenerativeRhythm, PulseGenerator, RhythmProperty},
+    ParameterSet,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates pulses with a random walk: emits a pulse density value, which moves by a random
+/// amount of at most `step` with each pulse. Values bounce off the `min` and `max` bounds.
+///
+/// The emitted values are pulse probabilities: with the default probability gate, the walk's
+/// density defines how likely each pulse triggers.
+#[derive(Clone, Debug)]
+pub struct RandomWalkGenerator {
+    start: RhythmProperty,
+    min: RhythmProperty,
+    max: RhythmProperty,
+    step: RhythmProperty,
+    value: Option<f64>,
+    rand_gen: Xoshiro256PlusPlus,
+    seed: Option<u64>,
+}
+
+impl RandomWalkGenerator {
+    /// Create a new random walk with the given start value, bounds and maximum step size.
+    pub fn new(
+        start: RhythmProperty,
+        min: RhythmProperty,
+        max: RhythmProperty,
+        step: RhythmProperty,
+        seed: Option<u64>,
+    ) -> Self {
+        let value = None;
+        let rand_seed = seed.unwrap_or_else(|| rng().random());
+        let rand_gen = Xoshiro256PlusPlus::seed_from_u64(rand_seed);
+        Self {
+            start,
+            min,
+            max,
+            step,
+            value,
+            rand_gen,
+            seed,
+        }
+    }
+
+    fn bounds(&self) -> (f64, f64) {
+        let min = self.min.value().clamp(0.0, 1.0);
+        let max = self.max.value().clamp(0.0, 1.0);
+        (min.min(max), min.max(max))
+    }
+}
+
+impl PulseGenerator for RandomWalkGenerator {
+    fn set_parameters(&mut self, parameters: &ParameterSet) {
+        for property in [
+            &mut self.start,
+            &mut self.min,
+            &mut self.max,
+            &mut self.step,
+        ] {
+            property.set_parameters(parameters);
+        }
+    }
+
+    fn generate(&mut self) -> f32 {
+        let (min, max) = self.bounds();
+        let value = self
+            .value
+            .unwrap_or_else(|| self.start.value())
+            .clamp(min, max);
+        // move on
+        let step = self.step.value().abs();
+        let mut next = value;
+        if step > 0.0 {
+            next += self.rand_gen.random_range(-step..=step);
+        }
+        if next > max {
+            next = max - (next - max);
+        }
+        if next < min {
+            next = min + (min - next);
+        }
+        self.value = Some(next.clamp(min, max));
+        value as f32
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+        // reset random number generator to its initial state, when the generator is seeded
+        if let Some(seed) = self.seed {
+            self.rand_gen = Xoshiro256PlusPlus::seed_from_u64(seed);
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A rhythm which emits pulse densities from a [`RandomWalkGenerator`].
+pub type RandomWalkRhythm = GenerativeRhythm<RandomWalkGenerator>;
+
+impl RandomWalkRhythm {
+    /// Create a new random walk rhythm. See [`RandomWalkGenerator::new`].
+    pub fn new(
+        start: RhythmProperty,
+        min: RhythmProperty,
+        max: RhythmProperty,
+        step: RhythmProperty,
+        seed: Option<u64>,
+    ) -> Self {
+        Self::from_generator(RandomWalkGenerator::new(start, min, max, step, seed))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{rhythms::RandomWalkRhythm, Rhythm};
+
+    #[test]
+    fn random_walk() {
+        let mut rhythm =
+            RandomWalkRhythm::new(0.5.into(), 0.25.into(), 0.75.into(), 0.1.into(), Some(1234));
+        let values = (0..64)
+            .map(|_| rhythm.run().unwrap().value)
+            .collect::<Vec<_>>();
+        assert_eq!(values[0], 0.5);
+        assert!(values.iter().all(|v| (0.25..=0.75).contains(v)));
+        assert!(values.windows(2).all(|w| (w[1] - w[0]).abs() <= 0.1 + 1e-6));
+        assert!(values.iter().any(|v| *v != 0.5));
+        rhythm.reset();
+        assert_eq!(
+            (0..64)
+                .map(|_| rhythm.run().unwrap().value)
+                .collect::<Vec<_>>(),
+            values
+        );
+    }
+}
@@ -83,11 +83,8 @@ impl Rhythm for ScriptedRhythm {
     }
 
     fn len(&self) -> usize {
-        if let Some(pulse_iter) = &self.pulse_iter {
-            pulse_iter.len()
-        } else {
-            1
-        }
+        // pulses are generated on the fly, so the rhythm has no known length
+        0
     }
 
     fn run(&mut self) -> Option<RhythmEvent> {
@@ -0,0 +1,326 @@
+//! Well-known rhythm necklaces, timelines and maximally even rhythm generators.
+//!
+//! All functions in this module create or transform `Vec<bool>` patterns, like
+//! [`euclidean`](super::euclidean::euclidean) does. Use [`FixedRhythm`](super::fixed::FixedRhythm)
+//! constructors to turn them into rhythms.
+
+// -------------------------------------------------------------------------------------------------
+
+/// Named timeline patterns from traditional music, as described by Godfried Toussaint in
+/// "The Geometry of Musical Rhythm".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timeline {
+    /// `x . . x . . x .`
+    Tresillo,
+    /// `x . x x . x x .`
+    Cinquillo,
+    /// `x . . x x . x .`
+    Habanera,
+    /// `x . . . x . x . . . x . x . . .`
+    Shiko,
+    /// `x . . x . . x . . . x . x . . .`
+    SonClave,
+    /// `. . x . x . . . x . . x . . x .`
+    SonClave23,
+    /// `x . . x . . . x . . x . x . . .`
+    RumbaClave,
+    /// `. . x . x . . . x . . x . . . x`
+    RumbaClave23,
+    /// `x . . x . . x . . . x x . . . .`
+    Soukous,
+    /// `x . . x . . x . . . x . . x . .`
+    BossaNova,
+    /// `x . . x . . x . . . x . . . x .`
+    Gahu,
+    /// `x . x . x x . x . x . x`
+    Bembe,
+    /// `x . x . x . . x . x . .`
+    FumeFume,
+}
+
+impl Timeline {
+    /// All timeline names, as used in `TryFrom<&str>`.
+    pub fn names() -> Vec<&'static str> {
+        vec![
+            "tresillo",
+            "cinquillo",
+            "habanera",
+            "shiko",
+            "son_clave",
+            "son_clave_23",
+            "rumba_clave",
+            "rumba_clave_23",
+            "soukous",
+            "bossa_nova",
+            "gahu",
+            "bembe",
+            "fume_fume",
+        ]
+    }
+
+    /// The timeline's pattern as onsets (true) and rests (false).
+    pub fn pattern(&self) -> Vec<bool> {
+        let pattern = match self {
+            Timeline::Tresillo => "x..x..x.",
+            Timeline::Cinquillo => "x.xx.xx.",
+            Timeline::Habanera => "x..xx.x.",
+            Timeline::Shiko => "x...x.x...x.x...",
+            Timeline::SonClave => "x..x..x...x.x...",
+            Timeline::SonClave23 => "..x.x...x..x..x.",
+            Timeline::RumbaClave => "x..x...x..x.x...",
+            Timeline::RumbaClave23 => "..x.x...x..x...x",
+            Timeline::Soukous => "x..x..x...xx....",
+            Timeline::BossaNova => "x..x..x...x..x..",
+            Timeline::Gahu => "x..x..x...x...x.",
+            Timeline::Bembe => "x.x.xx.x.x.x",
+            Timeline::FumeFume => "x.x.x..x.x..",
+        };
+        pattern.chars().map(|c| c == 'x').collect()
+    }
+}
+
+impl TryFrom<&str> for Timeline {
+    type Error = String;
+
+    fn try_from(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "tresillo" => Ok(Self::Tresillo),
+            "cinquillo" => Ok(Self::Cinquillo),
+            "habanera" => Ok(Self::Habanera),
+            "shiko" => Ok(Self::Shiko),
+            "son_clave" | "son_clave_32" | "son" => Ok(Self::SonClave),
+            "son_clave_23" => Ok(Self::SonClave23),
+            "rumba_clave" | "rumba_clave_32" | "rumba" => Ok(Self::RumbaClave),
+            "rumba_clave_23" => Ok(Self::RumbaClave23),
+            "soukous" => Ok(Self::Soukous),
+            "bossa_nova" | "bossa" => Ok(Self::BossaNova),
+            "gahu" => Ok(Self::Gahu),
+            "bembe" => Ok(Self::Bembe),
+            "fume_fume" => Ok(Self::FumeFume),
+            _ => Err(format!(
+                "invalid timeline '{}', valid timelines are: {}",
+                name,
+                Self::names().join(", ")
+            )),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Generates a maximally even rhythm with the given number of steps (onsets) and pulses (length)
+/// using Bresenham's line algorithm: onsets are placed at `ceil(i * pulses / steps)`.
+///
+/// The result is a rotation of the [`euclidean`](super::euclidean::euclidean) rhythm with the
+/// same steps and pulses, which always starts with an onset.
+pub fn maximally_even(steps: u32, pulses: u32, offset: i32) -> Vec<bool> {
+    if steps >= pulses {
+        return vec![true; pulses as usize];
+    }
+    let (steps, pulses) = (steps as u64, pulses as u64);
+    let rhythm = (0..pulses)
+        .map(|index| (index * steps) % pulses < steps)
+        .collect::<Vec<_>>();
+    rotate(&rhythm, offset)
+}
+
+/// Generates a maximally even rhythm over pulses with the given relative durations: the given
+/// number of steps (onsets) are spread evenly over the total duration of all pulses, and each
+/// onset is placed on the first pulse which starts at or after its ideal position.
+///
+/// With equal weights, this is the same as [`maximally_even`]. Pulses with large weights may
+/// absorb multiple onsets, so the result can contain less than `steps` onsets. Negative weights
+/// are treated as 0.
+pub fn weighted_maximally_even(steps: u32, weights: &[f64]) -> Vec<bool> {
+    let mut rhythm = vec![false; weights.len()];
+    let mut starts = Vec::with_capacity(weights.len());
+    let mut total = 0.0;
+    for weight in weights {
+        starts.push(total);
+        total += weight.max(0.0);
+    }
+    if steps == 0 || total <= 0.0 {
+        return rhythm;
+    }
+    const EPSILON: f64 = 1e-9;
+    for step in 0..steps {
+        let position = step as f64 * total / steps as f64;
+        if let Some(index) = starts.iter().position(|start| *start >= position - EPSILON) {
+            rhythm[index] = true;
+        }
+    }
+    rhythm
+}
+
+/// Generates the Christoffel word with the given number of steps (onsets) and pulses (length) as
+/// rhythm: the discretization of a line with slope `steps / (pulses - steps)`.
+///
+/// Lower words start with a rest and end with an onset. Upper words are the reversed lower words.
+pub fn christoffel(steps: u32, pulses: u32, upper: bool) -> Vec<bool> {
+    if steps >= pulses {
+        return vec![true; pulses as usize];
+    }
+    let (steps, pulses) = (steps as u64, pulses as u64);
+    let mut rhythm = (0..pulses)
+        .map(|index| (index + 1) * steps / pulses > index * steps / pulses)
+        .collect::<Vec<_>>();
+    if upper {
+        rhythm.reverse();
+    }
+    rhythm
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Inverts the given rhythm: onsets become rests and rests become onsets.
+pub fn complement(rhythm: &[bool]) -> Vec<bool> {
+    rhythm.iter().map(|onset| !onset).collect()
+}
+
+/// Rotates the given rhythm by the given offset. Like in
+/// [`euclidean`](super::euclidean::euclidean), positive offsets rotate to the left, negative
+/// offsets to the right.
+pub fn rotate(rhythm: &[bool], offset: i32) -> Vec<bool> {
+    let mut rhythm = rhythm.to_vec();
+    if !rhythm.is_empty() {
+        let len = rhythm.len();
+        match offset {
+            n if n > 0 => rhythm.rotate_left((n as usize) % len),
+            n if n < 0 => rhythm.rotate_right((n.unsigned_abs() as usize) % len),
+            _ => (),
+        }
+    }
+    rhythm
+}
+
+/// Returns all distinct rotations of the given rhythm which start with an onset.
+pub fn rotations(rhythm: &[bool]) -> Vec<Vec<bool>> {
+    let mut result: Vec<Vec<bool>> = Vec::new();
+    for (index, onset) in rhythm.iter().enumerate() {
+        if *onset {
+            let rotation = rotate(rhythm, index as i32);
+            if !result.contains(&rotation) {
+                result.push(rotation);
+            }
+        }
+    }
+    result
+}
+
+/// Returns the canonical representation of the given rhythm's necklace: the rotation which is
+/// lexicographically greatest, with onsets sorting before rests. Rhythms which are rotations of
+/// each other share the same necklace.
+pub fn necklace(rhythm: &[bool]) -> Vec<bool> {
+    (0..rhythm.len())
+        .map(|index| rotate(rhythm, index as i32))
+        .max()
+        .unwrap_or_default()
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::rhythm::euclidean::euclidean;
+
+    fn pattern(string: &str) -> Vec<bool> {
+        string.split(' ').map(|c| c == "x").collect()
+    }
+
+    #[test]
+    fn timelines() {
+        for name in Timeline::names() {
+            let timeline = Timeline::try_from(name).unwrap();
+            assert!(matches!(timeline.pattern().len(), 8 | 12 | 16));
+        }
+        assert!(Timeline::try_from("Bossa-Nova").is_ok());
+        assert!(Timeline::try_from("polka").is_err());
+
+        assert_eq!(Timeline::Tresillo.pattern(), euclidean(3, 8, 0));
+        assert_eq!(Timeline::Cinquillo.pattern(), euclidean(5, 8, 0));
+        assert_eq!(
+            necklace(&Timeline::Bembe.pattern()),
+            necklace(&euclidean(7, 12, 0))
+        );
+        assert_eq!(
+            rotate(&Timeline::SonClave.pattern(), 8),
+            Timeline::SonClave23.pattern()
+        );
+        assert_eq!(
+            rotate(&Timeline::RumbaClave.pattern(), 8),
+            Timeline::RumbaClave23.pattern()
+        );
+    }
+
+    #[test]
+    fn maximally_even_sets() {
+        assert_eq!(maximally_even(3, 8, 0), pattern("x ~ ~ x ~ ~ x ~"));
+        assert_eq!(maximally_even(5, 8, 0), pattern("x ~ x ~ x x ~ x"));
+        assert_eq!(maximally_even(5, 8, 2), pattern("x ~ x x ~ x x ~"));
+        assert_eq!(maximally_even(0, 4, 0), vec![false; 4]);
+        assert_eq!(maximally_even(5, 4, 0), vec![true; 4]);
+        for (steps, pulses) in [(2, 5), (4, 9), (5, 12), (7, 16), (13, 24)] {
+            assert_eq!(
+                necklace(&maximally_even(steps, pulses, 0)),
+                necklace(&euclidean(steps, pulses, 0))
+            );
+        }
+
+        assert_eq!(
+            weighted_maximally_even(3, &[1.0; 8]),
+            maximally_even(3, 8, 0)
+        );
+        assert_eq!(
+            weighted_maximally_even(2, &[2.0, 1.0, 1.0, 2.0, 1.0, 1.0]),
+            pattern("x ~ ~ x ~ ~")
+        );
+        assert_eq!(
+            weighted_maximally_even(3, &[3.0, 1.0, 1.0, 1.0]),
+            pattern("x x x ~")
+        );
+        assert_eq!(
+            weighted_maximally_even(3, &[1.0, 1.0, 4.0]),
+            pattern("x ~ x")
+        );
+        assert_eq!(weighted_maximally_even(3, &[]), Vec::<bool>::new());
+        assert_eq!(weighted_maximally_even(2, &[0.0, 0.0]), vec![false; 2]);
+    }
+
+    #[test]
+    fn christoffel_words() {
+        assert_eq!(christoffel(3, 8, false), pattern("~ ~ x ~ ~ x ~ x"));
+        assert_eq!(christoffel(3, 8, true), pattern("x ~ x ~ ~ x ~ ~"));
+        assert_eq!(christoffel(2, 5, false), pattern("~ ~ x ~ x"));
+        assert_eq!(christoffel(0, 3, true), vec![false; 3]);
+        assert_eq!(christoffel(3, 3, false), vec![true; 3]);
+        assert_eq!(
+            necklace(&christoffel(5, 12, true)),
+            necklace(&euclidean(5, 12, 0))
+        );
+    }
+
+    #[test]
+    fn transformations() {
+        let tresillo = Timeline::Tresillo.pattern();
+        assert_eq!(complement(&tresillo), pattern("~ x x ~ x x ~ x"));
+        assert_eq!(rotate(&tresillo, 3), pattern("x ~ ~ x ~ x ~ ~"));
+        assert_eq!(rotate(&tresillo, -2), pattern("x ~ x ~ ~ x ~ ~"));
+        assert_eq!(rotate(&tresillo, 3 + 8), rotate(&tresillo, 3));
+        assert_eq!(rotate(&[], 3), Vec::<bool>::new());
+        assert_eq!(
+            rotations(&tresillo),
+            vec![
+                pattern("x ~ ~ x ~ ~ x ~"),
+                pattern("x ~ ~ x ~ x ~ ~"),
+                pattern("x ~ x ~ ~ x ~ ~")
+            ]
+        );
+        assert_eq!(rotations(&pattern("x ~ x ~")).len(), 1);
+        assert_eq!(necklace(&pattern("~ ~ x x ~")), pattern("x x ~ ~ ~"));
+        assert_eq!(
+            necklace(&Timeline::SonClave23.pattern()),
+            necklace(&Timeline::SonClave.pattern())
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Transformations of static pulse rhythms.
+
+use crate::{rhythm::fixed::FixedRhythm, Pulse};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Transforms static pulse rhythms: pulse vectors or [`FixedRhythm`]s.
+///
+/// Mirrors the vocabulary of the Lua `pulse` library for Rust hosts. All transformations operate
+/// on the top level steps of the rhythm: sub divisions stay intact, unless noted otherwise.
+///
+/// Pulses with values > 0 are onsets, pulses with value 0 are rests. Boolean operations combine
+/// sub division pulses by their position in the sub division: unweighted sub divisions on a
+/// common grid, weighted sub divisions on their weighted onset positions. The weights of this
+/// rhythm's pulses are kept.
+///
+/// ### Example
+///
+/// ```rust
+/// use pattrns::prelude::*;
+///
+/// let pulses = vec![Pulse::from(1.0), Pulse::from(0.0), Pulse::from(0.0)];
+/// assert_eq!(
+///     pulses.clone().rotated_right(1).union(pulses),
+///     vec![Pulse::from(1.0), Pulse::from(1.0), Pulse::from(0.0)]
+/// );
+/// ```
+pub trait PulseTransform: Sized {
+    /// Shift the steps by the given amount to the right (positive amount) or to the left
+    /// (negative amount), like `pulse.rotate` in Lua. Note that euclidean and timeline offsets,
+    /// see [`timeline::rotate`](crate::rhythm::timeline::rotate), rotate to the left instead.
+    #[must_use]
+    fn rotated_right(self, amount: i32) -> Self;
+
+    /// Play the rhythm backwards. Sub divisions get reversed as well.
+    #[must_use]
+    fn reversed(self) -> Self;
+
+    /// Expand (factor > 1) or shrink (factor < 1) the number of steps by the given factor,
+    /// spreading the existing steps evenly and filling gaps with rests, like `pulse.spread`
+    /// in Lua. When shrinking, steps which fall onto the same position overwrite each other.
+    ///
+    /// ### Panics
+    /// Panics if the given factor is not > 0.
+    #[must_use]
+    fn stretched(self, factor: f64) -> Self;
+
+    /// Alternate the steps of this and the other rhythm: `a1, b1, a2, b2, ...`. Remaining steps
+    /// of the longer rhythm get appended.
+    #[must_use]
+    fn interleaved(self, other: Self) -> Self;
+
+    /// Step-wise boolean OR: onsets of both rhythms. Pulse values combine to their maximum.
+    /// The shorter rhythm is filled up with rests.
+    #[must_use]
+    fn union(self, other: Self) -> Self;
+
+    /// Step-wise boolean AND: onsets which are present in both rhythms. Pulse values combine to
+    /// their minimum. The shorter rhythm is filled up with rests.
+    #[must_use]
+    fn intersection(self, other: Self) -> Self;
+
+    /// Step-wise boolean XOR: onsets which are present in one of the rhythms only. The shorter
+    /// rhythm is filled up with rests.
+    #[must_use]
+    fn xor(self, other: Self) -> Self;
+
+    /// Scale all pulse values by the given factor and clamp them to range \[0 - 1\]. With the
+    /// default probability pulse values, this scales the rhythm's density.
+    #[must_use]
+    fn scaled_density(self, factor: f32) -> Self;
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl PulseTransform for Vec<Pulse> {
+    fn rotated_right(mut self, amount: i32) -> Self {
+        if !self.is_empty() {
+            let len = self.len();
+            match amount {
+                n if n > 0 => self.rotate_right((n as usize) % len),
+                n if n < 0 => self.rotate_left((n.unsigned_abs() as usize) % len),
+                _ => (),
+            }
+        }
+        self
+    }
+
+    fn reversed(self) -> Self {
+        self.into_iter().rev().map(reverse_pulse).collect()
+    }
+
+    fn stretched(self, factor: f64) -> Self {
+        assert!(factor > 0.0, "Invalid stretch factor");
+        let len = (self.len() as f64 * factor).round() as usize;
+        let mut result = vec![Pulse::Pulse(0.0); len];
+        for (index, pulse) in self.into_iter().enumerate() {
+            let position = (index as f64 * factor).round() as usize;
+            if position < len {
+                result[position] = pulse;
+            }
+        }
+        result
+    }
+
+    fn interleaved(self, other: Self) -> Self {
+        let mut result = Vec::with_capacity(self.len() + other.len());
+        let mut first = self.into_iter();
+        let mut second = other.into_iter();
+        loop {
+            match (first.next(), second.next()) {
+                (None, None) => break,
+                (a, b) => result.extend(a.into_iter().chain(b)),
+            }
+        }
+        result
+    }
+
+    fn union(self, other: Self) -> Self {
+        combine_pulses(&self, &other, &f32::max)
+    }
+
+    fn intersection(self, other: Self) -> Self {
+        combine_pulses(&self, &other, &f32::min)
+    }
+
+    fn xor(self, other: Self) -> Self {
+        combine_pulses(&self, &other, &|a, b| match (a > 0.0, b > 0.0) {
+            (true, false) => a,
+            (false, true) => b,
+            _ => 0.0,
+        })
+    }
+
+    fn scaled_density(self, factor: f32) -> Self {
+        self.into_iter()
+            .map(|pulse| scale_pulse(pulse, factor))
+            .collect()
+    }
+}
+
+impl PulseTransform for FixedRhythm {
+    fn rotated_right(self, amount: i32) -> Self {
+        self.with_pulses(|pulses| pulses.rotated_right(amount))
+    }
+
+    fn reversed(self) -> Self {
+        self.with_pulses(|pulses| pulses.reversed())
+    }
+
+    fn stretched(self, factor: f64) -> Self {
+        self.with_pulses(|pulses| pulses.stretched(factor))
+    }
+
+    fn interleaved(self, other: Self) -> Self {
+        self.with_pulses(|pulses| pulses.interleaved(other.pulses().to_vec()))
+    }
+
+    fn union(self, other: Self) -> Self {
+        self.with_pulses(|pulses| pulses.union(other.pulses().to_vec()))
+    }
+
+    fn intersection(self, other: Self) -> Self {
+        self.with_pulses(|pulses| pulses.intersection(other.pulses().to_vec()))
+    }
+
+    fn xor(self, other: Self) -> Self {
+        self.with_pulses(|pulses| pulses.xor(other.pulses().to_vec()))
+    }
+
+    fn scaled_density(self, factor: f32) -> Self {
+        self.with_pulses(|pulses| pulses.scaled_density(factor))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+fn reverse_pulse(pulse: Pulse) -> Pulse {
+    match pulse {
+        Pulse::Pulse(value) => Pulse::Pulse(value),
+        Pulse::SubDivision(sub_pulses) => Pulse::SubDivision(sub_pulses.reversed()),
+        Pulse::Weighted(pulse, weight) => Pulse::Weighted(Box::new(reverse_pulse(*pulse)), weight),
+    }
+}
+
+fn scale_pulse(pulse: Pulse, factor: f32) -> Pulse {
+    match pulse {
+        Pulse::Pulse(value) => Pulse::Pulse((value * factor).clamp(0.0, 1.0)),
+        Pulse::SubDivision(sub_pulses) => Pulse::SubDivision(sub_pulses.scaled_density(factor)),
+        Pulse::Weighted(pulse, weight) => {
+            Pulse::Weighted(Box::new(scale_pulse(*pulse, factor)), weight)
+        }
+    }
+}
+
+fn combine_pulses(a: &[Pulse], b: &[Pulse], op: &impl Fn(f32, f32) -> f32) -> Vec<Pulse> {
+    let rest = Pulse::Pulse(0.0);
+    (0..a.len().max(b.len()))
+        .map(|index| {
+            combine_pulse(
+                a.get(index).unwrap_or(&rest),
+                b.get(index).unwrap_or(&rest),
+                op,
+            )
+        })
+        .collect()
+}
+
+// Combine two pulses which cover the same step. Sub divisions of different lengths get spread to
+// a common grid first, so onsets keep their positions within the step. Weighted sub divisions
+// get combined on their weighted onset positions instead.
+fn combine_pulse(a: &Pulse, b: &Pulse, op: &impl Fn(f32, f32) -> f32) -> Pulse {
+    match (a, b) {
+        (Pulse::Pulse(a), Pulse::Pulse(b)) => Pulse::Pulse(op(*a, *b)),
+        (Pulse::Weighted(a, weight), b) => {
+            let b = match b {
+                Pulse::Weighted(b, _) => b,
+                b => b,
+            };
+            Pulse::Weighted(Box::new(combine_pulse(a, b, op)), *weight)
+        }
+        (a, Pulse::Weighted(b, weight)) => {
+            Pulse::Weighted(Box::new(combine_pulse(a, b, op)), *weight)
+        }
+        _ if has_weights(a) || has_weights(b) => combine_weighted_pulse(a, b, op),
+        _ => {
+            let len = lcm(grid_len(a), grid_len(b));
+            Pulse::SubDivision(combine_pulses(
+                &spread_pulse(a, len),
+                &spread_pulse(b, len),
+                op,
+            ))
+        }
+    }
+}
+
+// Combine two pulses, one of them a weighted sub division, on their onset positions. Rests only
+// end the onsets which precede them, so they don't split up the other pulse's onsets.
+fn combine_weighted_pulse(a: &Pulse, b: &Pulse, op: &impl Fn(f32, f32) -> f32) -> Pulse {
+    const EPSILON: f64 = 1e-9;
+    let (a, b) = (pulse_positions(a), pulse_positions(b));
+    let mut boundaries = vec![0.0];
+    for positions in [&a, &b] {
+        for (index, (start, pulse)) in positions.iter().enumerate() {
+            if is_onset(pulse) || (index > 0 && is_onset(positions[index - 1].1)) {
+                boundaries.push(*start);
+            }
+        }
+    }
+    boundaries.sort_by(f64::total_cmp);
+    boundaries.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+    let rest = Pulse::Pulse(0.0);
+    let pulse_at = |positions: &[(f64, &Pulse)], position: f64| {
+        positions
+            .iter()
+            .find(|(start, _)| (start - position).abs() < EPSILON)
+            .map_or(&rest, |(_, pulse)| *pulse)
+            .clone()
+    };
+    let sub_pulses = boundaries
+        .iter()
+        .enumerate()
+        .map(|(index, start)| {
+            let end = boundaries.get(index + 1).copied().unwrap_or(1.0);
+            let pulse = combine_pulse(&pulse_at(&a, *start), &pulse_at(&b, *start), op);
+            Pulse::weighted(pulse, end - start)
+        })
+        .collect();
+    Pulse::SubDivision(sub_pulses)
+}
+
+fn has_weights(pulse: &Pulse) -> bool {
+    matches!(pulse, Pulse::SubDivision(sub_pulses)
+        if sub_pulses.iter().any(|pulse| matches!(pulse, Pulse::Weighted(..))))
+}
+
+fn is_onset(pulse: &Pulse) -> bool {
+    match pulse {
+        Pulse::Pulse(value) => *value > 0.0,
+        _ => true,
+    }
+}
+
+// Relative start positions of the given pulse's unweighted sub division pulses within its step.
+fn pulse_positions(pulse: &Pulse) -> Vec<(f64, &Pulse)> {
+    match pulse {
+        Pulse::SubDivision(sub_pulses) if !sub_pulses.is_empty() => {
+            let total_weight = sub_pulses.iter().map(Pulse::weight).sum::<f64>();
+            let mut weight = 0.0;
+            sub_pulses
+                .iter()
+                .map(|sub_pulse| {
+                    let start = weight / total_weight;
+                    weight += sub_pulse.weight();
+                    match sub_pulse {
+                        Pulse::Weighted(sub_pulse, _) => (start, sub_pulse.as_ref()),
+                        sub_pulse => (start, sub_pulse),
+                    }
+                })
+                .collect()
+        }
+        pulse => vec![(0.0, pulse)],
+    }
+}
+
+fn grid_len(pulse: &Pulse) -> usize {
+    match pulse {
+        Pulse::SubDivision(sub_pulses) => sub_pulses.len().max(1),
+        _ => 1,
+    }
+}
+
+fn spread_pulse(pulse: &Pulse, len: usize) -> Vec<Pulse> {
+    let mut result = vec![Pulse::Pulse(0.0); len];
+    match pulse {
+        Pulse::SubDivision(sub_pulses) => {
+            let step = len / sub_pulses.len().max(1);
+            for (index, sub_pulse) in sub_pulses.iter().enumerate() {
+                result[index * step] = sub_pulse.clone();
+            }
+        }
+        pulse => result[0] = pulse.clone(),
+    }
+    result
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+    a / gcd(a, b) * b
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use crate::{prelude::*, rhythm::timeline, Pulse, RhythmEvent};
+
+    fn pulses(values: &[f32]) -> Vec<Pulse> {
+        values.iter().copied().map(Pulse::from).collect()
+    }
+
+    #[test]
+    fn transforms() {
+        let rhythm = pulses(&[1.0, 0.0, 0.5, 0.0]);
+        assert_eq!(
+            rhythm.clone().rotated_right(1),
+            pulses(&[0.0, 1.0, 0.0, 0.5])
+        );
+        assert_eq!(
+            rhythm.clone().rotated_right(-5),
+            pulses(&[0.0, 0.5, 0.0, 1.0])
+        );
+        // rotates to the right, while timeline offsets rotate to the left
+        assert_eq!(
+            pulses(&[1.0, 0.0, 0.0, 0.0]).rotated_right(1),
+            pulses(&[0.0, 1.0, 0.0, 0.0])
+        );
+        assert_eq!(
+            timeline::rotate(&[true, false, false, false], 1),
+            vec![false, false, false, true]
+        );
+        assert_eq!(
+            vec![Pulse::from(1.0), Pulse::from(vec![1.0, 0.0])].reversed(),
+            vec![Pulse::from(vec![0.0, 1.0]), Pulse::from(1.0)]
+        );
+        assert_eq!(
+            pulses(&[1.0, 1.0]).stretched(2.0),
+            pulses(&[1.0, 0.0, 1.0, 0.0])
+        );
+        assert_eq!(
+            pulses(&[1.0, 0.0, 1.0, 0.0]).stretched(0.5),
+            pulses(&[1.0, 1.0])
+        );
+        assert_eq!(
+            pulses(&[1.0, 1.0, 1.0]).interleaved(pulses(&[0.0])),
+            pulses(&[1.0, 0.0, 1.0, 1.0])
+        );
+        assert_eq!(
+            rhythm.clone().scaled_density(0.5),
+            pulses(&[0.5, 0.0, 0.25, 0.0])
+        );
+    }
+
+    #[test]
+    fn boolean_ops() {
+        let a = pulses(&[1.0, 0.0, 1.0, 0.0]);
+        let b = pulses(&[0.5, 1.0, 0.0]);
+        assert_eq!(a.clone().union(b.clone()), pulses(&[1.0, 1.0, 1.0, 0.0]));
+        assert_eq!(
+            a.clone().intersection(b.clone()),
+            pulses(&[0.5, 0.0, 0.0, 0.0])
+        );
+        assert_eq!(a.xor(b), pulses(&[0.0, 1.0, 1.0, 0.0]));
+
+        // sub divisions get combined on a common grid
+        assert_eq!(
+            vec![Pulse::from(vec![1.0, 0.0])].union(vec![Pulse::from(vec![0.0, 0.0, 1.0])]),
+            vec![Pulse::from(vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0])]
+        );
+        assert_eq!(
+            vec![Pulse::from(1.0)].union(vec![Pulse::from(vec![0.0, 1.0])]),
+            vec![Pulse::from(vec![1.0, 1.0])]
+        );
+
+        // weighted sub divisions get combined on their weighted onset positions
+        let step_times = |pulses: Vec<Pulse>| {
+            pulses[0]
+                .to_rhythm_events()
+                .iter()
+                .map(|event| (event.value, (event.step_time * 12.0).round()))
+                .collect::<Vec<_>>()
+        };
+        let weighted = vec![Pulse::from(vec![
+            Pulse::weighted(1.0, 3.0),
+            Pulse::from(1.0),
+        ])];
+        assert_eq!(
+            step_times(
+                weighted
+                    .clone()
+                    .union(vec![Pulse::from(vec![0.0, 0.0, 1.0])])
+            ),
+            vec![(1.0, 8.0), (1.0, 1.0), (1.0, 3.0)]
+        );
+        assert_eq!(
+            step_times(weighted.clone().union(vec![Pulse::from(vec![0.0, 1.0])])),
+            vec![(1.0, 6.0), (1.0, 3.0), (1.0, 3.0)]
+        );
+        assert_eq!(
+            step_times(weighted.intersection(vec![Pulse::from(vec![1.0, 0.0])])),
+            vec![(1.0, 6.0), (0.0, 3.0), (0.0, 3.0)]
+        );
+
+        // fixed rhythms
+        let mut rhythm = [1.0, 0.0]
+            .to_rhythm()
+            .xor([1.0, 1.0].to_rhythm())
+            .rotated_right(1);
+        assert_eq!(
+            vec![rhythm.run(), rhythm.run()],
+            vec![
+                Some(RhythmEvent {
+                    value: 1.0,
+                    step_time: 1.0,
+                }),
+                Some(RhythmEvent {
+                    value: 0.0,
+                    step_time: 1.0,
+                })
+            ]
+        );
+    }
+}
@@ -1,6 +1,11 @@
 //! Arrange `Phrase`s into a playback sequence.
 
-use crate::{phrase::PatternIndex, BeatTimeBase, Pattern, PatternEvent, Phrase, SampleTime};
+use std::rc::Rc;
+
+use crate::{
+    phrase::PatternIndex, BeatTimeBase, ClockState, ParameterSet, Pattern, PatternEvent, Phrase,
+    SampleTime, TempoMap,
+};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -8,6 +13,7 @@ use crate::{phrase::PatternIndex, BeatTimeBase, Pattern, PatternEvent, Phrase, S
 #[derive(Clone, Debug)]
 pub struct Sequence {
     time_base: BeatTimeBase,
+    tempo_map: Option<Rc<TempoMap>>,
     phrases: Vec<Phrase>,
     phrase_index: usize,
     sample_position_in_phrase: SampleTime,
@@ -18,12 +24,14 @@ pub struct Sequence {
 impl Sequence {
     /// Create a new sequence from a vector of [`Phrase`]s.
     pub fn new(time_base: BeatTimeBase, phrases: Vec<Phrase>) -> Self {
+        let tempo_map = None;
         let phrase_index = 0;
         let sample_position_in_phrase = 0;
         let sample_position = 0;
         let sample_offset = 0;
         Self {
             time_base,
+            tempo_map,
             phrases,
             phrase_index,
             sample_position_in_phrase,
@@ -38,10 +46,72 @@ impl Sequence {
     }
 
     /// Update the sequence's internal time bases with a new time base.
+    ///
+    /// When a tempo map is set, the time base's tempo only applies when the tempo map has no
+    /// tempo changes: tempo changes in the map override the time base's tempo.
+    /// See [`TempoMap::set_time_base`].
+    ///
+    /// When the tempo changes while playing, the beat position in the current phrase is kept:
+    /// the rest of the phrase plays with the new tempo.
     pub fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        // rescale the position in the current phrase to the new tempo, unless the tempo map's
+        // tempo changes override it. The sample offset is an absolute clock position and the
+        // absolute playback position remains as it is, so only the phrase position is rescaled.
+        let tempo_map_overrides_tempo = self
+            .tempo_map
+            .as_ref()
+            .is_some_and(|tempo_map| !tempo_map.has_default_tempo());
+        if !tempo_map_overrides_tempo {
+            let tempo_ratio = time_base.samples_per_beat() / self.time_base.samples_per_beat();
+            self.sample_position_in_phrase = ((self.sample_position_in_phrase as f64 * tempo_ratio)
+                .round() as SampleTime)
+                .min(self.sample_position);
+        }
         self.time_base = *time_base;
+        // refresh the tempo map's time base
+        if let Some(tempo_map) = &self.tempo_map {
+            let mut tempo_map = TempoMap::clone(tempo_map);
+            tempo_map.set_time_base(time_base);
+            self.tempo_map = Some(Rc::new(tempo_map));
+        }
         for phrase in &mut self.phrases {
             phrase.set_time_base(time_base);
+            phrase.set_tempo_map(self.tempo_map.clone());
+        }
+    }
+
+    /// Sample time at which the sequence starts. Emitted events are shifted by this offset and
+    /// sample times passed to the sequence are absolute times, which include the offset.
+    /// Tempo map positions are relative to the offset.
+    pub fn sample_offset(&self) -> SampleTime {
+        self.sample_offset
+    }
+
+    /// Set a new sample offset. See [`Self::sample_offset`].
+    pub fn set_sample_offset(&mut self, sample_offset: SampleTime) {
+        self.sample_offset = sample_offset;
+    }
+
+    /// Return a new sequence which follows the given tempo map. See [`Self::set_tempo_map`].
+    #[must_use]
+    pub fn with_tempo_map<T: Into<Option<TempoMap>>>(self, tempo_map: T) -> Self {
+        let mut new = self;
+        new.set_tempo_map(tempo_map);
+        new
+    }
+
+    /// Read-only access to our tempo map, if any.
+    pub fn tempo_map(&self) -> Option<&Rc<TempoMap>> {
+        self.tempo_map.as_ref()
+    }
+
+    /// Set or remove a tempo map, which changes the time base's tempo over time. Phrase lengths
+    /// and all beat time based patterns in all phrases follow the tempo map's tempo. Tempo map
+    /// positions are absolute beat positions, starting at the beginning of the sequence.
+    pub fn set_tempo_map<T: Into<Option<TempoMap>>>(&mut self, tempo_map: T) {
+        self.tempo_map = tempo_map.into().map(Rc::new);
+        for phrase in &mut self.phrases {
+            phrase.set_tempo_map(self.tempo_map.clone());
         }
     }
 
@@ -55,6 +125,22 @@ impl Sequence {
         &mut self.phrases
     }
 
+    /// Parameters of all patterns in all phrases. Duplicate parameter ids are skipped.
+    pub fn parameters(&self) -> ParameterSet {
+        let mut parameters = ParameterSet::new();
+        for phrase in &self.phrases {
+            for parameter in phrase.parameters() {
+                if !parameters
+                    .iter()
+                    .any(|p| p.borrow().id() == parameter.borrow().id())
+                {
+                    parameters.push(Rc::clone(parameter));
+                }
+            }
+        }
+        parameters
+    }
+
     /// returns maximum pattern count in all phrases.
     pub fn phrase_pattern_slot_count(&self) -> usize {
         let mut count = 0;
@@ -70,6 +156,11 @@ impl Sequence {
     where
         F: FnMut(PatternIndex, PatternEvent),
     {
+        let sample_offset = self.sample_offset;
+        let consumer = &mut |pattern_index, event: PatternEvent| {
+            consumer(pattern_index, event.with_offset(sample_offset))
+        };
+        let time = time.saturating_sub(sample_offset);
         debug_assert!(time >= self.sample_position, "can not rewind playback here");
         while time - self.sample_position > 0 {
             let (next_phrase_start, samples_to_run) = self.samples_until_next_phrase(time);
@@ -100,8 +191,52 @@ impl Sequence {
         }
     }
 
+    /// Follow the given clock state, e.g. from an external MIDI clock: runs patterns until the
+    /// clock's position plus the given lookahead is reached, calling the given `consumer` for all
+    /// emitted events. The clock's tempo replaces our time base's tempo, when present.
+    ///
+    /// When the clock's position jumped, the sequence gets reset and seeked to the new position.
+    /// When the clock ran ahead of the sequence, late events get skipped. Stopped clocks emit
+    /// nothing.
+    pub fn consume_events_until_clock<F>(
+        &mut self,
+        clock_state: &ClockState,
+        lookahead: SampleTime,
+        consumer: &mut F,
+    ) where
+        F: FnMut(PatternIndex, PatternEvent),
+    {
+        // follow the clock's tempo
+        if let Some(beats_per_min) = clock_state.beats_per_min {
+            if beats_per_min != self.time_base.beats_per_min {
+                let time_base = BeatTimeBase {
+                    beats_per_min,
+                    ..self.time_base
+                };
+                self.set_time_base(&time_base);
+            }
+        }
+        // follow the clock's position
+        if clock_state.position_changed {
+            let sample_offset = self.sample_offset;
+            self.reset();
+            self.sample_offset = sample_offset;
+            self.advance_until_time(clock_state.sample_position);
+        } else if clock_state.sample_position > self.sample_offset + self.sample_position {
+            self.advance_until_time(clock_state.sample_position);
+        }
+        // emit events ahead of the clock
+        if clock_state.running {
+            let time = clock_state.sample_position + lookahead;
+            if time > self.sample_offset + self.sample_position {
+                self.consume_events_until_time(time, consumer);
+            }
+        }
+    }
+
     /// Move sequence playback head to the given sample time, ignoring all events.
     pub fn advance_until_time(&mut self, sample_time: SampleTime) {
+        let sample_time = sample_time.saturating_sub(self.sample_offset);
         debug_assert!(
             sample_time >= self.sample_position,
             "can not rewind playback here"
@@ -140,6 +275,7 @@ impl Sequence {
         // reset sample offset
         self.sample_offset = 0;
         // reset our own iter state
+        self.phrase_index = 0;
         self.sample_position = 0;
         self.sample_position_in_phrase = 0;
         // reset all our phrase iters
@@ -156,11 +292,62 @@ impl Sequence {
         &mut self.phrases[self.phrase_index]
     }
 
+    // Samples until the current phrase ends and samples to run until the given time. All times
+    // are relative to the sequence's sample offset.
     fn samples_until_next_phrase(&self, time: u64) -> (u64, u64) {
-        let phrase_length_in_samples =
-            self.current_phrase().length().to_samples(&self.time_base) as SampleTime;
-        let next_phrase_start = phrase_length_in_samples - self.sample_position_in_phrase;
+        let length = self.current_phrase().length();
+        let phrase_length = length.to_samples(&self.time_base);
+        let phrase_length_in_samples = if let Some(tempo_map) = &self.tempo_map {
+            // apply tempo map from the phrase's start to its end
+            let phrase_start = (self.sample_position - self.sample_position_in_phrase) as f64;
+            let start = tempo_map.unmap_sample_time(&self.time_base, &length, phrase_start);
+            let end = tempo_map.map_sample_time(&self.time_base, &length, start + phrase_length);
+            (end - phrase_start).max(1.0) as SampleTime
+        } else {
+            phrase_length as SampleTime
+        };
+        // NB: a phrase which got shorter than the position in it ends right away
+        let next_phrase_start =
+            phrase_length_in_samples.saturating_sub(self.sample_position_in_phrase);
         let samples_to_run = time - self.sample_position;
         (next_phrase_start, samples_to_run)
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn reset() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let new_phrase = |note: &str| {
+            Phrase::new(
+                time_base,
+                vec![time_base.every_nth_bar(1.0).emit(new_note_emitter(note))],
+                BeatTimeStep::Bar(1.0),
+            )
+        };
+        let mut sequence = Sequence::new(time_base, vec![new_phrase("c4"), new_phrase("d4")]);
+        let run = |sequence: &mut Sequence, time: SampleTime| {
+            let mut notes = Vec::new();
+            sequence.consume_events_until_time(time, &mut |_, event| {
+                if let Some(Event::NoteEvents(note_events)) = event.event {
+                    notes.extend(note_events.into_iter().flatten().map(|note| note.note));
+                }
+            });
+            notes
+        };
+        assert_eq!(run(&mut sequence, 88200 + 44100), vec![Note::C4, Note::D4]);
+        // resets restart playback from the first phrase
+        sequence.reset();
+        assert_eq!(run(&mut sequence, 44100), vec![Note::C4]);
+    }
+}
@@ -2182,6 +2182,7 @@ mod test {
 
         assert!(Cycle::from("c4'mode").is_ok());
         assert!(Cycle::from("c'm7#^-").is_ok());
+        assert!(Cycle::from("c'V7/V c'viiø/bb").is_ok());
         assert!(Cycle::from("[[[[[[[[]]]]]][[[[[]][[[]]]]]][[[][[[]]]]][[[[]]]]]]").is_ok());
 
         Ok(())
@@ -2441,6 +2442,14 @@ mod test {
             ],
         )?;
 
+        assert_cycles(
+            "c4'V7/V c4'maj/1",
+            vec![vec![vec![
+                Event::at(Fraction::from(0), Fraction::new(1, 2)).with_chord(0, 4, "V7/V"),
+                Event::at(Fraction::new(1, 2), Fraction::new(1, 2)).with_chord(0, 4, "maj"),
+            ]]],
+        )?;
+
         assert_cycles(
             "[1 2] [3 4,[5 6]:42]",
             vec![vec![
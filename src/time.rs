@@ -8,6 +8,12 @@ pub use beats::{BeatTimeBase, BeatTimeStep};
 mod seconds;
 pub use seconds::{SecondTimeBase, SecondTimeStep};
 
+mod meter;
+pub use meter::{MeterMap, TimeSignature};
+
+mod tempo;
+pub use tempo::{TempoChange, TempoMap, TempoRamp};
+
 // -------------------------------------------------------------------------------------------------
 
 /// Sample time value type as emitted by
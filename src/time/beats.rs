@@ -58,12 +58,16 @@ impl SampleTimeDisplay for BeatTimeBase {
 // -------------------------------------------------------------------------------------------------
 
 /// Defines a number of steps in sixteenth, beat or bar amounts.
+///
+/// `Beats` are counts of the current meter, which may differ from `Quarter` notes when a
+/// [`TempoMap`](crate::TempoMap) with time signature changes is applied.
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum BeatTimeStep {
     SixtyFourth(f32),
     ThirtySecond(f32),
     Sixteenth(f32),
     Eighth(f32),
+    Quarter(f32),
     Beats(f32),
     Half(f32),
     Whole(f32),
@@ -78,6 +82,7 @@ impl BeatTimeStep {
             BeatTimeStep::ThirtySecond(amount) => amount,
             BeatTimeStep::Sixteenth(amount) => amount,
             BeatTimeStep::Eighth(amount) => amount,
+            BeatTimeStep::Quarter(amount) => amount,
             BeatTimeStep::Beats(amount) => amount,
             BeatTimeStep::Half(amount) => amount,
             BeatTimeStep::Whole(amount) => amount,
@@ -91,6 +96,7 @@ impl BeatTimeStep {
             BeatTimeStep::ThirtySecond(_) => *self = BeatTimeStep::ThirtySecond(step),
             BeatTimeStep::Sixteenth(_) => *self = BeatTimeStep::Sixteenth(step),
             BeatTimeStep::Eighth(_) => *self = BeatTimeStep::Eighth(step),
+            BeatTimeStep::Quarter(_) => *self = BeatTimeStep::Quarter(step),
             BeatTimeStep::Beats(_) => *self = BeatTimeStep::Beats(step),
             BeatTimeStep::Half(_) => *self = BeatTimeStep::Half(step),
             BeatTimeStep::Whole(_) => *self = BeatTimeStep::Whole(step),
@@ -105,6 +111,7 @@ impl BeatTimeStep {
             BeatTimeStep::ThirtySecond(_) => time_base.samples_per_beat() / 8.0,
             BeatTimeStep::Sixteenth(_) => time_base.samples_per_beat() / 4.0,
             BeatTimeStep::Eighth(_) => time_base.samples_per_beat() / 2.0,
+            BeatTimeStep::Quarter(_) => time_base.samples_per_beat(),
             BeatTimeStep::Beats(_) => time_base.samples_per_beat(),
             BeatTimeStep::Half(_) => time_base.samples_per_beat() * 2.0,
             BeatTimeStep::Whole(_) => time_base.samples_per_beat() * 4.0,
@@ -0,0 +1,198 @@
+use std::fmt::Display;
+
+// -------------------------------------------------------------------------------------------------
+
+/// A musical time signature, e.g. 4/4 or 7/8.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TimeSignature {
+    /// Number of counts in a bar.
+    pub numerator: u32,
+    /// Note value of a single count: 4 is a quarter note, 8 an eighth note.
+    pub denominator: u32,
+}
+
+impl TimeSignature {
+    /// Create a new time signature.
+    ///
+    /// ### Panics
+    /// Panics when numerator or denominator are 0.
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        assert!(numerator > 0, "Time signature numerator must be > 0");
+        assert!(denominator > 0, "Time signature denominator must be > 0");
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// Length of a single bar in quarter note beats.
+    pub fn beats_per_bar(&self) -> f64 {
+        self.numerator as f64 * self.beats_per_count()
+    }
+
+    /// Length of a single count in quarter note beats.
+    pub fn beats_per_count(&self) -> f64 {
+        4.0 / self.denominator as f64
+    }
+}
+
+impl Display for TimeSignature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Time signature changes over bars.
+///
+/// Converts bar and count positions, which depend on the current time signature, to and from
+/// linear quarter note beat positions. Bars are counted from 0, and a time signature is valid
+/// from its bar until the next time signature change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeterMap {
+    // time signature changes with their start positions in bars, beats and counts
+    changes: Vec<MeterChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MeterChange {
+    bar: u32,
+    beat: f64,
+    count: f64,
+    time_signature: TimeSignature,
+}
+
+impl MeterMap {
+    /// Create a new meter map with the given initial time signature.
+    pub fn new(time_signature: TimeSignature) -> Self {
+        let changes = vec![MeterChange {
+            bar: 0,
+            beat: 0.0,
+            count: 0.0,
+            time_signature,
+        }];
+        Self { changes }
+    }
+
+    /// Return a new meter map with the given time signature change applied.
+    #[must_use]
+    pub fn with_time_signature(mut self, bar: u32, time_signature: TimeSignature) -> Self {
+        self.add_time_signature(bar, time_signature);
+        self
+    }
+
+    /// Change the time signature, starting at the given bar. An existing change at the same bar
+    /// gets replaced.
+    pub fn add_time_signature(&mut self, bar: u32, time_signature: TimeSignature) {
+        let change = MeterChange {
+            bar,
+            beat: 0.0,
+            count: 0.0,
+            time_signature,
+        };
+        let index = self.changes.partition_point(|c| c.bar < bar);
+        if self.changes.get(index).is_some_and(|c| c.bar == bar) {
+            self.changes[index] = change;
+        } else {
+            self.changes.insert(index, change);
+        }
+        // update start positions
+        for index in 1..self.changes.len() {
+            let previous = self.changes[index - 1];
+            let bars = (self.changes[index].bar - previous.bar) as f64;
+            self.changes[index].beat =
+                previous.beat + bars * previous.time_signature.beats_per_bar();
+            self.changes[index].count =
+                previous.count + bars * previous.time_signature.numerator as f64;
+        }
+    }
+
+    /// All time signature changes with their start bars.
+    pub fn time_signatures(&self) -> impl Iterator<Item = (u32, TimeSignature)> + '_ {
+        self.changes.iter().map(|c| (c.bar, c.time_signature))
+    }
+
+    /// Time signature at the given quarter note beat position.
+    pub fn time_signature_at(&self, beat: f64) -> TimeSignature {
+        self.changes[self.change_at(|c| c.beat <= beat)].time_signature
+    }
+
+    /// Convert a bar position to a quarter note beat position.
+    pub fn bars_to_beats(&self, bars: f64) -> f64 {
+        let change = &self.changes[self.change_at(|c| c.bar as f64 <= bars)];
+        change.beat + (bars - change.bar as f64) * change.time_signature.beats_per_bar()
+    }
+
+    /// Convert a quarter note beat position to a bar position.
+    pub fn beats_to_bars(&self, beats: f64) -> f64 {
+        let change = &self.changes[self.change_at(|c| c.beat <= beats)];
+        change.bar as f64 + (beats - change.beat) / change.time_signature.beats_per_bar()
+    }
+
+    /// Convert a position in counts (time signature denominator notes) to a quarter note beat
+    /// position.
+    pub fn counts_to_beats(&self, counts: f64) -> f64 {
+        let change = &self.changes[self.change_at(|c| c.count <= counts)];
+        change.beat + (counts - change.count) * change.time_signature.beats_per_count()
+    }
+
+    /// Convert a quarter note beat position to a position in counts (time signature denominator
+    /// notes).
+    pub fn beats_to_counts(&self, beats: f64) -> f64 {
+        let change = &self.changes[self.change_at(|c| c.beat <= beats)];
+        change.count + (beats - change.beat) / change.time_signature.beats_per_count()
+    }
+
+    /// Bar, count within the bar and fraction of the count at the given quarter note beat
+    /// position. Bars and counts start at 0.
+    pub fn position(&self, beats: f64) -> (u64, u32, f64) {
+        let change = &self.changes[self.change_at(|c| c.beat <= beats)];
+        let counts = ((beats - change.beat) / change.time_signature.beats_per_count()).max(0.0);
+        let numerator = change.time_signature.numerator as u64;
+        let whole_counts = counts as u64;
+        let bar = change.bar as u64 + whole_counts / numerator;
+        let count = (whole_counts % numerator) as u32;
+        (bar, count, counts - whole_counts as f64)
+    }
+
+    // Index of the last change which matches the given predicate.
+    fn change_at<F: Fn(&MeterChange) -> bool>(&self, predicate: F) -> usize {
+        self.changes.partition_point(predicate).max(1) - 1
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn meter_map() {
+        let meter = MeterMap::new(TimeSignature::new(7, 8))
+            .with_time_signature(2, TimeSignature::new(4, 4))
+            .with_time_signature(3, TimeSignature::new(6, 8));
+
+        assert_eq!(meter.time_signature_at(0.0), TimeSignature::new(7, 8));
+        assert_eq!(meter.time_signature_at(7.0), TimeSignature::new(4, 4));
+        assert_eq!(meter.time_signature_at(11.0), TimeSignature::new(6, 8));
+
+        assert_eq!(meter.bars_to_beats(1.0), 3.5);
+        assert_eq!(meter.bars_to_beats(2.0), 7.0);
+        assert_eq!(meter.bars_to_beats(3.0), 11.0);
+        assert_eq!(meter.bars_to_beats(4.0), 14.0);
+        assert_eq!(meter.beats_to_bars(12.5), 3.5);
+
+        assert_eq!(meter.counts_to_beats(7.0), 3.5);
+        assert_eq!(meter.counts_to_beats(14.0), 7.0);
+        assert_eq!(meter.counts_to_beats(18.0), 11.0);
+        assert_eq!(meter.beats_to_counts(11.5), 19.0);
+
+        assert_eq!(meter.position(0.0), (0, 0, 0.0));
+        assert_eq!(meter.position(3.75), (1, 0, 0.5));
+        assert_eq!(meter.position(9.0), (2, 2, 0.0));
+        assert_eq!(meter.position(13.5), (3, 5, 0.0));
+        assert_eq!(meter.position(14.0), (4, 0, 0.0));
+    }
+}
@@ -0,0 +1,596 @@
+use crate::{
+    time::{MeterMap, SampleTimeBase, SampleTimeDisplay, TimeSignature},
+    BeatTimeBase, BeatTimeStep, ExactSampleTime, SampleTime,
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Defines how the tempo moves from a previous tempo change to the next one in a [`TempoMap`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TempoRamp {
+    /// Keep the previous tempo and jump to the new tempo at the change's position.
+    Step,
+    /// Linearly ramp from the previous to the new tempo.
+    Linear,
+    /// Ramp from the previous to the new tempo with the given curvature exponent (> 0).
+    /// Values < 1 change the tempo fast at the beginning of the ramp, values > 1 slowly.
+    Curve(f32),
+}
+
+/// A single tempo change point in a [`TempoMap`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TempoChange {
+    /// Position in beats.
+    pub beat: f64,
+    /// Tempo at the position in beats per minute.
+    pub beats_per_min: f32,
+    /// How the tempo moves from the previous change to this one.
+    pub ramp: TempoRamp,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Tempo and time signature automation for beat time based patterns and sequences.
+///
+/// A tempo map starts with the tempo and meter of the [`BeatTimeBase`] it got created with, and
+/// applies tempo changes with steps or ramps at given beat positions and time signature changes
+/// at given bars. After the last change, the tempo stays constant. Tempos are specified in
+/// quarter note beats per minute, and beat positions are quarter note beats.
+///
+/// Tempo maps convert beat positions to sample times and vice versa. Beat time based patterns
+/// and sequences, which got a tempo map assigned, schedule their steps with it: bar steps
+/// follow the current bar length, beat steps the current time signature's denominator and
+/// note value steps (e.g. quarters or sixteenths) are independent of the time signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    time_base: BeatTimeBase,
+    meter: MeterMap,
+    changes: Vec<TempoChange>,
+    // accumulated seconds at each change's position
+    seconds: Vec<f64>,
+}
+
+impl TempoMap {
+    /// Create a new tempo map with the tempo, meter and sample rate of the given time base.
+    pub fn new(time_base: &BeatTimeBase) -> Self {
+        let time_base = *time_base;
+        let changes = vec![TempoChange {
+            beat: 0.0,
+            beats_per_min: time_base.beats_per_min,
+            ramp: TempoRamp::Step,
+        }];
+        let seconds = vec![0.0];
+        let meter = MeterMap::new(TimeSignature::new(time_base.beats_per_bar.max(1), 4));
+        Self {
+            time_base,
+            meter,
+            changes,
+            seconds,
+        }
+    }
+
+    /// Return a new tempo map with the given time signature change applied.
+    /// See [`add_time_signature`](Self::add_time_signature).
+    #[must_use]
+    pub fn with_time_signature(mut self, bar: u32, time_signature: TimeSignature) -> Self {
+        self.add_time_signature(bar, time_signature);
+        self
+    }
+
+    /// Change the time signature, starting at the given bar. Bars are counted from 0.
+    /// An existing change at the same bar gets replaced.
+    pub fn add_time_signature(&mut self, bar: u32, time_signature: TimeSignature) {
+        self.meter.add_time_signature(bar, time_signature);
+    }
+
+    /// The tempo map's time signature changes.
+    pub fn meter(&self) -> &MeterMap {
+        &self.meter
+    }
+
+    /// Return a new tempo map with the given tempo change applied.
+    /// See [`add_tempo_change`](Self::add_tempo_change).
+    #[must_use]
+    pub fn with_tempo_change(mut self, beat: f64, beats_per_min: f32, ramp: TempoRamp) -> Self {
+        self.add_tempo_change(beat, beats_per_min, ramp);
+        self
+    }
+
+    /// Add a tempo change at the given beat position. An existing change at the same position
+    /// gets replaced.
+    ///
+    /// ### Panics
+    /// Panics when the beat position is negative, the tempo is not > 0 or when a curve ramp's
+    /// exponent is not > 0.
+    pub fn add_tempo_change(&mut self, beat: f64, beats_per_min: f32, ramp: TempoRamp) {
+        assert!(beat >= 0.0, "Tempo change position must be >= 0");
+        assert!(beats_per_min > 0.0, "Tempo must be > 0");
+        if let TempoRamp::Curve(exponent) = ramp {
+            assert!(exponent > 0.0, "Tempo curve exponent must be > 0");
+        }
+        let change = TempoChange {
+            beat,
+            beats_per_min,
+            ramp,
+        };
+        let index = self.changes.partition_point(|c| c.beat < beat);
+        if self.changes.get(index).is_some_and(|c| c.beat == beat) {
+            self.changes[index] = change;
+        } else {
+            self.changes.insert(index, change);
+        }
+        self.update_seconds();
+    }
+
+    /// The time base the tempo map got created with.
+    pub fn time_base(&self) -> &BeatTimeBase {
+        &self.time_base
+    }
+
+    /// Update the time base, e.g. when the sample rate or the host's tempo changed.
+    ///
+    /// When the map has no tempo changes besides its unchanged initial tempo, the initial tempo
+    /// follows the new time base's tempo. Otherwise the map's tempo changes override the time
+    /// base's tempo and are kept. Time signatures are kept too: the time base's meter only
+    /// applies to new tempo maps.
+    pub fn set_time_base(&mut self, time_base: &BeatTimeBase) {
+        if self.has_default_tempo() {
+            self.changes[0].beats_per_min = time_base.beats_per_min;
+        }
+        self.time_base = *time_base;
+        self.update_seconds();
+    }
+
+    /// Returns true if the map only contains the initial tempo of its time base.
+    pub fn has_default_tempo(&self) -> bool {
+        self.changes.len() == 1 && self.changes[0].beats_per_min == self.time_base.beats_per_min
+    }
+
+    /// All tempo changes, sorted by position. The first change is the initial tempo.
+    pub fn changes(&self) -> &[TempoChange] {
+        &self.changes
+    }
+
+    /// Tempo in beats per minute at the given beat position.
+    pub fn tempo_at(&self, beat: f64) -> f64 {
+        let index = self.segment_at_beat(beat);
+        match self.changes.get(index + 1) {
+            Some(next) => {
+                let current = &self.changes[index];
+                let x = (beat - current.beat) / (next.beat - current.beat);
+                segment_tempo(current, next, x)
+            }
+            None => self.changes[index].beats_per_min as f64,
+        }
+    }
+
+    /// Convert the given beat position to seconds.
+    pub fn beats_to_seconds(&self, beat: f64) -> f64 {
+        let beat = beat.max(0.0);
+        let index = self.segment_at_beat(beat);
+        let current = &self.changes[index];
+        self.seconds[index]
+            + match self.changes.get(index + 1) {
+                Some(next) => segment_seconds(current, next, beat),
+                None => (beat - current.beat) * 60.0 / current.beats_per_min as f64,
+            }
+    }
+
+    /// Convert the given time in seconds to a beat position.
+    pub fn seconds_to_beats(&self, seconds: f64) -> f64 {
+        let seconds = seconds.max(0.0);
+        let index = self.seconds.partition_point(|s| *s <= seconds).max(1) - 1;
+        let current = &self.changes[index];
+        let remaining = seconds - self.seconds[index];
+        match self.changes.get(index + 1) {
+            Some(next) => segment_beats(current, next, remaining),
+            None => current.beat + remaining * current.beats_per_min as f64 / 60.0,
+        }
+    }
+
+    /// Convert the given beat position to a sample time.
+    pub fn beats_to_samples(&self, beat: f64) -> ExactSampleTime {
+        self.beats_to_seconds(beat) * self.time_base.samples_per_sec as f64
+    }
+
+    /// Convert the given sample time to a beat position.
+    pub fn samples_to_beats(&self, samples: ExactSampleTime) -> f64 {
+        self.seconds_to_beats(samples / self.time_base.samples_per_sec as f64)
+    }
+
+    /// Convert a position in units of the given step's resolution (e.g. bars) to a quarter note
+    /// beat position. The step's step count is ignored.
+    pub fn steps_to_beats(&self, step: &BeatTimeStep, steps: f64) -> f64 {
+        match step {
+            BeatTimeStep::SixtyFourth(_) => steps / 16.0,
+            BeatTimeStep::ThirtySecond(_) => steps / 8.0,
+            BeatTimeStep::Sixteenth(_) => steps / 4.0,
+            BeatTimeStep::Eighth(_) => steps / 2.0,
+            BeatTimeStep::Quarter(_) => steps,
+            BeatTimeStep::Beats(_) => self.meter.counts_to_beats(steps),
+            BeatTimeStep::Half(_) => steps * 2.0,
+            BeatTimeStep::Whole(_) => steps * 4.0,
+            BeatTimeStep::Bar(_) => self.meter.bars_to_beats(steps),
+        }
+    }
+
+    /// Convert a quarter note beat position to a position in units of the given step's
+    /// resolution (e.g. bars). The step's step count is ignored.
+    pub fn beats_to_steps(&self, step: &BeatTimeStep, beats: f64) -> f64 {
+        match step {
+            BeatTimeStep::SixtyFourth(_) => beats * 16.0,
+            BeatTimeStep::ThirtySecond(_) => beats * 8.0,
+            BeatTimeStep::Sixteenth(_) => beats * 4.0,
+            BeatTimeStep::Eighth(_) => beats * 2.0,
+            BeatTimeStep::Quarter(_) => beats,
+            BeatTimeStep::Beats(_) => self.meter.beats_to_counts(beats),
+            BeatTimeStep::Half(_) => beats / 2.0,
+            BeatTimeStep::Whole(_) => beats / 4.0,
+            BeatTimeStep::Bar(_) => self.meter.beats_to_bars(beats),
+        }
+    }
+
+    /// Convert a sample time, which got calculated with the constant tempo and meter of the
+    /// given time base in units of the given step, to a sample time which follows the map.
+    pub(crate) fn map_sample_time(
+        &self,
+        time_base: &BeatTimeBase,
+        step: &BeatTimeStep,
+        samples: ExactSampleTime,
+    ) -> ExactSampleTime {
+        let steps = samples / step.samples_per_step(time_base);
+        self.beats_to_samples(self.steps_to_beats(step, steps))
+    }
+
+    /// Convert a sample time which follows the map to a sample time with the constant tempo
+    /// and meter of the given time base in units of the given step. Inverse of `map_sample_time`.
+    pub(crate) fn unmap_sample_time(
+        &self,
+        time_base: &BeatTimeBase,
+        step: &BeatTimeStep,
+        samples: ExactSampleTime,
+    ) -> ExactSampleTime {
+        let beats = self.samples_to_beats(samples);
+        self.beats_to_steps(step, beats) * step.samples_per_step(time_base)
+    }
+
+    // Index of the change which starts the segment the given beat position is in.
+    fn segment_at_beat(&self, beat: f64) -> usize {
+        self.changes.partition_point(|c| c.beat <= beat).max(1) - 1
+    }
+
+    fn update_seconds(&mut self) {
+        self.seconds.clear();
+        self.seconds.push(0.0);
+        for pair in self.changes.windows(2) {
+            let seconds = *self.seconds.last().unwrap();
+            self.seconds
+                .push(seconds + segment_seconds(&pair[0], &pair[1], pair[1].beat));
+        }
+    }
+}
+
+impl SampleTimeBase for TempoMap {
+    fn samples_per_second(&self) -> u32 {
+        self.time_base.samples_per_sec
+    }
+}
+
+impl SampleTimeDisplay for TempoMap {
+    /// generate a bar.beat.tick string representation of the the given sample time, where beats
+    /// are counts of the current time signature.
+    fn display(&self, sample_time: SampleTime) -> String {
+        let beats = self.samples_to_beats(sample_time as ExactSampleTime);
+        let (bar, count, fraction) = self.meter.position(beats);
+        let ticks = (fraction * 960.0 + 0.5) as u64;
+        format!("{}.{}.{:03}", bar + 1, count + 1, ticks)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+// Number of integration steps for curved tempo ramps.
+const CURVE_INTEGRATION_STEPS: usize = 64;
+
+// Tempo within a segment at the relative position x in range [0, 1].
+fn segment_tempo(current: &TempoChange, next: &TempoChange, x: f64) -> f64 {
+    let from = current.beats_per_min as f64;
+    let to = next.beats_per_min as f64;
+    match next.ramp {
+        TempoRamp::Step => from,
+        TempoRamp::Linear => from + (to - from) * x,
+        TempoRamp::Curve(exponent) => from + (to - from) * x.powf(exponent as f64),
+    }
+}
+
+// Seconds from the segment's start to the given beat position in the segment.
+fn segment_seconds(current: &TempoChange, next: &TempoChange, beat: f64) -> f64 {
+    let length = next.beat - current.beat;
+    let from = current.beats_per_min as f64;
+    let to = next.beats_per_min as f64;
+    let beats = beat - current.beat;
+    match next.ramp {
+        TempoRamp::Linear if (to - from).abs() > f64::EPSILON => {
+            // integral of 60 / tempo(b) over a linear tempo ramp
+            let tempo = from + (to - from) * beats / length;
+            60.0 * length / (to - from) * (tempo / from).ln()
+        }
+        TempoRamp::Curve(_) if (to - from).abs() > f64::EPSILON => {
+            // simpson integration of 60 / tempo(b)
+            let x = beats / length;
+            let h = x / CURVE_INTEGRATION_STEPS as f64;
+            let f = |i: usize| 1.0 / segment_tempo(current, next, i as f64 * h);
+            let mut sum = f(0) + f(CURVE_INTEGRATION_STEPS);
+            for i in 1..CURVE_INTEGRATION_STEPS {
+                sum += if i % 2 == 1 { 4.0 * f(i) } else { 2.0 * f(i) };
+            }
+            60.0 * length * h / 3.0 * sum
+        }
+        _ => 60.0 * beats / from,
+    }
+}
+
+// Beat position of the given seconds, relative to the segment's start.
+fn segment_beats(current: &TempoChange, next: &TempoChange, seconds: f64) -> f64 {
+    let length = next.beat - current.beat;
+    let from = current.beats_per_min as f64;
+    let to = next.beats_per_min as f64;
+    match next.ramp {
+        TempoRamp::Linear if (to - from).abs() > f64::EPSILON => {
+            let tempo = from * (seconds * (to - from) / (60.0 * length)).exp();
+            current.beat + (tempo - from) / (to - from) * length
+        }
+        TempoRamp::Curve(_) if (to - from).abs() > f64::EPSILON => {
+            // bisect the inverse of the monotonic seconds function
+            let (mut low, mut high) = (current.beat, next.beat);
+            for _ in 0..52 {
+                let mid = (low + high) / 2.0;
+                if segment_seconds(current, next, mid) < seconds {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+            (low + high) / 2.0
+        }
+        _ => current.beat + seconds * from / 60.0,
+    }
+}
+
+// --------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::prelude::*;
+
+    fn assert_near(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "expected {b}, got {a}");
+    }
+
+    #[test]
+    fn tempo_map() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+
+        // constant tempo
+        let tempo_map = TempoMap::new(&time_base);
+        assert_near(tempo_map.beats_to_seconds(4.0), 2.0);
+        assert_near(tempo_map.seconds_to_beats(2.0), 4.0);
+        assert_near(
+            tempo_map.beats_to_samples(1.0),
+            time_base.samples_per_beat(),
+        );
+
+        // step changes
+        let tempo_map = TempoMap::new(&time_base).with_tempo_change(4.0, 60.0, TempoRamp::Step);
+        assert_near(tempo_map.tempo_at(2.0), 120.0);
+        assert_near(tempo_map.tempo_at(4.0), 60.0);
+        assert_near(tempo_map.beats_to_seconds(4.0), 2.0);
+        assert_near(tempo_map.beats_to_seconds(6.0), 4.0);
+        assert_near(tempo_map.seconds_to_beats(4.0), 6.0);
+        assert_eq!(tempo_map.display(44100 * 4), "2.3.000");
+
+        // linear ramps
+        let tempo_map = TempoMap::new(&time_base)
+            .with_tempo_change(4.0, 240.0, TempoRamp::Linear)
+            .with_tempo_change(8.0, 240.0, TempoRamp::Step);
+        assert_near(tempo_map.tempo_at(2.0), 180.0);
+        let seconds = 60.0 * 4.0 / 120.0 * 2.0f64.ln();
+        assert_near(tempo_map.beats_to_seconds(4.0), seconds);
+        assert_near(tempo_map.beats_to_seconds(8.0), seconds + 1.0);
+        for beat in [0.5, 2.0, 3.9, 4.0, 7.0, 12.0] {
+            assert_near(
+                tempo_map.seconds_to_beats(tempo_map.beats_to_seconds(beat)),
+                beat,
+            );
+        }
+
+        // curved ramps
+        let tempo_map = TempoMap::new(&time_base)
+            .with_tempo_change(4.0, 240.0, TempoRamp::Curve(1.0))
+            .with_tempo_change(8.0, 60.0, TempoRamp::Curve(2.0));
+        assert_near(tempo_map.beats_to_seconds(4.0), seconds);
+        for beat in [0.5, 2.0, 3.9, 4.0, 7.0, 12.0] {
+            assert_near(
+                tempo_map.seconds_to_beats(tempo_map.beats_to_seconds(beat)),
+                beat,
+            );
+        }
+        assert!(tempo_map.beats_to_seconds(6.0) < tempo_map.beats_to_seconds(7.0));
+    }
+
+    #[test]
+    fn tempo_mapped_patterns() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let tempo_map = TempoMap::new(&time_base).with_tempo_change(4.0, 60.0, TempoRamp::Step);
+
+        // beat time patterns follow the tempo map
+        let pattern = time_base
+            .every_nth_beat(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        let times = pattern.take(7).map(|e| e.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![0, 22050, 44100, 66150, 88200, 132300, 176400]);
+        let mut pattern = time_base
+            .every_nth_beat(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        pattern.advance_until_time(100000);
+        assert_eq!(
+            pattern.next().map(|e| (e.time, e.duration)),
+            Some((132300, 44100))
+        );
+
+        // second time patterns ignore it
+        let pattern = time_base
+            .every_nth_seconds(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        let times = pattern.take(4).map(|e| e.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![0, 44100, 88200, 132300]);
+
+        // sequences apply the map to phrase lengths and patterns
+        let phrase = Phrase::new(
+            time_base,
+            vec![time_base.every_nth_bar(1.0).emit(new_note_emitter("c4"))],
+            BeatTimeStep::Bar(1.0),
+        );
+        let mut sequence =
+            Sequence::new(time_base, vec![phrase.clone(), phrase]).with_tempo_map(tempo_map);
+        let mut times = Vec::new();
+        sequence.consume_events_until_time(44100 * 12, &mut |_, event| {
+            if event.event.is_some() {
+                times.push(event.time);
+            }
+        });
+        assert_eq!(times, vec![0, 88200, 264600, 441000]);
+
+        // sequences with sample offsets shift the map and emitted events
+        let mut sequence = sequence.clone();
+        sequence.reset();
+        sequence.set_sample_offset(1000);
+        let mut times = Vec::new();
+        sequence.consume_events_until_time(1000 + 44100 * 12, &mut |_, event| {
+            if event.event.is_some() {
+                times.push(event.time);
+            }
+        });
+        assert_eq!(times, vec![1000, 89200, 265600, 442000]);
+
+        // sequences refresh the map's time base
+        sequence.reset();
+        sequence.set_time_base(&BeatTimeBase {
+            samples_per_sec: 22050,
+            ..time_base
+        });
+        let mut times = Vec::new();
+        sequence.consume_events_until_time(22050 * 12, &mut |_, event| {
+            if event.event.is_some() {
+                times.push(event.time);
+            }
+        });
+        assert_eq!(times, vec![0, 44100, 132300, 220500]);
+    }
+
+    #[test]
+    fn time_base_tempo() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let slow_time_base = BeatTimeBase {
+            beats_per_min: 60.0,
+            ..time_base
+        };
+
+        // maps without tempo changes follow the time base's tempo
+        let mut tempo_map =
+            TempoMap::new(&time_base).with_time_signature(1, TimeSignature::new(3, 4));
+        assert!(tempo_map.has_default_tempo());
+        tempo_map.set_time_base(&slow_time_base);
+        assert_near(tempo_map.tempo_at(0.0), 60.0);
+        assert_near(tempo_map.beats_to_seconds(4.0), 4.0);
+        assert!(tempo_map.has_default_tempo());
+
+        // tempo changes override the time base's tempo
+        let mut tempo_map = TempoMap::new(&time_base).with_tempo_change(4.0, 60.0, TempoRamp::Step);
+        assert!(!tempo_map.has_default_tempo());
+        tempo_map.set_time_base(&slow_time_base);
+        assert_near(tempo_map.tempo_at(0.0), 120.0);
+        let mut tempo_map = TempoMap::new(&time_base).with_tempo_change(0.0, 90.0, TempoRamp::Step);
+        tempo_map.set_time_base(&slow_time_base);
+        assert_near(tempo_map.tempo_at(0.0), 90.0);
+
+        // sequences with a default tempo map follow the new time base's tempo
+        let phrase = Phrase::new(
+            time_base,
+            vec![time_base.every_nth_bar(1.0).emit(new_note_emitter("c4"))],
+            BeatTimeStep::Bar(1.0),
+        );
+        let mut sequence = Sequence::new(time_base, vec![phrase.clone(), phrase])
+            .with_tempo_map(TempoMap::new(&time_base));
+        sequence.set_time_base(&slow_time_base);
+        let mut times = Vec::new();
+        sequence.consume_events_until_time(44100 * 16, &mut |_, event| {
+            if event.event.is_some() {
+                times.push(event.time);
+            }
+        });
+        assert_eq!(times, vec![0, 176400, 352800, 529200]);
+    }
+
+    #[test]
+    fn time_signatures() {
+        let time_base = BeatTimeBase {
+            beats_per_min: 120.0,
+            beats_per_bar: 4,
+            samples_per_sec: 44100,
+        };
+        let tempo_map = TempoMap::new(&time_base)
+            .with_time_signature(0, TimeSignature::new(7, 8))
+            .with_time_signature(2, TimeSignature::new(4, 4));
+
+        // bar and beat steps follow the meter, other note values don't
+        let pattern = time_base
+            .every_nth_bar(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        let times = pattern.take(4).map(|e| e.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![0, 77175, 154350, 242550]);
+        let pattern = time_base
+            .every_nth_beat(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        let times = pattern.skip(13).take(3).map(|e| e.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![143325, 154350, 176400]);
+        let pattern = time_base
+            .every_nth_eighth(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        let times = pattern.skip(14).take(2).map(|e| e.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![154350, 165375]);
+        let pattern = time_base
+            .every_nth_quarter(1.0)
+            .with_tempo_map(Rc::new(tempo_map.clone()))
+            .emit(new_note_emitter("c4"));
+        let times = pattern.take(4).map(|e| e.time).collect::<Vec<_>>();
+        assert_eq!(times, vec![0, 22050, 44100, 66150]);
+
+        // display bar.beat.tick in counts of the current meter
+        assert_eq!(tempo_map.display(0), "1.1.000");
+        assert_eq!(tempo_map.display(77175 + 11025), "2.2.000");
+        assert_eq!(tempo_map.display(154350), "3.1.000");
+        assert_eq!(tempo_map.display(154350 + 33075), "3.2.480");
+    }
+}
@@ -0,0 +1,67 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns native arpeggiator.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---Opaque native arpeggio emitter user data. Construct new arpeggios via the `arpeggio` function
+---and use them as `event` in patterns.
+---@class Arpeggio : userdata
+local Arpeggio = {}
+
+---Arpeggio note order.
+---@alias ArpeggioMode "up"|"down"|"updown"|"random"|"played"|"converge"
+
+----------------------------------------------------------------------------------------------------
+
+---Construction options for a new arpeggio.
+---@class ArpeggioOptions
+---
+---Notes or chord to arpeggiate, when the pattern got no trigger event with notes. When undefined,
+---only the notes of trigger events get arpeggiated.
+---@field notes? NoteValue|NoteValue[]
+---
+---Order of the arpeggiated notes. "played" plays notes in the order they got defined or played.
+---"converge" plays notes outside in: lowest, highest, second lowest and so on. By default "up".
+---@field mode? ArpeggioMode
+---
+---Number of octaves the arpeggio spans, in range [1 - 10]. By default 1.
+---@field octaves? integer
+---
+---Number of times each note gets played before moving on to the next one. By default 1.
+---@field repeats? integer
+---
+---Note length (gate time) in range (0 - 1], relative to the pulse step length. By default 1.
+---@field gate_length? number
+---
+---When true, the notes of the last trigger event keep playing after trigger events without notes
+---(note-offs). Else the arpeggio falls back to the fixed `notes`. By default false.
+---@field latch? boolean
+---
+---Optional random seed for the "random" mode. When undefined, the arpeggio is randomly seeded.
+---@field seed? integer
+
+----------------------------------------------------------------------------------------------------
+
+---Create a native arpeggio emitter, which plays the notes of a chord one after another. Notes are
+---taken from the pattern's trigger event, when the pattern got triggered with notes, else from
+---the given fixed `notes`.
+---
+---### examples:
+---```lua
+---return pattern {
+---  unit = "1/16",
+---  event = arpeggio {
+---    notes = "c4'maj7",
+---    mode = "updown",
+---    octaves = 2,
+---    gate_length = 0.5
+---  }
+---}
+---```
+---@param options ArpeggioOptions?
+---@return Arpeggio
+---@nodiscard
+function arpeggio(options) end
@@ -0,0 +1,31 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns Arrangement class.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---@class Arrangement
+local Arrangement = {}
+
+----------------------------------------------------------------------------------------------------
+
+---Create a new arrangement, which plays the given phrases one after another. After the last
+---phrase, the arrangement starts again from the first phrase.
+---
+---Each phrase plays for its `length`. Pattern slots in mode `"continue"` take over the pattern
+---from the same slot in the previous phrase.
+---
+---### examples:
+---```lua
+---local drums = pattern { unit = "1/4", event = "c4 #1" }
+---local bass = cycle("c3 [~ c3] g2 ~")
+---local intro = phrase { length = 4, patterns = { drums, "stop" } }
+---local main = phrase { length = 8, patterns = { "continue", bass } }
+---return arrangement { intro, main, main }
+---```
+---@param phrases Phrase[]
+---@return Arrangement
+---@nodiscard
+function arrangement(phrases) end
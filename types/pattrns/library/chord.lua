@@ -51,3 +51,23 @@ function chord(key, mode) end
 ---Return supported chord names.
 ---@return string[]
 function chord_names() end
+
+---Name the chord which is formed by the given notes. Notes may be given in any order, octave
+---and inversion. Returns a chord string in the form `$note'$chord`, which can be passed to
+---functions `note` or `chord`, or nil when the notes don't form a known chord.
+---
+---Accepts the same arguments as function `note`, so this can also be used to detect the chord
+---of a pattern's trigger notes.
+---
+---### examples:
+---```lua
+---detect_chord("c4", "e4", "g4") --> "C4'major"
+---detect_chord("e4 g4 c5") --> "C4'major"
+---detect_chord("a3'minor7") --> "A3'minor7"
+---detect_chord("c4", "c#4") --> nil
+---detect_chord(context.trigger) -- chord of the notes which triggered the pattern
+---```
+---@param ... NoteValue
+---@return string|nil
+---@nodiscard
+function detect_chord(...) end
@@ -0,0 +1,148 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns native gates.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---Opaque native gate user data. Construct new gates via the `gate.XXX(...)` functions and
+---use them as `gate` in patterns.
+---@class Gate : userdata
+local Gate = {}
+
+---A gate or gate function, as accepted by the gate combinators.
+---@alias GateValue Gate|(fun(context: GateContext):boolean)|(fun(context: GateContext):fun(context: GateContext):boolean)
+
+----------------------------------------------------------------------------------------------------
+
+---Contains functions to construct native gates and to combine gates. Native gates run without
+---Lua callbacks, so they are a lot cheaper than filtering pulses in `gate` functions.
+---
+---All native gates, except the threshold and probability gates, skip pulses with value 0 and
+---pass all other pulse values. Pulse values in range (0, 1) are treated as probabilities by the
+---probability gate only, so combine gates with it to apply them:
+---```lua
+---gate = gate.all(gate.probability(), gate.every_nth_cycle(2))
+---```
+gate = {}
+
+---Create a gate which passes all pulse values greater than the given threshold. This is the
+---default gate of patterns, with a threshold of 0.
+---
+---### examples:
+---```lua
+---gate = gate.threshold(0.5)
+---```
+---@param value number? Threshold value. By default 0.
+---@return Gate
+function gate.threshold(value) end
+
+---Create a gate which skips all 0s and passes all 1s. Pulse values in range (0, 1) maybe pass,
+---using the pulse value as probability.
+---
+---### examples:
+---```lua
+---gate = gate.probability()
+---```
+---@param seed integer? Optional random seed. When undefined, the gate is randomly seeded.
+---@return Gate
+function gate.probability(seed) end
+
+---Create a gate which passes pulses in every nth cycle of the pattern's pulse only. For endless
+---pulses, such as pulse functions, a cycle is a bar.
+---
+---### examples:
+---```lua
+----- play every second run of the pulse, starting with the second one
+---gate = gate.every_nth_cycle(2, 1)
+---```
+---@param n integer Cycle interval, an integer > 0.
+---@param offset integer? Index of the first cycle which passes. By default 0.
+---@return Gate
+function gate.every_nth_cycle(n, offset) end
+
+---Create a gate which passes the first pulse which starts in a bar only.
+---
+---### examples:
+---```lua
+---gate = gate.first_in_bar()
+---```
+---@return Gate
+function gate.first_in_bar() end
+
+---Create a gate which passes the last pulse which starts in a bar only.
+---
+---### examples:
+---```lua
+---gate = gate.last_in_bar()
+---```
+---@return Gate
+function gate.last_in_bar() end
+
+---Create a gate which mutes all pulses, which start within the given beat time ranges. Beat times
+---are relative to the start of the pattern. When a length is set, the ranges repeat after the
+---given number of beats.
+---
+---### examples:
+---```lua
+----- mute the third beat in every bar of a 4/4 pattern
+---gate = gate.mute({ { 2, 3 } }, 4)
+---```
+---@param ranges [number, number][] Muted `{ start, end }` ranges in beats. Ends are exclusive.
+---@param length number? Optional loop length in beats.
+---@return Gate
+function gate.mute(ranges, length) end
+
+---Create a gate which evenly thins out pulses to the given density: with a density of 0.5 every
+---other pulse passes, with a density of 0.25 every fourth one and so on.
+---
+---### examples:
+---```lua
+---return pattern {
+---  parameter = { parameter.number("density", 1.0, { 0, 1 }) },
+---  pulse = pulse.new(16, 1),
+---  gate = gate.density("density"),
+---  event = "c4"
+---}
+---```
+---@param density number|ParameterId Density in range [0 - 1] or the id of a pattern parameter.
+---@return Gate
+function gate.density(density) end
+
+---Create a gate which passes pulses when all of the given gates pass them. All gates always run,
+---so gates which track pulse positions stay in sync.
+---
+---### examples:
+---```lua
+----- first pulse in every second bar
+---gate = gate.all(gate.first_in_bar(), gate.every_nth_cycle(2))
+---```
+---@param ... GateValue
+---@return Gate
+function gate.all(...) end
+
+---Create a gate which passes pulses when any of the given gates passes them. All gates always
+---run, so gates which track pulse positions stay in sync.
+---
+---### examples:
+---```lua
+---gate = gate.any(gate.first_in_bar(), function(context)
+---  return context.pulse_value > 0.5
+---end)
+---```
+---@param ... GateValue
+---@return Gate
+function gate.any(...) end
+
+---Create a gate which inverts the given gate's results. Note that this also passes pulses with
+---value 0, which got skipped by the inverted gate.
+---
+---### examples:
+---```lua
+----- mute the first pulse in a bar
+---gate = gate.all(gate.threshold(), gate.invert(gate.first_in_bar()))
+---```
+---@param gate GateValue
+---@return Gate
+function gate.invert(gate) end
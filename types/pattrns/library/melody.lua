@@ -0,0 +1,71 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns native melody generator.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---Opaque native melody emitter user data. Construct new melodies via the `melody` function and
+---use them as `event` in patterns.
+---@class Melody : userdata
+local Melody = {}
+
+----------------------------------------------------------------------------------------------------
+
+---Construction options for a new melody.
+---@class MelodyOptions
+---
+---Scale to pick the melody's notes from.
+---@field scale Scale
+---
+---Inclusive `{ low, high }` note range of the melody. By default one octave, starting from the
+---scale's root note.
+---@field range? [NoteValue, NoteValue]
+---
+---Maximum interval between two successive notes in scale steps. By default 4.
+---@field max_leap? number|ParameterId
+---
+---Probability of moving by a single scale step instead of leaping, in range [0 - 1].
+---By default 0.75.
+---@field step_probability? number|ParameterId
+---
+---Direction bias in range [-1 - 1]. Negative values favor descending, positive values ascending
+---moves. By default 0.
+---@field contour? number|ParameterId
+---
+---Number of last played notes the melody avoids to repeat, when other notes are available.
+---When 0, notes may repeat. By default 1.
+---@field avoid_repeats? integer
+---
+---When set, the melody ends every phrase of the given number of notes on the tonic, closest to
+---the previous note. By default undefined.
+---@field phrase_length? integer
+---
+---Optional random seed. When undefined, the melody is randomly seeded.
+---@field seed? integer
+
+----------------------------------------------------------------------------------------------------
+
+---Create a native melody emitter, which generates a random melody from the notes of a scale.
+---Melodies start on the scale's tonic and then move by scale steps or leaps within the note range.
+---Numeric constraints can be pattern parameter ids, to change them while the pattern plays.
+---
+---### examples:
+---```lua
+---return pattern {
+---  unit = "1/8",
+---  parameter = { parameter.number("contour", 0.0, { -1, 1 }) },
+---  event = melody {
+---    scale = scale("c4", "minor"),
+---    range = { "c4", "c6" },
+---    max_leap = 3,
+---    contour = "contour",
+---    phrase_length = 8
+---  }
+---}
+---```
+---@param options MelodyOptions
+---@return Melody
+---@nodiscard
+function melody(options) end
@@ -12,6 +12,10 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---@field volume number? Volume in range [0.0 - 1.0]
 ---@field panning number? Panning factor in range [-1.0 - 1.0] where 0 is center
 ---@field delay number? Delay factor in range [0.0 - 1.0]
+---@field length number? Note length >= 0, relative to the event's duration. When undefined, the note plays until the next note (legato).
+---
+---`vol` and `pan` are aliases for `volume` and `panning`. All other string keys with number values are
+---custom note attributes, such as `cutoff` or `pitch_bend`.
 local NoteTable = {}
 
 ----------------------------------------------------------------------------------------------------
@@ -80,6 +84,36 @@ function Note:panning(panning) end
 ---@nodiscard
 function Note:delay(delay) end
 
+---Set the note's length attribute to the specified value or values. Lengths are relative to the
+---note event's duration: a length of 0.5 stops the note after half of the pulse step (staccato).
+---Notes without a length play until the next note or note-off arrives (legato).
+---
+---### examples:
+---```lua
+---note("c4"):length(0.5)
+---note("c'maj"):length({0.25, 0.5, 1.0})
+---```
+---@param length number|number[] new length >= 0
+---@return Note
+---@nodiscard
+function Note:length(length) end
+
+---Set a custom, named note attribute to the specified value or values. Note attributes are not
+---interpreted by pattrns, but passed along with the note events to the player, which may use them
+---to control synth or sampler parameters. Built-in note properties, such as `volume`, can not be
+---set as attributes: use their setter functions instead.
+---
+---### examples:
+---```lua
+---note("c4"):attribute("cutoff", 0.5)
+---note("c'maj"):attribute("pitch_bend", {0, 0.5, 1})
+---```
+---@param name string attribute name
+---@param value number|number[] attribute value or values
+---@return Note
+---@nodiscard
+function Note:attribute(name, value) end
+
 ----------------------------------------------------------------------------------------------------
 
 ---@alias NoteValue Note|NoteTable|string|number|nil
@@ -94,13 +128,17 @@ function Note:delay(delay) end
 --- -'v' -> volume (number in range [0-1])
 --- -'p' -> panning (number in range [-1-1])
 --- -'d' -> delay (number in range [0-1])
+--- -'l' -> length (number >= 0)
 ---```
+--- Custom note attributes, such as a synth's `cutoff`, are specified as `name=value` pairs.
 ---
 ---### examples:
 --- ```lua
 --- note(48) --> middle C
 --- note("c4") --> middle C
 --- note("c4 #2 v0.5 d0.3") --> middle C with additional properties
+--- note("c4 l0.5") --> middle C which stops after half of the event's duration
+--- note("c4 cutoff=0.5") --> middle C with a custom cutoff attribute
 --- note({key="c4", volume=0.5}) --> middle C with volume 0.5
 --- note("c4'maj v0.7") --> C4 major chord with volume 0.7
 --- note("c4", "e4 v0.5", "off") --> custom chord with a c4, e4 and 'off' note
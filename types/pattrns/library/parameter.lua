@@ -17,11 +17,21 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---@alias ParameterNumberDefault number
 ---Default string value. Must be a valid string within the specified value set.
 ---@alias ParameterEnumDefault string
+---Default note value as note number or note string such as "c4". Must be in the specified range.
+---@alias ParameterNoteDefault integer|string
+---Default free-form string value.
+---@alias ParameterStringDefault string
+---Default integer array values. The array's length is fixed to the length of the default values.
+---@alias ParameterIntegerArrayDefault integer[]
+---Default number array values. The array's length is fixed to the length of the default values.
+---@alias ParameterNumberArrayDefault number[]
 
 ---Optional value range. When undefined (0.0 - 1.0)
 ---@alias ParameterIntegerRange { [1]: integer, [2]: integer }
 ---Optional value range. When undefined (0 - 100)
 ---@alias ParameterNumberRange { [1]: number, [2]: number }
+---Optional note value range. When undefined ("c0" - "g10")
+---@alias ParameterNoteRange { [1]: integer|string, [2]: integer|string }
 
 ---Optional name of the parameter as displayed to the user. When undefined, the id is used.
 ---@alias ParameterName string
@@ -29,6 +39,47 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---Optional long description of the parameter describing what the parameter does.
 ---@alias ParameterDescription string
 
+---Available LFO waveforms. All shapes start at the LFO's minimum value.
+---@alias ParameterLfoShape "sine"|"triangle"|"saw"|"square"
+
+---Optional automation of the parameter's value. Automated parameters update their value with
+---each pulse of the pattern that uses them, before the pattern's `pulse`, `gate` and `event`
+---functions read them, so parameters can evolve without the host changing their values.
+---
+---Define exactly one of `curve`, `lfo` or `steps`. Automation times are beats, relative to the
+---start of the pattern. Values get clamped into the parameter's range. Boolean parameters also
+---accept boolean and enum parameters also accept string values.
+---
+---### examples:
+---```lua
+----- ramp up within 16 beats, then hold the value
+---parameter.number("density", 0.2, {0, 1}, nil, nil, { curve = { {0, 0.2}, {16, 1.0} } })
+----- sine LFO which moves between 0.2 and 0.8 every 2 bars
+---parameter.number("brightness", 0.5, {0, 1}, nil, nil, { lfo = "sine", period = 8, range = {0.2, 0.8} })
+----- switch mode every bar
+---parameter.enum("mode", "up", {"up", "down"}, nil, nil, { steps = {"up", "down"}, step = 4 })
+---```
+---@class ParameterAutomation
+---
+---Curve points as `{ time, value }` pairs. Values are interpolated linearly between points.
+---@field curve? { [1]: number, [2]: number|boolean|string }[]
+---Optional loop length of a curve in beats. When undefined, the curve's last value is held.
+---@field length? number
+---
+---LFO waveform.
+---@field lfo? ParameterLfoShape
+---LFO period in beats. By default 1.
+---@field period? number
+---LFO start phase in range [0 - 1). By default 0.
+---@field phase? number
+---LFO value range. By default the parameter's value range.
+---@field range? { [1]: number|boolean|string, [2]: number|boolean|string }
+---
+---Looping step sequence values.
+---@field steps? (number|boolean|string)[]
+---Length of a single step in beats. By default 1.
+---@field step? number
+
 
 ----------------------------------------------------------------------------------------------------
 
@@ -51,8 +102,9 @@ parameter = {}
 ---@param default ParameterBooleanDefault
 ---@param name ParameterName?
 ---@param description ParameterDescription?
+---@param automation ParameterAutomation?
 ---@return Parameter
-function parameter.boolean(id, default, name, description) end
+function parameter.boolean(id, default, name, description, automation) end
 
 ---Creates an Parameter with "integer" Lua type with the given default value
 ---and other optional properties.
@@ -61,8 +113,9 @@ function parameter.boolean(id, default, name, description) end
 ---@param range ParameterIntegerRange?
 ---@param name ParameterName?
 ---@param description ParameterDescription?
+---@param automation ParameterAutomation?
 ---@return Parameter
-function parameter.integer(id, default, range, name, description) end
+function parameter.integer(id, default, range, name, description, automation) end
 
 ---Creates an Parameter with "number" Lua type with the given default value
 ---and other optional properties.
@@ -71,8 +124,9 @@ function parameter.integer(id, default, range, name, description) end
 ---@param range ParameterNumberRange?
 ---@param name ParameterName?
 ---@param description ParameterDescription?
+---@param automation ParameterAutomation?
 ---@return Parameter
-function parameter.number(id, default, range, name, description) end
+function parameter.number(id, default, range, name, description, automation) end
 
 ---Creates an Parameter with a "string" Lua type with the given default value,
 ---set of valid values to choose from and other optional properties.
@@ -81,5 +135,64 @@ function parameter.number(id, default, range, name, description) end
 ---@param values string[]
 ---@param name ParameterName?
 ---@param description ParameterDescription?
+---@param automation ParameterAutomation?
+---@return Parameter
+function parameter.enum(id, default, values, name, description, automation) end
+
+---Creates an Parameter with a note "integer" Lua type with the given default value and other
+---optional properties. Note parameter values are note numbers, which are displayed as note names
+---to the user. Use them in `note` or as event values directly.
+---
+---### examples:
+---```lua
+---parameter.note("root", "c4", {"c3", "c6"}, "Root Note")
+---```
+---@param id ParameterId
+---@param default ParameterNoteDefault
+---@param range ParameterNoteRange?
+---@param name ParameterName?
+---@param description ParameterDescription?
+---@param automation ParameterAutomation?
+---@return Parameter
+function parameter.note(id, default, range, name, description, automation) end
+
+---Creates an Parameter with a free-form "string" Lua type with the given default value and
+---other optional properties. String parameters can not be automated.
+---@param id ParameterId
+---@param default ParameterStringDefault
+---@param name ParameterName?
+---@param description ParameterDescription?
+---@return Parameter
+function parameter.string(id, default, name, description) end
+
+---Creates an Parameter with a fixed length "integer[]" Lua type with the given default values
+---and other optional properties. The range applies to all array items. Array parameters can not
+---be automated.
+---
+---### examples:
+---```lua
+---parameter.integer_array("steps", {1, 0, 0, 1, 0, 0, 1, 0}, {0, 1}, "Steps")
+---```
+---@param id ParameterId
+---@param default ParameterIntegerArrayDefault
+---@param range ParameterIntegerRange?
+---@param name ParameterName?
+---@param description ParameterDescription?
+---@return Parameter
+function parameter.integer_array(id, default, range, name, description) end
+
+---Creates an Parameter with a fixed length "number[]" Lua type with the given default values
+---and other optional properties. The range applies to all array items. Array parameters can not
+---be automated.
+---
+---### examples:
+---```lua
+---parameter.number_array("velocities", {1.0, 0.5, 0.75, 0.5}, {0, 1}, "Velocities")
+---```
+---@param id ParameterId
+---@param default ParameterNumberArrayDefault
+---@param range ParameterNumberRange?
+---@param name ParameterName?
+---@param description ParameterDescription?
 ---@return Parameter
-function parameter.enum(id, default, values, name, description) end
+function parameter.number_array(id, default, range, name, description) end
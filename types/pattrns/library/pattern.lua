@@ -79,7 +79,28 @@ error("Do not try to execute this file. It's just a type definition file.")
 ----------------------------------------------------------------------------------------------------
 
 ---Single pulse value or a nested subdivision of pulses within a rhythm's pulse.
----@alias PulseValue (0|1|number|boolean|nil)|(PulseValue)[]
+---
+---Pulses in subdivisions can be weighted, like the `@` weight in mini-notation, to split a step
+---unevenly: either as string `"value@weight"` or as subdivision table with a `weight` key.
+---```lua
+----- dotted 8th and 16th note with unit "1/4"
+---pulse = { { "1@3", 1 } }
+----- 16th triplet, which covers the first half of the step only
+---pulse = { { { 1, 1, 1, weight = 2 }, 0, 0 } }
+---```
+---@alias PulseValue (0|1|number|boolean|string|nil)|(PulseValue)[]
+
+----------------------------------------------------------------------------------------------------
+
+---Execution limits for the Lua callbacks of a single pattern. Unset values use the limits of the
+---script engine. Use `false` to disable a limit.
+---@class ScriptLimitsOptions
+---
+---Max time in milliseconds a single callback invocation may run.
+---@field timeout? number|false
+---
+---Max number of Lua VM instructions a single callback invocation may run.
+---@field instructions? integer|false
 
 ----------------------------------------------------------------------------------------------------
 
@@ -193,7 +214,11 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---  end
 ---end
 ---```
----@field pulse? PulseValue[]|(fun(context: PulseContext):PulseValue)|(fun(context: PulseContext):fun(context: PulseContext):PulseValue)
+---```lua
+----- native generative rhythms from the "rhythm" lib
+---pulse = rhythm.markov { states = { 1, 0 }, transitions = { { 0.2, 0.8 }, { 0.6, 0.4 } } }
+---```
+---@field pulse? PulseValue[]|Rhythm|(fun(context: PulseContext):PulseValue)|(fun(context: PulseContext):fun(context: PulseContext):PulseValue)
 ---
 ---If and how many times a pattern should repeat. When 0 or false, the pattern does not repeat
 ---and plays back only once. When true, the pattern repeats endlessly, which is the default.
@@ -216,9 +241,53 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---```
 ---@field repeats? (integer|boolean)
 ---
+---How pulse values are interpreted. By default "probability": pulse values are trigger
+---probabilities, which get passed to the gate.
+---
+---With "volume", all pulse values > 0 trigger and scale the volume of the emitted notes. With
+---"accents" or a table of accent volume levels, all pulse values > 0 trigger and select one of the
+---accent levels: the pulse value range (0 - 1] is split into equally sized ranges, one for each
+---level. "accents" uses the levels `{ 0.3, 0.7, 1.0 }`.
+---
+---Trigger probabilities then can be set via `probability`.
+---
+---### examples:
+---```lua
+----- ghost notes and accents in a drum pattern
+---pulse = { 1, 0, 0.2, 0, 0.6, 0, 0.2, 0.2 },
+---dynamics = "accents"
+---```
+---```lua
+----- pulse values are note volumes
+---pulse = { 1, 0.5, 0.75, 0.5 },
+---dynamics = "volume"
+---```
+---@field dynamics? "probability"|"volume"|"accents"|number[]
+---
+---Optional probability channel, which runs in parallel to the pulse: each pulse consumes one
+---value from the probability pulse, which then scales the trigger probability of the pulse.
+---Most useful in combination with `dynamics`, where pulse values no longer are probabilities.
+---Note that the default threshold gate passes all values > 0, so use a probability gate to
+---apply probabilities in range (0, 1).
+---
+---### examples:
+---```lua
+----- accented pulses always play, ghost notes only sometimes
+---pulse = { 1, 0.2, 0.2, 0.2 },
+---dynamics = "accents",
+---probability = { 1, 0.5, 0.25, 0.5 },
+---gate = function(context)
+---  return context.pulse_value > math.random()
+---end
+---```
+---@field probability? PulseValue[]|Rhythm|(fun(context: PulseContext):PulseValue)|(fun(context: PulseContext):fun(context: PulseContext):PulseValue)
+---
 ---Optional pulse train filter function which filters events between the pulse and event emitter.
 ---By default a threshold gate, which passes all pulse values greater than zero.
 ---
+---Use the native gates from the `gate` lib to filter pulses without Lua callbacks, and to combine
+---gates or gate functions.
+---
 ---Functions return true when a pulse value should be passed, and false when the event
 ---emitter should be skipped.
 ---
@@ -236,7 +305,11 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---  return context.pulse_value > 0.5
 ---end
 ---```
----@field gate? (fun(context: GateContext):boolean)|(fun(context: GateContext):fun(context: GateContext):boolean)
+---```lua
+----- native gates from the "gate" lib
+---gate = gate.all(gate.first_in_bar(), gate.every_nth_cycle(2))
+---```
+---@field gate? Gate|(fun(context: GateContext):boolean)|(fun(context: GateContext):fun(context: GateContext):boolean)
 ---
 ---Specify the event values of the pattern. For every pulse in the pulse pattern, an event
 ---is picked from the specified event sequence. When the end of the sequence is reached, it starts
@@ -290,7 +363,30 @@ error("Do not try to execute this file. It's just a type definition file.")
 ----- a tidal cycle
 ---event = cycle("<[a3 c4 e4 a4]*3 [d4 g3 g4 c4]>"),
 ---```
----@field event? Cycle|Sequence|Note|NoteValue|(NoteValue|Note)[]|(fun(context: EventContext):NoteValue)|(fun(context: EventContext):fun(context: EventContext):NoteValue)
+---```lua
+----- a native arpeggio of the trigger event's notes or a fixed chord
+---event = arpeggio { notes = "c4'maj", mode = "updown", octaves = 2 },
+---```
+---```lua
+----- a native random melody from a scale
+---event = melody { scale = scale("c4", "minor"), range = { "c4", "c6" }, phrase_length = 8 },
+---```
+---@field event? Arpeggio|Melody|Cycle|Sequence|Note|NoteValue|(NoteValue|Note)[]|(fun(context: EventContext):NoteValue)|(fun(context: EventContext):fun(context: EventContext):NoteValue)
+---
+---Optional execution limits for the pattern's pulse, gate and event callbacks, which override
+---the limits of the script engine. Use this to allow a single heavy generator to run longer,
+---or to limit a single callback more strictly, without affecting all other callbacks.
+---
+---### examples:
+---```lua
+----- allow a heavy event generator to run up to 500 ms per call
+---limits = { timeout = 500 }
+---```
+---```lua
+----- run the pulse function with a small instruction budget
+---limits = { instructions = 10000 }
+---```
+---@field limits? ScriptLimitsOptions
 
 
 ----------------------------------------------------------------------------------------------------
@@ -355,6 +451,81 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---}
 ---```
 ---@param options PatternOptions
----@return userdata
+---@return Pattern
 ---@nodiscard
 function pattern(options) end
+
+----------------------------------------------------------------------------------------------------
+
+---A pattern, as created by `pattern`. Transform functions consume the pattern they are called
+---on and return a new, transformed pattern. Transforms apply to the pattern's emitted events, and
+---operate on the pattern's cycle: a full run of the pattern's pulse.
+---@class Pattern : userdata
+local Pattern = {}
+
+---Speed up the pattern by the given factor.
+---### examples:
+---```lua
+---pattern { unit = "1/4", event = cycle("c4 e4 g4") }:fast(2)
+---```
+---@param factor number Speed factor. Must be > 0.
+---@return Pattern
+---@nodiscard
+function Pattern:fast(factor) end
+
+---Slow down the pattern by the given factor.
+---@param factor number Slow down factor. Must be > 0.
+---@return Pattern
+---@nodiscard
+function Pattern:slow(factor) end
+
+---Reverse the pattern's events within each cycle.
+---@return Pattern
+---@nodiscard
+function Pattern:rev() end
+
+---Repeat each event `count` times within the event's duration.
+---@param count integer Number of repetitions. Must be > 0.
+---@return Pattern
+---@nodiscard
+function Pattern:ply(count) end
+
+---Randomly replace events with rests. Uses the global random seed, when set via `math.randomseed`.
+---@param amount number? Probability in range [0, 1] to drop an event. By default 0.5.
+---@return Pattern
+---@nodiscard
+function Pattern:degrade(amount) end
+
+---Apply the given transform function every `count` cycles, starting with the first cycle.
+---### examples:
+---```lua
+---pattern { unit = "1/8", pulse = { 1, 0, 1, 1 }, event = "c4" }:every(4, function(p)
+---  return p:fast(2)
+---end)
+---```
+---@param count integer Cycle count. Must be > 0.
+---@param fn fun(pattern: Pattern): Pattern Transform function.
+---@return Pattern
+---@nodiscard
+function Pattern:every(count, fn) end
+
+---Randomly apply the given transform function to half of the pattern's events.
+---Uses the global random seed, when set via `math.randomseed`.
+---@param fn fun(pattern: Pattern): Pattern Transform function.
+---@return Pattern
+---@nodiscard
+function Pattern:sometimes(fn) end
+
+---Layer a copy of the pattern, delayed by the given fraction of a cycle, on top of the pattern.
+---Optionally applies the given transform function to the delayed copy.
+---### examples:
+---```lua
+---pattern { unit = "1/4", event = cycle("c4 e4 g4") }:off(0.125, function(p)
+---  return p:degrade(0.5)
+---end)
+---```
+---@param amount number Delay as fraction of a cycle. Must be >= 0.
+---@param fn (fun(pattern: Pattern): Pattern)? Optional transform function.
+---@return Pattern
+---@nodiscard
+function Pattern:off(amount, fn) end
@@ -0,0 +1,56 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns Phrase class.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---@class Phrase
+local Phrase = {}
+
+----------------------------------------------------------------------------------------------------
+
+---- *stop*: Stop the pattern which played in this slot in the previous phrase and play nothing.
+---- *continue*: Continue playing the pattern which played in this slot in the previous phrase.
+---@alias PatternSlotMode "stop"|"continue"
+
+---Construction options for a new phrase.
+---@class PhraseOptions
+---
+---Time unit of the phrase's length. By default "bars".
+---@field unit? "bars"|"beats"|"1/1"|"1/2"|"1/4"|"1/8"|"1/16"|"1/32"|"1/64"
+---
+---Length of the phrase in `unit` time units. By default 1.
+---When the phrase is part of an arrangement, the next phrase starts playing after this length.
+---@field length? number
+---
+---Patterns that are played together in the phrase. Each pattern occupies a slot in the phrase.
+---Use `"stop"` or `"continue"` to stop or continue the pattern which played in the same slot in
+---the previous phrase of an arrangement.
+---
+---Patterns and cycles are copied, so the same pattern can be used in multiple phrases.
+---### examples:
+---```lua
+---patterns = { kick, cycle("hh*8"):map({ hh = "c6" }), "continue" }
+---```
+---@field patterns? (userdata|Cycle|Phrase|PatternSlotMode)[]
+
+----------------------------------------------------------------------------------------------------
+
+---Create a new phrase, which plays the given patterns together.
+---
+---### examples:
+---```lua
+---local kick = pattern { unit = "1/4", event = "c4 #1" }
+---local hihat = cycle("c6*8")
+---return phrase {
+---  unit = "bars",
+---  length = 4,
+---  patterns = { kick, hihat }
+---}
+---```
+---@param options PhraseOptions
+---@return Phrase
+---@nodiscard
+function phrase(options) end
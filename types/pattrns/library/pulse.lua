@@ -56,6 +56,10 @@ end
 ---pulse.from{ 1, 0.5, 1, 1 }:euclidean(12)
 ---```
 ---```lua
+----- timelines, maximally even rhythms and their complements
+---pulse.timeline("son_clave") + pulse.maximally_even(5, 8):complement()
+---```
+---```lua
 ----- generate/init from functions
 ---pulse.new(8):init(1) --> 1,1,1,1,1,1,1,1
 ---pulse.new(12):init(function() return math.random(0.5, 1.0) end )
@@ -258,6 +262,228 @@ function pulse.euclidean(steps, length, offset, empty_value)
   end
 end
 
+---Named timeline patterns, as used in `pulse.timeline` (implementation detail).
+local timeline_patterns = {
+  { "tresillo", "x..x..x." },
+  { "cinquillo", "x.xx.xx." },
+  { "habanera", "x..xx.x." },
+  { "shiko", "x...x.x...x.x..." },
+  { "son_clave", "x..x..x...x.x..." },
+  { "son_clave_23", "..x.x...x..x..x." },
+  { "rumba_clave", "x..x...x..x.x..." },
+  { "rumba_clave_23", "..x.x...x..x...x" },
+  { "soukous", "x..x..x...xx...." },
+  { "bossa_nova", "x..x..x...x..x.." },
+  { "gahu", "x..x..x...x...x." },
+  { "bembe", "x.x.xx.x.x.x" },
+  { "fume_fume", "x.x.x..x.x.." },
+}
+
+---Alternative names of timeline patterns (implementation detail).
+local timeline_aliases = {
+  son = "son_clave",
+  son_clave_32 = "son_clave",
+  rumba = "rumba_clave",
+  rumba_clave_32 = "rumba_clave",
+  bossa = "bossa_nova",
+}
+
+---Get onset values from an existing pulse or number of onsets (implementation detail).
+local function onset_values(steps)
+  local values = {}
+  if type(steps) == "table" then
+    for _, v in ipairs(steps) do
+      table.insert(values, v)
+    end
+  else
+    assert(type(steps) == "number" and steps >= 0,
+      "invalid steps argument (must be a table or an integer >= 0)")
+    for _ = 1, steps do
+      table.insert(values, 1)
+    end
+  end
+  return values
+end
+
+---Create a pulse from a table of booleans, using the given onset values (implementation detail).
+local function pulse_from_onsets(onsets, values, empty_value)
+  local result = pulse.new()
+  local index = 1
+  for _, onset in ipairs(onsets) do
+    if onset then
+      result:push_back(values[index] or values[#values])
+      index = index + 1
+    else
+      result:push_back(empty_value)
+    end
+  end
+  return result
+end
+
+---Create a new pulse table from a well-known timeline pattern from traditional music, as
+---described by Godfried Toussaint in "The Geometry of Musical Rhythm". Optionally rotate the
+---contents too.
+---
+---Available timelines are: "tresillo", "cinquillo", "habanera", "shiko", "son_clave",
+---"son_clave_23", "rumba_clave", "rumba_clave_23", "soukous", "bossa_nova", "gahu", "bembe"
+---and "fume_fume".
+---
+---### examples:
+---```lua
+---pulse.timeline("tresillo")
+--- --> {1,0,0,1,0,0,1,0}
+---pulse.timeline("son_clave", 8, "-")
+--- --> {"-","-",1,"-",1,"-","-","-",1,"-","-",1,"-","-",1,"-"}
+---```
+---@param name string Name of the timeline.
+---@param offset integer? Optional rotation offset.
+---@param empty_value PulseTableValue? Value used as off value (by default 0).
+---@return Pulse
+---@nodiscard
+function pulse.timeline(name, offset, empty_value)
+  assert(type(name) == "string",
+    "invalid name argument (must be a string)")
+  assert(type(offset) == "number" or offset == nil,
+    "invalid offset argument (must be an integer or nil)")
+  local key = name:lower():gsub("[%- ]", "_")
+  key = timeline_aliases[key] or key
+  local names = {}
+  for _, timeline in ipairs(timeline_patterns) do
+    if timeline[1] == key then
+      local onsets = {}
+      for c in timeline[2]:gmatch(".") do
+        table.insert(onsets, c == "x")
+      end
+      local result = pulse_from_onsets(onsets, { 1 }, empty_value or 0)
+      if offset then
+        result:rotate(-offset)
+      end
+      return result
+    end
+    table.insert(names, timeline[1])
+  end
+  error(("invalid timeline '%s', valid timelines are: %s"):format(
+    name, table.concat(names, ", ")))
+end
+
+---Create a new maximally even rhythm pulse table with the given pulses or number of new pulses
+---in the given length, using Bresenham's line algorithm. The result is a rotation of the
+---`euclidean` rhythm, which always starts with a pulse. Optionally rotate the contents too.
+---
+---### examples:
+---```lua
+---pulse.maximally_even(5, 8)
+--- --> {1,0,1,0,1,1,0,1}
+---pulse.from{"x", "x", "x"}:maximally_even(8, 0, "-")
+--- --> {"x","-","-","x","-","-","x","-"}
+---```
+---@param steps table|integer Existing pulse or number of on steps in the pulse.
+---@param length integer Number of total steps in the pulse.
+---@param offset integer? Optional rotation offset.
+---@param empty_value PulseTableValue? Value used as off value (by default 0 or guessed from existing content).
+---@return Pulse
+---@nodiscard
+function pulse.maximally_even(steps, length, offset, empty_value)
+  local values = onset_values(steps)
+  assert(type(length) == "number" and length > 0,
+    "invalid length argument (expecting an integer > 0)")
+  assert(type(offset) == "number" or offset == nil,
+    "invalid offset argument (must be an integer or nil)")
+  empty_value = empty_value or empty_pulse_value(steps)
+  local onsets = {}
+  for i = 0, length - 1 do
+    table.insert(onsets, #values >= length or (i * #values) % length < #values)
+  end
+  local result = pulse_from_onsets(onsets, values, empty_value)
+  if offset then
+    result:rotate(-offset)
+  end
+  return result
+end
+
+---Create a new maximally even rhythm pulse table over steps with the given relative durations:
+---pulses are spread evenly over the total duration of all steps, and each pulse is placed on
+---the first step which starts at or after its ideal position. Steps with large weights may
+---absorb multiple pulses.
+---
+---### examples:
+---```lua
+---pulse.weighted_maximally_even(2, {2, 1, 1, 2, 1, 1})
+--- --> {1,0,0,1,0,0}
+---```
+---@param steps table|integer Existing pulse or number of on steps in the pulse.
+---@param weights number[] Relative durations of all steps in the pulse.
+---@param empty_value PulseTableValue? Value used as off value (by default 0 or guessed from existing content).
+---@return Pulse
+---@nodiscard
+function pulse.weighted_maximally_even(steps, weights, empty_value)
+  local values = onset_values(steps)
+  assert(type(weights) == "table" and #weights > 0,
+    "invalid weights argument (must be a non empty table of numbers)")
+  empty_value = empty_value or empty_pulse_value(steps)
+  local starts, total = {}, 0
+  for i, weight in ipairs(weights) do
+    assert(type(weight) == "number",
+      "invalid weights argument (must be a non empty table of numbers)")
+    starts[i] = total
+    total = total + math.max(weight, 0)
+  end
+  local onsets = {}
+  for i = 1, #weights do
+    onsets[i] = false
+  end
+  if total > 0 then
+    for j = 1, #values do
+      local position = (j - 1) * total / #values
+      for i = 1, #starts do
+        if starts[i] >= position - 1e-9 then
+          onsets[i] = true
+          break
+        end
+      end
+    end
+  end
+  return pulse_from_onsets(onsets, values, empty_value)
+end
+
+---Create a new pulse table from a Christoffel word with the given pulses or number of new pulses
+---in the given length: the discretization of a line with slope `steps / (length - steps)`.
+---Lower words start with an off value and end with a pulse, upper words are reversed lower words.
+---
+---### examples:
+---```lua
+---pulse.christoffel(3, 8)
+--- --> {0,0,1,0,0,1,0,1}
+---pulse.christoffel(3, 8, true)
+--- --> {1,0,1,0,0,1,0,0}
+---```
+---@param steps table|integer Existing pulse or number of on steps in the pulse.
+---@param length integer Number of total steps in the pulse.
+---@param upper boolean? When true, create the upper instead of the lower word.
+---@param empty_value PulseTableValue? Value used as off value (by default 0 or guessed from existing content).
+---@return Pulse
+---@nodiscard
+function pulse.christoffel(steps, length, upper, empty_value)
+  local values = onset_values(steps)
+  assert(type(length) == "number" and length > 0,
+    "invalid length argument (expecting an integer > 0)")
+  assert(type(upper) == "boolean" or upper == nil,
+    "invalid upper argument (must be a boolean or nil)")
+  empty_value = empty_value or empty_pulse_value(steps)
+  local k = #values
+  local onsets = {}
+  for i = 0, length - 1 do
+    local onset = k >= length or
+        math.floor((i + 1) * k / length) > math.floor(i * k / length)
+    if upper then
+      table.insert(onsets, 1, onset)
+    else
+      table.insert(onsets, onset)
+    end
+  end
+  return pulse_from_onsets(onsets, values, empty_value)
+end
+
 ----------------------------------------------------------------------------------------------------
 --- Access pulse table content
 ----------------------------------------------------------------------------------------------------
@@ -424,6 +650,32 @@ function pulse.rotate(self, amount)
   return self
 end
 
+---Invert the pulse table: off values become the given on value and all other values become
+---off values. Pulses which are 0, false, "" or the empty value are off values.
+---
+---### examples:
+---```lua
+---local p = pulse.timeline("tresillo")
+---p:complement() --> {0,1,1,0,1,1,0,1}
+---pulse.from{"x", "-", "-"}:complement("o", "-") --> {"-","o","o"}
+---```
+---@param value PulseTableValue? Value used as on value (by default 1).
+---@param empty_value PulseTableValue? Value used as off value (by default 0 or guessed from existing content).
+function pulse.complement(self, value, empty_value)
+  empty_value = empty_value or empty_pulse_value(self)
+  value = value or 1
+  for i = 1, #self do
+    local v = self[i]
+    if v == empty_value or v == 0 or v == false or v == "" or
+        (type(v) == "table" and next(v) == nil) then
+      self[i] = value
+    else
+      self[i] = empty_value
+    end
+  end
+  return self
+end
+
 ----------------------------------------------------------------------------------------------------
 --- Add/remove contents
 ----------------------------------------------------------------------------------------------------
@@ -0,0 +1,138 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns native rhythm generators.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---Opaque native rhythm user data. Construct new rhythms via the `rhythm.XXX(...)` functions and
+---use them as `pulse` in patterns.
+---@class Rhythm : userdata
+local Rhythm = {}
+
+---A fixed number or the id of a pattern parameter. When set to a parameter id, the property
+---follows the parameter's value. Parameters must be defined in the pattern's `parameter` list.
+---@alias RhythmPropertyValue number|ParameterId
+
+----------------------------------------------------------------------------------------------------
+
+---Options of a Markov chain rhythm.
+---@class MarkovRhythmOptions
+---Pulse values of the chain's states.
+---@field states PulseValue[]
+---Transition weights as matrix: `transitions[a][b]` is the weight of moving from state `a` to
+---state `b`. Weights must be finite numbers >= 0 and do not need to be normalized.
+---@field transitions RhythmPropertyValue[][]
+---Index of the initial state. By default 1.
+---@field start integer?
+---Optional random seed. When undefined, the rhythm is randomly seeded.
+---@field seed integer?
+---Optional number of pulses after which the rhythm restarts. When undefined, it runs endlessly.
+---@field length integer?
+
+---Options of an L-system rhythm.
+---@class LSystemRhythmOptions
+---Initial string of the L-system.
+---@field axiom string
+---Rewriting rules: single characters and their replacement strings.
+---@field rules table<string, string>?
+---Pulse values of characters. Characters without a value emit 0. By default `1` and `x` emit 1.
+---@field values table<string, PulseValue>?
+---Number of rewriting iterations in range [0 - 64]. By default 1.
+---@field iterations RhythmPropertyValue?
+---Optional number of pulses after which the rhythm restarts. By default the expanded string's
+---length.
+---@field length integer?
+
+---Options of an elementary cellular automaton rhythm.
+---@class AutomatonRhythmOptions
+---Wolfram rule number in range [0 - 255]. By default 30.
+---@field rule RhythmPropertyValue?
+---Initial cells of the automaton. The number of cells is the number of pulses per generation.
+---@field cells PulseValue[]
+---Optional number of pulses after which the rhythm restarts. When undefined, it runs endlessly.
+---@field length integer?
+
+---Options of a random walk rhythm.
+---@class RandomWalkRhythmOptions
+---Initial pulse value. By default 0.5.
+---@field start RhythmPropertyValue?
+---Lower bound of the walk's pulse values. By default 0.
+---@field min RhythmPropertyValue?
+---Upper bound of the walk's pulse values. By default 1.
+---@field max RhythmPropertyValue?
+---Maximum change of the pulse value with each pulse. By default 0.1.
+---@field step RhythmPropertyValue?
+---Optional random seed. When undefined, the rhythm is randomly seeded.
+---@field seed integer?
+---Optional number of pulses after which the rhythm restarts. When undefined, it runs endlessly.
+---@field length integer?
+
+----------------------------------------------------------------------------------------------------
+
+---Contains functions to construct native, generative rhythms. Native rhythms run without
+---Lua callbacks, so they are a lot cheaper than generating pulses in `pulse` functions.
+rhythm = {}
+
+---Create a rhythm from a Markov chain: each state emits its pulse value and then moves on to a
+---randomly picked next state, using the state's transition weights.
+---
+---### examples:
+---```lua
+----- hits are likely followed by rests, rests are likely followed by hits
+---pulse = rhythm.markov {
+---  states = { 1, 0 },
+---  transitions = { { 0.2, 0.8 }, { 0.6, 0.4 } },
+---  seed = 1234,
+---  length = 16
+---}
+---```
+---@param options MarkovRhythmOptions
+---@return Rhythm
+function rhythm.markov(options) end
+
+---Create a rhythm from an L-system: rewrites the axiom string with the given rules, then emits
+---the pulse values of the resulting string's characters. The rhythm repeats after all characters
+---got emitted.
+---
+---### examples:
+---```lua
+----- fibonacci rhythm: "abaababa..."
+---pulse = rhythm.lsystem {
+---  axiom = "a",
+---  rules = { a = "ab", b = "a" },
+---  values = { a = 1, b = 0 },
+---  iterations = 5
+---}
+---```
+---@param options LSystemRhythmOptions
+---@return Rhythm
+function rhythm.lsystem(options) end
+
+---Create a rhythm from an elementary cellular automaton: emits the cells of the current
+---generation, then evolves the cells with the automaton's rule. Cells wrap around at the edges.
+---
+---### examples:
+---```lua
+---return pattern {
+---  parameter = { parameter.integer("rule", 30, { 0, 255 }) },
+---  pulse = rhythm.automaton { rule = "rule", cells = { 0, 0, 0, 1, 0, 0, 0, 0 } },
+---  event = "c4"
+---}
+---```
+---@param options AutomatonRhythmOptions
+---@return Rhythm
+function rhythm.automaton(options) end
+
+---Create a rhythm with a random walk: emits pulse values, which randomly move by at most `step`
+---with each pulse and bounce off the `min` and `max` bounds. With the default gate, the values
+---are the probabilities of the pulses to trigger, so the rhythm's density drifts over time.
+---
+---### examples:
+---```lua
+---pulse = rhythm.random_walk { start = 0.5, min = 0.2, max = 0.9, step = 0.05 }
+---```
+---@param options RandomWalkRhythmOptions?
+---@return Rhythm
+function rhythm.random_walk(options) end
@@ -10,6 +10,10 @@ error("Do not try to execute this file. It's just a type definition file.")
 ---@alias DegreeValue integer|"i"|"ii"|"iii"|"iv"|"v"|"vi"|"vii"|"I"|"II"|"III"|"IV"|"V"|"VI"|"VII"
 
 ---@class Scale
+---Scale's root note value as integer: the key note in the scale's octave.
+---@field key integer
+---Scale's mode name, one of `scale_names()` or "custom scale".
+---@field mode ScaleMode
 ---Scale note values as integers, in ascending order of the mode, starting from the scale's key note.
 ---@field notes integer[]
 local Scale = {}
@@ -81,6 +85,33 @@ function Scale:notes_iter(count) end
 ---@nodiscard
 function Scale:fit(...) end
 
+---Create voiced chords from a progression of roman numeral chord symbols in the scale's key.
+---The scale must have 7 notes.
+---
+---Uppercase numerals are major, lowercase numerals minor chords. `o` marks diminished,
+---`ø` half-diminished and `+` augmented chords. `7`, `9`, `11` and `13` add the scale's
+---diatonic seventh and extensions, `maj7` a major seventh. Inversions use figured bass
+---notation: `6` and `64` for triads, `65`, `43` and `42` for seventh chords. `sus2` and `sus4`
+---replace the third. `b` and `#` before the numeral alter the root, e.g. for borrowed chords.
+---Secondary chords are appended with `/` and the tonicized numeral, e.g. `V7/V`, slash chords
+---with `/` and a bass note name, e.g. `I/E`.
+---
+---NB: Roman numerals also can be used as chord modes in note and cycle chord strings. They
+---then are relative to the major key of the chord's note, e.g. `note("c4'V7/V")`.
+---
+---### examples:
+---```lua
+---local cmaj = scale("c4", "major")
+---cmaj:progression("I vi ii7 V7/V") --> {{48,52,55}, {57,60,64}, {50,53,57,60}, {50,54,57,60}}
+---cmaj:progression("I6 bVII V65") --> {{52,55,60}, {58,62,65}, {59,62,65,67}}
+----- as pattern events
+---return pattern { unit = "1/1", event = cmaj:progression("ii7 V7 Imaj7") }
+---```
+---@param progression string Whitespace separated roman numeral chord symbols.
+---@return integer[][] chords
+---@nodiscard
+function Scale:progression(progression) end
+
 ----------------------------------------------------------------------------------------------------
 
 ---Available scale mode names.
@@ -128,4 +159,23 @@ function scale(key, intervals) end
 
 ---Return supported scale mode names.
 ---@return string[]
-function scale_names() end
\ No newline at end of file
+function scale_names() end
+
+---Find the scale which most likely fits the given notes, ranked against all known scale modes
+---in all keys. Scales which contain most of the given notes and use most of their own steps
+---are preferred. When multiple scales fit equally well, the lowest note is used as key.
+---Returns nil when no notes are given.
+---
+---Accepts the same arguments as function `note`, so this can also be used to detect the scale
+---of a pattern's trigger notes.
+---
+---### examples:
+---```lua
+---detect_scale("a3", "c4", "d4", "e4", "g4").mode --> "pentatonic minor"
+---detect_scale("c4 d4 e4 f4 g4 a4 b4").key --> 48
+---detect_scale(context.trigger) -- scale of the notes which triggered the pattern
+---```
+---@param ... NoteValue
+---@return Scale|nil
+---@nodiscard
+function detect_scale(...) end
@@ -69,6 +69,21 @@ function Sequence:panning(panning) end
 ---@nodiscard
 function Sequence:delay(delay) end
 
+---Set the length attribute of all notes to the specified value or values. Lengths are relative
+---to the note event's duration. Notes without a length play until the next note (legato).
+---@param length number|number[] new length >= 0
+---@return Sequence
+---@nodiscard
+function Sequence:length(length) end
+
+---Set a custom, named attribute of all notes, such as a `cutoff` or `pitch_bend` value, to the
+---specified value or values.
+---@param name string attribute name
+---@param value number|number[]
+---@return Sequence
+---@nodiscard
+function Sequence:attribute(name, value) end
+
 ----------------------------------------------------------------------------------------------------
 
 ---Create a sequence from an array of note values or note value varargs.
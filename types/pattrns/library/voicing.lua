@@ -0,0 +1,85 @@
+---@meta
+error("Do not try to execute this file. It's just a type definition file.")
+---
+---Part of the pattrns crate: Defines LuaLS annotations for the pattrns VoiceLeading class.
+---
+
+----------------------------------------------------------------------------------------------------
+
+---Voices successive chords with minimal voice movement. Keeps the previously voiced chord, so
+---each voiced chord moves as little as possible from the previous one.
+---@class VoiceLeading
+---Notes of the most recently voiced chord in ascending order, or an empty table.
+---@field voicing integer[]
+local VoiceLeading = {}
+
+---Voice the given chord relative to the previously voiced chord. Accepts the same arguments as
+---function `note`. The lowest given note is the chord's bass. Note properties such as volume or
+---instrument are taken over from the given notes. Rests and note-offs are passed as they are.
+---
+---### examples:
+---```lua
+---local leading = voice_leading()
+---leading:voice("c4'major") --> {"c4", "e4", "g4"}
+---leading:voice("f4'major") --> {"c4", "f4", "a4"}
+---```
+---@param ... NoteValue
+---@return Note
+---@nodiscard
+function VoiceLeading:voice(...) end
+
+---Voice each step of the given sequence in a row. Accepts a sequence or the same arguments as
+---function `sequence`.
+---
+---### examples:
+---```lua
+---voice_leading():voice_sequence(sequence("c4'maj", "a3'min", "f3'maj", "g3'maj"))
+---```
+---@param ... Sequence|NoteValue
+---@return Sequence
+---@nodiscard
+function VoiceLeading:voice_sequence(...) end
+
+---Forget the previously voiced chord, so the next chord is voiced in close position again.
+function VoiceLeading:reset() end
+
+----------------------------------------------------------------------------------------------------
+
+---Construction options for a new voice leading.
+---@class VoiceLeadingOptions
+---
+---Lowest and highest note of the voicings, spanning at least an octave. By default { "c3", "c6" }.
+---@field range? NoteValue[]
+---
+---Fixed number of voices in range [1..=8]. Chords with more notes drop their fifth first, then
+---their topmost notes. Chords with less notes double their bass, then their fifth.
+---By default each chord uses as many voices as it has distinct notes.
+---@field voices? integer
+---
+---When true, the chord's lowest note always stays in the bass and only the upper voices move.
+---By default false.
+---@field fixed_bass? boolean
+
+----------------------------------------------------------------------------------------------------
+
+---Create a new voice leading, which voices successive chords so that the total movement of all
+---voices is minimal, instead of jumping around in root position.
+---
+---### examples:
+---```lua
+----- voice a chord progression in an event callback
+---return pattern {
+---  unit = "1/1",
+---  event = function(context)
+---    local leading = voice_leading { voices = 4, range = { "c3", "c5" } }
+---    local chords = scale("c4", "major"):progression("I vi ii7 V7")
+---    return function(context)
+---      return leading:voice(chords[math.imod(context.step, #chords)])
+---    end
+---  end
+---}
+---```
+---@param options VoiceLeadingOptions?
+---@return VoiceLeading
+---@nodiscard
+function voice_leading(options) end